@@ -0,0 +1,247 @@
+use axum::{
+    extract::{Extension, Path, Query},
+    Json,
+};
+use chrono::{Duration, Utc};
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use std::{str::FromStr, sync::Arc};
+
+use crate::features::{
+    bond_analytics::BondAnalyticsService,
+    core::models::instrument::TinkoffInstrumentEnum,
+    db::{
+        mongo_extensions::{
+            bond_analytics::models::DbBondAnalytics,
+            bond_coupons::models::{DbBondAccruedInterest, DbBondCoupon},
+        },
+        MongoDb,
+    },
+    portfolio::coupons::{project_coupons, CouponProjection},
+};
+
+use super::{error::ApiError, params::parse_day_param};
+
+/// Горизонт по умолчанию для ожидаемых купонов, дней
+const DEFAULT_COUPON_DAYS: i64 = 90;
+/// Максимальный горизонт ожидаемых купонов, дней
+const MAX_COUPON_DAYS: i64 = 730;
+/// Период НКД по умолчанию, дней
+const DEFAULT_ACCRUED_INTEREST_DAYS: i64 = 30;
+/// Период истории аналитики по умолчанию, дней
+const DEFAULT_ANALYTICS_HISTORY_DAYS: i64 = 90;
+
+#[derive(Debug, Deserialize)]
+pub struct BondCouponsQuery {
+    /// Только купоны с датой выплаты от сегодня
+    #[serde(default)]
+    pub upcoming: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AccruedInterestQuery {
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BondAnalyticsQuery {
+    /// Чистая цена, % номинала; по умолчанию — закрытие последней свечи
+    pub price: Option<String>,
+    /// Дата расчёта, YYYY-MM-DD; по умолчанию сегодня
+    pub date: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BondAnalyticsHistoryQuery {
+    /// Первый день расчётов, YYYY-MM-DD; по умолчанию 90 дней до `to`
+    pub from: Option<String>,
+    /// Последний день расчётов, YYYY-MM-DD; по умолчанию сегодня
+    pub to: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CouponProjectionQuery {
+    pub days: Option<i64>,
+}
+
+async fn ensure_bond(mongo_db: &MongoDb, figi: &str) -> Result<(), ApiError> {
+    match mongo_db.find_instrument_by_figi(figi).await {
+        Some(TinkoffInstrumentEnum::Bond(_)) => Ok(()),
+        Some(_) => Err(ApiError::InvalidParameter(format!(
+            "Instrument with FIGI {} is not a bond",
+            figi
+        ))),
+        None => Err(ApiError::UnknownFigi(format!(
+            "Instrument with FIGI {} not found",
+            figi
+        ))),
+    }
+}
+
+/// GET /api/bonds/{figi}/coupons?upcoming=true
+///
+/// Купонный календарь облигации по номеру купона
+pub async fn get_bond_coupons(
+    Extension(mongo_db): Extension<MongoDb>,
+    Path(figi): Path<String>,
+    Query(query): Query<BondCouponsQuery>,
+) -> Result<Json<Vec<DbBondCoupon>>, ApiError> {
+    ensure_bond(&mongo_db, &figi).await?;
+
+    let mut coupons = mongo_db.find_bond_coupons(&figi).await?;
+    if query.upcoming {
+        let today = Utc::now().format("%Y-%m-%d").to_string();
+        coupons.retain(|coupon| coupon.coupon_date >= today);
+    }
+    Ok(Json(coupons))
+}
+
+/// GET /api/bonds/{figi}/accrued-interest?from=2025-03-01&to=2025-03-14
+///
+/// НКД по дням; по умолчанию за последние 30 дней
+pub async fn get_bond_accrued_interest(
+    Extension(mongo_db): Extension<MongoDb>,
+    Path(figi): Path<String>,
+    Query(query): Query<AccruedInterestQuery>,
+) -> Result<Json<Vec<DbBondAccruedInterest>>, ApiError> {
+    ensure_bond(&mongo_db, &figi).await?;
+
+    let to = match &query.to {
+        Some(value) => parse_day_param("to", value)?,
+        None => Utc::now().date_naive(),
+    };
+    let from = match &query.from {
+        Some(value) => parse_day_param("from", value)?,
+        None => to - Duration::days(DEFAULT_ACCRUED_INTEREST_DAYS),
+    };
+    if from > to {
+        return Err(ApiError::InvalidRange(
+            "Parameter 'from' must not be later than 'to'".to_string(),
+        ));
+    }
+
+    let interests = mongo_db
+        .find_bond_accrued_interests(
+            &figi,
+            &from.format("%Y-%m-%d").to_string(),
+            &to.format("%Y-%m-%d").to_string(),
+        )
+        .await?;
+    Ok(Json(interests))
+}
+
+/// GET /api/bonds/{figi}/analytics?price=98.5&date=2025-03-14
+///
+/// Доходность к погашению, текущая доходность, дюрация и выпуклость на дату
+pub async fn get_bond_analytics(
+    Extension(mongo_db): Extension<MongoDb>,
+    Extension(analytics): Extension<Arc<BondAnalyticsService>>,
+    Path(figi): Path<String>,
+    Query(query): Query<BondAnalyticsQuery>,
+) -> Result<Json<DbBondAnalytics>, ApiError> {
+    ensure_bond(&mongo_db, &figi).await?;
+
+    let price = match &query.price {
+        Some(value) => {
+            let price = Decimal::from_str(value).map_err(|_| {
+                ApiError::InvalidParameter(format!("Invalid price '{}'", value))
+            })?;
+            if price <= Decimal::ZERO {
+                return Err(ApiError::InvalidParameter(
+                    "Parameter 'price' must be positive".to_string(),
+                ));
+            }
+            Some(price)
+        }
+        None => None,
+    };
+    let date = match &query.date {
+        Some(value) => parse_day_param("date", value)?,
+        None => Utc::now().date_naive(),
+    };
+
+    let report = analytics
+        .analyze_bond(&figi, price, date)
+        .await
+        .map_err(ApiError::Database)?
+        .ok_or_else(|| {
+            ApiError::NotFound(format!("No price for bond {} on or before {}", figi, date))
+        })?;
+    Ok(Json(report))
+}
+
+/// GET /api/bonds/{figi}/analytics/history?from=2025-01-01&to=2025-03-14
+///
+/// Расчёты ночного задания по дням; по умолчанию за последние 90 дней
+pub async fn get_bond_analytics_history(
+    Extension(mongo_db): Extension<MongoDb>,
+    Path(figi): Path<String>,
+    Query(query): Query<BondAnalyticsHistoryQuery>,
+) -> Result<Json<Vec<DbBondAnalytics>>, ApiError> {
+    ensure_bond(&mongo_db, &figi).await?;
+
+    let to = match &query.to {
+        Some(value) => parse_day_param("to", value)?,
+        None => Utc::now().date_naive(),
+    };
+    let from = match &query.from {
+        Some(value) => parse_day_param("from", value)?,
+        None => to - Duration::days(DEFAULT_ANALYTICS_HISTORY_DAYS),
+    };
+    if from > to {
+        return Err(ApiError::InvalidRange(
+            "Parameter 'from' must not be later than 'to'".to_string(),
+        ));
+    }
+
+    let history = mongo_db
+        .find_bond_analytics(
+            &figi,
+            &from.format("%Y-%m-%d").to_string(),
+            &to.format("%Y-%m-%d").to_string(),
+        )
+        .await?;
+    Ok(Json(history))
+}
+
+/// GET /api/portfolio/{account_id}/coupons?days=90
+///
+/// Ожидаемые купоны по облигациям счёта
+pub async fn get_coupon_projection(
+    Extension(mongo_db): Extension<MongoDb>,
+    Path(account_id): Path<String>,
+    Query(query): Query<CouponProjectionQuery>,
+) -> Result<Json<CouponProjection>, ApiError> {
+    mongo_db
+        .get_account(&account_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Account {} not found", account_id)))?;
+
+    let days = query.days.unwrap_or(DEFAULT_COUPON_DAYS);
+    if !(1..=MAX_COUPON_DAYS).contains(&days) {
+        return Err(ApiError::InvalidParameter(format!(
+            "Parameter 'days' must be between 1 and {}",
+            MAX_COUPON_DAYS
+        )));
+    }
+    let today = Utc::now().date_naive();
+    let from = today.format("%Y-%m-%d").to_string();
+    let to = (today + Duration::days(days)).format("%Y-%m-%d").to_string();
+
+    let snapshot = mongo_db
+        .get_latest_portfolio_snapshot(&account_id)
+        .await?
+        .ok_or_else(|| {
+            ApiError::NotFound(format!("No portfolio snapshots for account {}", account_id))
+        })?;
+    let figis: Vec<String> = snapshot
+        .positions
+        .iter()
+        .filter(|position| position.instrument_type == "bond")
+        .map(|position| position.figi.clone())
+        .collect();
+    let coupons = mongo_db.find_coupons_between(Some(&figis), &from, &to).await?;
+
+    Ok(Json(project_coupons(&snapshot, &coupons, &from, &to)))
+}
@@ -0,0 +1,207 @@
+use axum::{
+    extract::{Extension, Path, Query},
+    Json,
+};
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::features::{
+    core::models::candle_interval::MyCandleInterval,
+    db::{
+        mongo_extensions::{
+            candle_days::models::DbCandleDay,
+            candles::models::DbHistoricalCandle,
+        },
+        MongoDb,
+    },
+};
+
+use super::{
+    error::ApiError,
+    params::{pagination, parse_datetime_param},
+};
+
+#[derive(Debug, Deserialize)]
+pub struct CandlesQuery {
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub interval: Option<String>,
+    /// `api` — свечи, загруженные из API (по умолчанию), `rollup` — собранные из минутных
+    pub source: Option<String>,
+    pub page: Option<u64>,
+    pub limit: Option<i64>,
+}
+
+/// Свеча с ценами в десятичном виде
+#[derive(Debug, Serialize)]
+pub struct CandleDto {
+    pub time: String,
+    pub open: Decimal,
+    pub high: Decimal,
+    pub low: Decimal,
+    pub close: Decimal,
+    pub volume: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CandlesPageDto {
+    pub figi: String,
+    pub interval: &'static str,
+    pub from: String,
+    pub to: String,
+    pub page: u64,
+    pub limit: i64,
+    pub total: u64,
+    pub has_more: bool,
+    pub candles: Vec<CandleDto>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CandleGapsQuery {
+    pub interval: Option<String>,
+}
+
+/// Незагруженные дни свечей по инструменту
+#[derive(Debug, Serialize)]
+pub struct CandleGapsDto {
+    pub figi: String,
+    pub total: usize,
+    pub days: Vec<DbCandleDay>,
+}
+
+impl From<DbHistoricalCandle> for CandleDto {
+    fn from(candle: DbHistoricalCandle) -> Self {
+        let time = DateTime::from_timestamp(candle.time.seconds, candle.time.nanos as u32)
+            .map(|dt| dt.to_rfc3339())
+            .unwrap_or_default();
+
+        Self {
+            time,
+            open: candle.open.value,
+            high: candle.high.value,
+            low: candle.low.value,
+            close: candle.close.value,
+            volume: candle.volume,
+        }
+    }
+}
+
+/// GET /api/candles/{figi}?from=&to=&interval=&source=&page=&limit=
+///
+/// По умолчанию отдаёт минутные свечи за последние сутки
+pub async fn get_candles(
+    Extension(mongo_db): Extension<MongoDb>,
+    Path(figi): Path<String>,
+    Query(query): Query<CandlesQuery>,
+) -> Result<Json<CandlesPageDto>, ApiError> {
+    let interval_name = query.interval.as_deref().unwrap_or("1m");
+    let interval = MyCandleInterval::parse(interval_name).ok_or_else(|| {
+        ApiError::InvalidInterval(format!("Unknown candle interval '{}'", interval_name))
+    })?;
+
+    let to = match &query.to {
+        Some(value) => parse_datetime_param("to", value)?,
+        None => Utc::now(),
+    };
+    let from = match &query.from {
+        Some(value) => parse_datetime_param("from", value)?,
+        None => to - Duration::days(1),
+    };
+    if from >= to {
+        return Err(ApiError::InvalidRange(format!(
+            "'from' ({}) must be earlier than 'to' ({})",
+            from.to_rfc3339(),
+            to.to_rfc3339()
+        )));
+    }
+
+    let (page, skip, limit) = pagination(query.page, query.limit)?;
+
+    // FIGI должен быть в каталоге инструментов; от типа зависит коллекция свечей
+    let instrument = mongo_db.find_instrument_by_figi(&figi).await.ok_or_else(|| {
+        ApiError::UnknownFigi(format!("Instrument with FIGI {} not found", figi))
+    })?;
+    let collection = match query.source.as_deref().unwrap_or("api") {
+        "api" => mongo_db.historical_candles_collection(instrument.kind(), interval),
+        "rollup" => mongo_db.rollup_candles_collection(interval),
+        other => {
+            return Err(ApiError::InvalidParameter(format!(
+                "Unknown candle source '{}', expected 'api' or 'rollup'",
+                other
+            )))
+        }
+    };
+
+    // Запрошенный диапазон должен пересекаться с сохранёнными данными
+    match mongo_db.get_candles_time_bounds(&collection, &figi).await? {
+        Some((first, last)) if from.timestamp() <= last && to.timestamp() > first => {}
+        Some((first, last)) => {
+            return Err(ApiError::OutOfRange(format!(
+                "Stored candles for {} cover {} .. {}",
+                figi,
+                DateTime::from_timestamp(first, 0).unwrap_or_default().to_rfc3339(),
+                DateTime::from_timestamp(last, 0).unwrap_or_default().to_rfc3339()
+            )));
+        }
+        None => {
+            return Err(ApiError::OutOfRange(format!(
+                "No stored {} candles for {}",
+                interval.short_name(),
+                figi
+            )));
+        }
+    }
+
+    let result = mongo_db
+        .find_historical_candles(
+            &collection,
+            &figi,
+            from.timestamp(),
+            to.timestamp(),
+            skip,
+            limit,
+        )
+        .await?;
+
+    let returned = result.candles.len() as u64;
+
+    Ok(Json(CandlesPageDto {
+        figi,
+        interval: interval.short_name(),
+        from: from.to_rfc3339(),
+        to: to.to_rfc3339(),
+        page,
+        limit,
+        total: result.total,
+        has_more: skip + returned < result.total,
+        candles: result.candles.into_iter().map(CandleDto::from).collect(),
+    }))
+}
+
+/// GET /api/candles/{figi}/gaps?interval=
+///
+/// Дни, которые загрузчик не смог загрузить или сканер нашёл пустыми;
+/// без `interval` — по всем интервалам
+pub async fn get_candle_gaps(
+    Extension(mongo_db): Extension<MongoDb>,
+    Path(figi): Path<String>,
+    Query(query): Query<CandleGapsQuery>,
+) -> Result<Json<CandleGapsDto>, ApiError> {
+    let interval = match query.interval.as_deref() {
+        Some(name) => Some(MyCandleInterval::parse(name).ok_or_else(|| {
+            ApiError::InvalidInterval(format!("Unknown candle interval '{}'", name))
+        })?),
+        None => None,
+    };
+
+    let days = mongo_db
+        .find_open_candle_days(&figi, interval.map(|interval| interval.short_name()))
+        .await?;
+
+    Ok(Json(CandleGapsDto {
+        figi,
+        total: days.len(),
+        days,
+    }))
+}
@@ -0,0 +1,94 @@
+use axum::{
+    extract::{Extension, Path, Query},
+    Json,
+};
+use chrono::Utc;
+use serde::Deserialize;
+
+use crate::features::db::{
+    mongo_extensions::currency_rates::models::DbCurrencyRate,
+    MongoDb,
+};
+
+use super::{
+    error::ApiError,
+    params::{parse_day_param, parse_rate_source},
+};
+
+#[derive(Debug, Deserialize)]
+pub struct CurrencyRateQuery {
+    /// cbrf (по умолчанию), exchange или wap
+    pub source: Option<String>,
+    /// Дата курса YYYY-MM-DD, по умолчанию сегодня
+    pub date: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CurrencyRateHistoryQuery {
+    pub source: Option<String>,
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+/// GET /api/currency-rates/{currency}?source=cbrf&date=2025-03-14
+///
+/// Курс на дату: последний курс источника не позже даты
+pub async fn get_currency_rate(
+    Extension(mongo_db): Extension<MongoDb>,
+    Path(currency): Path<String>,
+    Query(query): Query<CurrencyRateQuery>,
+) -> Result<Json<DbCurrencyRate>, ApiError> {
+    let source = parse_rate_source(query.source.as_deref())?;
+    let date = match &query.date {
+        Some(value) => parse_day_param("date", value)?,
+        None => Utc::now().date_naive(),
+    }
+    .format("%Y-%m-%d")
+    .to_string();
+
+    mongo_db
+        .get_currency_rate(&currency, source, &date)
+        .await?
+        .map(Json)
+        .ok_or_else(|| {
+            ApiError::NotFound(format!(
+                "No {} rate for {} on or before {}",
+                source,
+                currency.to_uppercase(),
+                date
+            ))
+        })
+}
+
+/// GET /api/currency-rates/{currency}/history?source=cbrf&from=2025-01-01&to=2025-03-31
+pub async fn get_currency_rate_history(
+    Extension(mongo_db): Extension<MongoDb>,
+    Path(currency): Path<String>,
+    Query(query): Query<CurrencyRateHistoryQuery>,
+) -> Result<Json<Vec<DbCurrencyRate>>, ApiError> {
+    let source = parse_rate_source(query.source.as_deref())?;
+    let from = query
+        .from
+        .as_deref()
+        .map(|value| parse_day_param("from", value))
+        .transpose()?;
+    let to = query
+        .to
+        .as_deref()
+        .map(|value| parse_day_param("to", value))
+        .transpose()?;
+    if let (Some(from), Some(to)) = (from, to) {
+        if from > to {
+            return Err(ApiError::InvalidRange(
+                "Parameter 'from' must not be later than 'to'".to_string(),
+            ));
+        }
+    }
+
+    let from = from.map(|day| day.format("%Y-%m-%d").to_string());
+    let to = to.map(|day| day.format("%Y-%m-%d").to_string());
+    let rates = mongo_db
+        .get_currency_rate_history(&currency, source, from.as_deref(), to.as_deref())
+        .await?;
+    Ok(Json(rates))
+}
@@ -0,0 +1,159 @@
+use axum::{
+    extract::{Extension, Path, Query},
+    Json,
+};
+use chrono::{Duration, Utc};
+use serde::Deserialize;
+use std::collections::BTreeSet;
+
+use crate::features::{
+    db::{mongo_extensions::dividends::models::DbDividend, MongoDb},
+    portfolio::dividends::{project_dividends, DividendProjection},
+};
+
+use super::error::ApiError;
+
+/// Горизонт по умолчанию для ближайших выплат, дней
+const DEFAULT_UPCOMING_DAYS: i64 = 90;
+/// Горизонт по умолчанию для прогноза дохода, дней
+const DEFAULT_PROJECTION_DAYS: i64 = 365;
+/// Максимальный горизонт запроса, дней
+const MAX_DIVIDEND_DAYS: i64 = 730;
+
+#[derive(Debug, Deserialize)]
+pub struct UpcomingDividendsQuery {
+    /// watchlist (по умолчанию), portfolio или all
+    pub scope: Option<String>,
+    /// Для scope=portfolio: один счёт вместо всех
+    pub account_id: Option<String>,
+    pub days: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DividendProjectionQuery {
+    pub days: Option<i64>,
+}
+
+fn horizon(days: Option<i64>, default: i64) -> Result<(String, String), ApiError> {
+    let days = days.unwrap_or(default);
+    if !(1..=MAX_DIVIDEND_DAYS).contains(&days) {
+        return Err(ApiError::InvalidParameter(format!(
+            "Parameter 'days' must be between 1 and {}",
+            MAX_DIVIDEND_DAYS
+        )));
+    }
+
+    let today = Utc::now().date_naive();
+    Ok((
+        today.format("%Y-%m-%d").to_string(),
+        (today + Duration::days(days)).format("%Y-%m-%d").to_string(),
+    ))
+}
+
+/// FIGI позиций из последних снимков счёта или всех счетов
+async fn portfolio_figis(
+    mongo_db: &MongoDb,
+    account_id: Option<&str>,
+) -> Result<Vec<String>, ApiError> {
+    let account_ids = match account_id {
+        Some(account_id) => vec![account_id.to_string()],
+        None => mongo_db
+            .get_accounts()
+            .await?
+            .into_iter()
+            .map(|account| account.account_id)
+            .collect(),
+    };
+
+    let mut figis = BTreeSet::new();
+    for account_id in account_ids {
+        if let Some(snapshot) = mongo_db.get_latest_portfolio_snapshot(&account_id).await? {
+            figis.extend(snapshot.positions.into_iter().map(|position| position.figi));
+        }
+    }
+    Ok(figis.into_iter().collect())
+}
+
+/// GET /api/dividends/upcoming?scope=watchlist|portfolio|all&account_id=&days=90
+///
+/// Дивиденды с датой фиксации от сегодня до горизонта
+pub async fn get_upcoming_dividends(
+    Extension(mongo_db): Extension<MongoDb>,
+    Query(query): Query<UpcomingDividendsQuery>,
+) -> Result<Json<Vec<DbDividend>>, ApiError> {
+    let (from, to) = horizon(query.days, DEFAULT_UPCOMING_DAYS)?;
+
+    let figis = match query.scope.as_deref().unwrap_or("watchlist") {
+        "watchlist" => Some(
+            mongo_db
+                .get_enabled_watchlists()
+                .await
+                .into_iter()
+                .map(|watchlist| watchlist.figi)
+                .collect::<Vec<_>>(),
+        ),
+        "portfolio" => {
+            if let Some(account_id) = &query.account_id {
+                mongo_db.get_account(account_id).await?.ok_or_else(|| {
+                    ApiError::NotFound(format!("Account {} not found", account_id))
+                })?;
+            }
+            Some(portfolio_figis(&mongo_db, query.account_id.as_deref()).await?)
+        }
+        "all" => None,
+        other => {
+            return Err(ApiError::InvalidParameter(format!(
+                "Parameter 'scope' must be watchlist, portfolio or all, got '{}'",
+                other
+            )))
+        }
+    };
+
+    let dividends = mongo_db
+        .find_dividends(figis.as_deref(), &from, &to)
+        .await?;
+    Ok(Json(dividends))
+}
+
+/// GET /api/dividends/{figi}
+///
+/// Сохранённая история и объявленные выплаты инструмента, новые первыми
+pub async fn get_dividend_history(
+    Extension(mongo_db): Extension<MongoDb>,
+    Path(figi): Path<String>,
+) -> Result<Json<Vec<DbDividend>>, ApiError> {
+    if mongo_db.find_instrument_by_figi(&figi).await.is_none() {
+        return Err(ApiError::UnknownFigi(format!("Instrument with FIGI {} not found", figi)));
+    }
+    Ok(Json(mongo_db.find_dividend_history(&figi).await?))
+}
+
+/// GET /api/portfolio/{account_id}/dividends?days=365
+///
+/// Ожидаемый дивидендный доход счёта по текущим позициям
+pub async fn get_dividend_projection(
+    Extension(mongo_db): Extension<MongoDb>,
+    Path(account_id): Path<String>,
+    Query(query): Query<DividendProjectionQuery>,
+) -> Result<Json<DividendProjection>, ApiError> {
+    mongo_db
+        .get_account(&account_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Account {} not found", account_id)))?;
+    let (from, to) = horizon(query.days, DEFAULT_PROJECTION_DAYS)?;
+
+    let snapshot = mongo_db
+        .get_latest_portfolio_snapshot(&account_id)
+        .await?
+        .ok_or_else(|| {
+            ApiError::NotFound(format!("No portfolio snapshots for account {}", account_id))
+        })?;
+    let figis: Vec<String> = snapshot
+        .positions
+        .iter()
+        .map(|position| position.figi.clone())
+        .collect();
+    let dividends = mongo_db.find_dividends(Some(&figis), &from, &to).await?;
+
+    Ok(Json(project_dividends(&snapshot, &dividends, &from, &to)))
+}
@@ -0,0 +1,91 @@
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+use tracing::error;
+
+/// Ошибки HTTP API, отдаются клиенту в виде JSON `{ "error": "...", "message": "..." }`
+#[derive(Debug)]
+pub enum ApiError {
+    /// Неизвестный или неподдерживаемый интервал свечей
+    InvalidInterval(String),
+    /// Некорректный диапазон дат (from >= to, неверный формат)
+    InvalidRange(String),
+    /// Запрошенный диапазон лежит вне сохранённых данных
+    OutOfRange(String),
+    /// Инструмент с таким FIGI не найден
+    UnknownFigi(String),
+    /// Некорректный параметр запроса
+    InvalidParameter(String),
+    /// Запись не найдена
+    NotFound(String),
+    /// Запись уже существует
+    Conflict(String),
+    /// Ошибка базы данных
+    Database(String),
+}
+
+#[derive(Debug, Serialize)]
+struct ApiErrorBody {
+    error: &'static str,
+    message: String,
+}
+
+impl ApiError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ApiError::InvalidInterval(_)
+            | ApiError::InvalidRange(_)
+            | ApiError::InvalidParameter(_) => StatusCode::BAD_REQUEST,
+            ApiError::OutOfRange(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            ApiError::UnknownFigi(_) | ApiError::NotFound(_) => StatusCode::NOT_FOUND,
+            ApiError::Conflict(_) => StatusCode::CONFLICT,
+            ApiError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn code(&self) -> &'static str {
+        match self {
+            ApiError::InvalidInterval(_) => "invalid_interval",
+            ApiError::InvalidRange(_) => "invalid_range",
+            ApiError::OutOfRange(_) => "out_of_range",
+            ApiError::UnknownFigi(_) => "unknown_figi",
+            ApiError::InvalidParameter(_) => "invalid_parameter",
+            ApiError::NotFound(_) => "not_found",
+            ApiError::Conflict(_) => "conflict",
+            ApiError::Database(_) => "database_error",
+        }
+    }
+
+    fn message(&self) -> String {
+        match self {
+            ApiError::InvalidInterval(m)
+            | ApiError::InvalidRange(m)
+            | ApiError::OutOfRange(m)
+            | ApiError::UnknownFigi(m)
+            | ApiError::InvalidParameter(m)
+            | ApiError::NotFound(m)
+            | ApiError::Conflict(m)
+            | ApiError::Database(m) => m.clone(),
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = ApiErrorBody {
+            error: self.code(),
+            message: self.message(),
+        };
+        (self.status_code(), Json(body)).into_response()
+    }
+}
+
+impl From<mongodb::error::Error> for ApiError {
+    fn from(e: mongodb::error::Error) -> Self {
+        error!("Database error while handling API request: {}", e);
+        ApiError::Database("Database request failed".to_string())
+    }
+}
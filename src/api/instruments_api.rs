@@ -0,0 +1,166 @@
+use axum::{
+    extract::{Extension, Path, Query},
+    Json,
+};
+use chrono::{Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::features::{
+    core::models::instrument::{InstrumentKind, TinkoffInstrumentEnum},
+    db::{
+        mongo_extensions::{
+            instrument_changelog::models::DbInstrumentChange,
+            instruments::models::InstrumentSearchFilter,
+        },
+        MongoDb,
+    },
+};
+
+use super::{
+    error::ApiError,
+    params::{pagination, parse_datetime_param},
+};
+
+/// Период журнала изменений по умолчанию, дней
+const DEFAULT_CHANGES_DAYS: i64 = 30;
+
+#[derive(Debug, Deserialize)]
+pub struct InstrumentsQuery {
+    pub ticker: Option<String>,
+    pub isin: Option<String>,
+    pub figi: Option<String>,
+    pub uid: Option<String>,
+    pub name: Option<String>,
+    /// Один или несколько типов через запятую: share, bond, etf, future, option, currency
+    pub instrument_type: Option<String>,
+    pub currency: Option<String>,
+    pub exchange: Option<String>,
+    pub trading_status: Option<String>,
+    /// Показывать инструменты, пропавшие из каталога API
+    #[serde(default)]
+    pub include_delisted: bool,
+    pub page: Option<u64>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct InstrumentChangesQuery {
+    /// RFC 3339 или YYYY-MM-DD; по умолчанию 30 дней назад
+    pub since: Option<String>,
+    /// Только записи, где менялось это поле, например `trading_status`
+    pub field: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct InstrumentsPageDto {
+    pub page: u64,
+    pub limit: i64,
+    pub total: u64,
+    pub has_more: bool,
+    pub instruments: Vec<TinkoffInstrumentEnum>,
+}
+
+/// Пустые строки в query-параметрах считаем отсутствующими
+fn non_empty(value: Option<String>) -> Option<String> {
+    value
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+impl InstrumentsQuery {
+    fn to_filter(&self) -> Result<InstrumentSearchFilter, ApiError> {
+        let mut kinds = Vec::new();
+        if let Some(types) = non_empty(self.instrument_type.clone()) {
+            for value in types.split(',').filter(|v| !v.trim().is_empty()) {
+                let kind = InstrumentKind::parse(value).ok_or_else(|| {
+                    ApiError::InvalidParameter(format!(
+                        "Unknown instrument_type '{}', expected share, bond, etf, future, option or currency",
+                        value.trim()
+                    ))
+                })?;
+                kinds.push(kind);
+            }
+        }
+
+        Ok(InstrumentSearchFilter {
+            ticker: non_empty(self.ticker.clone()),
+            isin: non_empty(self.isin.clone()),
+            figi: non_empty(self.figi.clone()),
+            uid: non_empty(self.uid.clone()),
+            name: non_empty(self.name.clone()),
+            kinds,
+            currency: non_empty(self.currency.clone()),
+            exchange: non_empty(self.exchange.clone()),
+            trading_status: non_empty(self.trading_status.clone()),
+            include_delisted: self.include_delisted,
+        })
+    }
+}
+
+/// GET /api/instruments?ticker=&isin=&figi=&uid=&name=&instrument_type=&currency=&exchange=&trading_status=&include_delisted=
+///
+/// Поиск сразу по акциям, облигациям, ETF и фьючерсам
+pub async fn search_instruments(
+    Extension(mongo_db): Extension<MongoDb>,
+    Query(query): Query<InstrumentsQuery>,
+) -> Result<Json<InstrumentsPageDto>, ApiError> {
+    let filter = query.to_filter()?;
+    let (page, skip, limit) = pagination(query.page, query.limit)?;
+
+    let result = mongo_db.search_instruments(&filter, skip, limit).await?;
+    let returned = result.instruments.len() as u64;
+
+    Ok(Json(InstrumentsPageDto {
+        page,
+        limit,
+        total: result.total,
+        has_more: skip + returned < result.total,
+        instruments: result.instruments,
+    }))
+}
+
+/// GET /api/instruments/{figi}
+///
+/// Вместо FIGI можно передать uid инструмента
+pub async fn get_instrument(
+    Extension(mongo_db): Extension<MongoDb>,
+    Path(figi): Path<String>,
+) -> Result<Json<TinkoffInstrumentEnum>, ApiError> {
+    // У опционов нет FIGI, их можно запросить по uid
+    let instrument = match mongo_db.find_instrument_by_figi(&figi).await {
+        Some(instrument) => Some(instrument),
+        None => mongo_db.find_instrument_by_uid(&figi).await,
+    };
+    instrument
+        .map(Json)
+        .ok_or_else(|| ApiError::UnknownFigi(format!("Instrument with FIGI {} not found", figi)))
+}
+
+/// GET /api/instruments/{figi}/changes?since=2025-03-01&field=klong
+///
+/// Что менялось в каталоге по инструменту начиная с даты; вместо FIGI можно передать uid
+pub async fn get_instrument_changes(
+    Extension(mongo_db): Extension<MongoDb>,
+    Path(figi): Path<String>,
+    Query(query): Query<InstrumentChangesQuery>,
+) -> Result<Json<Vec<DbInstrumentChange>>, ApiError> {
+    if mongo_db.find_instrument_by_figi(&figi).await.is_none()
+        && mongo_db.find_instrument_by_uid(&figi).await.is_none()
+    {
+        return Err(ApiError::UnknownFigi(format!(
+            "Instrument with FIGI {} not found",
+            figi
+        )));
+    }
+
+    let since = match non_empty(query.since.clone()) {
+        Some(value) => parse_datetime_param("since", &value)?,
+        None => Utc::now() - Duration::days(DEFAULT_CHANGES_DAYS),
+    };
+    let field = non_empty(query.field.clone());
+
+    let changes = mongo_db
+        .find_instrument_changes(&figi, &since.to_rfc3339(), field.as_deref())
+        .await?;
+    Ok(Json(changes))
+}
@@ -1,5 +1,29 @@
+pub mod bonds_api;
+pub mod candles_api;
+pub mod currency_rates_api;
+pub mod dividends_api;
+pub mod error;
 pub mod health_api;
 pub mod health_db;
+pub mod instruments_api;
+pub mod params;
+pub mod portfolio_api;
+pub mod tax_api;
+pub mod watchlists_api;
 
+pub use bonds_api::{
+    get_bond_accrued_interest, get_bond_analytics, get_bond_analytics_history, get_bond_coupons,
+    get_coupon_projection,
+};
+pub use candles_api::{get_candle_gaps, get_candles};
+pub use currency_rates_api::{get_currency_rate, get_currency_rate_history};
+pub use dividends_api::{get_dividend_history, get_dividend_projection, get_upcoming_dividends};
 pub use health_api::health_api;
 pub use health_db::health_db;
+pub use instruments_api::{get_instrument, get_instrument_changes, search_instruments};
+pub use portfolio_api::{
+    get_account_pnl, get_aggregate_pnl, get_operations, get_portfolio, get_portfolio_snapshot,
+    get_portfolio_snapshots, get_portfolio_valuation, list_accounts,
+};
+pub use tax_api::{get_ndfl_report, get_ndfl_report_csv};
+pub use watchlists_api::{create_watchlist, delete_watchlist, list_watchlists, update_watchlist};
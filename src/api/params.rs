@@ -0,0 +1,99 @@
+use chrono::{DateTime, NaiveDate, Utc};
+
+use crate::features::db::mongo_extensions::currency_rates::models::CurrencyRateSources;
+
+use super::error::ApiError;
+
+/// Размер страницы по умолчанию
+pub const DEFAULT_PAGE_LIMIT: i64 = 500;
+/// Максимальный размер страницы
+pub const MAX_PAGE_LIMIT: i64 = 5000;
+
+/// Разбирает дату из query-параметра: RFC 3339 (`2025-03-14T10:00:00Z`)
+/// или просто дата (`2025-03-14`, полночь UTC)
+pub fn parse_datetime_param(name: &str, value: &str) -> Result<DateTime<Utc>, ApiError> {
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(value) {
+        return Ok(datetime.with_timezone(&Utc));
+    }
+
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|datetime| datetime.and_utc())
+        .ok_or_else(|| {
+            ApiError::InvalidRange(format!(
+                "Parameter '{}' must be an RFC 3339 timestamp or a YYYY-MM-DD date, got '{}'",
+                name, value
+            ))
+        })
+}
+
+/// Разбирает календарный день из параметра: `2025-03-14`
+pub fn parse_day_param(name: &str, value: &str) -> Result<NaiveDate, ApiError> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| {
+        ApiError::InvalidRange(format!(
+            "Parameter '{}' must be a YYYY-MM-DD date, got '{}'",
+            name, value
+        ))
+    })
+}
+
+/// Источник курсов из параметра `source`, по умолчанию курс ЦБ
+pub fn parse_rate_source(source: Option<&str>) -> Result<&'static str, ApiError> {
+    let source = source.unwrap_or(CurrencyRateSources::CBRF);
+    CurrencyRateSources::ALL
+        .into_iter()
+        .find(|known| *known == source)
+        .ok_or_else(|| {
+            ApiError::InvalidParameter(format!(
+                "Unknown rate source '{}', expected one of: {}",
+                source,
+                CurrencyRateSources::ALL.join(", ")
+            ))
+        })
+}
+
+/// Проверяет параметры пагинации и возвращает (page, skip, limit).
+/// Страницы нумеруются с 1
+pub fn pagination(page: Option<u64>, limit: Option<i64>) -> Result<(u64, u64, i64), ApiError> {
+    let page = page.unwrap_or(1);
+    let limit = limit.unwrap_or(DEFAULT_PAGE_LIMIT);
+
+    if page == 0 {
+        return Err(ApiError::InvalidParameter(
+            "Parameter 'page' starts from 1".to_string(),
+        ));
+    }
+    if limit <= 0 || limit > MAX_PAGE_LIMIT {
+        return Err(ApiError::InvalidParameter(format!(
+            "Parameter 'limit' must be between 1 and {}",
+            MAX_PAGE_LIMIT
+        )));
+    }
+
+    Ok((page, (page - 1) * limit as u64, limit))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_datetime_param() {
+        let date = parse_datetime_param("from", "2025-03-14").unwrap();
+        assert_eq!(date.to_rfc3339(), "2025-03-14T00:00:00+00:00");
+
+        let datetime = parse_datetime_param("from", "2025-03-14T10:00:00+03:00").unwrap();
+        assert_eq!(datetime.to_rfc3339(), "2025-03-14T07:00:00+00:00");
+
+        assert!(parse_datetime_param("from", "14.03.2025").is_err());
+    }
+
+    #[test]
+    fn test_pagination() {
+        assert_eq!(pagination(None, None).unwrap(), (1, 0, DEFAULT_PAGE_LIMIT));
+        assert_eq!(pagination(Some(3), Some(100)).unwrap(), (3, 200, 100));
+        assert!(pagination(Some(0), None).is_err());
+        assert!(pagination(None, Some(MAX_PAGE_LIMIT + 1)).is_err());
+    }
+}
@@ -0,0 +1,497 @@
+use axum::{
+    extract::{Extension, Path, Query},
+    Json,
+};
+use std::sync::Arc;
+
+use chrono::{Duration, Utc};
+use mongodb::bson::doc;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::features::{
+    core::models::{money_value::TinkoffMoneyValueModel, quotation::TinkoffQuotationModel},
+    db::{
+        mongo_extensions::{
+            operations::models::DbOperation,
+            portfolio::models::{
+                DbAccount, DbPortfolioPosition, DbPortfolioSnapshot, DbPortfolioTotals,
+            },
+        },
+        MongoDb,
+    },
+    portfolio::{
+        pnl::{AccountPnl, AggregatePnl, PnlService},
+        valuation::{PortfolioValuation, ValuationService},
+    },
+};
+
+use super::{
+    error::ApiError,
+    params::{pagination, parse_datetime_param, parse_day_param, parse_rate_source},
+};
+
+/// Глубина истории снимков по умолчанию
+const DEFAULT_HISTORY_DAYS: i64 = 30;
+
+#[derive(Debug, Deserialize)]
+pub struct SnapshotsQuery {
+    /// Первый день, YYYY-MM-DD
+    pub from: Option<String>,
+    /// Последний день включительно, YYYY-MM-DD
+    pub to: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OperationsQuery {
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub figi: Option<String>,
+    /// Тип операции как в контракте: OPERATION_TYPE_BUY, OPERATION_TYPE_DIVIDEND ...
+    #[serde(rename = "type")]
+    pub operation_type: Option<String>,
+    pub page: Option<u64>,
+    pub limit: Option<i64>,
+}
+
+/// Сумма в валюте
+#[derive(Debug, Serialize)]
+pub struct MoneyDto {
+    pub value: Decimal,
+    pub currency: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PortfolioPositionDto {
+    pub figi: String,
+    pub instrument_uid: String,
+    pub instrument_type: String,
+    pub quantity: Decimal,
+    pub balance: Option<i64>,
+    pub blocked_balance: Option<i64>,
+    pub average_price: Option<MoneyDto>,
+    pub average_price_fifo: Option<MoneyDto>,
+    pub current_price: Option<MoneyDto>,
+    /// Стоимость позиции по текущей цене
+    pub current_value: Option<MoneyDto>,
+    pub current_nkd: Option<MoneyDto>,
+    pub expected_yield: Option<Decimal>,
+    pub var_margin: Option<MoneyDto>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PortfolioTotalsDto {
+    pub portfolio: Option<MoneyDto>,
+    pub shares: Option<MoneyDto>,
+    pub bonds: Option<MoneyDto>,
+    pub etf: Option<MoneyDto>,
+    pub currencies: Option<MoneyDto>,
+    pub futures: Option<MoneyDto>,
+    pub options: Option<MoneyDto>,
+    pub structured_notes: Option<MoneyDto>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PortfolioSnapshotDto {
+    pub account_id: String,
+    pub day: String,
+    pub taken_at: String,
+    pub currency: String,
+    pub totals: PortfolioTotalsDto,
+    pub expected_yield: Option<Decimal>,
+    pub positions: Vec<PortfolioPositionDto>,
+    pub money: Vec<MoneyDto>,
+    pub blocked_money: Vec<MoneyDto>,
+}
+
+/// Краткая запись истории: итог портфеля за день
+#[derive(Debug, Serialize)]
+pub struct PortfolioHistoryItemDto {
+    pub day: String,
+    pub taken_at: String,
+    pub total: Option<MoneyDto>,
+    pub expected_yield: Option<Decimal>,
+    pub positions: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PortfolioHistoryDto {
+    pub account_id: String,
+    pub from: String,
+    pub to: String,
+    pub snapshots: Vec<PortfolioHistoryItemDto>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OperationDto {
+    pub operation_id: String,
+    pub parent_operation_id: String,
+    pub operation_type: String,
+    pub name: String,
+    pub description: String,
+    pub date: String,
+    pub figi: String,
+    pub instrument_type: String,
+    pub quantity: i64,
+    pub quantity_done: i64,
+    pub payment: Option<MoneyDto>,
+    pub price: Option<MoneyDto>,
+    pub commission: Option<MoneyDto>,
+    pub accrued_int: Option<MoneyDto>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OperationsPageDto {
+    pub account_id: String,
+    pub from: String,
+    pub to: String,
+    pub page: u64,
+    pub limit: i64,
+    pub total: u64,
+    pub has_more: bool,
+    pub operations: Vec<OperationDto>,
+}
+
+fn money(value: &Option<TinkoffMoneyValueModel>) -> Option<MoneyDto> {
+    value.as_ref().map(MoneyDto::from)
+}
+
+fn quotation(value: &Option<TinkoffQuotationModel>) -> Option<Decimal> {
+    value.as_ref().map(|q| q.value)
+}
+
+impl From<&TinkoffMoneyValueModel> for MoneyDto {
+    fn from(model: &TinkoffMoneyValueModel) -> Self {
+        Self {
+            value: model.value,
+            currency: model.currency.clone(),
+        }
+    }
+}
+
+impl From<&DbPortfolioPosition> for PortfolioPositionDto {
+    fn from(position: &DbPortfolioPosition) -> Self {
+        let quantity = quotation(&position.quantity).unwrap_or_default();
+        let current_value = position.current_price.as_ref().map(|price| MoneyDto {
+            value: price.value * quantity,
+            currency: price.currency.clone(),
+        });
+
+        Self {
+            figi: position.figi.clone(),
+            instrument_uid: position.instrument_uid.clone(),
+            instrument_type: position.instrument_type.clone(),
+            quantity,
+            balance: position.balance,
+            blocked_balance: position.blocked_balance,
+            average_price: money(&position.average_position_price),
+            average_price_fifo: money(&position.average_position_price_fifo),
+            current_price: money(&position.current_price),
+            current_value,
+            current_nkd: money(&position.current_nkd),
+            expected_yield: quotation(&position.expected_yield),
+            var_margin: money(&position.var_margin),
+        }
+    }
+}
+
+impl From<&DbPortfolioTotals> for PortfolioTotalsDto {
+    fn from(totals: &DbPortfolioTotals) -> Self {
+        Self {
+            portfolio: money(&totals.portfolio),
+            shares: money(&totals.shares),
+            bonds: money(&totals.bonds),
+            etf: money(&totals.etf),
+            currencies: money(&totals.currencies),
+            futures: money(&totals.futures),
+            options: money(&totals.options),
+            structured_notes: money(&totals.structured_notes),
+        }
+    }
+}
+
+impl From<DbPortfolioSnapshot> for PortfolioSnapshotDto {
+    fn from(snapshot: DbPortfolioSnapshot) -> Self {
+        Self {
+            totals: PortfolioTotalsDto::from(&snapshot.totals),
+            expected_yield: quotation(&snapshot.expected_yield),
+            positions: snapshot.positions.iter().map(PortfolioPositionDto::from).collect(),
+            money: snapshot.money.iter().map(MoneyDto::from).collect(),
+            blocked_money: snapshot.blocked_money.iter().map(MoneyDto::from).collect(),
+            account_id: snapshot.account_id,
+            day: snapshot.day,
+            taken_at: snapshot.taken_at,
+            currency: snapshot.currency,
+        }
+    }
+}
+
+impl From<&DbPortfolioSnapshot> for PortfolioHistoryItemDto {
+    fn from(snapshot: &DbPortfolioSnapshot) -> Self {
+        Self {
+            day: snapshot.day.clone(),
+            taken_at: snapshot.taken_at.clone(),
+            total: money(&snapshot.totals.portfolio),
+            expected_yield: quotation(&snapshot.expected_yield),
+            positions: snapshot.positions.len(),
+        }
+    }
+}
+
+impl From<DbOperation> for OperationDto {
+    fn from(operation: DbOperation) -> Self {
+        Self {
+            payment: money(&operation.payment),
+            price: money(&operation.price),
+            commission: money(&operation.commission),
+            accrued_int: money(&operation.accrued_int),
+            operation_id: operation.operation_id,
+            parent_operation_id: operation.parent_operation_id,
+            operation_type: operation.operation_type,
+            name: operation.name,
+            description: operation.description,
+            date: operation.date,
+            figi: operation.figi,
+            instrument_type: operation.instrument_type,
+            quantity: operation.quantity,
+            quantity_done: operation.quantity_done,
+        }
+    }
+}
+
+/// Счёт должен быть известен по последней синхронизации счетов
+async fn ensure_account(mongo_db: &MongoDb, account_id: &str) -> Result<DbAccount, ApiError> {
+    mongo_db
+        .get_account(account_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Account {} not found", account_id)))
+}
+
+/// GET /api/portfolio/accounts
+pub async fn list_accounts(
+    Extension(mongo_db): Extension<MongoDb>,
+) -> Result<Json<Vec<DbAccount>>, ApiError> {
+    Ok(Json(mongo_db.get_accounts().await?))
+}
+
+/// GET /api/portfolio/{account_id}
+///
+/// Последний сохранённый снимок портфеля счёта
+pub async fn get_portfolio(
+    Extension(mongo_db): Extension<MongoDb>,
+    Path(account_id): Path<String>,
+) -> Result<Json<PortfolioSnapshotDto>, ApiError> {
+    ensure_account(&mongo_db, &account_id).await?;
+
+    let snapshot = mongo_db
+        .get_latest_portfolio_snapshot(&account_id)
+        .await?
+        .ok_or_else(|| {
+            ApiError::NotFound(format!("No portfolio snapshots for account {}", account_id))
+        })?;
+
+    Ok(Json(PortfolioSnapshotDto::from(snapshot)))
+}
+
+/// GET /api/portfolio/{account_id}/snapshots?from=&to=
+///
+/// История итогов портфеля по дням; по умолчанию за последние 30 дней
+pub async fn get_portfolio_snapshots(
+    Extension(mongo_db): Extension<MongoDb>,
+    Path(account_id): Path<String>,
+    Query(query): Query<SnapshotsQuery>,
+) -> Result<Json<PortfolioHistoryDto>, ApiError> {
+    ensure_account(&mongo_db, &account_id).await?;
+
+    let to = match &query.to {
+        Some(value) => parse_day_param("to", value)?,
+        None => Utc::now().date_naive(),
+    };
+    let from = match &query.from {
+        Some(value) => parse_day_param("from", value)?,
+        None => to - Duration::days(DEFAULT_HISTORY_DAYS),
+    };
+    if from > to {
+        return Err(ApiError::InvalidRange(format!(
+            "'from' ({}) must not be later than 'to' ({})",
+            from, to
+        )));
+    }
+
+    let from = from.format("%Y-%m-%d").to_string();
+    let to = to.format("%Y-%m-%d").to_string();
+    let snapshots = mongo_db
+        .get_portfolio_snapshots(&account_id, &from, &to)
+        .await?;
+
+    Ok(Json(PortfolioHistoryDto {
+        account_id,
+        from,
+        to,
+        snapshots: snapshots.iter().map(PortfolioHistoryItemDto::from).collect(),
+    }))
+}
+
+/// GET /api/portfolio/{account_id}/snapshots/{day}
+pub async fn get_portfolio_snapshot(
+    Extension(mongo_db): Extension<MongoDb>,
+    Path((account_id, day)): Path<(String, String)>,
+) -> Result<Json<PortfolioSnapshotDto>, ApiError> {
+    ensure_account(&mongo_db, &account_id).await?;
+    let day = parse_day_param("day", &day)?.format("%Y-%m-%d").to_string();
+
+    let snapshot = mongo_db
+        .get_portfolio_snapshot(&account_id, &day)
+        .await?
+        .ok_or_else(|| {
+            ApiError::NotFound(format!(
+                "No portfolio snapshot for account {} on {}",
+                account_id, day
+            ))
+        })?;
+
+    Ok(Json(PortfolioSnapshotDto::from(snapshot)))
+}
+
+/// GET /api/portfolio/{account_id}/operations?from=&to=&figi=&type=&page=&limit=
+///
+/// Операции из журнала счёта; по умолчанию за последние 30 дней
+pub async fn get_operations(
+    Extension(mongo_db): Extension<MongoDb>,
+    Path(account_id): Path<String>,
+    Query(query): Query<OperationsQuery>,
+) -> Result<Json<OperationsPageDto>, ApiError> {
+    ensure_account(&mongo_db, &account_id).await?;
+
+    let to = match &query.to {
+        Some(value) => parse_datetime_param("to", value)?,
+        None => Utc::now(),
+    };
+    let from = match &query.from {
+        Some(value) => parse_datetime_param("from", value)?,
+        None => to - Duration::days(DEFAULT_HISTORY_DAYS),
+    };
+    if from >= to {
+        return Err(ApiError::InvalidRange(format!(
+            "'from' ({}) must be earlier than 'to' ({})",
+            from.to_rfc3339(),
+            to.to_rfc3339()
+        )));
+    }
+
+    let (page, skip, limit) = pagination(query.page, query.limit)?;
+
+    let mut filter = doc! {};
+    if let Some(figi) = &query.figi {
+        filter.insert("figi", figi.trim().to_uppercase());
+    }
+    if let Some(operation_type) = &query.operation_type {
+        filter.insert("operation_type", operation_type.trim().to_uppercase());
+    }
+
+    let result = mongo_db
+        .find_operations(
+            &account_id,
+            filter,
+            from.timestamp(),
+            to.timestamp(),
+            skip,
+            limit,
+        )
+        .await?;
+    let returned = result.operations.len() as u64;
+
+    Ok(Json(OperationsPageDto {
+        account_id,
+        from: from.to_rfc3339(),
+        to: to.to_rfc3339(),
+        page,
+        limit,
+        total: result.total,
+        has_more: skip + returned < result.total,
+        operations: result.operations.into_iter().map(OperationDto::from).collect(),
+    }))
+}
+
+/// GET /api/portfolio/{account_id}/pnl
+///
+/// Реализованный и нереализованный результат по позициям счёта (FIFO)
+pub async fn get_account_pnl(
+    Extension(mongo_db): Extension<MongoDb>,
+    Extension(pnl): Extension<Arc<PnlService>>,
+    Path(account_id): Path<String>,
+) -> Result<Json<AccountPnl>, ApiError> {
+    ensure_account(&mongo_db, &account_id).await?;
+
+    let report = pnl
+        .account_pnl(&account_id)
+        .await
+        .map_err(ApiError::Database)?;
+    Ok(Json(report))
+}
+
+/// GET /api/portfolio/pnl
+///
+/// Результат по каждому счёту и суммарно по всем счетам
+pub async fn get_aggregate_pnl(
+    Extension(pnl): Extension<Arc<PnlService>>,
+) -> Result<Json<AggregatePnl>, ApiError> {
+    let report = pnl.aggregate_pnl().await.map_err(ApiError::Database)?;
+    Ok(Json(report))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ValuationQuery {
+    /// Базовая валюта: RUB (по умолчанию), USD, CNY ...
+    pub currency: Option<String>,
+    /// Момент оценки; по умолчанию — последний снимок портфеля
+    pub at: Option<String>,
+    /// Источник курсов: cbrf (по умолчанию), exchange или wap
+    pub source: Option<String>,
+}
+
+/// GET /api/portfolio/{account_id}/valuation?currency=USD&at=2025-03-14T12:00:00Z
+///
+/// Стоимость позиций и денег счёта в базовой валюте по архивным курсам
+pub async fn get_portfolio_valuation(
+    Extension(mongo_db): Extension<MongoDb>,
+    Extension(valuation): Extension<Arc<ValuationService>>,
+    Path(account_id): Path<String>,
+    Query(query): Query<ValuationQuery>,
+) -> Result<Json<PortfolioValuation>, ApiError> {
+    ensure_account(&mongo_db, &account_id).await?;
+
+    let currency = query.currency.as_deref().unwrap_or("RUB").to_uppercase();
+    if currency.len() != 3 || !currency.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Err(ApiError::InvalidParameter(format!(
+            "Parameter 'currency' must be a 3-letter currency code, got '{}'",
+            currency
+        )));
+    }
+    let source = parse_rate_source(query.source.as_deref())?;
+    let at = query
+        .at
+        .as_deref()
+        .map(|value| parse_datetime_param("at", value))
+        .transpose()?;
+    if at.is_some_and(|at| at > Utc::now()) {
+        return Err(ApiError::OutOfRange(
+            "Parameter 'at' must not be in the future".to_string(),
+        ));
+    }
+
+    let report = valuation
+        .account_valuation(&account_id, &currency, at, source)
+        .await
+        .map_err(ApiError::Database)?
+        .ok_or_else(|| {
+            ApiError::NotFound(format!("No portfolio snapshots for account {}", account_id))
+        })?;
+    if report.missing.iter().any(|item| *item == format!("{} rate", currency)) {
+        return Err(ApiError::InvalidParameter(format!(
+            "No {} rate for {} on {}",
+            source, currency, report.valued_at
+        )));
+    }
+    Ok(Json(report))
+}
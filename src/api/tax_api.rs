@@ -0,0 +1,86 @@
+use axum::{
+    extract::{Extension, Path, Query},
+    http::header,
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{Datelike, Utc};
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::features::{
+    db::MongoDb,
+    tax::{csv::ndfl_report_csv, ndfl::NdflReport, TaxService},
+};
+
+use super::error::ApiError;
+
+/// Первый год, за который есть операции брокера
+const MIN_TAX_YEAR: i32 = 2016;
+
+#[derive(Debug, Deserialize)]
+pub struct NdflQuery {
+    /// Отчёт только по одному счёту
+    pub account_id: Option<String>,
+}
+
+async fn build_report(
+    mongo_db: &MongoDb,
+    tax: &TaxService,
+    year: i32,
+    query: &NdflQuery,
+) -> Result<NdflReport, ApiError> {
+    let current_year = Utc::now().year();
+    if !(MIN_TAX_YEAR..=current_year).contains(&year) {
+        return Err(ApiError::OutOfRange(format!(
+            "Year must be between {} and {}, got {}",
+            MIN_TAX_YEAR, current_year, year
+        )));
+    }
+
+    if let Some(account_id) = &query.account_id {
+        mongo_db
+            .get_account(account_id)
+            .await?
+            .ok_or_else(|| ApiError::NotFound(format!("Account {} not found", account_id)))?;
+    }
+
+    tax.ndfl_report(year, query.account_id.as_deref())
+        .await
+        .map_err(ApiError::Database)
+}
+
+/// GET /api/tax/ndfl/{year}
+///
+/// Оценка НДФЛ за год: результат по сделкам FIFO в рублях, купоны, дивиденды и удержанный налог
+pub async fn get_ndfl_report(
+    Extension(mongo_db): Extension<MongoDb>,
+    Extension(tax): Extension<Arc<TaxService>>,
+    Path(year): Path<i32>,
+    Query(query): Query<NdflQuery>,
+) -> Result<Json<NdflReport>, ApiError> {
+    let report = build_report(&mongo_db, &tax, year, &query).await?;
+    Ok(Json(report))
+}
+
+/// GET /api/tax/ndfl/{year}/csv
+///
+/// Тот же отчёт в CSV: закрытые лоты и итоговые суммы
+pub async fn get_ndfl_report_csv(
+    Extension(mongo_db): Extension<MongoDb>,
+    Extension(tax): Extension<Arc<TaxService>>,
+    Path(year): Path<i32>,
+    Query(query): Query<NdflQuery>,
+) -> Result<Response, ApiError> {
+    let report = build_report(&mongo_db, &tax, year, &query).await?;
+    let filename = format!("attachment; filename=\"ndfl_{}.csv\"", year);
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (header::CONTENT_DISPOSITION, filename),
+        ],
+        ndfl_report_csv(&report),
+    )
+        .into_response())
+}
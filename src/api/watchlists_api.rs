@@ -0,0 +1,207 @@
+use axum::{
+    extract::{Extension, Path},
+    http::StatusCode,
+    Json,
+};
+use mongodb::bson::{doc, oid::ObjectId};
+use serde::{Deserialize, Serialize};
+
+use crate::features::{
+    db::{
+        mongo_extensions::watchlists::models::{
+            DbUserConfigWatchlist, DbWatchlistSubscriptions, ORDER_BOOK_DEPTHS,
+        },
+        MongoDb,
+    },
+    tinkoff_market_data_stream::MarketDataStreamHandle,
+};
+
+use super::error::ApiError;
+
+#[derive(Debug, Deserialize)]
+pub struct CreateWatchlistRequest {
+    pub figi: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    pub notes: Option<String>,
+    /// Виды данных стрима; по умолчанию только минутные свечи
+    #[serde(default)]
+    pub subscriptions: DbWatchlistSubscriptions,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateWatchlistRequest {
+    pub enabled: Option<bool>,
+    pub notes: Option<String>,
+    pub subscriptions: Option<DbWatchlistSubscriptions>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WatchlistDto {
+    pub id: String,
+    pub figi: String,
+    pub ticker: String,
+    pub exchange: String,
+    pub trading_mode: String,
+    pub isin: String,
+    pub enabled: bool,
+    pub notes: Option<String>,
+    pub subscriptions: DbWatchlistSubscriptions,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl From<DbUserConfigWatchlist> for WatchlistDto {
+    fn from(watchlist: DbUserConfigWatchlist) -> Self {
+        Self {
+            id: watchlist.id.to_hex(),
+            figi: watchlist.figi,
+            ticker: watchlist.ticker,
+            exchange: watchlist.exchange,
+            trading_mode: watchlist.trading_mode,
+            isin: watchlist.isin,
+            enabled: watchlist.enabled,
+            notes: watchlist.notes,
+            subscriptions: watchlist.subscriptions,
+        }
+    }
+}
+
+fn parse_id(id: &str) -> Result<ObjectId, ApiError> {
+    ObjectId::parse_str(id)
+        .map_err(|_| ApiError::InvalidParameter(format!("Invalid watchlist id '{}'", id)))
+}
+
+fn validate_subscriptions(subscriptions: &DbWatchlistSubscriptions) -> Result<(), ApiError> {
+    if !ORDER_BOOK_DEPTHS.contains(&subscriptions.order_book_depth) {
+        return Err(ApiError::InvalidParameter(format!(
+            "Invalid order_book_depth {}: expected one of {:?}",
+            subscriptions.order_book_depth, ORDER_BOOK_DEPTHS
+        )));
+    }
+    Ok(())
+}
+
+/// Приводит подписку стрима в соответствие с записью watchlist
+async fn sync_stream(stream: &MarketDataStreamHandle, watchlist: &DbUserConfigWatchlist) {
+    if watchlist.enabled && !watchlist.subscriptions.is_empty() {
+        stream
+            .subscribe(&watchlist.figi, watchlist.subscriptions)
+            .await;
+    } else {
+        stream.unsubscribe(&watchlist.figi).await;
+    }
+}
+
+/// GET /api/watchlists
+pub async fn list_watchlists(Extension(mongo_db): Extension<MongoDb>) -> Json<Vec<WatchlistDto>> {
+    let watchlists = mongo_db.get_watchlists().await;
+    Json(watchlists.into_iter().map(WatchlistDto::from).collect())
+}
+
+/// POST /api/watchlists
+///
+/// FIGI проверяется по каталогу инструментов, тикер/ISIN/биржа берутся оттуда же
+pub async fn create_watchlist(
+    Extension(mongo_db): Extension<MongoDb>,
+    Extension(stream): Extension<MarketDataStreamHandle>,
+    Json(request): Json<CreateWatchlistRequest>,
+) -> Result<(StatusCode, Json<WatchlistDto>), ApiError> {
+    let figi = request.figi.trim().to_uppercase();
+    validate_subscriptions(&request.subscriptions)?;
+
+    let instrument = mongo_db
+        .find_instrument_by_figi(&figi)
+        .await
+        .ok_or_else(|| ApiError::UnknownFigi(format!("Instrument with FIGI {} not found", figi)))?;
+
+    if mongo_db.get_watchlist_by_figi(&figi).await?.is_some() {
+        return Err(ApiError::Conflict(format!(
+            "Watchlist entry for FIGI {} already exists",
+            figi
+        )));
+    }
+
+    let watchlist = DbUserConfigWatchlist {
+        id: ObjectId::new(),
+        ticker: instrument.ticker().to_string(),
+        exchange: instrument.exchange().to_string(),
+        trading_mode: instrument.class_code().to_string(),
+        isin: instrument.isin().to_string(),
+        figi,
+        enabled: request.enabled,
+        notes: request.notes,
+        subscriptions: request.subscriptions,
+    };
+    mongo_db.insert_watchlist(&watchlist).await?;
+
+    if watchlist.enabled {
+        sync_stream(&stream, &watchlist).await;
+    }
+
+    Ok((StatusCode::CREATED, Json(WatchlistDto::from(watchlist))))
+}
+
+/// PATCH /api/watchlists/{id}
+///
+/// Включение/выключение записи и смена видов данных сразу меняют подписку работающего стрима
+pub async fn update_watchlist(
+    Extension(mongo_db): Extension<MongoDb>,
+    Extension(stream): Extension<MarketDataStreamHandle>,
+    Path(id): Path<String>,
+    Json(request): Json<UpdateWatchlistRequest>,
+) -> Result<Json<WatchlistDto>, ApiError> {
+    let id = parse_id(&id)?;
+
+    let mut set = doc! {};
+    if let Some(enabled) = request.enabled {
+        set.insert("enabled", enabled);
+    }
+    if let Some(notes) = &request.notes {
+        set.insert("notes", notes);
+    }
+    if let Some(subscriptions) = &request.subscriptions {
+        validate_subscriptions(subscriptions)?;
+        let subscriptions = bson::to_document(subscriptions)
+            .map_err(|e| ApiError::InvalidParameter(format!("Invalid subscriptions: {}", e)))?;
+        set.insert("subscriptions", subscriptions);
+    }
+    if set.is_empty() {
+        return Err(ApiError::InvalidParameter(
+            "Nothing to update: expected 'enabled', 'notes' and/or 'subscriptions'".to_string(),
+        ));
+    }
+
+    let watchlist = mongo_db
+        .update_watchlist(id, set)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Watchlist entry {} not found", id)))?;
+
+    if request.enabled.is_some() || request.subscriptions.is_some() {
+        sync_stream(&stream, &watchlist).await;
+    }
+
+    Ok(Json(WatchlistDto::from(watchlist)))
+}
+
+/// DELETE /api/watchlists/{id}
+pub async fn delete_watchlist(
+    Extension(mongo_db): Extension<MongoDb>,
+    Extension(stream): Extension<MarketDataStreamHandle>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    let id = parse_id(&id)?;
+
+    let watchlist = mongo_db
+        .delete_watchlist(id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Watchlist entry {} not found", id)))?;
+
+    if watchlist.enabled {
+        stream.unsubscribe(&watchlist.figi).await;
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
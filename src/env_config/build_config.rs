@@ -18,6 +18,7 @@ impl AppConfig {
 
         let content = fs::read_to_string(path)?;
         let config: AppConfig = toml::from_str(&content)?;
+        config.currency_rates.validate()?;
 
         Ok(config)
     }
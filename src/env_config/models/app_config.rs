@@ -1,6 +1,7 @@
-use chrono::{NaiveTime, Utc};
+use chrono::{NaiveDate, NaiveTime, Utc};
 use chrono_tz::Tz;
 use serde::Deserialize;
+use std::collections::HashMap;
 
 #[derive(Debug, Deserialize)]
 pub struct AppConfig {
@@ -11,8 +12,26 @@ pub struct AppConfig {
     pub tinkoff_market_data_updater: UpdaterConfig,
     pub tinkoff_market_data_stream: UpdaterConfig,
     pub currency_rates_updater: UpdaterConfig,
+    #[serde(default)]
+    pub currency_rates: CurrencyRatesConfig,
     pub historical_candle_data: HistoricalCandleDataConfig,
     pub historical_candle_updater: HistoricalCandleUpdaterConfig,
+    #[serde(default)]
+    pub trading_calendar: TradingCalendarConfig,
+    #[serde(default)]
+    pub candle_rollup: CandleRollupConfig,
+    #[serde(default)]
+    pub portfolio: PortfolioConfig,
+    #[serde(default)]
+    pub operations_ledger: OperationsLedgerConfig,
+    #[serde(default)]
+    pub dividends: DividendsConfig,
+    #[serde(default)]
+    pub bond_coupons: BondCouponsConfig,
+    #[serde(default)]
+    pub bond_analytics: BondAnalyticsConfig,
+    #[serde(default)]
+    pub instrument_changelog: InstrumentChangelogConfig,
 }
 
 #[derive(Debug, Deserialize)]
@@ -54,29 +73,500 @@ pub struct TinkoffApiConfig {
     pub domain: String,
     pub timeout: u64,
     pub keepalive: u64,
+    /// Переопределение лимитов API: имя метода -> запросов в минуту
+    #[serde(default)]
+    pub rate_limits: HashMap<String, u32>,
+    /// Сколько раз повторять запрос после RESOURCE_EXHAUSTED
+    #[serde(default = "default_rate_limit_retries")]
+    pub rate_limit_retries: u32,
+}
+
+fn default_rate_limit_retries() -> u32 {
+    5
 }
 
 #[derive(Debug, Deserialize)]
 pub struct HistoricalCandleDataConfig {
     pub enabled: bool,
     pub max_days_history: u32,
+    /// Дополнительная пауза воркера между запросами; основной темп задаёт лимитер API
+    #[serde(default)]
     pub request_delay_ms: u64,
+    /// Сколько FIGI загружается параллельно
+    #[serde(default = "default_historical_concurrency")]
+    pub max_concurrency: usize,
     pub run_on_startup: bool,
     #[serde(default = "default_false")]
     pub force_update: bool,
+    /// Наборы (тип инструмента, интервал) для загрузки; по умолчанию минутные свечи всех акций
+    #[serde(default = "default_historical_candle_targets")]
+    pub targets: Vec<HistoricalCandleTarget>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct HistoricalCandleTarget {
+    /// share, bond, etf, future или currency; опционы не поддерживаются (нет FIGI)
+    pub instrument_type: String,
+    /// Интервал в короткой форме: 1m, 5m, 1h, 1d ...
+    pub interval: String,
+    #[serde(default)]
+    pub scope: HistoricalCandleScope,
+    /// Глубина истории для этого набора, если отличается от max_days_history
+    pub max_days_history: Option<u32>,
+}
+
+/// Какие инструменты типа попадают в загрузку
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HistoricalCandleScope {
+    /// Все инструменты каталога
+    #[default]
+    All,
+    /// Только активные записи watchlist
+    Watchlist,
+}
+
+fn default_historical_concurrency() -> usize {
+    4
+}
+
+fn default_historical_candle_targets() -> Vec<HistoricalCandleTarget> {
+    vec![HistoricalCandleTarget {
+        instrument_type: "share".to_string(),
+        interval: "1m".to_string(),
+        scope: HistoricalCandleScope::All,
+        max_days_history: None,
+    }]
 }
 
+/// Валюты, курсы которых берутся из ответа MOEX (statistics/engines/currency/markets/selt/rates)
+#[derive(Debug, Deserialize)]
+pub struct CurrencyRatesConfig {
+    #[serde(default = "default_rate_currencies")]
+    pub currencies: Vec<CurrencyRateConfig>,
+    /// С какой даты (YYYY-MM-DD) догружать историю курсов ЦБ при старте обновления
+    #[serde(default = "default_rates_history_start")]
+    pub history_start: String,
+}
+
+impl Default for CurrencyRatesConfig {
+    fn default() -> Self {
+        Self {
+            currencies: default_rate_currencies(),
+            history_start: default_rates_history_start(),
+        }
+    }
+}
+
+fn default_rates_history_start() -> String {
+    "2016-01-01".to_string()
+}
+
+/// Валюта и колонки MOEX, из которых читаются её курсы.
+/// Источники необязательны, но хотя бы один должен быть задан
+#[derive(Debug, Clone, Deserialize)]
+pub struct CurrencyRateConfig {
+    /// Код валюты: USD, EUR, HKD ...
+    pub code: String,
+    pub name: String,
+    pub symbol: String,
+    /// Курс ЦБ РФ из блока cbrf
+    pub cbrf: Option<MoexRateColumns>,
+    /// Биржевой курс из блока cbrf
+    pub exchange: Option<MoexRateColumns>,
+    /// secid средневзвешенного курса в блоке wap_rates: CNYRUB_TOM, HKDRUB_TOM ...
+    pub wap_security_id: Option<String>,
+    /// Код валюты в справочнике ЦБ РФ (R01235 ...) для загрузки истории официального курса
+    pub cbr_id: Option<String>,
+}
+
+/// Колонки блока cbrf с курсом, процентом изменения и датой курса
+#[derive(Debug, Clone, Deserialize)]
+pub struct MoexRateColumns {
+    pub rate: String,
+    pub change: String,
+    pub date: String,
+}
+
+impl CurrencyRatesConfig {
+    /// Проверка конфигурации без обращения к MOEX; колонки сверяются с каждым
+    /// ответом MOEX при обновлении курсов
+    pub fn validate(&self) -> Result<(), String> {
+        let mut codes = std::collections::HashSet::new();
+        for currency in &self.currencies {
+            if currency.code.trim().is_empty() {
+                return Err("currency_rates: currency code must not be empty".to_string());
+            }
+            if !codes.insert(currency.code.to_uppercase()) {
+                return Err(format!("currency_rates: duplicate currency {}", currency.code));
+            }
+            if currency.cbrf.is_none()
+                && currency.exchange.is_none()
+                && currency.wap_security_id.is_none()
+                && currency.cbr_id.is_none()
+            {
+                return Err(format!(
+                    "currency_rates: {} has no cbrf, exchange, wap_security_id or cbr_id source",
+                    currency.code
+                ));
+            }
+        }
+        if NaiveDate::parse_from_str(&self.history_start, "%Y-%m-%d").is_err() {
+            return Err(format!(
+                "currency_rates: invalid history_start '{}', expected YYYY-MM-DD",
+                self.history_start
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl MoexRateColumns {
+    fn new(rate: &str, change: &str, date: &str) -> Self {
+        Self {
+            rate: rate.to_string(),
+            change: change.to_string(),
+            date: date.to_string(),
+        }
+    }
+}
+
+fn default_rate_currencies() -> Vec<CurrencyRateConfig> {
+    vec![
+        CurrencyRateConfig {
+            code: "USD".to_string(),
+            name: "Доллар США".to_string(),
+            symbol: "$".to_string(),
+            cbrf: Some(MoexRateColumns::new(
+                "CBRF_USD_LAST",
+                "CBRF_USD_LASTCHANGEPRCNT",
+                "CBRF_USD_TRADEDATE",
+            )),
+            exchange: Some(MoexRateColumns::new(
+                "USDTOM_UTS_CLOSEPRICE",
+                "USDTOM_UTS_CLOSEPRICETOPREVPRCN",
+                "USDTOM_UTS_TRADEDATE",
+            )),
+            wap_security_id: None,
+            cbr_id: Some("R01235".to_string()),
+        },
+        CurrencyRateConfig {
+            code: "EUR".to_string(),
+            name: "Евро".to_string(),
+            symbol: "€".to_string(),
+            cbrf: Some(MoexRateColumns::new(
+                "CBRF_EUR_LAST",
+                "CBRF_EUR_LASTCHANGEPRCNT",
+                "CBRF_EUR_TRADEDATE",
+            )),
+            exchange: None,
+            wap_security_id: None,
+            cbr_id: Some("R01239".to_string()),
+        },
+        CurrencyRateConfig {
+            code: "CNY".to_string(),
+            name: "Китайский юань".to_string(),
+            symbol: "¥".to_string(),
+            cbrf: None,
+            exchange: None,
+            wap_security_id: Some("CNYRUB_TOM".to_string()),
+            cbr_id: Some("R01375".to_string()),
+        },
+    ]
+}
+
+/// Обновление запускается раз в сутки после закрытия торгов предыдущего торгового дня
 #[derive(Debug, Deserialize)]
 pub struct HistoricalCandleUpdaterConfig {
     pub enabled: bool,
     pub max_retries: u32,
     pub retry_delay_seconds: u64,
-    pub update_start_time: String,
-    pub update_end_time: String,
     pub timezone: String,
     pub run_on_startup: bool,
 }
 
+/// Календарь торгов (InstrumentsService.TradingSchedules)
+#[derive(Debug, Deserialize)]
+pub struct TradingCalendarConfig {
+    /// Площадка, по расписанию которой работает стрим и обновление свечей,
+    /// и чьё расписание используется для площадок без своего
+    #[serde(default = "default_trading_exchange")]
+    pub default_exchange: String,
+    /// На сколько дней вперёд загружать расписание для стрима
+    #[serde(default = "default_schedule_days_ahead")]
+    pub days_ahead: u32,
+}
+
+impl Default for TradingCalendarConfig {
+    fn default() -> Self {
+        Self {
+            default_exchange: default_trading_exchange(),
+            days_ahead: default_schedule_days_ahead(),
+        }
+    }
+}
+
+/// Сборка свечей старших интервалов из минутных
+#[derive(Debug, Deserialize)]
+pub struct CandleRollupConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Интервалы в короткой форме: 5m, 15m, 1h, 1d, 1w, 1mo ...
+    #[serde(default = "default_rollup_intervals")]
+    pub intervals: Vec<String>,
+    /// Часовой пояс биржи, по которому выравниваются свечи
+    #[serde(default = "default_rollup_timezone")]
+    pub timezone: String,
+    /// Как часто собирать минуты, пришедшие из стрима
+    #[serde(default = "default_rollup_flush_seconds")]
+    pub flush_interval_seconds: u64,
+}
+
+impl Default for CandleRollupConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            intervals: default_rollup_intervals(),
+            timezone: default_rollup_timezone(),
+            flush_interval_seconds: default_rollup_flush_seconds(),
+        }
+    }
+}
+
+/// Снимки портфеля (OperationsService.GetPortfolio/GetPositions)
+#[derive(Debug, Deserialize)]
+pub struct PortfolioConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Как часто обновлять снимок портфеля
+    #[serde(default = "default_portfolio_interval_seconds")]
+    pub interval_seconds: u64,
+    /// Валюта итогов портфеля: RUB, USD или EUR
+    #[serde(default = "default_portfolio_currency")]
+    pub currency: String,
+    /// Счета для снимков; пусто — все открытые счета токена
+    #[serde(default)]
+    pub accounts: Vec<String>,
+}
+
+impl Default for PortfolioConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_seconds: default_portfolio_interval_seconds(),
+            currency: default_portfolio_currency(),
+            accounts: Vec::new(),
+        }
+    }
+}
+
+fn default_portfolio_interval_seconds() -> u64 {
+    900
+}
+
+fn default_portfolio_currency() -> String {
+    "RUB".to_string()
+}
+
+/// Журнал операций по счетам (OperationsService.GetOperationsByCursor)
+#[derive(Debug, Deserialize)]
+pub struct OperationsLedgerConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Как часто догружать новые операции
+    #[serde(default = "default_ledger_interval_seconds")]
+    pub interval_seconds: u64,
+    /// Операций на страницу запроса (не больше 1000)
+    #[serde(default = "default_ledger_page_size")]
+    pub page_size: i32,
+    /// На сколько дней назад перезапрашивать уже загруженный период:
+    /// операции, исполненные с задержкой, появляются в истории задним числом
+    #[serde(default = "default_ledger_overlap_days")]
+    pub overlap_days: u32,
+    /// Начало истории (YYYY-MM-DD), если дата открытия счёта неизвестна
+    #[serde(default = "default_ledger_history_start")]
+    pub history_start: String,
+}
+
+impl Default for OperationsLedgerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_seconds: default_ledger_interval_seconds(),
+            page_size: default_ledger_page_size(),
+            overlap_days: default_ledger_overlap_days(),
+            history_start: default_ledger_history_start(),
+        }
+    }
+}
+
+fn default_ledger_interval_seconds() -> u64 {
+    3600
+}
+
+fn default_ledger_page_size() -> i32 {
+    1000
+}
+
+fn default_ledger_overlap_days() -> u32 {
+    7
+}
+
+fn default_ledger_history_start() -> String {
+    "2016-01-01".to_string()
+}
+
+/// Календарь и история дивидендов (InstrumentsService.GetDividends)
+#[derive(Debug, Deserialize)]
+pub struct DividendsConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Как часто обходить каталог акций и ETF
+    #[serde(default = "default_dividends_interval_seconds")]
+    pub interval_seconds: u64,
+    /// Глубина истории выплат при обходе
+    #[serde(default = "default_dividends_history_days")]
+    pub history_days: u32,
+    /// На сколько дней вперёд запрашивать объявленные выплаты
+    #[serde(default = "default_dividends_days_ahead")]
+    pub days_ahead: u32,
+}
+
+impl Default for DividendsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_seconds: default_dividends_interval_seconds(),
+            history_days: default_dividends_history_days(),
+            days_ahead: default_dividends_days_ahead(),
+        }
+    }
+}
+
+fn default_dividends_interval_seconds() -> u64 {
+    86400
+}
+
+fn default_dividends_history_days() -> u32 {
+    1825
+}
+
+fn default_dividends_days_ahead() -> u32 {
+    365
+}
+
+/// Купонные календари и НКД облигаций (GetBondCoupons, GetAccruedInterests)
+#[derive(Debug, Deserialize)]
+pub struct BondCouponsConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Как часто обходить каталог облигаций
+    #[serde(default = "default_coupon_interval_seconds")]
+    pub interval_seconds: u64,
+    /// Глубина истории выплаченных купонов
+    #[serde(default = "default_coupon_history_days")]
+    pub history_days: u32,
+    /// За сколько последних дней обновлять НКД
+    #[serde(default = "default_accrued_interest_days")]
+    pub accrued_interest_days: u32,
+}
+
+impl Default for BondCouponsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_seconds: default_coupon_interval_seconds(),
+            history_days: default_coupon_history_days(),
+            accrued_interest_days: default_accrued_interest_days(),
+        }
+    }
+}
+
+/// Ночной расчёт доходности, дюрации и выпуклости облигаций каталога
+#[derive(Debug, Deserialize)]
+pub struct BondAnalyticsConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Время запуска, HH:MM в часовом поясе `timezone`
+    #[serde(default = "default_bond_analytics_run_at")]
+    pub run_at: String,
+    #[serde(default = "default_rollup_timezone")]
+    pub timezone: String,
+    #[serde(default = "default_false")]
+    pub run_on_startup: bool,
+}
+
+impl Default for BondAnalyticsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            run_at: default_bond_analytics_run_at(),
+            timezone: default_rollup_timezone(),
+            run_on_startup: false,
+        }
+    }
+}
+
+/// Журнал изменений полей инструментов при синхронизации каталога
+#[derive(Debug, Deserialize)]
+pub struct InstrumentChangelogConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Поля, изменения которых не записываются: меняются каждый день и только
+    /// раздувают журнал
+    #[serde(default = "default_changelog_ignored_fields")]
+    pub ignored_fields: Vec<String>,
+}
+
+impl Default for InstrumentChangelogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            ignored_fields: default_changelog_ignored_fields(),
+        }
+    }
+}
+
+fn default_changelog_ignored_fields() -> Vec<String> {
+    vec!["aci_value".to_string()]
+}
+
+fn default_bond_analytics_run_at() -> String {
+    "03:00".to_string()
+}
+
+fn default_coupon_interval_seconds() -> u64 {
+    86400
+}
+
+fn default_coupon_history_days() -> u32 {
+    365
+}
+
+fn default_accrued_interest_days() -> u32 {
+    30
+}
+
+fn default_rollup_intervals() -> Vec<String> {
+    ["5m", "15m", "1h", "1d"].iter().map(|s| s.to_string()).collect()
+}
+
+fn default_rollup_timezone() -> String {
+    "Europe/Moscow".to_string()
+}
+
+fn default_rollup_flush_seconds() -> u64 {
+    60
+}
+
+fn default_trading_exchange() -> String {
+    "MOEX".to_string()
+}
+
+fn default_schedule_days_ahead() -> u32 {
+    7
+}
+
 fn default_false() -> bool {
     false
 }
@@ -0,0 +1,135 @@
+// src/features/bond_analytics/job.rs
+
+use std::sync::Arc;
+
+use chrono::{NaiveDate, NaiveTime, Utc};
+use chrono_tz::Tz;
+use tokio::time::{self, Duration};
+use tracing::{error, info, warn};
+
+use crate::env_config::models::app_setting::AppSettings;
+use crate::features::{
+    core::models::instrument::{InstrumentKind, TinkoffInstrumentEnum},
+    db::MongoDb,
+};
+
+use super::service::BondAnalyticsService;
+
+/// Ночной расчёт аналитики по всем непогашенным облигациям каталога
+pub struct BondAnalyticsJob {
+    service: Arc<BondAnalyticsService>,
+    mongo_db: Arc<MongoDb>,
+    settings: Arc<AppSettings>,
+}
+
+impl BondAnalyticsJob {
+    pub fn new(
+        service: Arc<BondAnalyticsService>,
+        mongo_db: Arc<MongoDb>,
+        settings: Arc<AppSettings>,
+    ) -> Self {
+        Self {
+            service,
+            mongo_db,
+            settings,
+        }
+    }
+
+    pub async fn start_update_loop(self) {
+        let config = &self.settings.app_config.bond_analytics;
+        if !config.enabled {
+            info!("Bond analytics job is disabled in configuration");
+            return;
+        }
+
+        let timezone: Tz = config.timezone.parse().unwrap_or_else(|_| {
+            warn!(
+                "Invalid bond analytics timezone '{}', using Europe/Moscow",
+                config.timezone
+            );
+            chrono_tz::Europe::Moscow
+        });
+        let run_at = NaiveTime::parse_from_str(&config.run_at, "%H:%M").unwrap_or_else(|_| {
+            warn!(
+                "Invalid bond analytics run_at '{}', using 03:00",
+                config.run_at
+            );
+            NaiveTime::from_hms_opt(3, 0, 0).unwrap_or_default()
+        });
+
+        info!(
+            "Starting bond analytics job (daily at {} {})",
+            run_at.format("%H:%M"),
+            timezone
+        );
+
+        let mut last_run_day: Option<NaiveDate> = None;
+        if config.run_on_startup {
+            let today = Utc::now().with_timezone(&timezone).date_naive();
+            self.run(today).await;
+            last_run_day = Some(today);
+        }
+
+        // Проверяем раз в минуту; за сутки расчёт запускается не больше одного раза
+        let mut interval = time::interval(Duration::from_secs(60));
+        loop {
+            interval.tick().await;
+
+            let local = Utc::now().with_timezone(&timezone);
+            let today = local.date_naive();
+            if last_run_day == Some(today) || local.time() < run_at {
+                continue;
+            }
+            last_run_day = Some(today);
+
+            self.run(today).await;
+        }
+    }
+
+    /// Расчёт и сохранение аналитики на дату по цене последней свечи
+    pub async fn run(&self, date: NaiveDate) {
+        let now = Utc::now().timestamp();
+        let started = std::time::Instant::now();
+        info!("Starting bond analytics for {}", date);
+
+        let mut computed = 0;
+        let mut skipped = 0;
+        let mut incomplete = 0;
+        for figi in self.mongo_db.get_unique_figis(InstrumentKind::Bond).await {
+            let matured = match self.mongo_db.find_instrument_by_figi(&figi).await {
+                Some(TinkoffInstrumentEnum::Bond(bond)) => bond
+                    .maturity_date
+                    .as_ref()
+                    .is_some_and(|maturity| maturity.seconds > 0 && maturity.seconds < now),
+                _ => continue,
+            };
+            if matured {
+                continue;
+            }
+
+            match self.service.analyze_bond(&figi, None, date).await {
+                // Без метрик сохранять нечего: история не должна копить пустые расчёты
+                Ok(Some(analytics)) if analytics.coupons_missing || analytics.amortization => {
+                    incomplete += 1
+                }
+                Ok(Some(analytics)) => {
+                    match self.mongo_db.upsert_bond_analytics(&analytics).await {
+                        Ok(()) => computed += 1,
+                        Err(e) => error!("Failed to save bond analytics for {}: {}", figi, e),
+                    }
+                }
+                Ok(None) => skipped += 1,
+                Err(e) => warn!("Failed to compute bond analytics for {}: {}", figi, e),
+            }
+        }
+
+        info!(
+            "Bond analytics for {} completed in {:?}: {} bonds computed, {} without price, {} without coupon or amortization schedule",
+            date,
+            started.elapsed(),
+            computed,
+            skipped,
+            incomplete
+        );
+    }
+}
@@ -0,0 +1,222 @@
+// src/features/bond_analytics/math.rs
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+/// База для перевода дней в годы
+const DAYS_IN_YEAR: f64 = 365.0;
+/// Границы поиска доходности: от -99% до 1000% годовых
+const YIELD_LOWER: f64 = -0.99;
+const YIELD_UPPER: f64 = 10.0;
+const BISECTION_STEPS: usize = 200;
+
+/// Платёж по облигации на одну бумагу в валюте номинала: купон, амортизация или погашение
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CashFlow {
+    pub date: NaiveDate,
+    pub amount: f64,
+}
+
+/// Доходности — доли годовых (0.12 = 12%), дюрации — в годах
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct BondMetrics {
+    /// Эффективная доходность к погашению
+    pub ytm: Option<f64>,
+    /// Годовой купон к чистой цене
+    pub current_yield: Option<f64>,
+    pub macaulay_duration: Option<f64>,
+    pub modified_duration: Option<f64>,
+    pub convexity: Option<f64>,
+}
+
+/// Будущие платежи как (срок в годах, сумма)
+fn timed_flows(settlement: NaiveDate, flows: &[CashFlow]) -> Vec<(f64, f64)> {
+    flows
+        .iter()
+        .filter(|flow| flow.date > settlement)
+        .map(|flow| {
+            (
+                (flow.date - settlement).num_days() as f64 / DAYS_IN_YEAR,
+                flow.amount,
+            )
+        })
+        .collect()
+}
+
+fn present_value(flows: &[(f64, f64)], rate: f64) -> f64 {
+    flows
+        .iter()
+        .map(|(years, amount)| amount / (1.0 + rate).powf(*years))
+        .sum()
+}
+
+/// Эффективная доходность к погашению: ставка, при которой дисконтированные платежи
+/// равны грязной цене. Поиск делением отрезка: приведённая стоимость монотонно убывает
+/// по ставке при положительных платежах
+pub fn yield_to_maturity(
+    dirty_price: f64,
+    settlement: NaiveDate,
+    flows: &[CashFlow],
+) -> Option<f64> {
+    let flows = timed_flows(settlement, flows);
+    if flows.is_empty() || dirty_price <= 0.0 {
+        return None;
+    }
+
+    let excess = |rate: f64| present_value(&flows, rate) - dirty_price;
+    let (mut low, mut high) = (YIELD_LOWER, YIELD_UPPER);
+    if excess(low) < 0.0 || excess(high) > 0.0 {
+        return None;
+    }
+
+    for _ in 0..BISECTION_STEPS {
+        let middle = (low + high) / 2.0;
+        if excess(middle) > 0.0 {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+    Some((low + high) / 2.0)
+}
+
+/// Метрики облигации с погашением по полному графику платежей.
+/// Цены и НКД — на одну бумагу в валюте номинала
+pub fn analyze(
+    clean_price: f64,
+    accrued_interest: f64,
+    annual_coupon: f64,
+    settlement: NaiveDate,
+    flows: &[CashFlow],
+) -> BondMetrics {
+    let current_yield = (clean_price > 0.0).then(|| annual_coupon / clean_price);
+    let Some(ytm) = yield_to_maturity(clean_price + accrued_interest, settlement, flows) else {
+        return BondMetrics {
+            current_yield,
+            ..BondMetrics::default()
+        };
+    };
+
+    let discounted: Vec<(f64, f64)> = timed_flows(settlement, flows)
+        .into_iter()
+        .map(|(years, amount)| (years, amount / (1.0 + ytm).powf(years)))
+        .collect();
+    let price: f64 = discounted.iter().map(|(_, value)| value).sum();
+    let macaulay = discounted
+        .iter()
+        .map(|(years, value)| years * value)
+        .sum::<f64>()
+        / price;
+    let convexity = discounted
+        .iter()
+        .map(|(years, value)| years * (years + 1.0) * value)
+        .sum::<f64>()
+        / (price * (1.0 + ytm).powi(2));
+
+    BondMetrics {
+        ytm: Some(ytm),
+        current_yield,
+        macaulay_duration: Some(macaulay),
+        modified_duration: Some(macaulay / (1.0 + ytm)),
+        convexity: Some(convexity),
+    }
+}
+
+/// Метрики бессрочной облигации: погашения нет, доходность считается как у вечной ренты
+/// с годовым купоном C и грязной ценой P: y = C / P, D_mac = (1 + y) / y,
+/// D_mod = 1 / y, выпуклость 2 / y². Колл-опционы эмитента не учитываются
+pub fn analyze_perpetual(
+    clean_price: f64,
+    accrued_interest: f64,
+    annual_coupon: f64,
+) -> BondMetrics {
+    let current_yield = (clean_price > 0.0).then(|| annual_coupon / clean_price);
+    let dirty_price = clean_price + accrued_interest;
+    if dirty_price <= 0.0 || annual_coupon <= 0.0 {
+        return BondMetrics {
+            current_yield,
+            ..BondMetrics::default()
+        };
+    }
+
+    let ytm = annual_coupon / dirty_price;
+    BondMetrics {
+        ytm: Some(ytm),
+        current_yield,
+        macaulay_duration: Some((1.0 + ytm) / ytm),
+        modified_duration: Some(1.0 / ytm),
+        convexity: Some(2.0 / (ytm * ytm)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+    }
+
+    fn assert_close(actual: Option<f64>, expected: f64) {
+        let actual = actual.expect("metric is missing");
+        assert!(
+            (actual - expected).abs() < 1e-6,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_zero_coupon_bond() {
+        let flows = [CashFlow {
+            date: date("2026-01-01"),
+            amount: 1000.0,
+        }];
+        let metrics = analyze(900.0, 0.0, 0.0, date("2025-01-01"), &flows);
+
+        let ytm = 1000.0 / 900.0 - 1.0;
+        assert_close(metrics.ytm, ytm);
+        assert_close(metrics.current_yield, 0.0);
+        assert_close(metrics.macaulay_duration, 1.0);
+        assert_close(metrics.modified_duration, 1.0 / (1.0 + ytm));
+        assert_close(metrics.convexity, 2.0 / (1.0 + ytm).powi(2));
+    }
+
+    #[test]
+    fn test_par_coupon_bond() {
+        let flows = [
+            CashFlow {
+                date: date("2026-01-01"),
+                amount: 100.0,
+            },
+            CashFlow {
+                date: date("2027-01-01"),
+                amount: 1100.0,
+            },
+        ];
+        let metrics = analyze(1000.0, 0.0, 100.0, date("2025-01-01"), &flows);
+
+        assert_close(metrics.ytm, 0.10);
+        assert_close(metrics.current_yield, 0.10);
+        let macaulay = (100.0 / 1.1 + 2.0 * 1100.0 / 1.21) / 1000.0;
+        assert_close(metrics.macaulay_duration, macaulay);
+        assert_close(metrics.modified_duration, macaulay / 1.1);
+        // Платежи до даты расчёта не учитываются
+        let settled = analyze(1000.0, 0.0, 100.0, date("2026-01-01"), &flows);
+        assert_close(settled.ytm, 0.10);
+        assert_close(settled.macaulay_duration, 1.0);
+    }
+
+    #[test]
+    fn test_perpetual_bond() {
+        let metrics = analyze_perpetual(980.0, 20.0, 80.0);
+
+        assert_close(metrics.ytm, 0.08);
+        assert_close(metrics.current_yield, 80.0 / 980.0);
+        assert_close(metrics.macaulay_duration, 13.5);
+        assert_close(metrics.modified_duration, 12.5);
+        assert_close(metrics.convexity, 312.5);
+        assert_eq!(analyze_perpetual(1000.0, 0.0, 0.0).ytm, None);
+    }
+}
@@ -0,0 +1,6 @@
+pub mod job;
+pub mod math;
+pub mod service;
+
+pub use job::BondAnalyticsJob;
+pub use service::BondAnalyticsService;
@@ -0,0 +1,302 @@
+// src/features/bond_analytics/service.rs
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+
+use crate::features::{
+    core::models::{
+        bond::TinkoffBondModel,
+        instrument::{InstrumentKind, TinkoffInstrumentEnum},
+    },
+    db::{
+        mongo_extensions::{
+            bond_analytics::models::DbBondAnalytics, bond_coupons::models::DbBondCoupon,
+        },
+        MongoDb,
+    },
+};
+
+use super::math::{analyze, analyze_perpetual, BondMetrics, CashFlow};
+
+const DAY_FORMAT: &str = "%Y-%m-%d";
+
+/// Будущие платежи на одну облигацию после даты расчёта
+struct Schedule {
+    flows: Vec<CashFlow>,
+    /// Купоны за год после даты расчёта
+    annual_coupon: f64,
+    estimated_coupons: usize,
+}
+
+/// Купоны после даты расчёта и погашение номинала в дату погашения.
+/// Купон без объявленной суммы (плавающий) берётся равным последнему известному
+fn build_schedule(
+    bond: &TinkoffBondModel,
+    coupons: &[DbBondCoupon],
+    nominal: f64,
+    settlement: NaiveDate,
+    maturity: Option<NaiveDate>,
+) -> Schedule {
+    let mut flows = Vec::new();
+    let mut estimated_coupons = 0;
+    let mut last_known: Option<f64> = None;
+
+    for coupon in coupons {
+        let amount = coupon
+            .pay_one_bond
+            .as_ref()
+            .filter(|_| coupon.amount_known)
+            .and_then(|amount| amount.value.to_f64());
+        if amount.is_some() {
+            last_known = amount;
+        }
+
+        let Ok(date) = NaiveDate::parse_from_str(&coupon.coupon_date, DAY_FORMAT) else {
+            continue;
+        };
+        if date <= settlement {
+            continue;
+        }
+        let Some(value) = amount.or(last_known) else {
+            continue;
+        };
+        if amount.is_none() {
+            estimated_coupons += 1;
+        }
+        flows.push(CashFlow {
+            date,
+            amount: value,
+        });
+    }
+
+    let horizon = settlement + Duration::days(365);
+    let mut annual_coupon: f64 = flows
+        .iter()
+        .filter(|flow| flow.date <= horizon)
+        .map(|flow| flow.amount)
+        .sum();
+    if annual_coupon == 0.0 {
+        annual_coupon =
+            last_known.unwrap_or_default() * bond.coupon_quantity_per_year.max(0) as f64;
+    }
+
+    if let Some(maturity) = maturity.filter(|maturity| *maturity > settlement) {
+        flows.push(CashFlow {
+            date: maturity,
+            amount: nominal,
+        });
+    }
+    flows.sort_by_key(|flow| flow.date);
+
+    Schedule {
+        flows,
+        annual_coupon,
+        estimated_coupons,
+    }
+}
+
+/// НКД на дату по купонному календарю: доля объявленного купона, накопленная
+/// с начала текущего купонного периода
+fn accrued_from_coupons(coupons: &[DbBondCoupon], date: NaiveDate) -> Option<Decimal> {
+    coupons.iter().find_map(|coupon| {
+        let start =
+            NaiveDate::parse_from_str(coupon.coupon_start_date.as_ref()?, DAY_FORMAT).ok()?;
+        let end = coupon
+            .coupon_end_date
+            .as_ref()
+            .unwrap_or(&coupon.coupon_date);
+        let end = NaiveDate::parse_from_str(end, DAY_FORMAT).ok()?;
+        if date < start || date >= end {
+            return None;
+        }
+        let amount = coupon
+            .pay_one_bond
+            .as_ref()
+            .filter(|_| coupon.amount_known)?
+            .value;
+        let period = if coupon.coupon_period > 0 {
+            i64::from(coupon.coupon_period)
+        } else {
+            (end - start).num_days()
+        };
+        let elapsed = (date - start).num_days();
+        Some((amount * Decimal::from(elapsed) / Decimal::from(period)).round_dp(2))
+    })
+}
+
+/// Доходность, дюрация и выпуклость облигаций по купонному календарю, НКД и цене
+pub struct BondAnalyticsService {
+    mongo_db: Arc<MongoDb>,
+}
+
+impl BondAnalyticsService {
+    pub fn new(mongo_db: Arc<MongoDb>) -> Self {
+        Self { mongo_db }
+    }
+
+    /// Расчёт на дату по чистой цене в процентах номинала; без цены берётся закрытие
+    /// последней свечи не позже конца даты. None — FIGI не облигация или цены нет.
+    ///
+    /// Метрики не считаются, если купонный календарь купонной облигации не загружен
+    /// или облигация амортизируемая: без графика погашения номинала платежи неизвестны
+    pub async fn analyze_bond(
+        &self,
+        figi: &str,
+        clean_price_percent: Option<Decimal>,
+        date: NaiveDate,
+    ) -> Result<Option<DbBondAnalytics>, String> {
+        let Some(TinkoffInstrumentEnum::Bond(bond)) =
+            self.mongo_db.find_instrument_by_figi(figi).await
+        else {
+            return Ok(None);
+        };
+
+        let (clean_price_percent, price_time) = match clean_price_percent {
+            Some(price) => (price, None),
+            None => {
+                let end_of_day = (date + Duration::days(1))
+                    .and_hms_opt(0, 0, 0)
+                    .map(|dt| dt.and_utc().timestamp() - 1)
+                    .unwrap_or_default();
+                match self
+                    .mongo_db
+                    .find_last_close(InstrumentKind::Bond, figi, end_of_day)
+                    .await
+                {
+                    Some((time, close)) => (
+                        close,
+                        DateTime::from_timestamp(time, 0).map(|time| time.to_rfc3339()),
+                    ),
+                    None => return Ok(None),
+                }
+            }
+        };
+
+        let day = date.format(DAY_FORMAT).to_string();
+        let accrued = self
+            .mongo_db
+            .find_bond_accrued_interests(figi, &day, &day)
+            .await
+            .map_err(|e| format!("Failed to read accrued interest: {}", e))?
+            .pop();
+        let coupons = self
+            .mongo_db
+            .find_bond_coupons(figi)
+            .await
+            .map_err(|e| format!("Failed to read bond coupons: {}", e))?;
+
+        let currency = bond
+            .nominal
+            .as_ref()
+            .map(|nominal| nominal.currency.to_uppercase())
+            .unwrap_or_else(|| bond.currency.to_uppercase());
+        let nominal = accrued
+            .as_ref()
+            .and_then(|accrued| accrued.nominal.as_ref())
+            .map(|nominal| nominal.value)
+            .filter(|nominal| !nominal.is_zero())
+            .or_else(|| bond.nominal.as_ref().map(|nominal| nominal.value))
+            .unwrap_or_default();
+        let accrued_interest = accrued
+            .as_ref()
+            .and_then(|accrued| accrued.value.as_ref())
+            .map(|value| value.value)
+            .or_else(|| accrued_from_coupons(&coupons, date))
+            .unwrap_or_default();
+        let clean_price = clean_price_percent * nominal / Decimal::ONE_HUNDRED;
+
+        let maturity = bond
+            .maturity_date
+            .as_ref()
+            .filter(|maturity| maturity.seconds > 0)
+            .and_then(|maturity| DateTime::from_timestamp(maturity.seconds, 0))
+            .map(|maturity| maturity.date_naive());
+        let perpetual = bond.perpetual_flag || maturity.is_none();
+        let coupons_missing = coupons.is_empty() && bond.coupon_quantity_per_year > 0;
+
+        let nominal_f64 = nominal.to_f64().unwrap_or_default();
+        let schedule = build_schedule(&bond, &coupons, nominal_f64, date, maturity);
+        let clean_f64 = clean_price.to_f64().unwrap_or_default();
+        let accrued_f64 = accrued_interest.to_f64().unwrap_or_default();
+        let metrics = if coupons_missing || bond.amortization_flag {
+            BondMetrics::default()
+        } else if perpetual {
+            analyze_perpetual(clean_f64, accrued_f64, schedule.annual_coupon)
+        } else {
+            analyze(
+                clean_f64,
+                accrued_f64,
+                schedule.annual_coupon,
+                date,
+                &schedule.flows,
+            )
+        };
+
+        Ok(Some(DbBondAnalytics {
+            figi: bond.figi.clone(),
+            ticker: bond.ticker.clone(),
+            date: day,
+            currency,
+            nominal,
+            clean_price_percent,
+            price_time,
+            accrued_interest,
+            dirty_price: clean_price + accrued_interest,
+            perpetual,
+            amortization: bond.amortization_flag,
+            coupons_missing,
+            cash_flows: schedule.flows.len(),
+            estimated_coupons: schedule.estimated_coupons,
+            metrics,
+            computed_at: Utc::now().to_rfc3339(),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::core::models::money_value::TinkoffMoneyValueModel;
+
+    fn day(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, DAY_FORMAT).unwrap()
+    }
+
+    fn coupon(start: &str, coupon_date: &str, units: i64) -> DbBondCoupon {
+        DbBondCoupon {
+            figi: "BBG00R05JT04".to_string(),
+            ticker: "RU000A1013N6".to_string(),
+            coupon_number: 1,
+            coupon_date: coupon_date.to_string(),
+            fix_date: None,
+            coupon_start_date: Some(start.to_string()),
+            coupon_end_date: Some(coupon_date.to_string()),
+            coupon_period: 182,
+            coupon_type: "COUPON_TYPE_CONSTANT".to_string(),
+            pay_one_bond: Some(TinkoffMoneyValueModel::new("rub", units, 0)),
+            amount_known: units != 0,
+            floating: false,
+            amortization: false,
+            updated_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_accrued_from_coupons() {
+        let coupons = vec![
+            coupon("2024-10-16", "2025-04-16", 0),
+            coupon("2025-04-16", "2025-10-15", 91),
+        ];
+
+        assert_eq!(
+            accrued_from_coupons(&coupons, day("2025-07-16")),
+            Some(Decimal::new(4550, 2))
+        );
+        // Сумма текущего купона не объявлена
+        assert_eq!(accrued_from_coupons(&coupons, day("2025-01-15")), None);
+        assert_eq!(accrued_from_coupons(&coupons, day("2025-10-15")), None);
+    }
+}
@@ -0,0 +1,117 @@
+use crate::features::cbr_api::models::CbrDailyRate;
+use chrono::NaiveDate;
+use regex::Regex;
+use reqwest::Client;
+use rust_decimal::Decimal;
+use std::str::FromStr;
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+/// Динамика официального курса валюты за период; даты в формате DD/MM/YYYY
+const CBR_RATE_DYNAMICS_URL: &str =
+    "https://www.cbr.ru/scripts/XML_dynamic.asp?date_req1={from}&date_req2={to}&VAL_NM_RQ={cbr_id}";
+const REQUEST_TIMEOUT: u64 = 30; // секунд
+
+pub struct CbrApiClient {
+    http_client: Client,
+}
+
+impl CbrApiClient {
+    pub fn new() -> Self {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(REQUEST_TIMEOUT))
+            .build()
+            .unwrap_or_else(|_| Client::new());
+
+        Self {
+            http_client: client,
+        }
+    }
+
+    /// Официальные курсы валюты `cbr_id` (R01235, R01239 ...) за [from, to].
+    /// ЦБ отдаёт только дни, на которые устанавливал курс
+    pub async fn get_rate_dynamics(
+        &self,
+        cbr_id: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<CbrDailyRate>, Box<dyn std::error::Error + Send + Sync>> {
+        info!("Fetching CBR rates for {} from {} to {}", cbr_id, from, to);
+
+        let url = CBR_RATE_DYNAMICS_URL
+            .replace("{from}", &from.format("%d/%m/%Y").to_string())
+            .replace("{to}", &to.format("%d/%m/%Y").to_string())
+            .replace("{cbr_id}", cbr_id);
+        let response = self.http_client.get(&url).send().await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            error!("CBR API returned error status: {} for {}", status, cbr_id);
+            return Err(format!("CBR API error: {} for {}", status, cbr_id).into());
+        }
+
+        let body = response.text().await?;
+        let rates = parse_rate_dynamics(&body);
+        info!("Received {} CBR rates for {}", rates.len(), cbr_id);
+        Ok(rates)
+    }
+}
+
+/// Разбирает записи `<Record Date="DD.MM.YYYY">` ответа XML_dynamic.
+/// Значения ЦБ пишет с десятичной запятой и за лот номиналом `Nominal`
+fn parse_rate_dynamics(body: &str) -> Vec<CbrDailyRate> {
+    let record = Regex::new(
+        r#"<Record Date="(\d{2}\.\d{2}\.\d{4})"[^>]*>\s*<Nominal>(\d+)</Nominal>\s*<Value>([\d,.]+)</Value>"#,
+    )
+    .expect("valid CBR record pattern");
+
+    record
+        .captures_iter(body)
+        .filter_map(|captures| {
+            let date = NaiveDate::parse_from_str(&captures[1], "%d.%m.%Y").ok();
+            let nominal = Decimal::from_str(&captures[2]).ok();
+            let value = Decimal::from_str(&captures[3].replace(',', ".")).ok();
+            match (date, nominal, value) {
+                (Some(date), Some(nominal), Some(value)) if !nominal.is_zero() => {
+                    Some(CbrDailyRate {
+                        date,
+                        rate: value / nominal,
+                    })
+                }
+                _ => {
+                    warn!("Skipping malformed CBR record: {}", &captures[0]);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rate_dynamics() {
+        let body = r#"<?xml version="1.0" encoding="windows-1251"?>
+<ValCurs ID="R01375" DateRange1="14.03.2025" DateRange2="15.03.2025" name="Foreign Currency Market Dynamic">
+<Record Date="14.03.2025" Id="R01375"><Nominal>1</Nominal><Value>11,9537</Value><VunitRate>11,9537</VunitRate></Record>
+<Record Date="15.03.2025" Id="R01375"><Nominal>10</Nominal><Value>119,0000</Value><VunitRate>11,9</VunitRate></Record>
+</ValCurs>"#;
+
+        let rates = parse_rate_dynamics(body);
+        assert_eq!(
+            rates,
+            vec![
+                CbrDailyRate {
+                    date: NaiveDate::from_ymd_opt(2025, 3, 14).unwrap(),
+                    rate: Decimal::from_str("11.9537").unwrap(),
+                },
+                CbrDailyRate {
+                    date: NaiveDate::from_ymd_opt(2025, 3, 15).unwrap(),
+                    rate: Decimal::from_str("11.9").unwrap(),
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,5 @@
+pub mod client;
+pub mod models;
+
+// Реэкспорт клиента для прямого доступа
+pub use client::CbrApiClient;
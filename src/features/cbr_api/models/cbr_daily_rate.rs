@@ -0,0 +1,11 @@
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+
+/// Официальный курс ЦБ РФ на дату из XML_dynamic
+#[derive(Debug, Clone, PartialEq)]
+pub struct CbrDailyRate {
+    /// Дата, с которой действует курс
+    pub date: NaiveDate,
+    /// Рублей за единицу валюты (курс ЦБ делится на номинал)
+    pub rate: Decimal,
+}
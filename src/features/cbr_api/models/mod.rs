@@ -0,0 +1,3 @@
+mod cbr_daily_rate;
+
+pub use cbr_daily_rate::*;
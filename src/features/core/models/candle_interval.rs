@@ -1,6 +1,8 @@
 // models/candle_interval.rs
 
-#[derive(Debug, Clone, Copy)]
+use chrono::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum MyCandleInterval {
     /// Интервал не определён.
     Unspecified = 0,
@@ -71,4 +73,101 @@ impl MyCandleInterval {
             Self::Month => "CANDLE_INTERVAL_MONTH",
         }
     }
-}
\ No newline at end of file
+}
+
+impl MyCandleInterval {
+    /// Разбирает интервал из query-параметра: короткая форма (`1m`, `1h`, `1d`)
+    /// или полное имя из контракта (`CANDLE_INTERVAL_1_MIN`)
+    pub fn parse(value: &str) -> Option<Self> {
+        let interval = match value.trim().to_lowercase().as_str() {
+            "1m" | "1min" | "candle_interval_1_min" => Self::OneMin,
+            "2m" | "2min" | "candle_interval_2_min" => Self::TwoMin,
+            "3m" | "3min" | "candle_interval_3_min" => Self::ThreeMin,
+            "5m" | "5min" | "candle_interval_5_min" => Self::FiveMin,
+            "10m" | "10min" | "candle_interval_10_min" => Self::TenMin,
+            "15m" | "15min" | "candle_interval_15_min" => Self::FifteenMin,
+            "30m" | "30min" | "candle_interval_30_min" => Self::ThirtyMin,
+            "1h" | "hour" | "candle_interval_hour" => Self::Hour,
+            "2h" | "candle_interval_2_hour" => Self::TwoHour,
+            "4h" | "candle_interval_4_hour" => Self::FourHour,
+            "1d" | "day" | "candle_interval_day" => Self::Day,
+            "1w" | "week" | "candle_interval_week" => Self::Week,
+            "1mo" | "month" | "candle_interval_month" => Self::Month,
+            _ => return None,
+        };
+        Some(interval)
+    }
+
+    /// Короткое имя интервала, используется в API и в названиях коллекций
+    pub fn short_name(&self) -> &'static str {
+        match self {
+            Self::Unspecified => "unspecified",
+            Self::OneMin => "1m",
+            Self::TwoMin => "2m",
+            Self::ThreeMin => "3m",
+            Self::FiveMin => "5m",
+            Self::TenMin => "10m",
+            Self::FifteenMin => "15m",
+            Self::ThirtyMin => "30m",
+            Self::Hour => "1h",
+            Self::TwoHour => "2h",
+            Self::FourHour => "4h",
+            Self::Day => "1d",
+            Self::Week => "1w",
+            Self::Month => "1mo",
+        }
+    }
+
+    /// Максимальный период одного запроса GetCandles для интервала
+    /// (см. ограничения в комментариях к вариантам)
+    pub fn max_request_window(&self) -> Duration {
+        match self {
+            Self::Unspecified
+            | Self::OneMin
+            | Self::TwoMin
+            | Self::ThreeMin
+            | Self::FiveMin
+            | Self::TenMin
+            | Self::FifteenMin => Duration::days(1),
+            Self::ThirtyMin => Duration::days(2),
+            Self::Hour => Duration::weeks(1),
+            Self::TwoHour | Self::FourHour => Duration::days(30),
+            Self::Day => Duration::days(365),
+            Self::Week => Duration::days(2 * 365),
+            Self::Month => Duration::days(10 * 365),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_interval() {
+        assert_eq!(MyCandleInterval::parse("1m"), Some(MyCandleInterval::OneMin));
+        assert_eq!(MyCandleInterval::parse("1D"), Some(MyCandleInterval::Day));
+        assert_eq!(
+            MyCandleInterval::parse("CANDLE_INTERVAL_HOUR"),
+            Some(MyCandleInterval::Hour)
+        );
+        assert!(MyCandleInterval::parse("7m").is_none());
+        assert!(MyCandleInterval::parse("").is_none());
+    }
+
+    #[test]
+    fn test_short_name_roundtrip() {
+        for raw in 1..=13 {
+            let interval = MyCandleInterval::from_i32(raw).unwrap();
+            let parsed = MyCandleInterval::parse(interval.short_name()).unwrap();
+            assert_eq!(parsed, interval);
+        }
+    }
+
+    #[test]
+    fn test_max_request_window() {
+        assert_eq!(MyCandleInterval::OneMin.max_request_window(), Duration::days(1));
+        assert_eq!(MyCandleInterval::Hour.max_request_window(), Duration::days(7));
+        assert_eq!(MyCandleInterval::Day.max_request_window(), Duration::days(365));
+    }
+}
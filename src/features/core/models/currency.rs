@@ -0,0 +1,154 @@
+use serde::{Deserialize, Serialize};
+
+use crate::features::core::models::{
+    money_value::TinkoffMoneyValueModel, quotation::TinkoffQuotationModel,
+    real_exchange::TinkoffRealExchangeModel, time_stamp::TinkoffTimestampModel,
+    trading_status::TinkoffTradingStatusModel,
+};
+use crate::gen::tinkoff_public_invest_api_contract_v1::Currency;
+
+/// Валюта, торгуемая на бирже (USD000UTSTOM, CNYRUB_TOM ...)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TinkoffCurrencyModel {
+    // Basic fields
+    pub figi: String,
+    pub ticker: String,
+    pub class_code: String,
+    pub isin: String,
+    pub uid: String,
+    pub position_uid: String,
+    pub name: String,
+    pub lot: i32,
+    pub currency: String,
+    pub exchange: String,
+
+    // Flag fields
+    pub short_enabled_flag: bool,
+    pub otc_flag: bool,
+    pub buy_available_flag: bool,
+    pub sell_available_flag: bool,
+    pub api_trade_available_flag: bool,
+    pub for_iis_flag: bool,
+    pub for_qual_investor_flag: bool,
+    pub weekend_flag: bool,
+    pub blocked_tca_flag: bool,
+
+    // Enhanced enum fields
+    pub trading_status: TinkoffTradingStatusModel,
+    pub real_exchange: TinkoffRealExchangeModel,
+
+    // Currency specific fields
+    pub nominal: Option<TinkoffMoneyValueModel>,
+    /// Код валюты ISO 4217 в нижнем регистре, например `usd`
+    pub iso_currency_name: String,
+    pub country_of_risk: String,
+    pub country_of_risk_name: String,
+
+    // Optional fields with enhanced types
+    pub klong: Option<TinkoffQuotationModel>,
+    pub kshort: Option<TinkoffQuotationModel>,
+    pub dlong: Option<TinkoffQuotationModel>,
+    pub dshort: Option<TinkoffQuotationModel>,
+    pub dlong_min: Option<TinkoffQuotationModel>,
+    pub dshort_min: Option<TinkoffQuotationModel>,
+    pub min_price_increment: Option<TinkoffQuotationModel>,
+    pub first_1min_candle_date: Option<TinkoffTimestampModel>,
+    pub first_1day_candle_date: Option<TinkoffTimestampModel>,
+}
+
+impl From<&Currency> for TinkoffCurrencyModel {
+    fn from(currency: &Currency) -> Self {
+        TinkoffCurrencyModel {
+            figi: currency.figi.clone(),
+            ticker: currency.ticker.clone(),
+            class_code: currency.class_code.clone(),
+            isin: currency.isin.clone(),
+            uid: currency.uid.clone(),
+            position_uid: currency.position_uid.clone(),
+            name: currency.name.clone(),
+            lot: currency.lot,
+            currency: currency.currency.clone(),
+            exchange: currency.exchange.clone(),
+
+            short_enabled_flag: currency.short_enabled_flag,
+            otc_flag: currency.otc_flag,
+            buy_available_flag: currency.buy_available_flag,
+            sell_available_flag: currency.sell_available_flag,
+            api_trade_available_flag: currency.api_trade_available_flag,
+            for_iis_flag: currency.for_iis_flag,
+            for_qual_investor_flag: currency.for_qual_investor_flag,
+            weekend_flag: currency.weekend_flag,
+            blocked_tca_flag: currency.blocked_tca_flag,
+
+            trading_status: TinkoffTradingStatusModel::from(currency.trading_status),
+            real_exchange: TinkoffRealExchangeModel::from(currency.real_exchange),
+
+            nominal: currency.nominal.as_ref().map(TinkoffMoneyValueModel::from),
+            iso_currency_name: currency.iso_currency_name.clone(),
+            country_of_risk: currency.country_of_risk.clone(),
+            country_of_risk_name: currency.country_of_risk_name.clone(),
+
+            klong: currency.klong.as_ref().map(TinkoffQuotationModel::from),
+            kshort: currency.kshort.as_ref().map(TinkoffQuotationModel::from),
+            dlong: currency.dlong.as_ref().map(TinkoffQuotationModel::from),
+            dshort: currency.dshort.as_ref().map(TinkoffQuotationModel::from),
+            dlong_min: currency.dlong_min.as_ref().map(TinkoffQuotationModel::from),
+            dshort_min: currency
+                .dshort_min
+                .as_ref()
+                .map(TinkoffQuotationModel::from),
+            min_price_increment: currency
+                .min_price_increment
+                .as_ref()
+                .map(TinkoffQuotationModel::from),
+            first_1min_candle_date: currency
+                .first_1min_candle_date
+                .as_ref()
+                .map(TinkoffTimestampModel::from),
+            first_1day_candle_date: currency
+                .first_1day_candle_date
+                .as_ref()
+                .map(TinkoffTimestampModel::from),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gen::tinkoff_public_invest_api_contract_v1::{MoneyValue, SecurityTradingStatus};
+    use prost_types::Timestamp;
+    use rust_decimal::Decimal;
+
+    #[test]
+    fn test_currency_from_proto() {
+        let currency = Currency {
+            figi: "BBG0013HGFT4".to_string(),
+            ticker: "USD000UTSTOM".to_string(),
+            uid: "a22a1263-8e1b-4546-a1aa-416463f104d3".to_string(),
+            iso_currency_name: "usd".to_string(),
+            lot: 1000,
+            trading_status: SecurityTradingStatus::NormalTrading as i32,
+            nominal: Some(MoneyValue {
+                currency: "usd".to_string(),
+                units: 1,
+                nano: 0,
+            }),
+            first_1day_candle_date: Some(Timestamp {
+                seconds: 1_356_998_400,
+                nanos: 0,
+            }),
+            ..Default::default()
+        };
+
+        let model = TinkoffCurrencyModel::from(&currency);
+        assert_eq!(model.figi, "BBG0013HGFT4");
+        assert_eq!(model.uid, currency.uid);
+        assert_eq!(model.iso_currency_name, "usd");
+        assert_eq!(model.lot, 1000);
+        assert_eq!(model.trading_status.value, "NORMAL_TRADING");
+        assert_eq!(model.nominal.unwrap().value, Decimal::ONE);
+        assert_eq!(model.first_1day_candle_date.unwrap().seconds, 1_356_998_400);
+        assert!(model.min_price_increment.is_none());
+    }
+}
@@ -1,8 +1,8 @@
 use serde::{Deserialize, Serialize};
 
 use super::{
-    bond::TinkoffBondModel, etf::TinkoffEtfModel, future::TinkoffFutureModel,
-    share::TinkoffShareModel,
+    bond::TinkoffBondModel, currency::TinkoffCurrencyModel, etf::TinkoffEtfModel,
+    future::TinkoffFutureModel, option::TinkoffOptionModel, share::TinkoffShareModel,
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -12,4 +12,226 @@ pub enum TinkoffInstrumentEnum {
     Bond(TinkoffBondModel),
     Etf(TinkoffEtfModel),
     Future(TinkoffFutureModel),
+    Option(TinkoffOptionModel),
+    Currency(TinkoffCurrencyModel),
+}
+
+/// Тип инструмента в каталоге (одна коллекция на тип)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InstrumentKind {
+    Share,
+    Bond,
+    Etf,
+    Future,
+    Option,
+    Currency,
+}
+
+impl InstrumentKind {
+    /// Все типы в порядке поиска по каталогу
+    pub const ALL: [InstrumentKind; 6] = [
+        InstrumentKind::Share,
+        InstrumentKind::Bond,
+        InstrumentKind::Etf,
+        InstrumentKind::Future,
+        InstrumentKind::Option,
+        InstrumentKind::Currency,
+    ];
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "share" | "shares" => Some(Self::Share),
+            "bond" | "bonds" => Some(Self::Bond),
+            "etf" | "etfs" => Some(Self::Etf),
+            "future" | "futures" => Some(Self::Future),
+            "option" | "options" => Some(Self::Option),
+            "currency" | "currencies" => Some(Self::Currency),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Share => "share",
+            Self::Bond => "bond",
+            Self::Etf => "etf",
+            Self::Future => "future",
+            Self::Option => "option",
+            Self::Currency => "currency",
+        }
+    }
+
+    /// Множественное число для имён коллекций: `tinkoff_{plural}_1m_historical`
+    pub fn plural(&self) -> &'static str {
+        match self {
+            Self::Share => "shares",
+            Self::Bond => "bonds",
+            Self::Etf => "etfs",
+            Self::Future => "futures",
+            Self::Option => "options",
+            Self::Currency => "currencies",
+        }
+    }
+}
+
+impl TinkoffInstrumentEnum {
+    pub fn kind(&self) -> InstrumentKind {
+        match self {
+            Self::Share(_) => InstrumentKind::Share,
+            Self::Bond(_) => InstrumentKind::Bond,
+            Self::Etf(_) => InstrumentKind::Etf,
+            Self::Future(_) => InstrumentKind::Future,
+            Self::Option(_) => InstrumentKind::Option,
+            Self::Currency(_) => InstrumentKind::Currency,
+        }
+    }
+
+    /// FIGI нет у опционов: для них пустая строка, искать их нужно по `uid`
+    pub fn figi(&self) -> &str {
+        match self {
+            Self::Share(share) => &share.figi,
+            Self::Bond(bond) => &bond.figi,
+            Self::Etf(etf) => &etf.figi,
+            Self::Future(future) => &future.figi,
+            Self::Option(_) => "",
+            Self::Currency(currency) => &currency.figi,
+        }
+    }
+
+    pub fn ticker(&self) -> &str {
+        match self {
+            Self::Share(share) => &share.ticker,
+            Self::Bond(bond) => &bond.ticker,
+            Self::Etf(etf) => &etf.ticker,
+            Self::Future(future) => &future.ticker,
+            Self::Option(option) => &option.ticker,
+            Self::Currency(currency) => &currency.ticker,
+        }
+    }
+
+    pub fn class_code(&self) -> &str {
+        match self {
+            Self::Share(share) => &share.class_code,
+            Self::Bond(bond) => &bond.class_code,
+            Self::Etf(etf) => &etf.class_code,
+            Self::Future(future) => &future.class_code,
+            Self::Option(option) => &option.class_code,
+            Self::Currency(currency) => &currency.class_code,
+        }
+    }
+
+    /// ISIN есть у всех типов, кроме фьючерсов и опционов
+    pub fn isin(&self) -> &str {
+        match self {
+            Self::Share(share) => &share.isin,
+            Self::Bond(bond) => &bond.isin,
+            Self::Etf(etf) => &etf.isin,
+            Self::Future(_) | Self::Option(_) => "",
+            Self::Currency(currency) => &currency.isin,
+        }
+    }
+
+    pub fn uid(&self) -> &str {
+        match self {
+            Self::Share(share) => &share.uid,
+            Self::Bond(bond) => &bond.uid,
+            Self::Etf(etf) => &etf.uid,
+            Self::Future(future) => &future.uid,
+            Self::Option(option) => &option.uid,
+            Self::Currency(currency) => &currency.uid,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Share(share) => &share.name,
+            Self::Bond(bond) => &bond.name,
+            Self::Etf(etf) => &etf.name,
+            Self::Future(future) => &future.name,
+            Self::Option(option) => &option.name,
+            Self::Currency(currency) => &currency.name,
+        }
+    }
+
+    pub fn currency(&self) -> &str {
+        match self {
+            Self::Share(share) => &share.currency,
+            Self::Bond(bond) => &bond.currency,
+            Self::Etf(etf) => &etf.currency,
+            Self::Future(future) => &future.currency,
+            Self::Option(option) => &option.currency,
+            Self::Currency(currency) => &currency.currency,
+        }
+    }
+
+    pub fn exchange(&self) -> &str {
+        match self {
+            Self::Share(share) => &share.exchange,
+            Self::Bond(bond) => &bond.exchange,
+            Self::Etf(etf) => &etf.exchange,
+            Self::Future(future) => &future.exchange,
+            Self::Option(option) => &option.exchange,
+            Self::Currency(currency) => &currency.exchange,
+        }
+    }
+
+    pub fn lot(&self) -> i32 {
+        match self {
+            Self::Share(share) => share.lot,
+            Self::Bond(bond) => bond.lot,
+            Self::Etf(etf) => etf.lot,
+            Self::Future(future) => future.lot,
+            Self::Option(option) => option.lot,
+            Self::Currency(currency) => currency.lot,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::db::mongo_db::Collections;
+
+    #[test]
+    fn test_instrument_kind_parse() {
+        for kind in InstrumentKind::ALL {
+            assert_eq!(InstrumentKind::parse(kind.as_str()), Some(kind));
+            assert_eq!(InstrumentKind::parse(kind.plural()), Some(kind));
+        }
+        assert_eq!(
+            InstrumentKind::parse(" Options "),
+            Some(InstrumentKind::Option)
+        );
+        assert_eq!(
+            InstrumentKind::parse("currencies"),
+            Some(InstrumentKind::Currency)
+        );
+        assert_eq!(InstrumentKind::parse("index"), None);
+    }
+
+    #[test]
+    fn test_instrument_kind_plural_keeps_collection_names() {
+        // Имена коллекций свечей до опционов и валют строились как `{as_str}s`
+        for kind in [
+            InstrumentKind::Share,
+            InstrumentKind::Bond,
+            InstrumentKind::Etf,
+            InstrumentKind::Future,
+        ] {
+            assert_eq!(kind.plural(), format!("{}s", kind.as_str()));
+        }
+
+        let catalogue = [
+            (InstrumentKind::Share, Collections::TINKOFF_SHARES),
+            (InstrumentKind::Bond, Collections::TINKOFF_BONDS),
+            (InstrumentKind::Etf, Collections::TINKOFF_ETFS),
+            (InstrumentKind::Future, Collections::TINKOFF_FUTURES),
+            (InstrumentKind::Option, Collections::TINKOFF_OPTIONS),
+            (InstrumentKind::Currency, Collections::TINKOFF_CURRENCIES),
+        ];
+        for (kind, collection) in catalogue {
+            assert_eq!(format!("tinkoff_{}", kind.plural()), collection);
+        }
+    }
 }
@@ -9,4 +9,7 @@ pub mod share;
 pub mod etf;
 pub mod future;
 pub mod real_exchange;
-pub mod instrument;
\ No newline at end of file
+pub mod instrument;
+pub mod option_type;
+pub mod option;
+pub mod currency;
\ No newline at end of file
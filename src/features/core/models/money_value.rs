@@ -1,27 +1,65 @@
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
 use crate::gen::tinkoff_public_invest_api_contract_v1::MoneyValue;
 
-/// Human-readable MoneyValue model
-#[derive(Debug, Clone, Serialize, Deserialize)]
+use super::quotation::{decimal_to_units_nano, units_nano_to_decimal};
+
+/// Human-readable MoneyValue model.
+///
+/// `value` — точное десятичное значение units/nano, пишется строкой и при чтении
+/// пересчитывается из units/nano
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "MoneyValueParts")]
 pub struct TinkoffMoneyValueModel {
     pub currency: String,
     pub units: i64,
     pub nano: i32,
-    pub value: f64,
+    pub value: Decimal,
 }
 
-impl From<&MoneyValue> for TinkoffMoneyValueModel {
-    fn from(m: &MoneyValue) -> Self {
-        let units = m.units;
-        let nano = m.nano;
-        let value = units as f64 + (nano as f64 / 1_000_000_000.0);
+#[derive(Deserialize)]
+struct MoneyValueParts {
+    currency: String,
+    units: i64,
+    nano: i32,
+}
 
+impl From<MoneyValueParts> for TinkoffMoneyValueModel {
+    fn from(parts: MoneyValueParts) -> Self {
+        Self::new(parts.currency, parts.units, parts.nano)
+    }
+}
+
+impl TinkoffMoneyValueModel {
+    pub fn new(currency: impl Into<String>, units: i64, nano: i32) -> Self {
         Self {
-            currency: m.currency.clone(),
+            currency: currency.into(),
             units,
             nano,
-            value,
+            value: units_nano_to_decimal(units, nano),
+        }
+    }
+
+    /// Сумма в валюте с округлением до 9 знаков
+    pub fn from_decimal(currency: impl Into<String>, value: Decimal) -> Self {
+        let (units, nano) = decimal_to_units_nano(value);
+        Self::new(currency, units, nano)
+    }
+}
+
+impl From<&MoneyValue> for TinkoffMoneyValueModel {
+    fn from(m: &MoneyValue) -> Self {
+        Self::new(m.currency.clone(), m.units, m.nano)
+    }
+}
+
+impl From<&TinkoffMoneyValueModel> for MoneyValue {
+    fn from(model: &TinkoffMoneyValueModel) -> Self {
+        MoneyValue {
+            currency: model.currency.clone(),
+            units: model.units,
+            nano: model.nano,
         }
     }
 }
@@ -0,0 +1,200 @@
+use serde::{Deserialize, Serialize};
+
+use crate::features::core::models::{
+    money_value::TinkoffMoneyValueModel,
+    option_type::{
+        TinkoffOptionDirectionModel, TinkoffOptionPaymentTypeModel,
+        TinkoffOptionSettlementTypeModel, TinkoffOptionStyleModel,
+    },
+    quotation::TinkoffQuotationModel,
+    real_exchange::TinkoffRealExchangeModel,
+    time_stamp::TinkoffTimestampModel,
+    trading_status::TinkoffTradingStatusModel,
+};
+use crate::gen::tinkoff_public_invest_api_contract_v1::Option as TinkoffOption;
+
+/// Опцион. FIGI и ISIN у опционов в API нет, инструмент идентифицируется по `uid`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TinkoffOptionModel {
+    // Basic fields
+    pub uid: String,
+    pub position_uid: String,
+    pub ticker: String,
+    pub class_code: String,
+    pub name: String,
+    pub lot: i32,
+    pub currency: String,
+    pub settlement_currency: String,
+    pub exchange: String,
+
+    // Flag fields
+    pub short_enabled_flag: bool,
+    pub for_iis_flag: bool,
+    pub otc_flag: bool,
+    pub buy_available_flag: bool,
+    pub sell_available_flag: bool,
+    pub for_qual_investor_flag: bool,
+    pub weekend_flag: bool,
+    pub blocked_tca_flag: bool,
+    pub api_trade_available_flag: bool,
+
+    // Enhanced enum fields
+    pub trading_status: TinkoffTradingStatusModel,
+    pub real_exchange: TinkoffRealExchangeModel,
+    pub direction: TinkoffOptionDirectionModel,
+    pub payment_type: TinkoffOptionPaymentTypeModel,
+    pub style: TinkoffOptionStyleModel,
+    pub settlement_type: TinkoffOptionSettlementTypeModel,
+
+    // Option specific fields
+    pub basic_asset: String,
+    pub basic_asset_position_uid: String,
+    pub basic_asset_size: Option<TinkoffQuotationModel>,
+    pub asset_type: String,
+    pub strike_price: Option<TinkoffMoneyValueModel>,
+    pub expiration_date: Option<TinkoffTimestampModel>,
+    pub first_trade_date: Option<TinkoffTimestampModel>,
+    pub last_trade_date: Option<TinkoffTimestampModel>,
+    pub country_of_risk: String,
+    pub country_of_risk_name: String,
+    pub sector: String,
+
+    // Optional fields with enhanced types
+    pub klong: Option<TinkoffQuotationModel>,
+    pub kshort: Option<TinkoffQuotationModel>,
+    pub dlong: Option<TinkoffQuotationModel>,
+    pub dshort: Option<TinkoffQuotationModel>,
+    pub dlong_min: Option<TinkoffQuotationModel>,
+    pub dshort_min: Option<TinkoffQuotationModel>,
+    pub min_price_increment: Option<TinkoffQuotationModel>,
+    pub first_1min_candle_date: Option<TinkoffTimestampModel>,
+    pub first_1day_candle_date: Option<TinkoffTimestampModel>,
+}
+
+impl From<&TinkoffOption> for TinkoffOptionModel {
+    fn from(option: &TinkoffOption) -> Self {
+        TinkoffOptionModel {
+            uid: option.uid.clone(),
+            position_uid: option.position_uid.clone(),
+            ticker: option.ticker.clone(),
+            class_code: option.class_code.clone(),
+            name: option.name.clone(),
+            lot: option.lot,
+            currency: option.currency.clone(),
+            settlement_currency: option.settlement_currency.clone(),
+            exchange: option.exchange.clone(),
+
+            short_enabled_flag: option.short_enabled_flag,
+            for_iis_flag: option.for_iis_flag,
+            otc_flag: option.otc_flag,
+            buy_available_flag: option.buy_available_flag,
+            sell_available_flag: option.sell_available_flag,
+            for_qual_investor_flag: option.for_qual_investor_flag,
+            weekend_flag: option.weekend_flag,
+            blocked_tca_flag: option.blocked_tca_flag,
+            api_trade_available_flag: option.api_trade_available_flag,
+
+            trading_status: TinkoffTradingStatusModel::from(option.trading_status),
+            real_exchange: TinkoffRealExchangeModel::from(option.real_exchange),
+            direction: TinkoffOptionDirectionModel::from(option.direction),
+            payment_type: TinkoffOptionPaymentTypeModel::from(option.payment_type),
+            style: TinkoffOptionStyleModel::from(option.style),
+            settlement_type: TinkoffOptionSettlementTypeModel::from(option.settlement_type),
+
+            basic_asset: option.basic_asset.clone(),
+            basic_asset_position_uid: option.basic_asset_position_uid.clone(),
+            basic_asset_size: option
+                .basic_asset_size
+                .as_ref()
+                .map(TinkoffQuotationModel::from),
+            asset_type: option.asset_type.clone(),
+            strike_price: option
+                .strike_price
+                .as_ref()
+                .map(TinkoffMoneyValueModel::from),
+            expiration_date: option
+                .expiration_date
+                .as_ref()
+                .map(TinkoffTimestampModel::from),
+            first_trade_date: option
+                .first_trade_date
+                .as_ref()
+                .map(TinkoffTimestampModel::from),
+            last_trade_date: option
+                .last_trade_date
+                .as_ref()
+                .map(TinkoffTimestampModel::from),
+            country_of_risk: option.country_of_risk.clone(),
+            country_of_risk_name: option.country_of_risk_name.clone(),
+            sector: option.sector.clone(),
+
+            klong: option.klong.as_ref().map(TinkoffQuotationModel::from),
+            kshort: option.kshort.as_ref().map(TinkoffQuotationModel::from),
+            dlong: option.dlong.as_ref().map(TinkoffQuotationModel::from),
+            dshort: option.dshort.as_ref().map(TinkoffQuotationModel::from),
+            dlong_min: option.dlong_min.as_ref().map(TinkoffQuotationModel::from),
+            dshort_min: option.dshort_min.as_ref().map(TinkoffQuotationModel::from),
+            min_price_increment: option
+                .min_price_increment
+                .as_ref()
+                .map(TinkoffQuotationModel::from),
+            first_1min_candle_date: option
+                .first_1min_candle_date
+                .as_ref()
+                .map(TinkoffTimestampModel::from),
+            first_1day_candle_date: option
+                .first_1day_candle_date
+                .as_ref()
+                .map(TinkoffTimestampModel::from),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gen::tinkoff_public_invest_api_contract_v1::{
+        MoneyValue, OptionDirection, OptionPaymentType, OptionSettlementType, OptionStyle,
+    };
+    use rust_decimal::Decimal;
+
+    #[test]
+    fn test_option_from_proto() {
+        let option = TinkoffOption {
+            uid: "0d5c8d0c-2b3c-4b4e-9f3a-6c1f1f1b2a11".to_string(),
+            ticker: "SR320CC4A".to_string(),
+            basic_asset: "SBER".to_string(),
+            direction: OptionDirection::Call as i32,
+            payment_type: OptionPaymentType::Marginal as i32,
+            style: OptionStyle::American as i32,
+            settlement_type: OptionSettlementType::OptionExecutionTypePhysicalDelivery as i32,
+            strike_price: Some(MoneyValue {
+                currency: "rub".to_string(),
+                units: 320,
+                nano: 0,
+            }),
+            ..Default::default()
+        };
+
+        let model = TinkoffOptionModel::from(&option);
+        assert_eq!(model.uid, option.uid);
+        assert_eq!(model.ticker, "SR320CC4A");
+        assert_eq!(model.direction.name, "OPTION_DIRECTION_CALL");
+        assert_eq!(model.payment_type.name, "OPTION_PAYMENT_TYPE_MARGINAL");
+        assert_eq!(model.style.name, "OPTION_STYLE_AMERICAN");
+        assert_eq!(
+            model.settlement_type.name,
+            "OPTION_EXECUTION_TYPE_PHYSICAL_DELIVERY"
+        );
+        assert_eq!(model.strike_price.unwrap().value, Decimal::from(320));
+        assert!(model.expiration_date.is_none());
+
+        // Значение вне перечисления не ломает разбор
+        let model = TinkoffOptionModel::from(&TinkoffOption {
+            direction: 42,
+            ..option
+        });
+        assert_eq!(model.direction.raw, 42);
+        assert_eq!(model.direction.name, "UNKNOWN");
+    }
+}
@@ -0,0 +1,85 @@
+use serde::{Deserialize, Serialize};
+
+use crate::gen::tinkoff_public_invest_api_contract_v1::{
+    OptionDirection, OptionPaymentType, OptionSettlementType, OptionStyle,
+};
+
+/// Направление опциона: OPTION_DIRECTION_PUT, OPTION_DIRECTION_CALL
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TinkoffOptionDirectionModel {
+    pub raw: i32,
+    pub name: String,
+}
+
+impl From<i32> for TinkoffOptionDirectionModel {
+    fn from(raw: i32) -> Self {
+        let name = OptionDirection::try_from(raw)
+            .map(|value| value.as_str_name())
+            .unwrap_or("UNKNOWN");
+
+        Self {
+            raw,
+            name: name.to_string(),
+        }
+    }
+}
+
+/// Тип расчётов по опциону: премиальный или маржируемый
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TinkoffOptionPaymentTypeModel {
+    pub raw: i32,
+    pub name: String,
+}
+
+impl From<i32> for TinkoffOptionPaymentTypeModel {
+    fn from(raw: i32) -> Self {
+        let name = OptionPaymentType::try_from(raw)
+            .map(|value| value.as_str_name())
+            .unwrap_or("UNKNOWN");
+
+        Self {
+            raw,
+            name: name.to_string(),
+        }
+    }
+}
+
+/// Стиль опциона: американский или европейский
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TinkoffOptionStyleModel {
+    pub raw: i32,
+    pub name: String,
+}
+
+impl From<i32> for TinkoffOptionStyleModel {
+    fn from(raw: i32) -> Self {
+        let name = OptionStyle::try_from(raw)
+            .map(|value| value.as_str_name())
+            .unwrap_or("UNKNOWN");
+
+        Self {
+            raw,
+            name: name.to_string(),
+        }
+    }
+}
+
+/// Способ исполнения опциона: поставочный или расчётный
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TinkoffOptionSettlementTypeModel {
+    pub raw: i32,
+    pub name: String,
+}
+
+impl From<i32> for TinkoffOptionSettlementTypeModel {
+    fn from(raw: i32) -> Self {
+        let name = OptionSettlementType::try_from(raw)
+            .map(|value| value.as_str_name())
+            .unwrap_or("UNKNOWN");
+
+        Self {
+            raw,
+            name: name.to_string(),
+        }
+    }
+}
@@ -1,21 +1,122 @@
+use rust_decimal::{Decimal, RoundingStrategy};
 use serde::{Deserialize, Serialize};
 
 use crate::gen::tinkoff_public_invest_api_contract_v1::Quotation;
 
-/// Human-readable Quotation model
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Знаков после запятой в дробной части nano
+const NANO_SCALE: u32 = 9;
+
+/// Human-readable Quotation model.
+///
+/// `value` — точное десятичное значение units/nano; в BSON и JSON пишется строкой.
+/// При чтении `value` всегда пересчитывается из units/nano, поэтому старые документы
+/// с `value` типа double читаются без потерь
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "QuotationParts")]
 pub struct TinkoffQuotationModel {
     pub units: i64,
     pub nano: i32,
-    pub value: f64,
+    pub value: Decimal,
+}
+
+/// Хранимые поля Quotation, из которых восстанавливается значение
+#[derive(Deserialize)]
+struct QuotationParts {
+    units: i64,
+    nano: i32,
+}
+
+impl From<QuotationParts> for TinkoffQuotationModel {
+    fn from(parts: QuotationParts) -> Self {
+        Self::new(parts.units, parts.nano)
+    }
+}
+
+impl TinkoffQuotationModel {
+    pub fn new(units: i64, nano: i32) -> Self {
+        Self {
+            units,
+            nano,
+            value: units_nano_to_decimal(units, nano),
+        }
+    }
+
+    /// Значение с округлением до 9 знаков (точность units/nano)
+    pub fn from_decimal(value: Decimal) -> Self {
+        let (units, nano) = decimal_to_units_nano(value);
+        Self::new(units, nano)
+    }
 }
 
 impl From<&Quotation> for TinkoffQuotationModel {
     fn from(q: &Quotation) -> Self {
-        let units = q.units;
-        let nano = q.nano;
-        let value = units as f64 + (nano as f64 / 1_000_000_000.0);
+        Self::new(q.units, q.nano)
+    }
+}
+
+impl From<&TinkoffQuotationModel> for Quotation {
+    fn from(model: &TinkoffQuotationModel) -> Self {
+        Quotation {
+            units: model.units,
+            nano: model.nano,
+        }
+    }
+}
+
+impl From<Decimal> for TinkoffQuotationModel {
+    fn from(value: Decimal) -> Self {
+        Self::from_decimal(value)
+    }
+}
+
+/// Собирает точное десятичное значение из пары units/nano
+pub fn units_nano_to_decimal(units: i64, nano: i32) -> Decimal {
+    (Decimal::from(units) + Decimal::new(nano as i64, NANO_SCALE)).normalize()
+}
+
+/// Раскладывает значение на units/nano (знак у обеих частей одинаковый, как в API).
+/// Значение округляется до 9 знаков после запятой
+pub fn decimal_to_units_nano(value: Decimal) -> (i64, i32) {
+    let value = value.round_dp_with_strategy(NANO_SCALE, RoundingStrategy::MidpointNearestEven);
+    let units = value.trunc();
+    let nano = ((value - units) * Decimal::from(1_000_000_000)).trunc();
+
+    (
+        i64::try_from(units).unwrap_or(if value.is_sign_negative() { i64::MIN } else { i64::MAX }),
+        i32::try_from(nano).unwrap_or_default(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_quotation_decimal_roundtrip() {
+        for raw in ["0", "114.25", "-1.5", "0.000000001", "-0.75", "123456789.987654321"] {
+            let value = Decimal::from_str(raw).unwrap();
+            let model = TinkoffQuotationModel::from_decimal(value);
+            assert_eq!(model.value, value.normalize(), "{}", raw);
+
+            let quotation = Quotation::from(&model);
+            assert_eq!(TinkoffQuotationModel::from(&quotation), model);
+        }
+
+        assert_eq!(decimal_to_units_nano(Decimal::from_str("-1.5").unwrap()), (-1, -500_000_000));
+        // 0.1 + 0.2 без дрейфа f64
+        let sum = TinkoffQuotationModel::new(0, 100_000_000).value
+            + TinkoffQuotationModel::new(0, 200_000_000).value;
+        assert_eq!(sum, Decimal::from_str("0.3").unwrap());
+    }
+
+    #[test]
+    fn test_quotation_bson_value_is_recomputed() {
+        let stored = bson::doc! { "units": 2_i64, "nano": 10_000_000, "value": 2.0099999 };
+        let model: TinkoffQuotationModel = bson::from_document(stored).unwrap();
+        assert_eq!(model.value, Decimal::from_str("2.01").unwrap());
 
-        Self { units, nano, value }
+        let document = bson::to_document(&model).unwrap();
+        assert_eq!(document.get_str("value").unwrap(), "2.01");
     }
 }
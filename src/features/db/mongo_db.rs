@@ -1,9 +1,15 @@
 use crate::env_config::models::app_setting::AppSettings;
 use mongodb::bson::Document;
-use mongodb::{options::ClientOptions, Client, Collection, Database as MongoDatabase};
+use mongodb::error::ErrorKind;
+use mongodb::options::ClientOptions;
+use mongodb::{Client, Collection, Database as MongoDatabase};
+use serde::Serialize;
 use std::time::Duration;
 use tracing::{error, info};
 
+/// Код ошибки MongoDB при нарушении уникального индекса
+const DUPLICATE_KEY_CODE: i32 = 11000;
+
 // Database names constant
 pub struct DbNames;
 impl DbNames {
@@ -12,6 +18,8 @@ impl DbNames {
     pub const USER_CONFIG: &'static str = "user_config";
     pub const MARKET_REFERENCE: &'static str = "market_reference";
     pub const MARKET_CANDLES: &'static str = "market_candles";
+    pub const MARKET_STREAM: &'static str = "market_stream";
+    pub const PORTFOLIO: &'static str = "portfolio";
 }
 
 // Collection names constant
@@ -23,12 +31,34 @@ impl Collections {
     pub const TINKOFF_BONDS: &'static str = "tinkoff_bonds";
     pub const TINKOFF_ETFS: &'static str = "tinkoff_etfs";
     pub const TINKOFF_FUTURES: &'static str = "tinkoff_futures";
+    pub const TINKOFF_OPTIONS: &'static str = "tinkoff_options";
+    pub const TINKOFF_CURRENCIES: &'static str = "tinkoff_currencies";
+    pub const INSTRUMENT_CHANGELOG: &'static str = "instrument_changelog";
     pub const STATUS: &'static str = "_status";
+    pub const MIGRATIONS: &'static str = "_migrations";
+    pub const CANDLE_DAYS: &'static str = "_candle_days";
     pub const CURRENCY_RATES: &'static str = "currency_rates";
+    pub const CURRENCY_RATES_HISTORY: &'static str = "currency_rates_history";
+    pub const TRADING_SCHEDULES: &'static str = "trading_schedules";
+    pub const DIVIDENDS: &'static str = "dividends";
+    pub const BOND_COUPONS: &'static str = "bond_coupons";
+    pub const BOND_ACCRUED_INTERESTS: &'static str = "bond_accrued_interests";
+    pub const BOND_ANALYTICS: &'static str = "bond_analytics";
 
     pub const CANDLES_TRACKING: &'static str = "candles_tracking";
     pub const TINKOFF_1M: &'static str = "tinkoff_1m";
-    pub const TINKOFF_1M_SHARES_1M_HISTORICAL: &'static str = "tinkoff_shares_1m_historical";
+
+    // Portfolio collections
+    pub const ACCOUNTS: &'static str = "accounts";
+    pub const PORTFOLIO_SNAPSHOTS: &'static str = "portfolio_snapshots";
+    pub const OPERATIONS: &'static str = "operations";
+    pub const OPERATIONS_SYNC: &'static str = "_operations_sync";
+
+    // Market data stream collections
+    pub const TINKOFF_ORDER_BOOKS: &'static str = "tinkoff_order_books";
+    pub const TINKOFF_TRADES: &'static str = "tinkoff_trades";
+    pub const TINKOFF_LAST_PRICES: &'static str = "tinkoff_last_prices";
+    pub const TINKOFF_TRADING_STATUSES: &'static str = "tinkoff_trading_statuses";
 }
 
 #[derive(Clone)]
@@ -84,6 +114,34 @@ impl MongoDb {
     pub fn database(&self, name: &str) -> MongoDatabase {
         self.client.database(name)
     }
+
+    /// Вставляет документы одним неупорядоченным insert_many. Документы, которые уже есть
+    /// в коллекции по уникальному индексу, пропускаются; возвращаются их позиции в `documents`
+    pub async fn insert_many_skip_duplicates<T>(
+        &self,
+        collection: &Collection<T>,
+        documents: &[T],
+    ) -> Result<Vec<usize>, mongodb::error::Error>
+    where
+        T: Serialize + Send + Sync,
+    {
+        if documents.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let error = match collection.insert_many(documents).ordered(false).await {
+            Ok(_) => return Ok(Vec::new()),
+            Err(error) => error,
+        };
+        if let ErrorKind::InsertMany(failure) = error.kind.as_ref() {
+            if let (Some(write_errors), None) = (&failure.write_errors, &failure.write_concern_error) {
+                if write_errors.iter().all(|e| e.code == DUPLICATE_KEY_CODE) {
+                    return Ok(write_errors.iter().map(|e| e.index).collect());
+                }
+            }
+        }
+        Err(error)
+    }
     // Convenience methods for commonly used collections
     pub fn shares_collection(&self) -> Collection<Document> {
         self.client
@@ -117,17 +175,22 @@ impl MongoDb {
             .database(DbNames::MARKET_DATA)
             .collection::<Document>(Collections::TINKOFF_FUTURES)
     }
+    pub fn options_collection(&self) -> Collection<Document> {
+        self.client
+            .database(DbNames::MARKET_DATA)
+            .collection::<Document>(Collections::TINKOFF_OPTIONS)
+    }
+    pub fn currencies_collection(&self) -> Collection<Document> {
+        self.client
+            .database(DbNames::MARKET_DATA)
+            .collection::<Document>(Collections::TINKOFF_CURRENCIES)
+    }
 
     pub fn candles_tracking_collection(&self) -> Collection<Document> {
         self.client
             .database(DbNames::MARKET_SERVICES)
             .collection::<Document>(Collections::CANDLES_TRACKING)
     }
-    pub fn get_historical_collection(&self) -> Collection<Document> {
-        self.client
-            .database(DbNames::MARKET_CANDLES)
-            .collection::<Document>(Collections::TINKOFF_1M_SHARES_1M_HISTORICAL)
-    }
 
     
 }
@@ -0,0 +1,70 @@
+// src/features/db/mongo_extensions/bond_analytics/bond_analytics.rs
+
+use futures::stream::TryStreamExt;
+use mongodb::bson::doc;
+use mongodb::options::IndexOptions;
+use mongodb::{Collection, IndexModel};
+use tracing::{error, info};
+
+use crate::features::db::{
+    mongo_db::{Collections, DbNames},
+    MongoDb,
+};
+
+use super::models::DbBondAnalytics;
+
+impl MongoDb {
+    pub fn bond_analytics_collection(&self) -> Collection<DbBondAnalytics> {
+        self.database(DbNames::MARKET_REFERENCE)
+            .collection::<DbBondAnalytics>(Collections::BOND_ANALYTICS)
+    }
+
+    pub async fn ensure_bond_analytics_indexes(&self) {
+        match self
+            .bond_analytics_collection()
+            .create_index(
+                IndexModel::builder()
+                    .keys(doc! { "figi": 1, "date": 1 })
+                    .options(IndexOptions::builder().unique(true).build())
+                    .build(),
+            )
+            .await
+        {
+            Ok(_) => info!("Created figi/date index for bond analytics collection"),
+            Err(e) => error!(
+                "Failed to create index for bond analytics collection: {}",
+                e
+            ),
+        }
+    }
+
+    /// Сохраняет расчёт за дату; повторный расчёт в тот же день перезаписывает прежний
+    pub async fn upsert_bond_analytics(
+        &self,
+        analytics: &DbBondAnalytics,
+    ) -> Result<(), mongodb::error::Error> {
+        self.bond_analytics_collection()
+            .replace_one(
+                doc! { "figi": &analytics.figi, "date": &analytics.date },
+                analytics,
+            )
+            .upsert(true)
+            .await?;
+        Ok(())
+    }
+
+    /// Сохранённые расчёты облигации за дни [from_day, to_day] по возрастанию даты
+    pub async fn find_bond_analytics(
+        &self,
+        figi: &str,
+        from_day: &str,
+        to_day: &str,
+    ) -> Result<Vec<DbBondAnalytics>, mongodb::error::Error> {
+        self.bond_analytics_collection()
+            .find(doc! { "figi": figi, "date": { "$gte": from_day, "$lte": to_day } })
+            .sort(doc! { "date": 1 })
+            .await?
+            .try_collect()
+            .await
+    }
+}
@@ -0,0 +1,2 @@
+pub mod bond_analytics;
+pub mod models;
@@ -0,0 +1,37 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::features::bond_analytics::math::BondMetrics;
+
+/// Аналитика облигации на дату. Ключ — FIGI и дата расчёта (YYYY-MM-DD)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DbBondAnalytics {
+    pub figi: String,
+    pub ticker: String,
+    /// Дата расчётов, YYYY-MM-DD
+    pub date: String,
+    /// Валюта номинала
+    pub currency: String,
+    pub nominal: Decimal,
+    /// Чистая цена, % номинала
+    pub clean_price_percent: Decimal,
+    /// Время свечи, по которой взята цена, RFC 3339; None — цена передана в запросе
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub price_time: Option<String>,
+    /// НКД на одну бумагу
+    pub accrued_interest: Decimal,
+    /// Чистая цена + НКД на одну бумагу
+    pub dirty_price: Decimal,
+    pub perpetual: bool,
+    /// Амортизация: график погашения номинала неизвестен, метрики не считаются
+    pub amortization: bool,
+    /// Купонный календарь купонной облигации не загружен, метрики не считаются
+    #[serde(default)]
+    pub coupons_missing: bool,
+    /// Будущих платежей в расчёте
+    pub cash_flows: usize,
+    /// Купонов с неизвестной суммой (плавающие), взятых по последнему известному купону
+    pub estimated_coupons: usize,
+    pub metrics: BondMetrics,
+    pub computed_at: String,
+}
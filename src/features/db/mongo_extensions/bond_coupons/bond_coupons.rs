@@ -0,0 +1,151 @@
+// src/features/db/mongo_extensions/bond_coupons/bond_coupons.rs
+
+use futures::stream::TryStreamExt;
+use mongodb::bson::doc;
+use mongodb::options::IndexOptions;
+use mongodb::{Collection, IndexModel};
+use tracing::{error, info};
+
+use crate::features::db::{
+    mongo_db::{Collections, DbNames},
+    MongoDb,
+};
+
+use super::models::{DbBondAccruedInterest, DbBondCoupon};
+
+impl MongoDb {
+    pub fn bond_coupons_collection(&self) -> Collection<DbBondCoupon> {
+        self.database(DbNames::MARKET_REFERENCE)
+            .collection::<DbBondCoupon>(Collections::BOND_COUPONS)
+    }
+
+    pub fn bond_accrued_interests_collection(&self) -> Collection<DbBondAccruedInterest> {
+        self.database(DbNames::MARKET_REFERENCE)
+            .collection::<DbBondAccruedInterest>(Collections::BOND_ACCRUED_INTERESTS)
+    }
+
+    pub async fn ensure_bond_coupons_indexes(&self) {
+        match self
+            .bond_coupons_collection()
+            .create_index(
+                IndexModel::builder()
+                    .keys(doc! { "figi": 1, "coupon_number": 1 })
+                    .options(IndexOptions::builder().unique(true).build())
+                    .build(),
+            )
+            .await
+        {
+            Ok(_) => info!("Created figi/coupon_number index for bond coupons collection"),
+            Err(e) => error!("Failed to create index for bond coupons collection: {}", e),
+        }
+
+        match self
+            .bond_coupons_collection()
+            .create_index(IndexModel::builder().keys(doc! { "coupon_date": 1 }).build())
+            .await
+        {
+            Ok(_) => info!("Created coupon_date index for bond coupons collection"),
+            Err(e) => error!("Failed to create coupon_date index for bond coupons collection: {}", e),
+        }
+
+        match self
+            .bond_accrued_interests_collection()
+            .create_index(
+                IndexModel::builder()
+                    .keys(doc! { "figi": 1, "date": 1 })
+                    .options(IndexOptions::builder().unique(true).build())
+                    .build(),
+            )
+            .await
+        {
+            Ok(_) => info!("Created figi/date index for bond accrued interests collection"),
+            Err(e) => error!("Failed to create index for bond accrued interests collection: {}", e),
+        }
+    }
+
+    /// Сохраняет купоны по ключу (figi, coupon_number): объявленная позже ставка
+    /// плавающего купона перезаписывает пустую запись. Возвращает число новых купонов
+    pub async fn upsert_bond_coupons(
+        &self,
+        coupons: &[DbBondCoupon],
+    ) -> Result<u64, mongodb::error::Error> {
+        let collection = self.bond_coupons_collection();
+        let mut inserted = 0;
+        for coupon in coupons {
+            let result = collection
+                .replace_one(
+                    doc! { "figi": &coupon.figi, "coupon_number": coupon.coupon_number },
+                    coupon,
+                )
+                .upsert(true)
+                .await?;
+            if result.upserted_id.is_some() {
+                inserted += 1;
+            }
+        }
+        Ok(inserted)
+    }
+
+    pub async fn upsert_bond_accrued_interests(
+        &self,
+        interests: &[DbBondAccruedInterest],
+    ) -> Result<(), mongodb::error::Error> {
+        let collection = self.bond_accrued_interests_collection();
+        for interest in interests {
+            collection
+                .replace_one(doc! { "figi": &interest.figi, "date": &interest.date }, interest)
+                .upsert(true)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Купонный календарь облигации по номеру купона
+    pub async fn find_bond_coupons(
+        &self,
+        figi: &str,
+    ) -> Result<Vec<DbBondCoupon>, mongodb::error::Error> {
+        self.bond_coupons_collection()
+            .find(doc! { "figi": figi })
+            .sort(doc! { "coupon_number": 1 })
+            .await?
+            .try_collect()
+            .await
+    }
+
+    /// Купоны с датой выплаты в [from_day, to_day] по возрастанию даты.
+    /// `figis` ограничивает выборку облигациями; None — все облигации
+    pub async fn find_coupons_between(
+        &self,
+        figis: Option<&[String]>,
+        from_day: &str,
+        to_day: &str,
+    ) -> Result<Vec<DbBondCoupon>, mongodb::error::Error> {
+        let mut filter = doc! { "coupon_date": { "$gte": from_day, "$lte": to_day } };
+        if let Some(figis) = figis {
+            filter.insert("figi", doc! { "$in": figis });
+        }
+
+        self.bond_coupons_collection()
+            .find(filter)
+            .sort(doc! { "coupon_date": 1, "figi": 1 })
+            .await?
+            .try_collect()
+            .await
+    }
+
+    /// НКД облигации за дни [from_day, to_day] по возрастанию даты
+    pub async fn find_bond_accrued_interests(
+        &self,
+        figi: &str,
+        from_day: &str,
+        to_day: &str,
+    ) -> Result<Vec<DbBondAccruedInterest>, mongodb::error::Error> {
+        self.bond_accrued_interests_collection()
+            .find(doc! { "figi": figi, "date": { "$gte": from_day, "$lte": to_day } })
+            .sort(doc! { "date": 1 })
+            .await?
+            .try_collect()
+            .await
+    }
+}
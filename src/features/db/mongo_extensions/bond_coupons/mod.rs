@@ -0,0 +1,2 @@
+pub mod bond_coupons;
+pub mod models;
@@ -0,0 +1,52 @@
+use serde::{Deserialize, Serialize};
+
+use crate::features::core::models::{
+    money_value::TinkoffMoneyValueModel, quotation::TinkoffQuotationModel,
+};
+
+/// Купон облигации (InstrumentsService.GetBondCoupons). Ключ — FIGI и номер купона;
+/// даты хранятся как YYYY-MM-DD
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DbBondCoupon {
+    pub figi: String,
+    pub ticker: String,
+    pub coupon_number: i64,
+    /// Дата выплаты
+    pub coupon_date: String,
+    /// Дата фиксации реестра
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fix_date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub coupon_start_date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub coupon_end_date: Option<String>,
+    /// Купонный период, дней
+    pub coupon_period: i32,
+    /// Тип купона как в контракте: COUPON_TYPE_CONSTANT, COUPON_TYPE_FLOATING ...
+    pub coupon_type: String,
+    /// Выплата на одну облигацию; у будущих плавающих купонов обычно ещё не известна
+    pub pay_one_bond: Option<TinkoffMoneyValueModel>,
+    /// Размер выплаты известен
+    pub amount_known: bool,
+    /// Облигация с плавающим купоном (floating_coupon_flag)
+    pub floating: bool,
+    /// Облигация с амортизацией долга (amortization_flag): номинал гасится частями,
+    /// выплата купона считается от остатка номинала
+    pub amortization: bool,
+    pub updated_at: String,
+}
+
+/// НКД облигации на дату (InstrumentsService.GetAccruedInterests)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DbBondAccruedInterest {
+    pub figi: String,
+    /// Дата, YYYY-MM-DD
+    pub date: String,
+    /// НКД в валюте номинала
+    pub value: Option<TinkoffQuotationModel>,
+    /// НКД в процентах номинала
+    pub value_percent: Option<TinkoffQuotationModel>,
+    /// Номинал на дату (меняется при амортизации)
+    pub nominal: Option<TinkoffQuotationModel>,
+    pub updated_at: String,
+}
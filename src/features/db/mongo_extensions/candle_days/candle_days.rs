@@ -0,0 +1,165 @@
+// src/features/db/mongo_extensions/candle_days/candle_days.rs
+
+use futures::stream::TryStreamExt;
+use mongodb::bson::{doc, Document};
+use mongodb::options::IndexOptions;
+use mongodb::{Collection, IndexModel};
+use tracing::{error, info};
+
+use crate::features::db::{
+    mongo_db::{Collections, DbNames},
+    MongoDb,
+};
+
+use super::models::{DbCandleDay, DbCandleDayStatus};
+
+impl MongoDb {
+    pub fn candle_days_collection(&self) -> Collection<DbCandleDay> {
+        self.database(DbNames::MARKET_CANDLES)
+            .collection::<DbCandleDay>(Collections::CANDLE_DAYS)
+    }
+
+    pub async fn ensure_candle_days_indexes(&self) {
+        match self
+            .candle_days_collection()
+            .create_index(
+                IndexModel::builder()
+                    .keys(doc! { "figi": 1, "interval": 1, "day": 1 })
+                    .options(IndexOptions::builder().unique(true).build())
+                    .build(),
+            )
+            .await
+        {
+            Ok(_) => info!("Created FIGI/interval/day index for candle days collection"),
+            Err(e) => error!("Failed to create index for candle days collection: {}", e),
+        }
+    }
+
+    /// Отметки по FIGI и интервалу за дни [from_day, to_day] (YYYY-MM-DD)
+    pub async fn get_candle_days(
+        &self,
+        figi: &str,
+        interval: &str,
+        from_day: &str,
+        to_day: &str,
+    ) -> Result<Vec<DbCandleDay>, mongodb::error::Error> {
+        self.candle_days_collection()
+            .find(doc! {
+                "figi": figi,
+                "interval": interval,
+                "day": { "$gte": from_day, "$lte": to_day }
+            })
+            .sort(doc! { "day": 1 })
+            .await?
+            .try_collect()
+            .await
+    }
+
+    /// Незакрытые дни (ошибки загрузки и найденные сканером пропуски)
+    pub async fn find_open_candle_days(
+        &self,
+        figi: &str,
+        interval: Option<&str>,
+    ) -> Result<Vec<DbCandleDay>, mongodb::error::Error> {
+        let mut filter = doc! {
+            "figi": figi,
+            "status": { "$ne": DbCandleDayStatus::Complete.as_str() }
+        };
+        if let Some(interval) = interval {
+            filter.insert("interval", interval);
+        }
+
+        self.candle_days_collection()
+            .find(filter)
+            .sort(doc! { "interval": 1, "day": 1 })
+            .await?
+            .try_collect()
+            .await
+    }
+
+    pub async fn mark_candle_day_complete(
+        &self,
+        figi: &str,
+        interval: &str,
+        day: &str,
+        candle_count: i64,
+    ) -> Result<(), mongodb::error::Error> {
+        self.candle_days_collection()
+            .update_one(
+                candle_day_key(figi, interval, day),
+                doc! {
+                    "$set": {
+                        "status": DbCandleDayStatus::Complete.as_str(),
+                        "candle_count": candle_count,
+                        "updated_at": chrono::Utc::now().to_rfc3339(),
+                    },
+                    "$unset": { "error": "" }
+                },
+            )
+            .upsert(true)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn mark_candle_day_failed(
+        &self,
+        figi: &str,
+        interval: &str,
+        day: &str,
+        error: &str,
+    ) -> Result<(), mongodb::error::Error> {
+        self.candle_days_collection()
+            .update_one(
+                candle_day_key(figi, interval, day),
+                doc! {
+                    "$set": {
+                        "status": DbCandleDayStatus::Failed.as_str(),
+                        "error": error,
+                        "updated_at": chrono::Utc::now().to_rfc3339(),
+                    },
+                    "$inc": { "attempts": 1 }
+                },
+            )
+            .upsert(true)
+            .await?;
+        Ok(())
+    }
+
+    /// Отмечает пропуск, только если по дню ещё нет никакой отметки
+    pub async fn mark_candle_day_gap(
+        &self,
+        figi: &str,
+        interval: &str,
+        day: &str,
+    ) -> Result<(), mongodb::error::Error> {
+        self.candle_days_collection()
+            .update_one(
+                candle_day_key(figi, interval, day),
+                doc! {
+                    "$setOnInsert": {
+                        "status": DbCandleDayStatus::Gap.as_str(),
+                        "candle_count": 0,
+                        "attempts": 0,
+                        "updated_at": chrono::Utc::now().to_rfc3339(),
+                    }
+                },
+            )
+            .upsert(true)
+            .await?;
+        Ok(())
+    }
+}
+
+impl DbCandleDayStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Complete => "complete",
+            Self::Failed => "failed",
+            Self::Gap => "gap",
+        }
+    }
+}
+
+fn candle_day_key(figi: &str, interval: &str, day: &str) -> Document {
+    doc! { "figi": figi, "interval": interval, "day": day }
+}
@@ -0,0 +1,2 @@
+pub mod candle_days;
+pub mod models;
@@ -0,0 +1,31 @@
+use serde::{Deserialize, Serialize};
+
+/// Состояние загрузки одного дня свечей
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DbCandleDayStatus {
+    /// День загружен целиком (в том числе если свечей не было)
+    Complete,
+    /// Запрос за день завершился ошибкой
+    Failed,
+    /// Сканер нашёл торговый день без свечей внутри сохранённого диапазона
+    Gap,
+}
+
+/// Отметка о загрузке свечей по паре (FIGI, интервал) за день UTC
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DbCandleDay {
+    pub figi: String,
+    pub interval: String,
+    /// День в формате YYYY-MM-DD (UTC)
+    pub day: String,
+    pub status: DbCandleDayStatus,
+    #[serde(default)]
+    pub candle_count: i64,
+    /// Сколько раз день пытались загрузить с ошибкой
+    #[serde(default)]
+    pub attempts: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub updated_at: String,
+}
@@ -0,0 +1,313 @@
+// src/features/db/mongo_extensions/candles/candles.rs
+
+use std::collections::HashMap;
+
+use futures::stream::TryStreamExt;
+use mongodb::bson::{doc, Bson, Document};
+use mongodb::{Collection, IndexModel};
+use rust_decimal::Decimal;
+use tracing::{debug, error, info, warn};
+
+use crate::features::{
+    core::models::{candle_interval::MyCandleInterval, instrument::InstrumentKind},
+    db::{mongo_db::DbNames, MongoDb},
+};
+
+use super::models::{DbCandlesPage, DbCandlesUpsertResult, DbHistoricalCandle};
+
+/// Уникальный ключ свечи: FIGI, интервал и время открытия
+pub fn candle_unique_index() -> IndexModel {
+    IndexModel::builder()
+        .keys(doc! { "figi": 1, "interval": 1, "time.seconds": 1 })
+        .options(
+            mongodb::options::IndexOptions::builder()
+                .unique(true)
+                .name("figi_interval_time_unique".to_string())
+                .build(),
+        )
+        .build()
+}
+
+/// Фильтр по уникальному ключу свечи, собранный из самого документа
+fn candle_key(document: &Document) -> Option<Document> {
+    let figi = document.get_str("figi").ok()?;
+    let interval = document.get_str("interval").ok()?;
+    let seconds = document.get_document("time").ok()?.get_i64("seconds").ok()?;
+
+    Some(doc! { "figi": figi, "interval": interval, "time.seconds": seconds })
+}
+
+impl MongoDb {
+    /// Коллекция исторических свечей для типа инструмента и интервала,
+    /// например `tinkoff_shares_1m_historical` или `tinkoff_bonds_1d_historical`
+    pub fn historical_candles_collection(
+        &self,
+        kind: InstrumentKind,
+        interval: MyCandleInterval,
+    ) -> Collection<Document> {
+        let name = format!(
+            "tinkoff_{}_{}_historical",
+            kind.plural(),
+            interval.short_name()
+        );
+        self.database(DbNames::MARKET_CANDLES)
+            .collection::<Document>(&name)
+    }
+
+    /// Коллекция свечей, собранных из минутных (rollup), например `tinkoff_rollup_5m`.
+    /// Одна коллекция на интервал для всех типов инструментов
+    pub fn rollup_candles_collection(&self, interval: MyCandleInterval) -> Collection<Document> {
+        let name = format!("tinkoff_rollup_{}", interval.short_name());
+        self.database(DbNames::MARKET_CANDLES)
+            .collection::<Document>(&name)
+    }
+
+    /// Коллекция минутных свечей стрима по FIGI (`tinkoff_1m_{figi}`)
+    pub fn stream_candles_collection(&self, figi: &str) -> Collection<Document> {
+        self.database(DbNames::MARKET_CANDLES)
+            .collection::<Document>(&format!("tinkoff_1m_{}", figi))
+    }
+
+    /// Записывает свечи по ключу (figi, interval, time.seconds): новые свечи вставляются
+    /// одним insert_many, уже сохранённые обновляются. Повторная загрузка того же периода
+    /// обновляет значения, а не создаёт дубликаты
+    pub async fn upsert_candles(
+        &self,
+        collection: &Collection<Document>,
+        documents: Vec<Document>,
+    ) -> Result<DbCandlesUpsertResult, mongodb::error::Error> {
+        let mut keyed = Vec::with_capacity(documents.len());
+        for document in documents {
+            match candle_key(&document) {
+                Some(filter) => keyed.push((filter, document)),
+                None => error!(
+                    "Candle document without figi/interval/time, skipped: {:?}",
+                    document
+                ),
+            }
+        }
+
+        let new_documents: Vec<Document> = keyed.iter().map(|(_, document)| document.clone()).collect();
+        let existing = self
+            .insert_many_skip_duplicates(collection, &new_documents)
+            .await?;
+
+        let mut result = DbCandlesUpsertResult {
+            inserted: (keyed.len() - existing.len()) as u64,
+            updated: 0,
+        };
+        for index in existing {
+            let (filter, document) = &keyed[index];
+            let update = collection
+                .update_one(filter.clone(), doc! { "$set": document })
+                .await?;
+            if update.modified_count > 0 {
+                result.updated += 1;
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Возвращает страницу свечей по FIGI в полуинтервале [from, to) (секунды UTC),
+    /// отсортированную по времени, вместе с общим количеством свечей в диапазоне
+    pub async fn find_historical_candles(
+        &self,
+        collection: &Collection<Document>,
+        figi: &str,
+        from_seconds: i64,
+        to_seconds: i64,
+        skip: u64,
+        limit: i64,
+    ) -> Result<DbCandlesPage, mongodb::error::Error> {
+        let filter = doc! {
+            "figi": figi,
+            "time.seconds": { "$gte": from_seconds, "$lt": to_seconds }
+        };
+
+        let total = collection.count_documents(filter.clone()).await?;
+
+        let documents: Vec<Document> = collection
+            .find(filter)
+            .sort(doc! { "time.seconds": 1 })
+            .skip(skip)
+            .limit(limit)
+            .await?
+            .try_collect()
+            .await?;
+
+        let mut candles = Vec::with_capacity(documents.len());
+        for document in documents {
+            candles.push(bson::from_document::<DbHistoricalCandle>(document)?);
+        }
+
+        debug!(
+            "Found {} candles for {} (page size {}, total {})",
+            candles.len(),
+            figi,
+            limit,
+            total
+        );
+
+        Ok(DbCandlesPage { candles, total })
+    }
+
+    /// Все свечи по FIGI в полуинтервале [from, to) (секунды UTC), по возрастанию времени
+    pub async fn find_candles_between(
+        &self,
+        collection: &Collection<Document>,
+        figi: &str,
+        from_seconds: i64,
+        to_seconds: i64,
+    ) -> Result<Vec<DbHistoricalCandle>, mongodb::error::Error> {
+        let documents: Vec<Document> = collection
+            .find(doc! {
+                "figi": figi,
+                "time.seconds": { "$gte": from_seconds, "$lt": to_seconds }
+            })
+            .sort(doc! { "time.seconds": 1 })
+            .await?
+            .try_collect()
+            .await?;
+
+        let mut candles = Vec::with_capacity(documents.len());
+        for document in documents {
+            candles.push(bson::from_document::<DbHistoricalCandle>(document)?);
+        }
+        Ok(candles)
+    }
+
+    /// Последняя свеча по FIGI, открытая не позже `at_seconds`
+    pub async fn find_last_candle_before(
+        &self,
+        collection: &Collection<Document>,
+        figi: &str,
+        at_seconds: i64,
+    ) -> Result<Option<DbHistoricalCandle>, mongodb::error::Error> {
+        let document = collection
+            .find_one(doc! { "figi": figi, "time.seconds": { "$lte": at_seconds } })
+            .sort(doc! { "time.seconds": -1 })
+            .await?;
+
+        match document {
+            Some(document) => Ok(Some(bson::from_document::<DbHistoricalCandle>(document)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Время открытия и цена закрытия последней свечи не позже `at_seconds`
+    /// среди минутных и дневных свечей типа инструмента и дневных rollup-свечей
+    pub async fn find_last_close(
+        &self,
+        kind: InstrumentKind,
+        figi: &str,
+        at_seconds: i64,
+    ) -> Option<(i64, Decimal)> {
+        let collections = [
+            self.historical_candles_collection(kind, MyCandleInterval::OneMin),
+            self.historical_candles_collection(kind, MyCandleInterval::Day),
+            self.rollup_candles_collection(MyCandleInterval::Day),
+        ];
+
+        let mut latest: Option<(i64, Decimal)> = None;
+        for collection in &collections {
+            match self.find_last_candle_before(collection, figi, at_seconds).await {
+                Ok(Some(candle)) => {
+                    if latest.is_none_or(|(time, _)| candle.time.seconds > time) {
+                        latest = Some((candle.time.seconds, candle.close.value));
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => warn!("Failed to read candles for {}: {}", figi, e),
+            }
+        }
+        latest
+    }
+
+    /// FIGI, по которым в коллекции есть свечи
+    pub async fn distinct_candle_figis(
+        &self,
+        collection: &Collection<Document>,
+    ) -> Result<Vec<String>, mongodb::error::Error> {
+        let values = collection.distinct("figi", doc! {}).await?;
+        Ok(values
+            .into_iter()
+            .filter_map(|value| value.as_str().map(str::to_string))
+            .collect())
+    }
+
+    /// Количество сохранённых свечей по дням UTC (YYYY-MM-DD) в полуинтервале [from, to)
+    pub async fn count_candles_by_day(
+        &self,
+        collection: &Collection<Document>,
+        figi: &str,
+        from_seconds: i64,
+        to_seconds: i64,
+    ) -> Result<HashMap<String, i64>, mongodb::error::Error> {
+        let pipeline = vec![
+            doc! {
+                "$match": {
+                    "figi": figi,
+                    "time.seconds": { "$gte": from_seconds, "$lt": to_seconds }
+                }
+            },
+            doc! {
+                "$group": {
+                    "_id": {
+                        "$dateToString": {
+                            "format": "%Y-%m-%d",
+                            "date": { "$toDate": { "$multiply": ["$time.seconds", 1000_i64] } }
+                        }
+                    },
+                    "count": { "$sum": 1 }
+                }
+            },
+        ];
+
+        let documents: Vec<Document> = collection.aggregate(pipeline).await?.try_collect().await?;
+
+        Ok(documents
+            .into_iter()
+            .filter_map(|document| {
+                let day = document.get_str("_id").ok()?.to_string();
+                let count = match document.get("count")? {
+                    Bson::Int32(count) => *count as i64,
+                    Bson::Int64(count) => *count,
+                    _ => return None,
+                };
+                Some((day, count))
+            })
+            .collect())
+    }
+
+    /// Время первой и последней сохранённой свечи по FIGI (секунды UTC)
+    pub async fn get_candles_time_bounds(
+        &self,
+        collection: &Collection<Document>,
+        figi: &str,
+    ) -> Result<Option<(i64, i64)>, mongodb::error::Error> {
+        let first = collection
+            .find_one(doc! { "figi": figi })
+            .sort(doc! { "time.seconds": 1 })
+            .await?;
+        let last = collection
+            .find_one(doc! { "figi": figi })
+            .sort(doc! { "time.seconds": -1 })
+            .await?;
+
+        let seconds = |document: Option<Document>| {
+            document
+                .as_ref()
+                .and_then(|d| d.get_document("time").ok())
+                .and_then(|t| t.get_i64("seconds").ok())
+        };
+
+        match (seconds(first), seconds(last)) {
+            (Some(first), Some(last)) => Ok(Some((first, last))),
+            _ => {
+                info!("No stored candles found for {}", figi);
+                Ok(None)
+            }
+        }
+    }
+}
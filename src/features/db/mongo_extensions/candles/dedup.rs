@@ -0,0 +1,201 @@
+// src/features/db/mongo_extensions/candles/dedup.rs
+
+use futures::stream::TryStreamExt;
+use mongodb::bson::{doc, oid::ObjectId, Bson, Document};
+use mongodb::Collection;
+use tracing::{error, info, warn};
+
+use crate::features::db::{
+    mongo_db::{Collections, DbNames},
+    MongoDb,
+};
+
+use super::candles::candle_unique_index;
+
+/// Идентификатор записи о выполненной миграции
+const DEDUP_MIGRATION_ID: &str = "candles_dedup_v1";
+
+/// Сколько _id удалять одним запросом
+const DELETE_BATCH_SIZE: usize = 1000;
+
+/// Коллекция свечей и значения ключа, которых может не быть в старых документах
+struct CandleCollection {
+    name: String,
+    interval: String,
+    figi: Option<String>,
+}
+
+/// Разбирает имя коллекции свечей:
+/// `tinkoff_{type}s_{interval}_historical` (загрузчик) или `tinkoff_1m_{figi}` (стрим)
+fn parse_candle_collection(name: &str) -> Option<CandleCollection> {
+    if let Some(rest) = name
+        .strip_prefix("tinkoff_")
+        .and_then(|rest| rest.strip_suffix("_historical"))
+    {
+        let (_, interval) = rest.rsplit_once('_')?;
+        return Some(CandleCollection {
+            name: name.to_string(),
+            interval: interval.to_string(),
+            figi: None,
+        });
+    }
+
+    let figi = name.strip_prefix("tinkoff_1m_")?;
+    Some(CandleCollection {
+        name: name.to_string(),
+        interval: "1m".to_string(),
+        figi: Some(figi.to_string()),
+    })
+}
+
+impl MongoDb {
+    /// Разовая миграция: убирает дубликаты свечей (figi, interval, time.seconds),
+    /// накопившиеся до перехода на upsert, и создаёт уникальный индекс.
+    /// Из каждой группы дубликатов остаётся последняя записанная версия свечи
+    pub async fn run_candles_dedup_migration(&self) {
+        let database = self.database(DbNames::MARKET_CANDLES);
+        let migrations = database.collection::<Document>(Collections::MIGRATIONS);
+
+        match migrations.find_one(doc! { "_id": DEDUP_MIGRATION_ID }).await {
+            Ok(Some(_)) => return,
+            Ok(None) => {}
+            Err(e) => {
+                error!("Failed to check candle dedup migration status: {}", e);
+                return;
+            }
+        }
+
+        let names = match database.list_collection_names().await {
+            Ok(names) => names,
+            Err(e) => {
+                error!("Failed to list candle collections: {}", e);
+                return;
+            }
+        };
+
+        info!("Running candle dedup migration");
+
+        let mut processed = 0;
+        let mut removed_total = 0;
+        for target in names.iter().filter_map(|name| parse_candle_collection(name)) {
+            let collection = database.collection::<Document>(&target.name);
+            match self.dedup_candle_collection(&collection, &target).await {
+                Ok(removed) => {
+                    processed += 1;
+                    removed_total += removed;
+                }
+                Err(e) => {
+                    // Миграция повторится при следующем запуске
+                    error!("Candle dedup migration failed for {}: {}", target.name, e);
+                    return;
+                }
+            }
+        }
+
+        let record = doc! {
+            "_id": DEDUP_MIGRATION_ID,
+            "completed_at": chrono::Utc::now().to_rfc3339(),
+            "collections": processed,
+            "removed": removed_total as i64,
+        };
+        match migrations.insert_one(record).await {
+            Ok(_) => info!(
+                "Candle dedup migration completed: {} collections, {} duplicates removed",
+                processed, removed_total
+            ),
+            Err(e) => error!("Failed to record candle dedup migration: {}", e),
+        }
+    }
+
+    async fn dedup_candle_collection(
+        &self,
+        collection: &Collection<Document>,
+        target: &CandleCollection,
+    ) -> Result<u64, mongodb::error::Error> {
+        // Старые документы не содержат интервала (а документы стрима и FIGI)
+        collection
+            .update_many(
+                doc! { "interval": { "$exists": false } },
+                doc! { "$set": { "interval": &target.interval } },
+            )
+            .await?;
+        if let Some(figi) = &target.figi {
+            collection
+                .update_many(
+                    doc! { "figi": { "$exists": false } },
+                    doc! { "$set": { "figi": figi } },
+                )
+                .await?;
+        }
+
+        let pipeline = vec![
+            doc! {
+                "$group": {
+                    "_id": { "figi": "$figi", "interval": "$interval", "seconds": "$time.seconds" },
+                    "ids": { "$push": "$_id" },
+                    "count": { "$sum": 1 }
+                }
+            },
+            doc! { "$match": { "count": { "$gt": 1 } } },
+        ];
+        let groups: Vec<Document> = collection
+            .aggregate(pipeline)
+            .allow_disk_use(true)
+            .await?
+            .try_collect()
+            .await?;
+
+        let mut to_delete: Vec<Bson> = Vec::new();
+        for group in groups {
+            let Ok(ids) = group.get_array("ids") else {
+                continue;
+            };
+            let mut ids: Vec<ObjectId> = ids.iter().filter_map(|id| id.as_object_id()).collect();
+            // ObjectId растёт со временем вставки: последний — самая свежая версия свечи
+            ids.sort();
+            ids.pop();
+            to_delete.extend(ids.into_iter().map(Bson::ObjectId));
+        }
+
+        let mut removed = 0;
+        for batch in to_delete.chunks(DELETE_BATCH_SIZE) {
+            let result = collection
+                .delete_many(doc! { "_id": { "$in": batch.to_vec() } })
+                .await?;
+            removed += result.deleted_count;
+        }
+
+        if removed > 0 {
+            warn!(
+                "Removed {} duplicate candles from {}",
+                removed,
+                collection.name()
+            );
+        }
+
+        collection.create_index(candle_unique_index()).await?;
+
+        Ok(removed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_candle_collection() {
+        let historical = parse_candle_collection("tinkoff_shares_1m_historical").unwrap();
+        assert_eq!(historical.interval, "1m");
+        assert!(historical.figi.is_none());
+
+        let daily = parse_candle_collection("tinkoff_bonds_1d_historical").unwrap();
+        assert_eq!(daily.interval, "1d");
+
+        let stream = parse_candle_collection("tinkoff_1m_BBG004730N88").unwrap();
+        assert_eq!(stream.interval, "1m");
+        assert_eq!(stream.figi.as_deref(), Some("BBG004730N88"));
+
+        assert!(parse_candle_collection("_status").is_none());
+    }
+}
@@ -0,0 +1,3 @@
+pub mod candles;
+pub mod dedup;
+pub mod models;
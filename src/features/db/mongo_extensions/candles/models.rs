@@ -0,0 +1,74 @@
+use mongodb::bson::{self, Bson};
+use serde::{Deserialize, Serialize};
+
+use crate::features::core::models::quotation::TinkoffQuotationModel;
+use crate::gen::tinkoff_public_invest_api_contract_v1::Quotation;
+
+/// Цена в документах свечей: units/nano и точное значение `value` (строкой), как у
+/// `TinkoffQuotationModel`. Документы без `value` читаются, значение пересчитывается
+pub type DbCandlePrice = TinkoffQuotationModel;
+
+/// BSON цены свечи из Quotation API; отсутствующая цена пишется нулём
+pub fn candle_price_bson(quotation: Option<&Quotation>) -> Bson {
+    let price = quotation.map_or_else(|| DbCandlePrice::new(0, 0), DbCandlePrice::from);
+    bson::to_bson(&price).unwrap_or(Bson::Null)
+}
+
+/// Время свечи в формате seconds/nanos
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DbCandleTime {
+    pub seconds: i64,
+    pub nanos: i32,
+}
+
+/// Модель документа исторической свечи (коллекции `market_candles.*_historical`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DbHistoricalCandle {
+    pub figi: String,
+    /// Интервал в короткой форме (1m, 1d ...); входит в уникальный ключ свечи
+    #[serde(default)]
+    pub interval: String,
+    pub volume: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_time: Option<String>,
+    pub open: DbCandlePrice,
+    pub high: DbCandlePrice,
+    pub low: DbCandlePrice,
+    pub close: DbCandlePrice,
+    pub time: DbCandleTime,
+}
+
+/// Страница свечей, найденных по фильтру
+#[derive(Debug)]
+pub struct DbCandlesPage {
+    pub candles: Vec<DbHistoricalCandle>,
+    pub total: u64,
+}
+
+/// Результат пакетной записи свечей
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DbCandlesUpsertResult {
+    pub inserted: u64,
+    pub updated: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::Decimal;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_candle_price_bson_stores_decimal_value() {
+        let price = candle_price_bson(Some(&Quotation { units: 114, nano: 250_000_000 }));
+        let document = price.as_document().unwrap();
+        assert_eq!(document.get_i64("units").unwrap(), 114);
+        assert_eq!(document.get_i32("nano").unwrap(), 250_000_000);
+        assert_eq!(document.get_str("value").unwrap(), "114.25");
+
+        // Свечи, сохранённые до появления value, читаются с пересчитанным значением
+        let stored: DbCandlePrice =
+            bson::from_document(bson::doc! { "units": 2_i64, "nano": 10_000_000 }).unwrap();
+        assert_eq!(stored.value, Decimal::from_str("2.01").unwrap());
+    }
+}
@@ -1,5 +1,6 @@
 
 
+use crate::env_config::models::app_config::CurrencyRateConfig;
 use crate::features::{
     db::{
         mongo_db::{Collections, DbNames},
@@ -7,23 +8,29 @@ use crate::features::{
         MongoDb,
     },
 
+    cbr_api::models::CbrDailyRate,
     moex_api::models::MoexRatesResponse,
 };
 
+use chrono::Utc;
+use futures::stream::TryStreamExt;
 use mongodb::bson::{doc, Document};
-use tracing::info;
+use mongodb::options::IndexOptions;
+use mongodb::{Collection, IndexModel};
+use tracing::{error, info};
 
-use super::models::CurrencyRatesResponse;
+use super::models::{CurrencyRateSources, CurrencyRatesResponse, DbCurrencyRate};
 
 impl MongoDb {
     pub async fn save_currency_rates(
         &self,
         moex_rates: &MoexRatesResponse,
+        currencies: &[CurrencyRateConfig],
     ) -> Result<CurrencyRatesResponse, Box<dyn std::error::Error + Send + Sync>> {
         info!("Processing and saving currency rates to MongoDB");
 
         // Map MoexRatesResponse to CurrencyRatesResponse using the mapper from mongo_extensions
-        let currency_rates = MoexRatesMapper::map_to_currency_rates(moex_rates)?;
+        let currency_rates = MoexRatesMapper::map_to_currency_rates(moex_rates, currencies)?;
 
         // Get the collection for currency rates
         let collection = self
@@ -33,21 +40,26 @@ impl MongoDb {
         // Convert currency_rates to BSON Document
         let rates_doc = mongodb::bson::to_document(&currency_rates)?;
 
-        // Clear existing data
-        collection.delete_many(doc! {}).await?;
-        info!("Previous records deleted from currency rates collection");
-
-        // Save new data
-        let result = collection.insert_one(rates_doc).await?;
+        // Снимок за дату заменяется, снимки прошлых дат остаются
+        let result = collection
+            .replace_one(doc! { "date": &currency_rates.date }, rates_doc)
+            .upsert(true)
+            .await?;
         info!(
-            "Currency rates successfully saved to MongoDB. Inserted ID: {:?}",
-            result.inserted_id
+            "Currency rates for {} saved to MongoDB (matched: {}, upserted: {:?})",
+            currency_rates.date, result.matched_count, result.upserted_id
         );
 
+        // Курсы каждого источника копятся в архиве по датам
+        if let Err(e) = self.archive_currency_rates(&currency_rates).await {
+            error!("Failed to archive currency rates: {}", e);
+        }
+
         // Return the converted data
         Ok(currency_rates)
     }
 
+    /// Последний сохранённый снимок курсов
     pub async fn get_currency_rates(&self) -> Option<CurrencyRatesResponse> {
         info!("Fetching currency rates from MongoDB");
 
@@ -55,7 +67,7 @@ impl MongoDb {
             .database(DbNames::MARKET_REFERENCE)
             .collection::<Document>(Collections::CURRENCY_RATES);
 
-        match collection.find_one(doc! {}).await {
+        match collection.find_one(doc! {}).sort(doc! { "date": -1 }).await {
             Ok(Some(doc)) => {
                 match bson::from_document::<CurrencyRatesResponse>(doc) {
                     Ok(rates) => {
@@ -78,4 +90,160 @@ impl MongoDb {
             }
         }
     }
-}
\ No newline at end of file
+
+    pub fn currency_rates_history_collection(&self) -> Collection<DbCurrencyRate> {
+        self.database(DbNames::MARKET_REFERENCE)
+            .collection::<DbCurrencyRate>(Collections::CURRENCY_RATES_HISTORY)
+    }
+
+    pub async fn ensure_currency_rates_indexes(&self) {
+        match self
+            .database(DbNames::MARKET_REFERENCE)
+            .collection::<Document>(Collections::CURRENCY_RATES)
+            .create_index(IndexModel::builder().keys(doc! { "date": -1 }).build())
+            .await
+        {
+            Ok(_) => info!("Created date index for currency rates collection"),
+            Err(e) => error!("Failed to create index for currency rates collection: {}", e),
+        }
+
+        match self
+            .currency_rates_history_collection()
+            .create_index(
+                IndexModel::builder()
+                    .keys(doc! { "currency": 1, "source": 1, "date": 1 })
+                    .options(IndexOptions::builder().unique(true).build())
+                    .build(),
+            )
+            .await
+        {
+            Ok(_) => info!("Created currency/source/date index for currency rates history"),
+            Err(e) => error!("Failed to create index for currency rates history: {}", e),
+        }
+    }
+
+    /// Раскладывает снимок на курсы по источникам и сохраняет их по дате курса
+    async fn archive_currency_rates(
+        &self,
+        currency_rates: &CurrencyRatesResponse,
+    ) -> Result<(), mongodb::error::Error> {
+        let collection = self.currency_rates_history_collection();
+        let updated_at = Utc::now().to_rfc3339();
+
+        for (code, info) in &currency_rates.currencies {
+            let mut rates = Vec::new();
+            if let Some(central_bank) = &info.central_bank {
+                rates.push((CurrencyRateSources::CBRF, &central_bank.date, central_bank.current_rate));
+            }
+            if let Some(exchange) = &info.exchange {
+                rates.push((CurrencyRateSources::EXCHANGE, &exchange.date, exchange.current_rate));
+            }
+            if let Some(wap) = &info.wap_rate {
+                // WAP приходит за лот номиналом nominal
+                let rate = if wap.nominal.is_zero() {
+                    wap.current_rate
+                } else {
+                    wap.current_rate / wap.nominal
+                };
+                rates.push((CurrencyRateSources::WAP, &wap.date, rate));
+            }
+
+            for (source, date, rate) in rates {
+                if date.is_empty() || rate.is_zero() {
+                    continue;
+                }
+
+                let rate = DbCurrencyRate {
+                    currency: code.clone(),
+                    source: source.to_string(),
+                    date: date.clone(),
+                    rate,
+                    updated_at: updated_at.clone(),
+                };
+                // В снимке лишь несколько валют, поэтому курсы пишутся по одному
+                collection
+                    .replace_one(
+                        doc! { "currency": &rate.currency, "source": &rate.source, "date": &rate.date },
+                        &rate,
+                    )
+                    .upsert(true)
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Дописывает в архив официальные курсы ЦБ из истории. Даты, уже сохранённые в архиве,
+    /// не перезаписываются, поэтому повторная загрузка того же периода ничего не меняет.
+    /// Возвращает количество новых курсов
+    pub async fn archive_cbr_rates(
+        &self,
+        currency: &str,
+        rates: &[CbrDailyRate],
+    ) -> Result<i64, mongodb::error::Error> {
+        let updated_at = Utc::now().to_rfc3339();
+        let documents: Vec<DbCurrencyRate> = rates
+            .iter()
+            .map(|rate| DbCurrencyRate {
+                currency: currency.to_uppercase(),
+                source: CurrencyRateSources::CBRF.to_string(),
+                date: rate.date.format("%Y-%m-%d").to_string(),
+                rate: rate.rate,
+                updated_at: updated_at.clone(),
+            })
+            .collect();
+
+        // Уникальный индекс currency/source/date отсекает уже сохранённые даты
+        let existing = self
+            .insert_many_skip_duplicates(&self.currency_rates_history_collection(), &documents)
+            .await?;
+        Ok((documents.len() - existing.len()) as i64)
+    }
+
+    /// Курс на дату: последний известный курс источника не позже `date` (YYYY-MM-DD).
+    /// ЦБ не устанавливает курс на выходные, поэтому берётся ближайший предыдущий
+    pub async fn get_currency_rate(
+        &self,
+        currency: &str,
+        source: &str,
+        date: &str,
+    ) -> Result<Option<DbCurrencyRate>, mongodb::error::Error> {
+        self.currency_rates_history_collection()
+            .find_one(doc! {
+                "currency": currency.to_uppercase(),
+                "source": source,
+                "date": { "$lte": date },
+            })
+            .sort(doc! { "date": -1 })
+            .await
+    }
+
+    /// Курсы источника за период по возрастанию даты; границы включительно
+    pub async fn get_currency_rate_history(
+        &self,
+        currency: &str,
+        source: &str,
+        from: Option<&str>,
+        to: Option<&str>,
+    ) -> Result<Vec<DbCurrencyRate>, mongodb::error::Error> {
+        let mut filter = doc! { "currency": currency.to_uppercase(), "source": source };
+        let mut date = Document::new();
+        if let Some(from) = from {
+            date.insert("$gte", from);
+        }
+        if let Some(to) = to {
+            date.insert("$lte", to);
+        }
+        if !date.is_empty() {
+            filter.insert("date", date);
+        }
+
+        self.currency_rates_history_collection()
+            .find(filter)
+            .sort(doc! { "date": 1 })
+            .await?
+            .try_collect()
+            .await
+    }
+}
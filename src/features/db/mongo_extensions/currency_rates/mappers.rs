@@ -1,102 +1,105 @@
 
 
+use crate::env_config::models::app_config::{CurrencyRateConfig, MoexRateColumns};
 use crate::features::moex_api::models::MoexRatesResponse;
 
+use rust_decimal::Decimal;
+use serde_json::Value;
 use std::collections::HashMap;
+use std::str::FromStr;
 use tracing::{info, warn};
 
 use super::models::{CurrencyDisplayInfo, CurrencyInfo, CurrencyRatesResponse, ExchangeRateInfo, RateChange, RateInfo, TradingVolume, WapRateInfo};
 
 pub struct MoexRatesMapper;
 
-#[derive(Debug)]
-struct CurrencyConfig {
-    code: &'static str,
-    name: &'static str,
-    symbol: &'static str,
-    cbrf_key: &'static str,
-    cbrf_change_key: &'static str,
-    cbrf_date_key: &'static str,
-    exchange_key: Option<(&'static str, &'static str, &'static str)>,
-    wap_security_id: Option<&'static str>, // Для привязки к данным wap_rates
-}
+/// Колонки блока wap_rates, из которых читается средневзвешенный курс
+const WAP_COLUMNS: [&str; 7] = [
+    "secid",
+    "price",
+    "lasttoprevprice",
+    "tradedate",
+    "tradetime",
+    "nominal",
+    "decimals",
+];
 
 impl MoexRatesMapper {
-    const CURRENCIES: [CurrencyConfig; 3] = [
-        CurrencyConfig {
-            code: "USD",
-            name: "Доллар США",
-            symbol: "$",
-            cbrf_key: "CBRF_USD_LAST",
-            cbrf_change_key: "CBRF_USD_LASTCHANGEPRCNT",
-            cbrf_date_key: "CBRF_USD_TRADEDATE",
-            exchange_key: Some((
-                "USDTOM_UTS_CLOSEPRICE",
-                "USDTOM_UTS_CLOSEPRICETOPREVPRCN",
-                "USDTOM_UTS_TRADEDATE",
-            )),
-            wap_security_id: None,
-        },
-        CurrencyConfig {
-            code: "EUR",
-            name: "Евро",
-            symbol: "€",
-            cbrf_key: "CBRF_EUR_LAST",
-            cbrf_change_key: "CBRF_EUR_LASTCHANGEPRCNT",
-            cbrf_date_key: "CBRF_EUR_TRADEDATE",
-            exchange_key: None,
-            wap_security_id: None,
-        },
-        CurrencyConfig {
-            code: "CNY",
-            name: "Китайский юань",
-            symbol: "¥",
-            cbrf_key: "",
-            cbrf_change_key: "",
-            cbrf_date_key: "",
-            exchange_key: None,
-            wap_security_id: Some("CNYRUB_TOM"),
-        },
-    ];
+    /// Сверяет колонки валют из конфигурации с колонками ответа MOEX.
+    /// Возвращает валюты, все источники которых есть в ответе, и описание проблем с остальными.
+    /// Строки данных не проверяются: пустой блок wap_rates — не ошибка конфигурации
+    pub fn validate_currencies(
+        response: &MoexRatesResponse,
+        currencies: &[CurrencyRateConfig],
+    ) -> (Vec<CurrencyRateConfig>, Vec<String>) {
+        let cbrf_indices = Self::build_indices(&response.cbrf.columns);
+        let wap_indices = Self::build_indices(&response.wap_rates.columns);
+
+        let mut valid = Vec::new();
+        let mut problems = Vec::new();
+        for currency in currencies {
+            let mut missing: Vec<String> = [&currency.cbrf, &currency.exchange]
+                .into_iter()
+                .flatten()
+                .flat_map(|columns| [&columns.rate, &columns.change, &columns.date])
+                .filter(|column| !cbrf_indices.contains_key(column.as_str()))
+                .map(|column| format!("cbrf column {}", column))
+                .collect();
+            if currency.wap_security_id.is_some() {
+                missing.extend(
+                    WAP_COLUMNS
+                        .iter()
+                        .filter(|column| !wap_indices.contains_key(*column))
+                        .map(|column| format!("wap_rates column {}", column)),
+                );
+            }
+
+            if missing.is_empty() {
+                valid.push(currency.clone());
+            } else {
+                problems.push(format!("{}: missing {}", currency.code, missing.join(", ")));
+            }
+        }
+        (valid, problems)
+    }
 
+    /// Собирает снимок курсов из ответа MOEX. Без строки данных или даты в блоке cbrf
+    /// снимок не собирается: сохранять его не под чем
     pub fn map_to_currency_rates(
         response: &MoexRatesResponse,
+        configs: &[CurrencyRateConfig],
     ) -> Result<CurrencyRatesResponse, Box<dyn std::error::Error + Send + Sync>>  {
         let mut currencies = HashMap::new();
         let mut display_info = HashMap::new();
 
-        if response.cbrf.data.is_empty() {
-            warn!("CBRF data is empty");
-            return Ok(CurrencyRatesResponse {
-                date: String::new(),
-                today_volume: None,
-                currencies,
-                display_info,
-            });
-        }
-
+        let row = response.cbrf.data.first().ok_or("CBRF data is empty")?;
         let cbrf_indices = Self::build_indices(&response.cbrf.columns);
         let wap_indices = Self::build_indices(&response.wap_rates.columns);
-        let today_date = response.cbrf.data[0][cbrf_indices["TODAY_DATE"]]
-            .as_str()
-            .unwrap_or("")
+        let column = |key: &str| cbrf_indices.get(key).and_then(|&idx| row.get(idx));
+
+        let today_date = column("TODAY_DATE")
+            .and_then(|value| value.as_str())
+            .filter(|date| !date.is_empty())
+            .ok_or("CBRF data has no TODAY_DATE")?
             .to_string();
 
         // Объемы торгов
-        let today_volume = Some(TradingVolume {
-            rubles: response.cbrf.data[0][cbrf_indices["TODAY_VALTODAY"]]
-                .as_f64()
-                .unwrap_or(0.0),
-            usd: response.cbrf.data[0][cbrf_indices["TODAY_VALTODAY_USD"]]
-                .as_f64()
-                .unwrap_or(0.0),
-        });
+        let today_volume = match (column("TODAY_VALTODAY"), column("TODAY_VALTODAY_USD")) {
+            (Some(rubles), Some(usd)) => Some(TradingVolume {
+                rubles: Self::decimal_value(rubles).unwrap_or(Decimal::ZERO),
+                usd: Self::decimal_value(usd).unwrap_or(Decimal::ZERO),
+            }),
+            _ => {
+                warn!("CBRF data has no TODAY_VALTODAY/TODAY_VALTODAY_USD columns");
+                None
+            }
+        };
 
         // Обработка основных валют
-        for config in Self::CURRENCIES.iter() {
+        for config in configs {
             Self::map_currency_data(
                 config,
-                &response,
+                response,
                 &cbrf_indices,
                 &wap_indices,
                 &mut currencies,
@@ -112,6 +115,30 @@ impl MoexRatesMapper {
         })
     }
 
+    /// Точное значение числа из ответа MOEX: берём десятичную запись JSON, а не f64
+    fn decimal_value(value: &Value) -> Option<Decimal> {
+        match value {
+            Value::Number(number) => {
+                let raw = number.to_string();
+                Decimal::from_str(&raw)
+                    .or_else(|_| Decimal::from_scientific(&raw))
+                    .ok()
+            }
+            Value::String(raw) => Decimal::from_str(raw.trim()).ok(),
+            _ => None,
+        }
+    }
+
+    /// Вчерашний курс по текущему и проценту изменения: current / (1 + pct / 100),
+    /// округлённый до 6 знаков
+    fn previous_rate(current_rate: Decimal, change_percent: Decimal) -> Decimal {
+        let ratio = Decimal::ONE + change_percent / Decimal::ONE_HUNDRED;
+        if ratio.is_zero() {
+            return current_rate;
+        }
+        (current_rate / ratio).round_dp(6)
+    }
+
     fn build_indices(columns: &[String]) -> HashMap<&str, usize> {
         columns
             .iter()
@@ -120,8 +147,30 @@ impl MoexRatesMapper {
             .collect()
     }
 
+    /// Курс из блока cbrf: (текущий, вчерашний, процент изменения, дата).
+    /// None, если в ответе нет колонки курса
+    fn cbrf_rate(
+        response: &MoexRatesResponse,
+        cbrf_indices: &HashMap<&str, usize>,
+        columns: &MoexRateColumns,
+    ) -> Option<(Decimal, Decimal, Decimal, String)> {
+        let row = response.cbrf.data.first()?;
+        let column = |key: &str| cbrf_indices.get(key).and_then(|&idx| row.get(idx));
+
+        let current_rate = Self::decimal_value(column(&columns.rate)?).unwrap_or(Decimal::ZERO);
+        let change_percent = column(&columns.change)
+            .and_then(Self::decimal_value)
+            .unwrap_or(Decimal::ZERO);
+        let date = column(&columns.date)
+            .and_then(|value| value.as_str())
+            .unwrap_or("")
+            .to_string();
+        let previous_rate = Self::previous_rate(current_rate, change_percent);
+        Some((current_rate, previous_rate, change_percent, date))
+    }
+
     fn map_currency_data(
-        config: &CurrencyConfig,
+        config: &CurrencyRateConfig,
         response: &MoexRatesResponse,
         cbrf_indices: &HashMap<&str, usize>,
         wap_indices: &HashMap<&str, usize>,
@@ -133,68 +182,48 @@ impl MoexRatesMapper {
         let mut wap_rate = None;
 
         // Central Bank Rate (CBRF)
-        if !config.cbrf_key.is_empty() {
-            let current_rate = response.cbrf.data[0][cbrf_indices[config.cbrf_key]]
-                .as_f64()
-                .unwrap_or(0.0);
-            let change_percent = response.cbrf.data[0][cbrf_indices[config.cbrf_change_key]]
-                .as_f64()
-                .unwrap_or(0.0);
-            let previous_rate = current_rate / (1.0 + change_percent / 100.0);
-            let date = response.cbrf.data[0][cbrf_indices[config.cbrf_date_key]]
-                .as_str()
-                .unwrap_or("")
-                .to_string();
-
-            central_bank = Some(RateInfo {
-                current_rate,
-                previous_rate,
-                change: RateChange {
-                    absolute: current_rate - previous_rate,
-                    percent: change_percent,
+        if let Some(columns) = &config.cbrf {
+            central_bank = Self::cbrf_rate(response, cbrf_indices, columns).map(
+                |(current_rate, previous_rate, change_percent, date)| RateInfo {
+                    current_rate,
+                    previous_rate,
+                    change: RateChange {
+                        absolute: current_rate - previous_rate,
+                        percent: change_percent,
+                    },
+                    date,
                 },
-                date,
-            });
+            );
         }
 
         // Exchange Rate
-        if let Some((price_key, change_key, date_key)) = config.exchange_key {
-            let current_rate = response.cbrf.data[0][cbrf_indices[price_key]]
-                .as_f64()
-                .unwrap_or(0.0);
-            let change_percent = response.cbrf.data[0][cbrf_indices[change_key]]
-                .as_f64()
-                .unwrap_or(0.0);
-            let previous_rate = current_rate / (1.0 + change_percent / 100.0);
-            let date = response.cbrf.data[0][cbrf_indices[date_key]]
-                .as_str()
-                .unwrap_or("")
-                .to_string();
-
-            exchange = Some(ExchangeRateInfo {
-                current_rate,
-                previous_rate,
-                change: RateChange {
-                    absolute: current_rate - previous_rate,
-                    percent: change_percent,
+        if let Some(columns) = &config.exchange {
+            exchange = Self::cbrf_rate(response, cbrf_indices, columns).map(
+                |(current_rate, previous_rate, change_percent, date)| ExchangeRateInfo {
+                    current_rate,
+                    previous_rate,
+                    change: RateChange {
+                        absolute: current_rate - previous_rate,
+                        percent: change_percent,
+                    },
+                    date,
+                    precision: None,
                 },
-                date,
-                precision: None,
-            });
+            );
         }
 
         // WAP Rate
-        if let Some(security_id) = config.wap_security_id {
+        if let Some(security_id) = config.wap_security_id.as_deref() {
             if let Some(wap_data) = response.wap_rates.data.iter().find(|row| {
                 row[wap_indices["secid"]]
                     .as_str()
                     .unwrap_or("") == security_id
             }) {
-                let current_rate = wap_data[wap_indices["price"]].as_f64().unwrap_or(0.0);
-                let change_percent = wap_data[wap_indices["lasttoprevprice"]]
-                    .as_f64()
-                    .unwrap_or(0.0);
-                let previous_rate = current_rate / (1.0 + change_percent / 100.0);
+                let current_rate =
+                    Self::decimal_value(&wap_data[wap_indices["price"]]).unwrap_or(Decimal::ZERO);
+                let change_percent =
+                    Self::decimal_value(&wap_data[wap_indices["lasttoprevprice"]]).unwrap_or(Decimal::ZERO);
+                let previous_rate = Self::previous_rate(current_rate, change_percent);
 
                 wap_rate = Some(WapRateInfo {
                     current_rate,
@@ -208,9 +237,8 @@ impl MoexRatesMapper {
                         .as_str()
                         .unwrap_or("")
                         .to_string(),
-                    nominal: wap_data[wap_indices["nominal"]]
-                        .as_f64()
-                        .unwrap_or(1.0),
+                    nominal:
+                        Self::decimal_value(&wap_data[wap_indices["nominal"]]).unwrap_or(Decimal::ONE),
                     precision: wap_data[wap_indices["decimals"]]
                         .as_u64()
                         .unwrap_or(0) as u8,
@@ -222,7 +250,7 @@ impl MoexRatesMapper {
         // Сначала заполняем display_info
         let mut display = None;
         if let Some(cb) = &central_bank {
-            let (trend, change_sign) = if cb.change.percent > 0.0 {
+            let (trend, change_sign) = if cb.change.percent > Decimal::ZERO {
                 ("рост", "+")
             } else {
                 ("снижение", "")
@@ -248,7 +276,7 @@ impl MoexRatesMapper {
                 }),
             });
         } else if let Some(wap) = &wap_rate {
-            let (trend, change_sign) = if wap.change_percent > 0.0 {
+            let (trend, change_sign) = if wap.change_percent > Decimal::ZERO {
                 ("рост", "+")
             } else {
                 ("снижение", "")
@@ -272,10 +300,10 @@ impl MoexRatesMapper {
 
         // Затем заполняем currencies
         currencies.insert(
-            config.code.to_string(),
+            config.code.clone(),
             CurrencyInfo {
-                name: config.name.to_string(),
-                symbol: config.symbol.to_string(),
+                name: config.name.clone(),
+                symbol: config.symbol.clone(),
                 central_bank,
                 exchange,
                 wap_rate,
@@ -284,9 +312,95 @@ impl MoexRatesMapper {
 
         // Если есть display_info, добавляем его
         if let Some(display) = display {
-            display_info.insert(config.code.to_string(), display);
+            display_info.insert(config.code.clone(), display);
         }
 
         Ok(())
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::moex_api::models::{CbrfRates, WapRates};
+    use serde_json::json;
+
+    fn currency(code: &str, cbrf: Option<MoexRateColumns>, wap: Option<&str>) -> CurrencyRateConfig {
+        CurrencyRateConfig {
+            code: code.to_string(),
+            name: code.to_string(),
+            symbol: code.to_string(),
+            cbrf,
+            exchange: None,
+            wap_security_id: wap.map(str::to_string),
+            cbr_id: None,
+        }
+    }
+
+    #[test]
+    fn test_validate_currencies_against_moex_columns() {
+        let wap_columns = |columns: &[&str]| WapRates {
+            columns: columns.iter().map(|column| column.to_string()).collect(),
+            // Строк нет: вне торгов блок wap_rates приходит пустым
+            data: Vec::new(),
+        };
+        let mut response = MoexRatesResponse {
+            cbrf: CbrfRates {
+                columns: vec![
+                    "CBRF_USD_LAST".to_string(),
+                    "CBRF_USD_LASTCHANGEPRCNT".to_string(),
+                    "CBRF_USD_TRADEDATE".to_string(),
+                ],
+                data: vec![vec![json!(92.5), json!(0.1), json!("2025-03-14")]],
+            },
+            wap_rates: wap_columns(&WAP_COLUMNS),
+        };
+        let columns = |code: &str| MoexRateColumns {
+            rate: format!("CBRF_{}_LAST", code),
+            change: format!("CBRF_{}_LASTCHANGEPRCNT", code),
+            date: format!("CBRF_{}_TRADEDATE", code),
+        };
+        let configs = vec![
+            currency("USD", Some(columns("USD")), None),
+            currency("EUR", Some(columns("EUR")), None),
+            currency("CNY", None, Some("CNYRUB_TOM")),
+        ];
+
+        let (valid, problems) = MoexRatesMapper::validate_currencies(&response, &configs);
+        let codes: Vec<&str> = valid.iter().map(|c| c.code.as_str()).collect();
+        assert_eq!(codes, ["USD", "CNY"]);
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].starts_with("EUR: missing cbrf column CBRF_EUR_LAST"));
+
+        response.wap_rates = wap_columns(&["secid", "tradedate"]);
+        let (valid, problems) = MoexRatesMapper::validate_currencies(&response, &configs);
+        let codes: Vec<&str> = valid.iter().map(|c| c.code.as_str()).collect();
+        assert_eq!(codes, ["USD"]);
+        assert!(problems[1].starts_with("CNY: missing wap_rates column price"));
+    }
+
+    #[test]
+    fn test_map_to_currency_rates_requires_snapshot_date() {
+        let response = |columns: &[&str], data: Vec<Vec<Value>>| MoexRatesResponse {
+            cbrf: CbrfRates {
+                columns: columns.iter().map(|column| column.to_string()).collect(),
+                data,
+            },
+            wap_rates: WapRates {
+                columns: Vec::new(),
+                data: Vec::new(),
+            },
+        };
+
+        let empty = response(&["TODAY_DATE"], Vec::new());
+        assert!(MoexRatesMapper::map_to_currency_rates(&empty, &[]).is_err());
+
+        let no_date = response(&["TODAY_VALTODAY"], vec![vec![json!(100)]]);
+        assert!(MoexRatesMapper::map_to_currency_rates(&no_date, &[]).is_err());
+
+        // Без колонок объёма снимок собирается, объём просто не заполняется
+        let no_volume = response(&["TODAY_DATE"], vec![vec![json!("2025-03-14")]]);
+        let rates = MoexRatesMapper::map_to_currency_rates(&no_volume, &[]).unwrap();
+        assert_eq!(rates.date, "2025-03-14");
+        assert!(rates.today_volume.is_none());
+    }
+}
@@ -1,7 +1,9 @@
 use std::collections::HashMap;
+use rust_decimal::Decimal;
 use serde::{Serialize, Deserialize};
 
-// Структуры для преобразованного ответа
+// Структуры для преобразованного ответа.
+// Курсы и объёмы хранятся как Decimal (строкой в BSON), чтобы не терять точность MOEX
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CurrencyRatesResponse {
     pub date: String,
@@ -12,8 +14,8 @@ pub struct CurrencyRatesResponse {
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TradingVolume {
-    pub rubles: f64,  // TODAY_VALTODAY
-    pub usd: f64,     // TODAY_VALTODAY_USD
+    pub rubles: Decimal, // TODAY_VALTODAY
+    pub usd: Decimal, // TODAY_VALTODAY_USD
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -30,16 +32,16 @@ pub struct CurrencyInfo {
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct RateInfo {
-    pub current_rate: f64,
-    pub previous_rate: f64,
+    pub current_rate: Decimal,
+    pub previous_rate: Decimal,
     pub change: RateChange,
     pub date: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ExchangeRateInfo {
-    pub current_rate: f64,
-    pub previous_rate: f64,
+    pub current_rate: Decimal,
+    pub previous_rate: Decimal,
     pub change: RateChange,
     pub date: String,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -48,20 +50,20 @@ pub struct ExchangeRateInfo {
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct WapRateInfo {
-    pub current_rate: f64,        // Средневзвешенная цена (price)
-    pub change_percent: f64,      // Процент изменения (lasttoprevprice)
-    pub previous_rate: f64,       // Вычисляется на основе текущей цены и процента
+    pub current_rate: Decimal,    // Средневзвешенная цена (price)
+    pub change_percent: Decimal,  // Процент изменения (lasttoprevprice)
+    pub previous_rate: Decimal,   // Вычисляется на основе текущей цены и процента
     pub date: String,             // tradedate
     pub time: String,             // tradetime
-    pub nominal: f64,             // Номинал
+    pub nominal: Decimal,         // Номинал
     pub precision: u8,            // Количество знаков после запятой (decimals)
     pub security_id: String,      // secid (например, CNYRUB_TOM)
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct RateChange {
-    pub absolute: f64,
-    pub percent: f64,
+    pub absolute: Decimal,
+    pub percent: Decimal,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -71,4 +73,31 @@ pub struct CurrencyDisplayInfo {
     pub change_text: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub wap_text: Option<String>, // Отображение средневзвешенного курса
-}
\ No newline at end of file
+}
+
+/// Источники курсов в архиве
+pub struct CurrencyRateSources;
+impl CurrencyRateSources {
+    /// Официальный курс ЦБ РФ (блок cbrf MOEX)
+    pub const CBRF: &'static str = "cbrf";
+    /// Биржевой курс TOM (блок cbrf MOEX)
+    pub const EXCHANGE: &'static str = "exchange";
+    /// Средневзвешенный курс (блок wap_rates MOEX)
+    pub const WAP: &'static str = "wap";
+
+    pub const ALL: [&'static str; 3] = [Self::CBRF, Self::EXCHANGE, Self::WAP];
+}
+
+/// Курс валюты из одного источника на дату. Ключ архива — (currency, source, date)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DbCurrencyRate {
+    /// Код валюты: USD, EUR ...
+    pub currency: String,
+    /// Источник: cbrf, exchange, wap
+    pub source: String,
+    /// Дата курса, YYYY-MM-DD
+    pub date: String,
+    /// Рублей за единицу валюты
+    pub rate: Decimal,
+    pub updated_at: String,
+}
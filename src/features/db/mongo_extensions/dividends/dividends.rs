@@ -0,0 +1,104 @@
+// src/features/db/mongo_extensions/dividends/dividends.rs
+
+use futures::stream::TryStreamExt;
+use mongodb::bson::doc;
+use mongodb::options::IndexOptions;
+use mongodb::{Collection, IndexModel};
+use tracing::{error, info};
+
+use crate::features::db::{
+    mongo_db::{Collections, DbNames},
+    MongoDb,
+};
+
+use super::models::DbDividend;
+
+impl MongoDb {
+    pub fn dividends_collection(&self) -> Collection<DbDividend> {
+        self.database(DbNames::MARKET_REFERENCE)
+            .collection::<DbDividend>(Collections::DIVIDENDS)
+    }
+
+    pub async fn ensure_dividends_indexes(&self) {
+        match self
+            .dividends_collection()
+            .create_index(
+                IndexModel::builder()
+                    .keys(doc! { "figi": 1, "record_date": 1 })
+                    .options(IndexOptions::builder().unique(true).build())
+                    .build(),
+            )
+            .await
+        {
+            Ok(_) => info!("Created figi/record_date index for dividends collection"),
+            Err(e) => error!("Failed to create index for dividends collection: {}", e),
+        }
+
+        match self
+            .dividends_collection()
+            .create_index(IndexModel::builder().keys(doc! { "record_date": 1 }).build())
+            .await
+        {
+            Ok(_) => info!("Created record_date index for dividends collection"),
+            Err(e) => error!("Failed to create record_date index for dividends collection: {}", e),
+        }
+    }
+
+    /// Сохраняет дивиденды по ключу (figi, record_date); пересмотренные суммы и даты
+    /// выплаты перезаписывают прежние. Возвращает число новых записей
+    pub async fn upsert_dividends(
+        &self,
+        dividends: &[DbDividend],
+    ) -> Result<u64, mongodb::error::Error> {
+        let collection = self.dividends_collection();
+        let mut inserted = 0;
+        for dividend in dividends {
+            let result = collection
+                .replace_one(
+                    doc! { "figi": &dividend.figi, "record_date": &dividend.record_date },
+                    dividend,
+                )
+                .upsert(true)
+                .await?;
+            if result.upserted_id.is_some() {
+                inserted += 1;
+            }
+        }
+        Ok(inserted)
+    }
+
+    /// Дивиденды с датой фиксации в [from_day, to_day] по возрастанию даты.
+    /// `figis` ограничивает выборку инструментами; None — все инструменты
+    pub async fn find_dividends(
+        &self,
+        figis: Option<&[String]>,
+        from_day: &str,
+        to_day: &str,
+    ) -> Result<Vec<DbDividend>, mongodb::error::Error> {
+        let mut filter = doc! { "record_date": { "$gte": from_day, "$lte": to_day } };
+        if let Some(figis) = figis {
+            filter.insert("figi", doc! { "$in": figis });
+        }
+
+        self.dividends_collection()
+            .find(filter)
+            .sort(doc! { "record_date": 1, "figi": 1 })
+            .await?
+            .try_collect()
+            .await
+    }
+
+    /// Вся сохранённая история дивидендов инструмента, новые первыми
+    pub async fn find_dividend_history(
+        &self,
+        figi: &str,
+    ) -> Result<Vec<DbDividend>, mongodb::error::Error> {
+        self.dividends_collection()
+            .find(doc! { "figi": figi })
+            .sort(doc! { "record_date": -1 })
+            .await?
+            .try_collect()
+            .await
+    }
+
+}
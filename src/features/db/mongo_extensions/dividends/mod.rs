@@ -0,0 +1,2 @@
+pub mod dividends;
+pub mod models;
@@ -0,0 +1,35 @@
+use serde::{Deserialize, Serialize};
+
+use crate::features::core::models::{
+    money_value::TinkoffMoneyValueModel, quotation::TinkoffQuotationModel,
+};
+
+/// Дивиденд инструмента (InstrumentsService.GetDividends).
+/// Ключ — FIGI и дата фиксации реестра; даты хранятся как YYYY-MM-DD
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DbDividend {
+    pub figi: String,
+    /// share или etf
+    pub instrument_type: String,
+    pub ticker: String,
+    /// Дата фиксации реестра
+    pub record_date: String,
+    /// Последний день покупки под дивиденд
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_buy_date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payment_date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub declared_date: Option<String>,
+    /// Выплата на одну бумагу
+    pub dividend_net: Option<TinkoffMoneyValueModel>,
+    /// Цена закрытия на дату фиксации
+    pub close_price: Option<TinkoffMoneyValueModel>,
+    /// Доходность выплаты, %
+    pub yield_value: Option<TinkoffQuotationModel>,
+    pub dividend_type: String,
+    pub regularity: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<String>,
+    pub updated_at: String,
+}
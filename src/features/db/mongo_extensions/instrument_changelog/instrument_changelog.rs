@@ -0,0 +1,78 @@
+// src/features/db/mongo_extensions/instrument_changelog/instrument_changelog.rs
+
+use futures::stream::TryStreamExt;
+use mongodb::bson::{doc, Document};
+use mongodb::{Collection, IndexModel};
+use tracing::{error, info};
+
+use crate::features::db::{
+    mongo_db::{Collections, DbNames},
+    MongoDb,
+};
+
+use super::models::DbInstrumentChange;
+
+impl MongoDb {
+    pub fn instrument_changelog_collection(&self) -> Collection<DbInstrumentChange> {
+        self.database(DbNames::MARKET_DATA)
+            .collection::<DbInstrumentChange>(Collections::INSTRUMENT_CHANGELOG)
+    }
+
+    pub async fn ensure_instrument_changelog_indexes(&self) {
+        match self
+            .instrument_changelog_collection()
+            .create_indexes([
+                IndexModel::builder()
+                    .keys(doc! { "figi": 1, "changed_at": 1 })
+                    .build(),
+                IndexModel::builder()
+                    .keys(doc! { "uid": 1, "changed_at": 1 })
+                    .build(),
+            ])
+            .await
+        {
+            Ok(_) => info!("Created figi and uid indexes for instrument changelog collection"),
+            Err(e) => error!(
+                "Failed to create indexes for instrument changelog collection: {}",
+                e
+            ),
+        }
+    }
+
+    pub async fn insert_instrument_changes(
+        &self,
+        changes: &[DbInstrumentChange],
+    ) -> Result<(), mongodb::error::Error> {
+        if changes.is_empty() {
+            return Ok(());
+        }
+        self.instrument_changelog_collection()
+            .insert_many(changes)
+            .await?;
+        Ok(())
+    }
+
+    /// Изменения инструмента по FIGI или uid (у опционов FIGI нет) начиная с `since`
+    /// (RFC 3339) по возрастанию времени. `field` оставляет только записи, где менялось это поле
+    pub async fn find_instrument_changes(
+        &self,
+        id: &str,
+        since: &str,
+        field: Option<&str>,
+    ) -> Result<Vec<DbInstrumentChange>, mongodb::error::Error> {
+        let mut filter: Document = doc! {
+            "$or": [{ "figi": id }, { "uid": id }],
+            "changed_at": { "$gte": since },
+        };
+        if let Some(field) = field {
+            filter.insert("fields.field", field);
+        }
+
+        self.instrument_changelog_collection()
+            .find(filter)
+            .sort(doc! { "changed_at": 1 })
+            .await?
+            .try_collect()
+            .await
+    }
+}
@@ -0,0 +1,2 @@
+pub mod instrument_changelog;
+pub mod models;
@@ -0,0 +1,42 @@
+use mongodb::bson::Bson;
+use serde::{Deserialize, Serialize};
+
+/// Тип изменения инструмента в каталоге
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InstrumentChangeKind {
+    /// Появился в ответе API
+    Added,
+    /// Изменились поля
+    Updated,
+    /// Пропал из ответа API
+    Delisted,
+    /// Снова появился после пометки delisted
+    Relisted,
+}
+
+/// Изменение одного поля: значения до и после синхронизации
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldChange {
+    pub field: String,
+    /// null — поля не было
+    pub old_value: Bson,
+    /// null — поле пропало
+    pub new_value: Bson,
+}
+
+/// Запись журнала изменений каталога: один инструмент за один прогон синхронизации
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DbInstrumentChange {
+    pub figi: String,
+    pub uid: String,
+    pub ticker: String,
+    /// share, bond, etf, future, option, currency
+    pub instrument_type: String,
+    pub change: InstrumentChangeKind,
+    /// Изменившиеся поля; для added и delisted пусто
+    #[serde(default)]
+    pub fields: Vec<FieldChange>,
+    /// Время синхронизации, RFC 3339
+    pub changed_at: String,
+}
@@ -0,0 +1,259 @@
+// src/features/db/mongo_extensions/instruments/instruments.rs
+
+use futures::stream::TryStreamExt;
+use mongodb::bson::{doc, Document, Regex};
+use mongodb::Collection;
+use tracing::{debug, error};
+
+use crate::features::{
+    core::models::{
+        bond::TinkoffBondModel,
+        currency::TinkoffCurrencyModel,
+        etf::TinkoffEtfModel,
+        future::TinkoffFutureModel,
+        instrument::{InstrumentKind, TinkoffInstrumentEnum},
+        option::TinkoffOptionModel,
+        share::TinkoffShareModel,
+    },
+    db::MongoDb,
+};
+
+use super::{
+    models::{InstrumentSearchFilter, InstrumentSearchPage},
+    sync::FIELD_DELISTED,
+};
+
+/// Десериализует документ каталога в модель нужного типа
+fn document_to_instrument(
+    kind: InstrumentKind,
+    doc: Document,
+) -> Result<TinkoffInstrumentEnum, bson::de::Error> {
+    Ok(match kind {
+        InstrumentKind::Share => {
+            TinkoffInstrumentEnum::Share(bson::from_document::<TinkoffShareModel>(doc)?)
+        }
+        InstrumentKind::Bond => {
+            TinkoffInstrumentEnum::Bond(bson::from_document::<TinkoffBondModel>(doc)?)
+        }
+        InstrumentKind::Etf => {
+            TinkoffInstrumentEnum::Etf(bson::from_document::<TinkoffEtfModel>(doc)?)
+        }
+        InstrumentKind::Future => {
+            TinkoffInstrumentEnum::Future(bson::from_document::<TinkoffFutureModel>(doc)?)
+        }
+        InstrumentKind::Option => {
+            TinkoffInstrumentEnum::Option(bson::from_document::<TinkoffOptionModel>(doc)?)
+        }
+        InstrumentKind::Currency => {
+            TinkoffInstrumentEnum::Currency(bson::from_document::<TinkoffCurrencyModel>(doc)?)
+        }
+    })
+}
+
+/// Регулярное выражение для поиска без учёта регистра
+fn case_insensitive(pattern: String) -> Regex {
+    Regex {
+        pattern,
+        options: "i".to_string(),
+    }
+}
+
+impl InstrumentSearchFilter {
+    /// Строит фильтр MongoDB, общий для всех коллекций каталога
+    fn to_document(&self) -> Document {
+        let mut filter = doc! {};
+
+        if let Some(ticker) = &self.ticker {
+            filter.insert("ticker", ticker.to_uppercase());
+        }
+        if let Some(isin) = &self.isin {
+            filter.insert("isin", isin.to_uppercase());
+        }
+        if let Some(figi) = &self.figi {
+            filter.insert("figi", figi.to_uppercase());
+        }
+        if let Some(uid) = &self.uid {
+            filter.insert("uid", uid.to_lowercase());
+        }
+        if let Some(name) = &self.name {
+            filter.insert("name", case_insensitive(regex::escape(name)));
+        }
+        if let Some(currency) = &self.currency {
+            filter.insert("currency", currency.to_lowercase());
+        }
+        if let Some(exchange) = &self.exchange {
+            filter.insert(
+                "exchange",
+                case_insensitive(format!("^{}$", regex::escape(exchange))),
+            );
+        }
+        if let Some(status) = &self.trading_status {
+            let status = status.to_uppercase();
+            let status = status
+                .strip_prefix("SECURITY_TRADING_STATUS_")
+                .unwrap_or(&status);
+            filter.insert("trading_status.value", status);
+        }
+        if !self.include_delisted {
+            filter.insert(FIELD_DELISTED, doc! { "$ne": true });
+        }
+
+        filter
+    }
+
+    fn kinds(&self) -> Vec<InstrumentKind> {
+        if self.kinds.is_empty() {
+            InstrumentKind::ALL.to_vec()
+        } else {
+            InstrumentKind::ALL
+                .into_iter()
+                .filter(|kind| self.kinds.contains(kind))
+                .collect()
+        }
+    }
+}
+
+impl MongoDb {
+    /// Коллекция каталога для типа инструмента
+    pub fn instruments_collection(&self, kind: InstrumentKind) -> Collection<Document> {
+        match kind {
+            InstrumentKind::Share => self.shares_collection(),
+            InstrumentKind::Bond => self.bonds_collection(),
+            InstrumentKind::Etf => self.etfs_collection(),
+            InstrumentKind::Future => self.futures_collection(),
+            InstrumentKind::Option => self.options_collection(),
+            InstrumentKind::Currency => self.currencies_collection(),
+        }
+    }
+
+    /// Поиск инструмента по FIGI во всех коллекциях
+    ///
+    /// Последовательно проверяет коллекции акций, облигаций, ETF, фьючерсов, опционов и валют.
+    /// Находит и снятые с торгов; при совпадении FIGI действующий инструмент важнее
+    pub async fn find_instrument_by_figi(&self, figi: &str) -> Option<TinkoffInstrumentEnum> {
+        self.find_instrument_by("figi", figi).await
+    }
+
+    /// Поиск инструмента по uid во всех коллекциях; единственный способ найти опцион
+    pub async fn find_instrument_by_uid(&self, uid: &str) -> Option<TinkoffInstrumentEnum> {
+        self.find_instrument_by("uid", uid).await
+    }
+
+    async fn find_instrument_by(&self, field: &str, value: &str) -> Option<TinkoffInstrumentEnum> {
+        if value.is_empty() {
+            return None;
+        }
+        let filter = doc! { field: value };
+
+        for kind in InstrumentKind::ALL {
+            match self
+                .instruments_collection(kind)
+                .find_one(filter.clone())
+                .sort(doc! { FIELD_DELISTED: 1 })
+                .await
+            {
+                Ok(Some(doc)) => match document_to_instrument(kind, doc) {
+                    Ok(instrument) => return Some(instrument),
+                    Err(e) => {
+                        error!("Failed to deserialize {} document: {}", kind.as_str(), e);
+                        // Continue checking other collections
+                    }
+                },
+                Ok(None) => {}
+                Err(e) => error!(
+                    "Failed to query {} collection for {} {}: {}",
+                    kind.as_str(),
+                    field,
+                    value,
+                    e
+                ),
+            }
+        }
+
+        None
+    }
+
+    /// Поиск по каталогу инструментов сразу по всем выбранным коллекциям.
+    ///
+    /// Результаты упорядочены по типу (акции, облигации, ETF, фьючерсы, опционы, валюты),
+    /// внутри типа по тикеру, поэтому `skip`/`limit` дают стабильную пагинацию поверх
+    /// нескольких коллекций
+    pub async fn search_instruments(
+        &self,
+        filter: &InstrumentSearchFilter,
+        skip: u64,
+        limit: i64,
+    ) -> Result<InstrumentSearchPage, mongodb::error::Error> {
+        let query = filter.to_document();
+        debug!("Searching instruments with filter {:?}", query);
+
+        let mut total = 0;
+        let mut to_skip = skip;
+        let mut remaining = limit;
+        let mut instruments = Vec::new();
+
+        for kind in filter.kinds() {
+            let collection = self.instruments_collection(kind);
+            let count = collection.count_documents(query.clone()).await?;
+            total += count;
+
+            // Коллекция целиком попадает в пропускаемую часть
+            if remaining <= 0 || to_skip >= count {
+                to_skip = to_skip.saturating_sub(count);
+                continue;
+            }
+
+            let documents: Vec<Document> = collection
+                .find(query.clone())
+                .sort(doc! { "ticker": 1, "figi": 1 })
+                .skip(to_skip)
+                .limit(remaining)
+                .await?
+                .try_collect()
+                .await?;
+            to_skip = 0;
+
+            for doc in documents {
+                match document_to_instrument(kind, doc) {
+                    Ok(instrument) => {
+                        instruments.push(instrument);
+                        remaining -= 1;
+                    }
+                    Err(e) => error!("Failed to deserialize {} document: {}", kind.as_str(), e),
+                }
+            }
+        }
+
+        Ok(InstrumentSearchPage { instruments, total })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::db::mongo_db::DbNames;
+    use mongodb::options::{ClientOptions, ServerAddress};
+    use mongodb::Client;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_find_instrument_by_empty_value() {
+        // Сервер не нужен: пустое значение отсекается до запроса
+        let options = ClientOptions::builder()
+            .hosts(vec![ServerAddress::Tcp {
+                host: "127.0.0.1".to_string(),
+                port: Some(1),
+            }])
+            .server_selection_timeout(Duration::from_millis(50))
+            .build();
+        let client = Client::with_options(options).unwrap();
+        let mongo_db = MongoDb {
+            default_database: client.database(DbNames::MARKET_DATA),
+            client,
+        };
+
+        assert!(mongo_db.find_instrument_by("figi", "").await.is_none());
+        assert!(mongo_db.find_instrument_by_figi("").await.is_none());
+        assert!(mongo_db.find_instrument_by_uid("").await.is_none());
+    }
+}
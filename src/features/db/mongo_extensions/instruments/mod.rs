@@ -0,0 +1,3 @@
+pub mod instruments;
+pub mod models;
+pub mod sync;
@@ -0,0 +1,47 @@
+use crate::features::{
+    core::models::instrument::{InstrumentKind, TinkoffInstrumentEnum},
+    db::mongo_extensions::instrument_changelog::models::DbInstrumentChange,
+};
+
+/// Фильтр поиска по каталогу инструментов.
+/// Пустые поля не участвуют в поиске
+#[derive(Debug, Default, Clone)]
+pub struct InstrumentSearchFilter {
+    pub ticker: Option<String>,
+    pub isin: Option<String>,
+    pub figi: Option<String>,
+    pub uid: Option<String>,
+    /// Подстрока названия, без учёта регистра
+    pub name: Option<String>,
+    /// Ограничение по типам инструментов; пустой список означает все типы
+    pub kinds: Vec<InstrumentKind>,
+    pub currency: Option<String>,
+    pub exchange: Option<String>,
+    /// Статус торгов, например `NORMAL_TRADING`
+    pub trading_status: Option<String>,
+    /// Включать инструменты, пропавшие из каталога API
+    pub include_delisted: bool,
+}
+
+/// Страница результатов поиска по каталогу
+#[derive(Debug)]
+pub struct InstrumentSearchPage {
+    pub instruments: Vec<TinkoffInstrumentEnum>,
+    pub total: u64,
+}
+
+/// Итог синхронизации коллекции каталога
+#[derive(Debug, Default, Clone)]
+pub struct InstrumentSyncStats {
+    pub added: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+    /// Пропали из ответа API в этот раз
+    pub delisted: usize,
+    /// Снова появились после пометки delisted
+    pub relisted: usize,
+    /// Документов в коллекции после синхронизации, включая delisted
+    pub total: usize,
+    /// Изменения по инструментам для журнала
+    pub changes: Vec<DbInstrumentChange>,
+}
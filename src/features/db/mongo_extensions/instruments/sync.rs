@@ -0,0 +1,333 @@
+// src/features/db/mongo_extensions/instruments/sync.rs
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::Utc;
+use futures::stream::TryStreamExt;
+use mongodb::bson::{doc, Bson, Document};
+use mongodb::error::ErrorKind;
+use mongodb::options::IndexOptions;
+use mongodb::{Collection, IndexModel};
+use tracing::{info, warn};
+
+use crate::features::{
+    core::models::instrument::InstrumentKind,
+    db::{
+        mongo_db::DbNames,
+        mongo_extensions::instrument_changelog::models::{
+            DbInstrumentChange, FieldChange, InstrumentChangeKind,
+        },
+        MongoDb,
+    },
+};
+
+use super::models::InstrumentSyncStats;
+
+/// Суффикс коллекции, в которую собирается новый каталог перед подменой
+const STAGING_SUFFIX: &str = "_staging";
+
+/// Код ошибки MongoDB NamespaceNotFound: коллекции ещё нет
+const NAMESPACE_NOT_FOUND_CODE: i32 = 26;
+
+/// Индекс по `_id`, который MongoDB создаёт сам
+const ID_INDEX_NAME: &str = "_id_";
+
+/// Служебные поля синхронизации в документах каталога
+pub const FIELD_DELISTED: &str = "delisted";
+pub const FIELD_DELISTED_AT: &str = "delisted_at";
+pub const FIELD_FIRST_SEEN_AT: &str = "first_seen_at";
+pub const FIELD_UPDATED_AT: &str = "updated_at";
+pub const FIELD_LAST_SEEN_AT: &str = "last_seen_at";
+
+const SERVICE_FIELDS: [&str; 6] = [
+    "_id",
+    FIELD_DELISTED,
+    FIELD_DELISTED_AT,
+    FIELD_FIRST_SEEN_AT,
+    FIELD_UPDATED_AT,
+    FIELD_LAST_SEEN_AT,
+];
+
+/// Документ без служебных полей — то, что пришло из API
+pub fn instrument_payload(document: &Document) -> Document {
+    let mut payload = document.clone();
+    for field in SERVICE_FIELDS {
+        payload.remove(field);
+    }
+    payload
+}
+
+/// Поля, значения которых различаются в двух документах каталога.
+/// Служебные поля синхронизации не сравниваются; вложенные документы сравниваются целиком
+pub fn diff_fields(old: &Document, new: &Document) -> Vec<FieldChange> {
+    let old = instrument_payload(old);
+    let new = instrument_payload(new);
+
+    let mut changes: Vec<FieldChange> = new
+        .iter()
+        .filter(|(field, value)| old.get(field.as_str()) != Some(*value))
+        .map(|(field, value)| FieldChange {
+            field: field.clone(),
+            old_value: old.get(field.as_str()).cloned().unwrap_or(Bson::Null),
+            new_value: value.clone(),
+        })
+        .collect();
+    changes.extend(
+        old.iter()
+            .filter(|(field, _)| !new.contains_key(field.as_str()))
+            .map(|(field, value)| FieldChange {
+                field: field.clone(),
+                old_value: value.clone(),
+                new_value: Bson::Null,
+            }),
+    );
+    changes
+}
+
+fn change_record(
+    kind: InstrumentKind,
+    document: &Document,
+    change: InstrumentChangeKind,
+    fields: Vec<FieldChange>,
+    changed_at: &str,
+) -> DbInstrumentChange {
+    let text = |field: &str| document.get_str(field).unwrap_or_default().to_string();
+    DbInstrumentChange {
+        figi: text("figi"),
+        uid: text("uid"),
+        ticker: text("ticker"),
+        instrument_type: kind.as_str().to_string(),
+        change,
+        fields,
+        changed_at: changed_at.to_string(),
+    }
+}
+
+fn is_delisted(document: &Document) -> bool {
+    document.get_bool(FIELD_DELISTED).unwrap_or(false)
+}
+
+/// Индексы для staging-коллекции: uid/figi/ticker и остальные индексы рабочей
+/// коллекции, кроме `_id_`
+async fn catalogue_indexes(
+    collection: &Collection<Document>,
+) -> Result<Vec<IndexModel>, mongodb::error::Error> {
+    let mut indexes = vec![
+        IndexModel::builder()
+            .keys(doc! { "uid": 1 })
+            .options(IndexOptions::builder().unique(true).build())
+            .build(),
+        IndexModel::builder().keys(doc! { "figi": 1 }).build(),
+        IndexModel::builder().keys(doc! { "ticker": 1 }).build(),
+    ];
+
+    let existing: Vec<IndexModel> = match collection.list_indexes().await {
+        Ok(cursor) => cursor.try_collect().await?,
+        Err(e) => match e.kind.as_ref() {
+            ErrorKind::Command(err) if err.code == NAMESPACE_NOT_FOUND_CODE => Vec::new(),
+            _ => return Err(e),
+        },
+    };
+    for index in existing {
+        let name = index.options.as_ref().and_then(|options| options.name.as_deref());
+        if name == Some(ID_INDEX_NAME) || indexes.iter().any(|known| known.keys == index.keys) {
+            continue;
+        }
+        indexes.push(index);
+    }
+    Ok(indexes)
+}
+
+impl MongoDb {
+    /// Синхронизирует коллекцию каталога с полным списком инструментов из API.
+    ///
+    /// Документы сопоставляются по `uid`: новые добавляются, изменившиеся обновляются,
+    /// пропавшие из ответа остаются с `delisted = true`. Результат собирается в
+    /// staging-коллекции и одной операцией renameCollection подменяет рабочую, поэтому
+    /// читатели видят либо прежний каталог целиком, либо новый.
+    ///
+    /// renameCollection с dropTarget удаляет рабочую коллекцию вместе с её индексами,
+    /// поэтому перед подменой на staging пересоздаются индексы uid/figi/ticker и все
+    /// индексы, которые были на рабочей коллекции к началу синхронизации. Индексы,
+    /// созданные позже, и записи в рабочую коллекцию во время синхронизации теряются:
+    /// каталог пишет только эта синхронизация.
+    ///
+    /// Поле `changes` отчёта содержит поинструментные изменения для журнала;
+    /// при первой загрузке пустой коллекции добавления в него не попадают. Журнал
+    /// пишется вызывающим кодом уже после подмены, и если запись в него не удалась,
+    /// изменения этого запуска в журнал не попадут: следующая синхронизация сравнивает
+    /// уже с новым каталогом
+    pub async fn sync_instruments(
+        &self,
+        kind: InstrumentKind,
+        documents: Vec<Document>,
+    ) -> Result<InstrumentSyncStats, mongodb::error::Error> {
+        let collection = self.instruments_collection(kind);
+        let name = collection.name().to_string();
+        let staging_name = format!("{}{}", name, STAGING_SUFFIX);
+        let staging = self
+            .database(DbNames::MARKET_DATA)
+            .collection::<Document>(&staging_name);
+
+        // Остатки прерванной синхронизации
+        staging.drop().await?;
+
+        let existing: Vec<Document> = collection.find(doc! {}).await?.try_collect().await?;
+        let mut previous: HashMap<String, Document> = existing
+            .into_iter()
+            .filter_map(|document| {
+                let uid = document.get_str("uid").ok()?.to_string();
+                Some((uid, document))
+            })
+            .collect();
+
+        let initial_load = previous.is_empty();
+        let now = Utc::now().to_rfc3339();
+        let mut stats = InstrumentSyncStats::default();
+        let mut seen = HashSet::new();
+        let mut merged = Vec::with_capacity(documents.len() + previous.len());
+
+        for mut document in documents {
+            let Ok(uid) = document.get_str("uid").map(String::from) else {
+                warn!("Skipping {} without uid", kind.as_str());
+                continue;
+            };
+            if uid.is_empty() || !seen.insert(uid.clone()) {
+                continue;
+            }
+
+            let (first_seen_at, updated_at) = match previous.remove(&uid) {
+                Some(old) => {
+                    let fields = diff_fields(&old, &document);
+                    let relisted = is_delisted(&old);
+                    if relisted {
+                        stats.relisted += 1;
+                        stats.changes.push(change_record(
+                            kind,
+                            &document,
+                            InstrumentChangeKind::Relisted,
+                            fields.clone(),
+                            &now,
+                        ));
+                    }
+                    let first_seen_at =
+                        old.get_str(FIELD_FIRST_SEEN_AT).unwrap_or(&now).to_string();
+                    let updated_at = if fields.is_empty() {
+                        stats.unchanged += 1;
+                        old.get_str(FIELD_UPDATED_AT).unwrap_or(&now).to_string()
+                    } else {
+                        stats.updated += 1;
+                        if !relisted {
+                            stats.changes.push(change_record(
+                                kind,
+                                &document,
+                                InstrumentChangeKind::Updated,
+                                fields,
+                                &now,
+                            ));
+                        }
+                        now.clone()
+                    };
+                    if let Ok(id) = old.get_object_id("_id") {
+                        document.insert("_id", id);
+                    }
+                    (first_seen_at, updated_at)
+                }
+                None => {
+                    stats.added += 1;
+                    if !initial_load {
+                        stats.changes.push(change_record(
+                            kind,
+                            &document,
+                            InstrumentChangeKind::Added,
+                            Vec::new(),
+                            &now,
+                        ));
+                    }
+                    (now.clone(), now.clone())
+                }
+            };
+
+            document.insert(FIELD_DELISTED, false);
+            document.insert(FIELD_FIRST_SEEN_AT, first_seen_at);
+            document.insert(FIELD_UPDATED_AT, updated_at);
+            document.insert(FIELD_LAST_SEEN_AT, now.clone());
+            merged.push(document);
+        }
+
+        // Инструменты, которых больше нет в ответе API
+        for (_, mut old) in previous {
+            if !is_delisted(&old) {
+                stats.delisted += 1;
+                stats.changes.push(change_record(
+                    kind,
+                    &old,
+                    InstrumentChangeKind::Delisted,
+                    Vec::new(),
+                    &now,
+                ));
+                old.insert(FIELD_DELISTED, true);
+                old.insert(FIELD_DELISTED_AT, now.clone());
+            }
+            merged.push(old);
+        }
+        stats.total = merged.len();
+
+        let indexes = catalogue_indexes(&collection).await?;
+        if !merged.is_empty() {
+            staging.insert_many(merged).await?;
+        }
+        staging.create_indexes(indexes).await?;
+
+        self.client
+            .database("admin")
+            .run_command(doc! {
+                "renameCollection": format!("{}.{}", DbNames::MARKET_DATA, staging_name),
+                "to": format!("{}.{}", DbNames::MARKET_DATA, name),
+                "dropTarget": true,
+            })
+            .await?;
+
+        info!(
+            "Synced {} catalogue: {} added, {} updated, {} unchanged, {} delisted, {} relisted",
+            kind.as_str(),
+            stats.added,
+            stats.updated,
+            stats.unchanged,
+            stats.delisted,
+            stats.relisted
+        );
+        Ok(stats)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_fields_ignores_service_fields() {
+        let old = doc! {
+            "figi": "BBG000000001",
+            "lot": 10,
+            "klong": { "units": 2_i64, "nano": 0 },
+            "short_enabled_flag": true,
+            FIELD_UPDATED_AT: "2025-03-01T00:00:00+00:00",
+            FIELD_DELISTED: false,
+        };
+        let new = doc! {
+            "figi": "BBG000000001",
+            "lot": 1,
+            "klong": { "units": 2_i64, "nano": 0 },
+            "min_price_increment": { "units": 0_i64, "nano": 10_000_000 },
+        };
+
+        let changes = diff_fields(&old, &new);
+        let fields: Vec<&str> = changes.iter().map(|change| change.field.as_str()).collect();
+        assert_eq!(fields, ["lot", "min_price_increment", "short_enabled_flag"]);
+        assert_eq!(changes[0].old_value, Bson::Int32(10));
+        assert_eq!(changes[0].new_value, Bson::Int32(1));
+        assert_eq!(changes[1].old_value, Bson::Null);
+        assert_eq!(changes[2].new_value, Bson::Null);
+    }
+}
@@ -1,3 +1,13 @@
 pub mod watchlists;
 pub mod currency_rates;
-pub mod shares;
\ No newline at end of file
+pub mod shares;
+pub mod candles;
+pub mod instruments;
+pub mod candle_days;
+pub mod trading_schedules;
+pub mod portfolio;
+pub mod operations;
+pub mod dividends;
+pub mod bond_coupons;
+pub mod bond_analytics;
+pub mod instrument_changelog;
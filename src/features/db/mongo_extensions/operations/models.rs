@@ -0,0 +1,80 @@
+use serde::{Deserialize, Serialize};
+
+use crate::features::core::models::{
+    money_value::TinkoffMoneyValueModel, quotation::TinkoffQuotationModel,
+};
+
+/// Сделка внутри операции
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DbOperationTrade {
+    pub num: String,
+    /// Время сделки, секунды UTC
+    pub date_seconds: i64,
+    pub quantity: i64,
+    pub price: Option<TinkoffMoneyValueModel>,
+}
+
+/// Операция по счёту (OperationsService.GetOperationsByCursor).
+/// Журнал только дописывается: сохранённая операция больше не меняется
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DbOperation {
+    pub account_id: String,
+    pub operation_id: String,
+    pub parent_operation_id: String,
+    /// Тип операции как в контракте: OPERATION_TYPE_BUY, OPERATION_TYPE_COUPON ...
+    pub operation_type: String,
+    pub state: String,
+    pub name: String,
+    pub description: String,
+    /// Время операции, RFC 3339
+    pub date: String,
+    /// Время операции, секунды UTC (для выборок по периоду)
+    pub date_seconds: i64,
+    pub figi: String,
+    pub instrument_uid: String,
+    pub instrument_type: String,
+    pub position_uid: String,
+    pub asset_uid: String,
+    /// Сумма операции со знаком: списание отрицательное
+    pub payment: Option<TinkoffMoneyValueModel>,
+    /// Цена за 1 инструмент
+    pub price: Option<TinkoffMoneyValueModel>,
+    pub commission: Option<TinkoffMoneyValueModel>,
+    #[serde(rename = "yield")]
+    pub yield_amount: Option<TinkoffMoneyValueModel>,
+    pub yield_relative: Option<TinkoffQuotationModel>,
+    /// НКД в сделке с облигацией
+    pub accrued_int: Option<TinkoffMoneyValueModel>,
+    pub quantity: i64,
+    pub quantity_done: i64,
+    pub quantity_rest: i64,
+    #[serde(default)]
+    pub trades: Vec<DbOperationTrade>,
+    pub imported_at: String,
+}
+
+/// Состояние загрузки операций счёта.
+///
+/// Загрузка идёт окнами [window_from, window_to); пока окно не выгружено целиком,
+/// `cursor` хранит курсор следующей страницы и после перезапуска загрузка продолжается с него
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DbOperationsSyncState {
+    pub account_id: String,
+    /// Операции до этого момента (секунды UTC) загружены
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub synced_until: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub window_from: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub window_to: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+    pub updated_at: String,
+}
+
+/// Страница операций, найденных по фильтру
+#[derive(Debug)]
+pub struct DbOperationsPage {
+    pub operations: Vec<DbOperation>,
+    pub total: u64,
+}
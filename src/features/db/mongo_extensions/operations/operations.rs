@@ -0,0 +1,146 @@
+// src/features/db/mongo_extensions/operations/operations.rs
+
+use futures::stream::TryStreamExt;
+use mongodb::bson::{doc, Document};
+use mongodb::options::IndexOptions;
+use mongodb::{Collection, IndexModel};
+use tracing::{error, info};
+
+use crate::features::db::{
+    mongo_db::{Collections, DbNames},
+    MongoDb,
+};
+
+use super::models::{DbOperation, DbOperationsPage, DbOperationsSyncState};
+
+impl MongoDb {
+    pub fn operations_collection(&self) -> Collection<DbOperation> {
+        self.database(DbNames::PORTFOLIO)
+            .collection::<DbOperation>(Collections::OPERATIONS)
+    }
+
+    pub fn operations_sync_collection(&self) -> Collection<DbOperationsSyncState> {
+        self.database(DbNames::PORTFOLIO)
+            .collection::<DbOperationsSyncState>(Collections::OPERATIONS_SYNC)
+    }
+
+    pub async fn ensure_operations_indexes(&self) {
+        let indexes = [
+            (
+                IndexModel::builder()
+                    .keys(doc! { "account_id": 1, "operation_id": 1 })
+                    .options(IndexOptions::builder().unique(true).build())
+                    .build(),
+                "account_id/operation_id",
+            ),
+            (
+                IndexModel::builder()
+                    .keys(doc! { "account_id": 1, "date_seconds": 1 })
+                    .build(),
+                "account_id/date_seconds",
+            ),
+            (
+                IndexModel::builder()
+                    .keys(doc! { "account_id": 1, "figi": 1, "date_seconds": 1 })
+                    .build(),
+                "account_id/figi/date_seconds",
+            ),
+        ];
+        for (index, name) in indexes {
+            match self.operations_collection().create_index(index).await {
+                Ok(_) => info!("Created {} index for operations collection", name),
+                Err(e) => error!("Failed to create {} index for operations collection: {}", name, e),
+            }
+        }
+
+        match self
+            .operations_sync_collection()
+            .create_index(
+                IndexModel::builder()
+                    .keys(doc! { "account_id": 1 })
+                    .options(IndexOptions::builder().unique(true).build())
+                    .build(),
+            )
+            .await
+        {
+            Ok(_) => info!("Created account_id index for operations sync collection"),
+            Err(e) => error!("Failed to create index for operations sync collection: {}", e),
+        }
+    }
+
+    /// Дописывает операции в журнал одним insert_many. Уже сохранённые операции
+    /// (тот же account_id/operation_id по уникальному индексу) не перезаписываются.
+    /// Возвращает количество новых операций
+    pub async fn append_operations(
+        &self,
+        operations: &[DbOperation],
+    ) -> Result<u64, mongodb::error::Error> {
+        let existing = self
+            .insert_many_skip_duplicates(&self.operations_collection(), operations)
+            .await?;
+        Ok((operations.len() - existing.len()) as u64)
+    }
+
+    pub async fn get_operations_sync_state(
+        &self,
+        account_id: &str,
+    ) -> Result<Option<DbOperationsSyncState>, mongodb::error::Error> {
+        self.operations_sync_collection()
+            .find_one(doc! { "account_id": account_id })
+            .await
+    }
+
+    pub async fn save_operations_sync_state(
+        &self,
+        state: &DbOperationsSyncState,
+    ) -> Result<(), mongodb::error::Error> {
+        self.operations_sync_collection()
+            .replace_one(doc! { "account_id": &state.account_id }, state)
+            .upsert(true)
+            .await?;
+        Ok(())
+    }
+
+    /// Все операции счёта по возрастанию времени
+    pub async fn find_account_operations(
+        &self,
+        account_id: &str,
+    ) -> Result<Vec<DbOperation>, mongodb::error::Error> {
+        self.operations_collection()
+            .find(doc! { "account_id": account_id })
+            .sort(doc! { "date_seconds": 1, "operation_id": 1 })
+            .await?
+            .try_collect()
+            .await
+    }
+
+    /// Операции счёта за период [from, to) (секунды UTC) по возрастанию времени
+    pub async fn find_operations(
+        &self,
+        account_id: &str,
+        filter: Document,
+        from: i64,
+        to: i64,
+        skip: u64,
+        limit: i64,
+    ) -> Result<DbOperationsPage, mongodb::error::Error> {
+        let mut query = doc! {
+            "account_id": account_id,
+            "date_seconds": { "$gte": from, "$lt": to },
+        };
+        query.extend(filter);
+
+        let collection = self.operations_collection();
+        let total = collection.count_documents(query.clone()).await?;
+        let operations = collection
+            .find(query)
+            .sort(doc! { "date_seconds": 1, "operation_id": 1 })
+            .skip(skip)
+            .limit(limit)
+            .await?
+            .try_collect()
+            .await?;
+
+        Ok(DbOperationsPage { operations, total })
+    }
+}
@@ -0,0 +1,84 @@
+use serde::{Deserialize, Serialize};
+
+use crate::features::core::models::{
+    money_value::TinkoffMoneyValueModel, quotation::TinkoffQuotationModel,
+};
+
+/// Счёт пользователя (UsersService.GetAccounts)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DbAccount {
+    pub account_id: String,
+    pub name: String,
+    /// Тип счёта как в контракте: ACCOUNT_TYPE_TINKOFF, ACCOUNT_TYPE_TINKOFF_IIS ...
+    pub account_type: String,
+    /// ACCOUNT_STATUS_OPEN, ACCOUNT_STATUS_CLOSED ...
+    pub status: String,
+    pub access_level: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub opened_date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub closed_date: Option<String>,
+    pub updated_at: String,
+}
+
+/// Позиция портфеля: данные GetPortfolio, дополненные балансом из GetPositions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DbPortfolioPosition {
+    pub figi: String,
+    pub instrument_uid: String,
+    pub position_uid: String,
+    pub instrument_type: String,
+    /// Количество в штуках
+    pub quantity: Option<TinkoffQuotationModel>,
+    pub average_position_price: Option<TinkoffMoneyValueModel>,
+    pub average_position_price_fifo: Option<TinkoffMoneyValueModel>,
+    pub current_price: Option<TinkoffMoneyValueModel>,
+    /// Текущий НКД (облигации)
+    pub current_nkd: Option<TinkoffMoneyValueModel>,
+    pub expected_yield: Option<TinkoffQuotationModel>,
+    pub expected_yield_fifo: Option<TinkoffQuotationModel>,
+    /// Вариационная маржа (фьючерсы)
+    pub var_margin: Option<TinkoffMoneyValueModel>,
+    pub blocked: bool,
+    pub blocked_lots: Option<TinkoffQuotationModel>,
+    /// Незаблокированный баланс из GetPositions
+    #[serde(default)]
+    pub balance: Option<i64>,
+    /// Заблокировано заявками, из GetPositions
+    #[serde(default)]
+    pub blocked_balance: Option<i64>,
+}
+
+/// Итоги портфеля по типам инструментов в валюте снимка
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DbPortfolioTotals {
+    pub portfolio: Option<TinkoffMoneyValueModel>,
+    pub shares: Option<TinkoffMoneyValueModel>,
+    pub bonds: Option<TinkoffMoneyValueModel>,
+    pub etf: Option<TinkoffMoneyValueModel>,
+    pub currencies: Option<TinkoffMoneyValueModel>,
+    pub futures: Option<TinkoffMoneyValueModel>,
+    pub options: Option<TinkoffMoneyValueModel>,
+    pub structured_notes: Option<TinkoffMoneyValueModel>,
+}
+
+/// Снимок портфеля счёта за день. В течение дня снимок перезаписывается,
+/// в истории остаётся последнее состояние дня
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DbPortfolioSnapshot {
+    pub account_id: String,
+    /// День снимка в формате YYYY-MM-DD (UTC)
+    pub day: String,
+    /// Время снимка, RFC 3339
+    pub taken_at: String,
+    /// Валюта, в которой рассчитаны итоги (RUB, USD, EUR)
+    pub currency: String,
+    pub totals: DbPortfolioTotals,
+    /// Относительная доходность портфеля, %
+    pub expected_yield: Option<TinkoffQuotationModel>,
+    pub positions: Vec<DbPortfolioPosition>,
+    /// Денежные позиции
+    pub money: Vec<TinkoffMoneyValueModel>,
+    /// Заблокированные денежные позиции
+    pub blocked_money: Vec<TinkoffMoneyValueModel>,
+}
@@ -0,0 +1,154 @@
+// src/features/db/mongo_extensions/portfolio/portfolio.rs
+
+use futures::stream::TryStreamExt;
+use mongodb::bson::{doc, to_document};
+use mongodb::options::IndexOptions;
+use mongodb::{Collection, IndexModel};
+use tracing::{error, info};
+
+use crate::features::db::{
+    mongo_db::{Collections, DbNames},
+    MongoDb,
+};
+
+use super::models::{DbAccount, DbPortfolioSnapshot};
+
+impl MongoDb {
+    pub fn accounts_collection(&self) -> Collection<DbAccount> {
+        self.database(DbNames::PORTFOLIO)
+            .collection::<DbAccount>(Collections::ACCOUNTS)
+    }
+
+    pub fn portfolio_snapshots_collection(&self) -> Collection<DbPortfolioSnapshot> {
+        self.database(DbNames::PORTFOLIO)
+            .collection::<DbPortfolioSnapshot>(Collections::PORTFOLIO_SNAPSHOTS)
+    }
+
+    pub async fn ensure_portfolio_indexes(&self) {
+        match self
+            .accounts_collection()
+            .create_index(
+                IndexModel::builder()
+                    .keys(doc! { "account_id": 1 })
+                    .options(IndexOptions::builder().unique(true).build())
+                    .build(),
+            )
+            .await
+        {
+            Ok(_) => info!("Created account_id index for accounts collection"),
+            Err(e) => error!("Failed to create index for accounts collection: {}", e),
+        }
+
+        match self
+            .portfolio_snapshots_collection()
+            .create_index(
+                IndexModel::builder()
+                    .keys(doc! { "account_id": 1, "day": 1 })
+                    .options(IndexOptions::builder().unique(true).build())
+                    .build(),
+            )
+            .await
+        {
+            Ok(_) => info!("Created account_id/day index for portfolio snapshots collection"),
+            Err(e) => error!("Failed to create index for portfolio snapshots collection: {}", e),
+        }
+    }
+
+    /// Сохраняет счета; запись счёта перезаписывается целиком
+    pub async fn upsert_accounts(&self, accounts: &[DbAccount]) -> Result<(), mongodb::error::Error> {
+        let collection = self.accounts_collection();
+        for account in accounts {
+            let document = to_document(account)?;
+            collection
+                .update_one(
+                    doc! { "account_id": &account.account_id },
+                    doc! { "$set": document },
+                )
+                .upsert(true)
+                .await?;
+        }
+        Ok(())
+    }
+
+    pub async fn get_accounts(&self) -> Result<Vec<DbAccount>, mongodb::error::Error> {
+        self.accounts_collection()
+            .find(doc! {})
+            .sort(doc! { "account_id": 1 })
+            .await?
+            .try_collect()
+            .await
+    }
+
+    pub async fn get_account(
+        &self,
+        account_id: &str,
+    ) -> Result<Option<DbAccount>, mongodb::error::Error> {
+        self.accounts_collection()
+            .find_one(doc! { "account_id": account_id })
+            .await
+    }
+
+    /// Сохраняет снимок портфеля; снимок за тот же день заменяется
+    pub async fn upsert_portfolio_snapshot(
+        &self,
+        snapshot: &DbPortfolioSnapshot,
+    ) -> Result<(), mongodb::error::Error> {
+        self.portfolio_snapshots_collection()
+            .replace_one(
+                doc! { "account_id": &snapshot.account_id, "day": &snapshot.day },
+                snapshot,
+            )
+            .upsert(true)
+            .await?;
+        Ok(())
+    }
+
+    /// Последний снимок портфеля счёта
+    pub async fn get_latest_portfolio_snapshot(
+        &self,
+        account_id: &str,
+    ) -> Result<Option<DbPortfolioSnapshot>, mongodb::error::Error> {
+        self.portfolio_snapshots_collection()
+            .find_one(doc! { "account_id": account_id })
+            .sort(doc! { "day": -1 })
+            .await
+    }
+
+    /// Снимок портфеля за день (YYYY-MM-DD)
+    pub async fn get_portfolio_snapshot(
+        &self,
+        account_id: &str,
+        day: &str,
+    ) -> Result<Option<DbPortfolioSnapshot>, mongodb::error::Error> {
+        self.portfolio_snapshots_collection()
+            .find_one(doc! { "account_id": account_id, "day": day })
+            .await
+    }
+
+    /// Последний снимок счёта за день `day` (YYYY-MM-DD) или раньше
+    pub async fn get_portfolio_snapshot_on_or_before(
+        &self,
+        account_id: &str,
+        day: &str,
+    ) -> Result<Option<DbPortfolioSnapshot>, mongodb::error::Error> {
+        self.portfolio_snapshots_collection()
+            .find_one(doc! { "account_id": account_id, "day": { "$lte": day } })
+            .sort(doc! { "day": -1 })
+            .await
+    }
+
+    /// Снимки портфеля счёта за дни [from_day, to_day] по возрастанию даты
+    pub async fn get_portfolio_snapshots(
+        &self,
+        account_id: &str,
+        from_day: &str,
+        to_day: &str,
+    ) -> Result<Vec<DbPortfolioSnapshot>, mongodb::error::Error> {
+        self.portfolio_snapshots_collection()
+            .find(doc! { "account_id": account_id, "day": { "$gte": from_day, "$lte": to_day } })
+            .sort(doc! { "day": 1 })
+            .await?
+            .try_collect()
+            .await
+    }
+}
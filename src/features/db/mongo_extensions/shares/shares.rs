@@ -1,18 +1,23 @@
 // src/features/db/mongo_extensions/shares/shares.rs
 use futures::TryStreamExt;
 
-use crate::features::db::{MongoDb, mongo_db::{DbNames, Collections}};
+use crate::features::core::models::instrument::InstrumentKind;
+use crate::features::db::MongoDb;
 
 use mongodb::bson::{doc, Document};
 use tracing::{info, error};
 
 impl MongoDb {
-    /// Получает список всех уникальных FIGI из коллекции tinkoff_shares
-    pub async fn get_unique_figis(&self) -> Vec<String> {
-        info!("Fetching unique FIGIs from tinkoff_shares collection");
+    /// Получает список всех уникальных FIGI из коллекции каталога для типа инструмента
+    pub async fn get_unique_figis(&self, kind: InstrumentKind) -> Vec<String> {
+        info!("Fetching unique FIGIs from {} collection", kind.as_str());
         
-        // Создаем агрегационный пайплайн для получения уникальных FIGI
+        // Создаем агрегационный пайплайн для получения уникальных FIGI;
+        // снятые с торгов (пропавшие из каталога API) не нужны сборщикам
         let pipeline = vec![
+            doc! {
+                "$match": { "delisted": { "$ne": true } }
+            },
             doc! {
                 "$group": {
                     "_id": "$figi"
@@ -22,8 +27,7 @@ impl MongoDb {
         
         // Получаем коллекцию и выполняем агрегацию
         let result = self
-            .database(DbNames::MARKET_DATA)
-            .collection::<Document>(Collections::TINKOFF_SHARES)
+            .instruments_collection(kind)
             .aggregate(pipeline)
             .await;
             
@@ -0,0 +1,32 @@
+use serde::{Deserialize, Serialize};
+
+/// Расписание торгов площадки на один день (из InstrumentsService.TradingSchedules).
+/// Время сессий — секунды UTC
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DbTradingDay {
+    pub exchange: String,
+    /// День в формате YYYY-MM-DD
+    pub day: String,
+    pub is_trading_day: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_time: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_time: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub evening_start_time: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub evening_end_time: Option<i64>,
+    pub updated_at: String,
+}
+
+impl DbTradingDay {
+    /// Границы торгов за день с учётом вечерней сессии
+    pub fn session_bounds(&self) -> Option<(i64, i64)> {
+        if !self.is_trading_day {
+            return None;
+        }
+        let start = self.start_time?;
+        let end = self.evening_end_time.unwrap_or_default().max(self.end_time?);
+        Some((start, end))
+    }
+}
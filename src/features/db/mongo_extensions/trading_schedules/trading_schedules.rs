@@ -0,0 +1,70 @@
+// src/features/db/mongo_extensions/trading_schedules/trading_schedules.rs
+
+use futures::stream::TryStreamExt;
+use mongodb::bson::{doc, to_document};
+use mongodb::options::IndexOptions;
+use mongodb::{Collection, IndexModel};
+use tracing::{error, info};
+
+use crate::features::db::{
+    mongo_db::{Collections, DbNames},
+    MongoDb,
+};
+
+use super::models::DbTradingDay;
+
+impl MongoDb {
+    pub fn trading_schedules_collection(&self) -> Collection<DbTradingDay> {
+        self.database(DbNames::MARKET_REFERENCE)
+            .collection::<DbTradingDay>(Collections::TRADING_SCHEDULES)
+    }
+
+    pub async fn ensure_trading_schedules_indexes(&self) {
+        match self
+            .trading_schedules_collection()
+            .create_index(
+                IndexModel::builder()
+                    .keys(doc! { "exchange": 1, "day": 1 })
+                    .options(IndexOptions::builder().unique(true).build())
+                    .build(),
+            )
+            .await
+        {
+            Ok(_) => info!("Created exchange/day index for trading schedules collection"),
+            Err(e) => error!("Failed to create index for trading schedules collection: {}", e),
+        }
+    }
+
+    /// Расписания всех площадок за дни [from_day, to_day] (YYYY-MM-DD)
+    pub async fn get_trading_days(
+        &self,
+        from_day: &str,
+        to_day: &str,
+    ) -> Result<Vec<DbTradingDay>, mongodb::error::Error> {
+        self.trading_schedules_collection()
+            .find(doc! { "day": { "$gte": from_day, "$lte": to_day } })
+            .sort(doc! { "exchange": 1, "day": 1 })
+            .await?
+            .try_collect()
+            .await
+    }
+
+    /// Сохраняет расписания; день площадки перезаписывается целиком
+    pub async fn upsert_trading_days(
+        &self,
+        days: &[DbTradingDay],
+    ) -> Result<(), mongodb::error::Error> {
+        let collection = self.trading_schedules_collection();
+        for day in days {
+            let document = to_document(day)?;
+            collection
+                .update_one(
+                    doc! { "exchange": &day.exchange, "day": &day.day },
+                    doc! { "$set": document },
+                )
+                .upsert(true)
+                .await?;
+        }
+        Ok(())
+    }
+}
@@ -2,7 +2,7 @@ use mongodb::bson::{doc, oid::ObjectId};
 use serde::{Deserialize, Serialize};
 
 /// Модель для коллекции DbUserConfigWatchlists
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DbUserConfigWatchlist {
     /// Уникальный идентификатор записи
     #[serde(rename = "_id")]
@@ -28,4 +28,71 @@ pub struct DbUserConfigWatchlist {
     /// Заметки/описание
     #[serde(skip_serializing_if = "Option::is_none")]
     pub notes: Option<String>,
+
+    /// Виды данных стрима по инструменту
+    #[serde(default)]
+    pub subscriptions: DbWatchlistSubscriptions,
+}
+
+/// Глубина стакана по умолчанию
+pub const DEFAULT_ORDER_BOOK_DEPTH: i32 = 10;
+
+/// Глубины стакана, которые принимает MarketDataStream
+pub const ORDER_BOOK_DEPTHS: [i32; 6] = [1, 10, 20, 30, 40, 50];
+
+/// Какие данные стрима получать по инструменту.
+/// Записи без этого поля подписываются только на минутные свечи, как раньше
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DbWatchlistSubscriptions {
+    /// Минутные свечи
+    #[serde(default = "default_true")]
+    pub candles: bool,
+
+    /// Стакан
+    #[serde(default)]
+    pub order_book: bool,
+
+    /// Глубина стакана (1, 10, 20, 30, 40 или 50)
+    #[serde(default = "default_order_book_depth")]
+    pub order_book_depth: i32,
+
+    /// Обезличенные сделки
+    #[serde(default)]
+    pub trades: bool,
+
+    /// Цена последней сделки
+    #[serde(default)]
+    pub last_price: bool,
+
+    /// Торговый статус инструмента
+    #[serde(default)]
+    pub info: bool,
+}
+
+impl Default for DbWatchlistSubscriptions {
+    fn default() -> Self {
+        Self {
+            candles: true,
+            order_book: false,
+            order_book_depth: DEFAULT_ORDER_BOOK_DEPTH,
+            trades: false,
+            last_price: false,
+            info: false,
+        }
+    }
+}
+
+impl DbWatchlistSubscriptions {
+    /// Нет ни одного включённого вида данных
+    pub fn is_empty(&self) -> bool {
+        !(self.candles || self.order_book || self.trades || self.last_price || self.info)
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_order_book_depth() -> i32 {
+    DEFAULT_ORDER_BOOK_DEPTH
 }
@@ -8,7 +8,9 @@ use crate::features::{
 
 };
 use futures::stream::TryStreamExt;
-use mongodb::bson::doc;
+use mongodb::bson::{doc, oid::ObjectId, Document};
+use mongodb::options::ReturnDocument;
+use mongodb::Collection;
 use tracing::{error, info};
 
 use super::models::DbUserConfigWatchlist;
@@ -79,3 +81,73 @@ impl MongoDb {
         result
     }
 }
+
+impl MongoDb {
+    fn watchlists_collection(&self) -> Collection<DbUserConfigWatchlist> {
+        self.database(DbNames::USER_CONFIG)
+            .collection::<DbUserConfigWatchlist>(Collections::WATCHLISTS)
+    }
+
+    /// Get a single watchlist entry by its id
+    pub async fn get_watchlist_by_id(
+        &self,
+        id: ObjectId,
+    ) -> Result<Option<DbUserConfigWatchlist>, mongodb::error::Error> {
+        self.watchlists_collection().find_one(doc! { "_id": id }).await
+    }
+
+    /// Get a watchlist entry by FIGI
+    pub async fn get_watchlist_by_figi(
+        &self,
+        figi: &str,
+    ) -> Result<Option<DbUserConfigWatchlist>, mongodb::error::Error> {
+        self.watchlists_collection()
+            .find_one(doc! { "figi": figi })
+            .await
+    }
+
+    /// Insert a new watchlist entry
+    pub async fn insert_watchlist(
+        &self,
+        watchlist: &DbUserConfigWatchlist,
+    ) -> Result<(), mongodb::error::Error> {
+        self.watchlists_collection().insert_one(watchlist).await?;
+        info!(
+            "Added watchlist entry {} for FIGI {}",
+            watchlist.id, watchlist.figi
+        );
+        Ok(())
+    }
+
+    /// Apply a `$set` update to a watchlist entry and return the updated document
+    pub async fn update_watchlist(
+        &self,
+        id: ObjectId,
+        set: Document,
+    ) -> Result<Option<DbUserConfigWatchlist>, mongodb::error::Error> {
+        let updated = self
+            .watchlists_collection()
+            .find_one_and_update(doc! { "_id": id }, doc! { "$set": set })
+            .return_document(ReturnDocument::After)
+            .await?;
+        if updated.is_some() {
+            info!("Updated watchlist entry {}", id);
+        }
+        Ok(updated)
+    }
+
+    /// Delete a watchlist entry and return the removed document
+    pub async fn delete_watchlist(
+        &self,
+        id: ObjectId,
+    ) -> Result<Option<DbUserConfigWatchlist>, mongodb::error::Error> {
+        let deleted = self
+            .watchlists_collection()
+            .find_one_and_delete(doc! { "_id": id })
+            .await?;
+        if deleted.is_some() {
+            info!("Deleted watchlist entry {}", id);
+        }
+        Ok(deleted)
+    }
+}
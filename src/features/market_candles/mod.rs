@@ -1 +1,2 @@
 pub mod tinkoff_shares_1m_historical;
+pub mod rollup;
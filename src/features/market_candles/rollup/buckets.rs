@@ -0,0 +1,222 @@
+// src/features/market_candles/rollup/buckets.rs
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, TimeZone, Timelike};
+use chrono_tz::Tz;
+
+use crate::features::core::models::candle_interval::MyCandleInterval;
+use crate::features::db::mongo_extensions::candles::models::{DbCandleTime, DbHistoricalCandle};
+
+/// Длительность внутридневного интервала (включая день) в минутах;
+/// None для недели и месяца
+fn interval_minutes(interval: MyCandleInterval) -> Option<i64> {
+    match interval {
+        MyCandleInterval::Unspecified | MyCandleInterval::Week | MyCandleInterval::Month => None,
+        MyCandleInterval::OneMin => Some(1),
+        MyCandleInterval::TwoMin => Some(2),
+        MyCandleInterval::ThreeMin => Some(3),
+        MyCandleInterval::FiveMin => Some(5),
+        MyCandleInterval::TenMin => Some(10),
+        MyCandleInterval::FifteenMin => Some(15),
+        MyCandleInterval::ThirtyMin => Some(30),
+        MyCandleInterval::Hour => Some(60),
+        MyCandleInterval::TwoHour => Some(120),
+        MyCandleInterval::FourHour => Some(240),
+        MyCandleInterval::Day => Some(24 * 60),
+    }
+}
+
+/// Примерная длительность интервала в минутах — для упорядочивания интервалов
+pub(super) fn interval_order(interval: MyCandleInterval) -> i64 {
+    match interval {
+        MyCandleInterval::Week => 7 * 24 * 60,
+        MyCandleInterval::Month => 31 * 24 * 60,
+        _ => interval_minutes(interval).unwrap_or(0),
+    }
+}
+
+/// Можно ли собрать свечи `large` из свечей `small`: каждая свеча `small`
+/// целиком попадает в одну свечу `large`
+pub(super) fn nests(small: MyCandleInterval, large: MyCandleInterval) -> bool {
+    match (interval_minutes(small), interval_minutes(large)) {
+        (Some(small), Some(large)) => large > small && large % small == 0,
+        // Неделя и месяц состоят из целых дней
+        (Some(small), None) => (24 * 60) % small == 0,
+        _ => false,
+    }
+}
+
+/// Местное время биржи -> секунды UTC. При переходе на летнее время берётся
+/// более ранний из вариантов, несуществующее время сдвигается на час вперёд
+fn local_to_utc(tz: Tz, local: NaiveDateTime) -> i64 {
+    tz.from_local_datetime(&local)
+        .earliest()
+        .or_else(|| tz.from_local_datetime(&(local + Duration::hours(1))).earliest())
+        .map(|dt| dt.timestamp())
+        .unwrap_or_else(|| local.and_utc().timestamp())
+}
+
+fn midnight(day: NaiveDate) -> NaiveDateTime {
+    day.and_hms_opt(0, 0, 0).unwrap()
+}
+
+/// Границы свечи интервала [начало, конец) (секунды UTC), в которую попадает момент `seconds`.
+/// Свечи выравниваются по местному времени биржи: дни — по местной полуночи,
+/// недели — с понедельника, месяцы — с первого числа
+pub(super) fn bucket_bounds(interval: MyCandleInterval, seconds: i64, tz: Tz) -> (i64, i64) {
+    let local = DateTime::from_timestamp(seconds, 0)
+        .unwrap_or_default()
+        .with_timezone(&tz)
+        .naive_local();
+    let day = local.date();
+
+    let (start, end) = match interval {
+        MyCandleInterval::Week => {
+            let monday = day - Duration::days(day.weekday().num_days_from_monday() as i64);
+            (midnight(monday), midnight(monday + Duration::days(7)))
+        }
+        MyCandleInterval::Month => {
+            let first = day.with_day(1).unwrap();
+            let next = first
+                .checked_add_months(chrono::Months::new(1))
+                .unwrap_or(first);
+            (midnight(first), midnight(next))
+        }
+        _ => {
+            let minutes = interval_minutes(interval).unwrap_or(1).max(1);
+            let minute_of_day = (local.hour() * 60 + local.minute()) as i64;
+            let start = midnight(day) + Duration::minutes(minute_of_day / minutes * minutes);
+            (start, start + Duration::minutes(minutes))
+        }
+    };
+
+    (local_to_utc(tz, start), local_to_utc(tz, end))
+}
+
+/// Собирает свечи интервала из отсортированных по времени свечей меньшего интервала
+pub(super) fn aggregate(
+    figi: &str,
+    candles: &[DbHistoricalCandle],
+    interval: MyCandleInterval,
+    tz: Tz,
+) -> Vec<DbHistoricalCandle> {
+    let mut result: Vec<DbHistoricalCandle> = Vec::new();
+
+    for candle in candles {
+        let (start, _) = bucket_bounds(interval, candle.time.seconds, tz);
+
+        match result.last_mut() {
+            Some(bar) if bar.time.seconds == start => {
+                if candle.high.value > bar.high.value {
+                    bar.high = candle.high.clone();
+                }
+                if candle.low.value < bar.low.value {
+                    bar.low = candle.low.clone();
+                }
+                bar.close = candle.close.clone();
+                bar.volume += candle.volume;
+            }
+            _ => result.push(DbHistoricalCandle {
+                figi: figi.to_string(),
+                interval: interval.short_name().to_string(),
+                volume: candle.volume,
+                display_time: DateTime::from_timestamp(start, 0)
+                    .map(|dt| dt.with_timezone(&tz).format("%Y-%m-%d %H:%M:%S").to_string()),
+                open: candle.open.clone(),
+                high: candle.high.clone(),
+                low: candle.low.clone(),
+                close: candle.close.clone(),
+                time: DbCandleTime {
+                    seconds: start,
+                    nanos: 0,
+                },
+            }),
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::db::mongo_extensions::candles::models::DbCandlePrice;
+
+    const MOSCOW: Tz = chrono_tz::Europe::Moscow;
+
+    fn utc(value: &str) -> i64 {
+        NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M")
+            .unwrap()
+            .and_utc()
+            .timestamp()
+    }
+
+    #[test]
+    fn test_bucket_bounds_in_exchange_timezone() {
+        // 10:37 UTC = 13:37 МСК
+        let seconds = utc("2024-03-06 10:37");
+        assert_eq!(
+            bucket_bounds(MyCandleInterval::FifteenMin, seconds, MOSCOW),
+            (utc("2024-03-06 10:30"), utc("2024-03-06 10:45"))
+        );
+        assert_eq!(
+            bucket_bounds(MyCandleInterval::Day, seconds, MOSCOW),
+            (utc("2024-03-05 21:00"), utc("2024-03-06 21:00"))
+        );
+        // 22:30 UTC — уже следующий день по Москве
+        assert_eq!(
+            bucket_bounds(MyCandleInterval::Day, utc("2024-03-06 22:30"), MOSCOW).0,
+            utc("2024-03-06 21:00")
+        );
+        assert_eq!(
+            bucket_bounds(MyCandleInterval::Week, seconds, MOSCOW),
+            (utc("2024-03-03 21:00"), utc("2024-03-10 21:00"))
+        );
+        assert_eq!(
+            bucket_bounds(MyCandleInterval::Month, seconds, MOSCOW),
+            (utc("2024-02-29 21:00"), utc("2024-03-31 21:00"))
+        );
+    }
+
+    #[test]
+    fn test_aggregate_ohlcv() {
+        let candle = |time: &str, open: i64, high: i64, low: i64, close: i64, volume: i64| {
+            let p = |units| DbCandlePrice::new(units, 0);
+            DbHistoricalCandle {
+                figi: "FIGI".to_string(),
+                interval: "1m".to_string(),
+                volume,
+                display_time: None,
+                open: p(open),
+                high: p(high),
+                low: p(low),
+                close: p(close),
+                time: DbCandleTime {
+                    seconds: utc(time),
+                    nanos: 0,
+                },
+            }
+        };
+        let minutes = vec![
+            candle("2024-03-06 10:00", 10, 12, 9, 11, 5),
+            candle("2024-03-06 10:01", 11, 15, 10, 14, 7),
+            candle("2024-03-06 10:04", 14, 14, 8, 9, 1),
+            candle("2024-03-06 10:05", 9, 10, 9, 10, 2),
+        ];
+
+        let bars = aggregate("FIGI", &minutes, MyCandleInterval::FiveMin, MOSCOW);
+        assert_eq!(bars.len(), 2);
+        let bar = &bars[0];
+        assert_eq!(bar.time.seconds, utc("2024-03-06 10:00"));
+        assert_eq!(bar.interval, "5m");
+        assert_eq!(
+            (bar.open.units, bar.high.units, bar.low.units, bar.close.units, bar.volume),
+            (10, 15, 8, 9, 13)
+        );
+        assert_eq!(bar.display_time.as_deref(), Some("2024-03-06 13:00:00"));
+
+        assert!(nests(MyCandleInterval::FiveMin, MyCandleInterval::FifteenMin));
+        assert!(!nests(MyCandleInterval::TenMin, MyCandleInterval::FifteenMin));
+        assert!(nests(MyCandleInterval::Hour, MyCandleInterval::Month));
+        assert!(!nests(MyCandleInterval::Week, MyCandleInterval::Month));
+    }
+}
@@ -0,0 +1,4 @@
+mod buckets;
+pub mod service;
+
+pub use service::CandleRollupService;
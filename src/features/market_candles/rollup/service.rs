@@ -0,0 +1,279 @@
+// src/features/market_candles/rollup/service.rs
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+
+use chrono_tz::Tz;
+use mongodb::bson::to_document;
+use tracing::{error, info};
+
+use crate::env_config::models::app_setting::AppSettings;
+use crate::features::core::models::{candle_interval::MyCandleInterval, instrument::InstrumentKind};
+use crate::features::db::mongo_extensions::candles::{
+    candles::candle_unique_index, models::DbHistoricalCandle,
+};
+use crate::features::db::MongoDb;
+
+use super::buckets::{aggregate, bucket_bounds, interval_order, nests};
+
+/// Размер шага при догоняющей сборке истории
+const CATCH_UP_STEP_SECONDS: i64 = 24 * 60 * 60;
+
+/// Сборка свечей старших интервалов (5m, 15m, 1h, 1d ...) из сохранённых минутных свечей.
+///
+/// Минутные свечи берутся из исторической коллекции типа инструмента и из коллекции стрима.
+/// Старшие интервалы собираются каскадом из уже собранного меньшего интервала, если он
+/// целиком в них укладывается (1h из 15m, 1d из 1h), поэтому пересчёт свечи дня или месяца
+/// не перечитывает все минуты периода
+pub struct CandleRollupService {
+    mongo_db: Arc<MongoDb>,
+    /// Интервалы по возрастанию длительности
+    intervals: Vec<MyCandleInterval>,
+    timezone: Tz,
+    /// Тип инструмента по FIGI (None — FIGI нет в каталоге)
+    kinds: Mutex<HashMap<String, Option<InstrumentKind>>>,
+    /// Диапазоны минут [from, to), пришедших из стрима и ещё не собранных
+    dirty: Mutex<HashMap<String, (i64, i64)>>,
+}
+
+impl CandleRollupService {
+    pub fn new(mongo_db: Arc<MongoDb>, settings: Arc<AppSettings>) -> Self {
+        let config = &settings.app_config.candle_rollup;
+
+        let mut intervals = Vec::new();
+        if config.enabled {
+            for name in &config.intervals {
+                match MyCandleInterval::parse(name) {
+                    Some(MyCandleInterval::OneMin) | Some(MyCandleInterval::Unspecified) => {
+                        error!("Candle rollup interval '{}' is not a higher timeframe", name)
+                    }
+                    Some(interval) if !intervals.contains(&interval) => intervals.push(interval),
+                    Some(_) => {}
+                    None => error!("Unknown candle rollup interval '{}'", name),
+                }
+            }
+        }
+        intervals.sort_by_key(|interval| interval_order(*interval));
+
+        let timezone = config.timezone.parse().unwrap_or_else(|_| {
+            error!(
+                "Invalid candle rollup timezone '{}', using Europe/Moscow",
+                config.timezone
+            );
+            chrono_tz::Europe::Moscow
+        });
+
+        Self {
+            mongo_db,
+            intervals,
+            timezone,
+            kinds: Mutex::new(HashMap::new()),
+            dirty: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.intervals.is_empty()
+    }
+
+    pub async fn ensure_indexes(&self) {
+        for interval in &self.intervals {
+            let collection = self.mongo_db.rollup_candles_collection(*interval);
+            if let Err(e) = collection.create_index(candle_unique_index()).await {
+                error!(
+                    "Failed to create unique candle index for {}: {}",
+                    collection.name(),
+                    e
+                );
+            }
+        }
+    }
+
+    /// Пересобирает все свечи старших интервалов, затронутые минутами [from, to) (секунды UTC)
+    pub async fn rollup_range(
+        &self,
+        figi: &str,
+        from_seconds: i64,
+        to_seconds: i64,
+    ) -> Result<(), mongodb::error::Error> {
+        if from_seconds >= to_seconds {
+            return Ok(());
+        }
+
+        let mut built: Vec<MyCandleInterval> = Vec::new();
+        for interval in &self.intervals {
+            let (start, _) = bucket_bounds(*interval, from_seconds, self.timezone);
+            let (_, end) = bucket_bounds(*interval, to_seconds - 1, self.timezone);
+
+            // Самый крупный из уже собранных интервалов, который укладывается в текущий
+            let source = built.iter().rev().find(|small| nests(**small, *interval));
+            let candles = match source {
+                Some(source) => {
+                    self.mongo_db
+                        .find_candles_between(
+                            &self.mongo_db.rollup_candles_collection(*source),
+                            figi,
+                            start,
+                            end,
+                        )
+                        .await?
+                }
+                None => self.load_minutes(figi, start, end).await?,
+            };
+
+            let mut documents = Vec::new();
+            for bar in aggregate(figi, &candles, *interval, self.timezone) {
+                documents.push(to_document(&bar)?);
+            }
+            self.mongo_db
+                .upsert_candles(&self.mongo_db.rollup_candles_collection(*interval), documents)
+                .await?;
+
+            built.push(*interval);
+        }
+
+        Ok(())
+    }
+
+    /// Минутные свечи FIGI из исторической коллекции и коллекции стрима;
+    /// для одной и той же минуты приоритет у исторической свечи
+    async fn load_minutes(
+        &self,
+        figi: &str,
+        from_seconds: i64,
+        to_seconds: i64,
+    ) -> Result<Vec<DbHistoricalCandle>, mongodb::error::Error> {
+        let mut minutes: BTreeMap<i64, DbHistoricalCandle> = BTreeMap::new();
+
+        let stream_candles = self
+            .mongo_db
+            .find_candles_between(
+                &self.mongo_db.stream_candles_collection(figi),
+                figi,
+                from_seconds,
+                to_seconds,
+            )
+            .await?;
+        for candle in stream_candles {
+            minutes.insert(candle.time.seconds, candle);
+        }
+
+        if let Some(kind) = self.instrument_kind(figi).await {
+            let historical = self
+                .mongo_db
+                .find_candles_between(
+                    &self
+                        .mongo_db
+                        .historical_candles_collection(kind, MyCandleInterval::OneMin),
+                    figi,
+                    from_seconds,
+                    to_seconds,
+                )
+                .await?;
+            for candle in historical {
+                minutes.insert(candle.time.seconds, candle);
+            }
+        }
+
+        Ok(minutes.into_values().collect())
+    }
+
+    async fn instrument_kind(&self, figi: &str) -> Option<InstrumentKind> {
+        if let Some(kind) = self.kinds.lock().unwrap().get(figi) {
+            return *kind;
+        }
+
+        let kind = self
+            .mongo_db
+            .find_instrument_by_figi(figi)
+            .await
+            .map(|instrument| instrument.kind());
+        self.kinds.lock().unwrap().insert(figi.to_string(), kind);
+        kind
+    }
+
+    /// Запоминает минуту из стрима для следующей сборки
+    pub fn mark_dirty(&self, figi: &str, minute_seconds: i64) {
+        let mut dirty = self.dirty.lock().unwrap();
+        let range = dirty
+            .entry(figi.to_string())
+            .or_insert((minute_seconds, minute_seconds + 60));
+        range.0 = range.0.min(minute_seconds);
+        range.1 = range.1.max(minute_seconds + 60);
+    }
+
+    /// Собирает старшие интервалы по накопленным минутам стрима
+    pub async fn flush_dirty(&self) {
+        let dirty: Vec<(String, (i64, i64))> = self.dirty.lock().unwrap().drain().collect();
+
+        for (figi, (from, to)) in dirty {
+            if let Err(e) = self.rollup_range(&figi, from, to).await {
+                error!("Failed to roll up stream candles for {}: {}", figi, e);
+                // Повторим при следующей сборке
+                self.mark_dirty(&figi, from);
+                self.mark_dirty(&figi, to - 60);
+            }
+        }
+    }
+
+    /// Догоняющая сборка для минутной истории типа инструмента: по каждому FIGI
+    /// от последней собранной свечи младшего интервала до последней минутной свечи
+    pub async fn catch_up(&self, kind: InstrumentKind) {
+        let Some(smallest) = self.intervals.first().copied() else {
+            return;
+        };
+
+        let minutes = self
+            .mongo_db
+            .historical_candles_collection(kind, MyCandleInterval::OneMin);
+        let figis = match self.mongo_db.distinct_candle_figis(&minutes).await {
+            Ok(figis) => figis,
+            Err(e) => {
+                error!("Failed to list FIGI in {}: {}", minutes.name(), e);
+                return;
+            }
+        };
+
+        let rolled = self.mongo_db.rollup_candles_collection(smallest);
+        let mut updated = 0;
+        for figi in &figis {
+            let Ok(Some((first, last))) = self.mongo_db.get_candles_time_bounds(&minutes, figi).await
+            else {
+                continue;
+            };
+            let rolled_last = match self.mongo_db.get_candles_time_bounds(&rolled, figi).await {
+                Ok(bounds) => bounds.map(|(_, last)| last),
+                Err(e) => {
+                    error!("Failed to read rollup bounds for {}: {}", figi, e);
+                    continue;
+                }
+            };
+
+            // Последняя собранная свеча могла быть неполной — пересобираем и её
+            let to = last + 60;
+            if rolled_last
+                .is_some_and(|rolled_last| bucket_bounds(smallest, last, self.timezone).0 <= rolled_last)
+            {
+                continue;
+            }
+            let mut from = rolled_last.unwrap_or(first).max(first);
+
+            while from < to {
+                let step_end = (from + CATCH_UP_STEP_SECONDS).min(to);
+                if let Err(e) = self.rollup_range(figi, from, step_end).await {
+                    error!("Failed to roll up candles for {}: {}", figi, e);
+                    break;
+                }
+                from = step_end;
+            }
+            updated += 1;
+        }
+
+        info!(
+            "Candle rollup caught up for {} of {} {} FIGI",
+            updated,
+            figis.len(),
+            kind.as_str()
+        );
+    }
+}
@@ -0,0 +1,345 @@
+// src/features/market_candles/tinkoff_shares_1m_historical/checkpoints.rs
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use mongodb::bson::Document;
+use mongodb::Collection;
+use tracing::{error, info, warn};
+
+use crate::features::core::models::candle_interval::MyCandleInterval;
+use crate::features::db::mongo_extensions::candle_days::models::DbCandleDayStatus;
+
+use super::service::HistoricalCandleDataService;
+use super::targets::CandleLoadTarget;
+
+/// Формат дня в отметках о загрузке
+pub const DAY_FORMAT: &str = "%Y-%m-%d";
+
+/// Дни [start, end)
+pub fn days_in_range(start: NaiveDate, end: NaiveDate) -> Vec<NaiveDate> {
+    start.iter_days().take_while(|day| *day < end).collect()
+}
+
+/// Группирует отсортированные дни в окна запроса: подряд идущие дни,
+/// не больше `max_days` в окне. Неторговые дни между ними окно не разрывают.
+/// Окно — полуинтервал [первый день, день после последнего)
+pub fn day_windows(
+    days: &[NaiveDate],
+    max_days: i64,
+    is_trading_day: impl Fn(NaiveDate) -> bool,
+) -> Vec<(NaiveDate, NaiveDate)> {
+    let max_days = max_days.max(1);
+    let mut windows: Vec<(NaiveDate, NaiveDate)> = Vec::new();
+
+    for day in days {
+        match windows.last_mut() {
+            Some((start, end))
+                if (*day - *start).num_days() < max_days
+                    && days_in_range(*end, *day)
+                        .into_iter()
+                        .all(|between| !is_trading_day(between)) =>
+            {
+                *end = *day + Duration::days(1);
+            }
+            _ => windows.push((*day, *day + Duration::days(1))),
+        }
+    }
+
+    windows
+}
+
+/// Начало дня UTC
+pub fn day_start(day: NaiveDate) -> DateTime<Utc> {
+    day.and_hms_opt(0, 0, 0).unwrap().and_utc()
+}
+
+/// Для недельных и месячных свечей день без свечи — норма, пропуски не ищем
+fn has_daily_candles(interval: MyCandleInterval) -> bool {
+    !matches!(interval, MyCandleInterval::Week | MyCandleInterval::Month)
+}
+
+impl HistoricalCandleDataService {
+    /// Сканер пропусков: ищет внутри уже сохранённого диапазона свечей торговые дни без свечей
+    /// и без отметки о загрузке и помечает их как gap, чтобы загрузка их повторила.
+    /// Дни со свечами, но без отметки (загруженные до появления отметок), считаются загруженными
+    pub(crate) async fn scan_gaps(
+        &self,
+        figi: &str,
+        exchange: &str,
+        target: &CandleLoadTarget,
+        collection: &Collection<Document>,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Vec<NaiveDate> {
+        let interval = target.interval.short_name();
+
+        let Some(status) = self.get_candle_history_status(figi, target.interval).await else {
+            return Vec::new();
+        };
+        let (Some(first), Some(last)) = (
+            DateTime::from_timestamp(status.first_candle_date_seconds, 0),
+            DateTime::from_timestamp(status.last_candle_date_seconds, 0),
+        ) else {
+            return Vec::new();
+        };
+
+        // Сканируем только пересечение запрошенного периода с сохранённым диапазоном
+        let scan_start = start.max(first.date_naive());
+        let scan_end = end.min(last.date_naive() + Duration::days(1));
+        if scan_start >= scan_end {
+            return Vec::new();
+        }
+
+        let counts = match self
+            .mongo_db
+            .count_candles_by_day(
+                collection,
+                figi,
+                day_start(scan_start).timestamp(),
+                day_start(scan_end).timestamp(),
+            )
+            .await
+        {
+            Ok(counts) => counts,
+            Err(e) => {
+                error!("Failed to count stored candles by day for {}: {}", figi, e);
+                return Vec::new();
+            }
+        };
+        let recorded: HashSet<String> = match self
+            .mongo_db
+            .get_candle_days(
+                figi,
+                interval,
+                &scan_start.format(DAY_FORMAT).to_string(),
+                &scan_end.format(DAY_FORMAT).to_string(),
+            )
+            .await
+        {
+            Ok(days) => days.into_iter().map(|day| day.day).collect(),
+            Err(e) => {
+                error!("Failed to read candle day records for {}: {}", figi, e);
+                return Vec::new();
+            }
+        };
+
+        let mut gaps = Vec::new();
+        for day in days_in_range(scan_start, scan_end) {
+            let key = day.format(DAY_FORMAT).to_string();
+            if recorded.contains(&key) {
+                continue;
+            }
+
+            let result = match counts.get(&key) {
+                Some(count) => {
+                    self.mongo_db
+                        .mark_candle_day_complete(figi, interval, &key, *count)
+                        .await
+                }
+                None if has_daily_candles(target.interval)
+                    && self.calendar.is_trading_day(exchange, day) =>
+                {
+                    gaps.push(day);
+                    self.mongo_db.mark_candle_day_gap(figi, interval, &key).await
+                }
+                None => continue,
+            };
+            if let Err(e) = result {
+                error!("Failed to record candle day {} for {}: {}", key, figi, e);
+            }
+        }
+
+        if !gaps.is_empty() {
+            warn!(
+                "Found {} gap days for {} ({}): {}",
+                gaps.len(),
+                figi,
+                target.label(),
+                format_days(&gaps)
+            );
+        }
+
+        gaps
+    }
+
+    /// Торговые дни периода, которые ещё не загружены целиком: без отметки, с ошибкой или пропуском
+    pub(crate) async fn days_to_load(
+        &self,
+        figi: &str,
+        exchange: &str,
+        target: &CandleLoadTarget,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Vec<NaiveDate> {
+        let days: Vec<NaiveDate> = days_in_range(start, end)
+            .into_iter()
+            .filter(|day| self.calendar.is_trading_day(exchange, *day))
+            .collect();
+        if self.settings.app_config.historical_candle_data.force_update || days.is_empty() {
+            return days;
+        }
+
+        let complete: HashSet<String> = match self
+            .mongo_db
+            .get_candle_days(
+                figi,
+                target.interval.short_name(),
+                &start.format(DAY_FORMAT).to_string(),
+                &end.format(DAY_FORMAT).to_string(),
+            )
+            .await
+        {
+            Ok(records) => records
+                .into_iter()
+                .filter(|record| record.status == DbCandleDayStatus::Complete)
+                .map(|record| record.day)
+                .collect(),
+            Err(e) => {
+                // Без отметок грузим период целиком: запись свечей идемпотентна
+                error!("Failed to read candle day records for {}: {}", figi, e);
+                HashSet::new()
+            }
+        };
+
+        days.into_iter()
+            .filter(|day| !complete.contains(&day.format(DAY_FORMAT).to_string()))
+            .collect()
+    }
+
+    /// Отмечает дни окна загруженными с количеством свечей за каждый день
+    pub(crate) async fn mark_window_complete(
+        &self,
+        figi: &str,
+        target: &CandleLoadTarget,
+        window: (NaiveDate, NaiveDate),
+        counts: &HashMap<NaiveDate, i64>,
+    ) {
+        for day in days_in_range(window.0, window.1) {
+            let key = day.format(DAY_FORMAT).to_string();
+            let count = counts.get(&day).copied().unwrap_or(0);
+            if let Err(e) = self
+                .mongo_db
+                .mark_candle_day_complete(figi, target.interval.short_name(), &key, count)
+                .await
+            {
+                error!("Failed to mark candle day {} complete for {}: {}", key, figi, e);
+            }
+        }
+    }
+
+    /// Отмечает дни окна как незагруженные, следующий запуск их повторит
+    pub(crate) async fn mark_window_failed(
+        &self,
+        figi: &str,
+        target: &CandleLoadTarget,
+        window: (NaiveDate, NaiveDate),
+        reason: &str,
+    ) {
+        for day in days_in_range(window.0, window.1) {
+            let key = day.format(DAY_FORMAT).to_string();
+            if let Err(e) = self
+                .mongo_db
+                .mark_candle_day_failed(figi, target.interval.short_name(), &key, reason)
+                .await
+            {
+                error!("Failed to mark candle day {} failed for {}: {}", key, figi, e);
+            }
+        }
+    }
+
+    /// Итог по незакрытым дням после загрузки FIGI
+    pub(crate) async fn report_open_days(&self, figi: &str, target: &CandleLoadTarget) {
+        match self
+            .mongo_db
+            .find_open_candle_days(figi, Some(target.interval.short_name()))
+            .await
+        {
+            Ok(days) if days.is_empty() => {
+                info!("No open gaps for {} ({})", figi, target.label());
+            }
+            Ok(days) => {
+                let list: Vec<String> = days
+                    .iter()
+                    .map(|day| format!("{} ({})", day.day, day.status.as_str()))
+                    .collect();
+                warn!(
+                    "{} days still missing for {} ({}): {}",
+                    days.len(),
+                    figi,
+                    target.label(),
+                    list.join(", ")
+                );
+            }
+            Err(e) => error!("Failed to read open candle days for {}: {}", figi, e),
+        }
+    }
+}
+
+fn format_days(days: &[NaiveDate]) -> String {
+    days.iter()
+        .map(|day| day.format(DAY_FORMAT).to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, DAY_FORMAT).unwrap()
+    }
+
+    #[test]
+    fn test_day_windows_split_on_gaps_and_size() {
+        let every_day = |_: NaiveDate| true;
+        let days = vec![
+            day("2024-03-01"),
+            day("2024-03-02"),
+            day("2024-03-03"),
+            day("2024-03-05"),
+        ];
+
+        assert_eq!(
+            day_windows(&days, 1, every_day),
+            vec![
+                (day("2024-03-01"), day("2024-03-02")),
+                (day("2024-03-02"), day("2024-03-03")),
+                (day("2024-03-03"), day("2024-03-04")),
+                (day("2024-03-05"), day("2024-03-06")),
+            ]
+        );
+        assert_eq!(
+            day_windows(&days, 2, every_day),
+            vec![
+                (day("2024-03-01"), day("2024-03-03")),
+                (day("2024-03-03"), day("2024-03-04")),
+                (day("2024-03-05"), day("2024-03-06")),
+            ]
+        );
+        assert_eq!(
+            day_windows(&days, 365, every_day),
+            vec![
+                (day("2024-03-01"), day("2024-03-04")),
+                (day("2024-03-05"), day("2024-03-06")),
+            ]
+        );
+
+        // Неторговый день 2024-03-04 окно не разрывает
+        let weekdays_only = |d: NaiveDate| d != day("2024-03-04");
+        assert_eq!(
+            day_windows(&days, 365, weekdays_only),
+            vec![(day("2024-03-01"), day("2024-03-06"))]
+        );
+    }
+
+    #[test]
+    fn test_days_in_range_is_half_open() {
+        assert_eq!(
+            days_in_range(day("2024-02-28"), day("2024-03-01")),
+            vec![day("2024-02-28"), day("2024-02-29")]
+        );
+        assert!(days_in_range(day("2024-03-01"), day("2024-03-01")).is_empty());
+    }
+}
@@ -1,4 +1,6 @@
+pub mod checkpoints;
 pub mod service;
 pub mod scheduler;
 pub mod status_tracker;
+pub mod targets;
 pub mod updater;
\ No newline at end of file
@@ -1,22 +1,38 @@
 use crate::{
 
     env_config::models::app_setting::AppSettings, features::db::MongoDb, gen::tinkoff_public_invest_api_contract_v1::{
-        CandleInterval, GetCandlesRequest, HistoricCandle,
-    }, services::tinkoff::client_grpc::TinkoffClient
+        GetCandlesRequest, HistoricCandle,
+    }, services::tinkoff::{client_grpc::TinkoffClient, rate_limiter::TinkoffMethod}
 };
 
-use chrono::{Duration, TimeZone, Utc};
-use futures::stream::TryStreamExt;
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+use futures::stream::{self, StreamExt};
 use mongodb::bson::{doc, Document};
-use mongodb::options::{FindOptions, IndexOptions};
+use mongodb::options::IndexOptions;
+use mongodb::Collection;
 use prost_types::Timestamp;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
+use tonic::Status;
 use tracing::{error, info, warn};
 
+use crate::features::{
+    core::models::candle_interval::MyCandleInterval,
+    db::mongo_extensions::candles::{candles::candle_unique_index, models::candle_price_bson},
+    market_candles::rollup::CandleRollupService,
+    market_reference::trading_calendar::TradingCalendar,
+};
+
+use super::checkpoints::{day_start, day_windows};
+use super::targets::CandleLoadTarget;
+
 pub struct HistoricalCandleDataService {
     pub(crate) client: Arc<TinkoffClient>,
     pub(crate) mongo_db: Arc<MongoDb>,
     pub(crate) settings: Arc<AppSettings>,
+    pub(crate) calendar: TradingCalendar,
+    pub(crate) rollup: CandleRollupService,
 }
 
 impl HistoricalCandleDataService {
@@ -26,6 +42,8 @@ impl HistoricalCandleDataService {
         settings: Arc<AppSettings>,
     ) -> Self {
         Self {
+            calendar: TradingCalendar::new(client.clone(), mongo_db.clone(), settings.clone()),
+            rollup: CandleRollupService::new(mongo_db.clone(), settings.clone()),
             client,
             mongo_db,
             settings,
@@ -80,265 +98,381 @@ impl HistoricalCandleDataService {
         self.initialize_status_collection().await;
         // Initialize indexes on the status collection
         self.ensure_status_collection_indexes().await;
+        // Per-day load checkpoints used to resume and retry gaps
+        self.mongo_db.ensure_candle_days_indexes().await;
+
+        let targets = self.load_targets();
+        if targets.is_empty() {
+            warn!("No historical candle targets configured");
+            return;
+        }
+
+        if self.rollup.is_enabled() {
+            self.rollup.ensure_indexes().await;
+        }
+
+        for target in &targets {
+            self.load_target(target).await;
 
-        // Сразу определяем период для запроса на основе max_days_history
-        let (start_date, end_date) = self.calculate_fetch_period();
+            // Минуты, загруженные до появления rollup, собираем в старшие интервалы
+            if target.interval == MyCandleInterval::OneMin && self.rollup.is_enabled() {
+                self.rollup.catch_up(target.kind).await;
+            }
+        }
+
+        info!("Historical candle data service completed");
+    }
 
-        // Получение всех уникальных FIGI из коллекции tinkoff_shares
-        let figis = self.mongo_db.get_unique_figis().await;
+    /// Загрузка одного набора (тип инструмента, интервал)
+    async fn load_target(&self, target: &CandleLoadTarget) {
+        info!("Loading historical candles for target {}", target.label());
+
+        let collection = self
+            .mongo_db
+            .historical_candles_collection(target.kind, target.interval);
+        // Index for candle queries by FIGI and time (used by the candles API)
+        self.ensure_historical_collection_indexes(&collection).await;
+
+        // Сразу определяем период для запроса на основе глубины истории набора
+        let (start_date, end_date) = self.calculate_fetch_period(target.max_days_history);
+
+        // Расписания торгов за период: выходные и праздники не запрашиваем
+        self.calendar
+            .ensure_range(start_date.date_naive(), end_date.date_naive())
+            .await;
+
+        let figis = self.target_figis(target).await;
 
         if figis.is_empty() {
-            info!("No FIGI found in tinkoff_shares collection");
+            info!("No FIGI found for target {}", target.label());
             return;
         }
 
         let total_figis = figis.len();
         info!(
-            "Found {} unique FIGI for historical data fetch",
-            total_figis
+            "Found {} unique FIGI for historical data fetch ({})",
+            total_figis,
+            target.label()
         );
-        
-        // Расчет примерного времени
-        let total_requests = total_figis * self.settings.app_config.historical_candle_data.max_days_history as usize;
-        let estimated_time_seconds = (total_requests as u64 * self.settings.app_config.historical_candle_data.request_delay_ms) / 1000;
+
+        // Расчет примерного времени: число окон запроса на один FIGI зависит от интервала,
+        // темп запросов ограничен лимитом GetCandles
+        let window_seconds = target.interval.max_request_window().num_seconds();
+        let period_seconds = (end_date - start_date).num_seconds();
+        let windows_per_figi = ((period_seconds + window_seconds - 1) / window_seconds).max(1);
+        let total_requests = total_figis * windows_per_figi as usize;
+        let per_minute = self
+            .client
+            .rate_limiter
+            .per_minute(TinkoffMethod::GetCandles)
+            .max(1) as u64;
+        let estimated_time_seconds = total_requests as u64 * 60 / per_minute;
         let estimated_hours = estimated_time_seconds / 3600;
         let estimated_minutes = (estimated_time_seconds % 3600) / 60;
         let estimated_seconds = estimated_time_seconds % 60;
-        
+
         info!(
             "Estimated completion time: ~{:02}:{:02}:{:02} (hh:mm:ss), total requests: {}",
             estimated_hours, estimated_minutes, estimated_seconds, total_requests
         );
 
-        // Process each instrument
-        for (idx, figi) in figis.iter().enumerate() {
-            // Простой прогресс
-            info!(
-                "Progress: {}/{}",
-                idx + 1, total_figis
-            );
-            
-            info!("Processing historical data for {}", figi);
+        // Process instruments with a bounded worker pool; the rate limiter paces requests
+        let concurrency = self
+            .settings
+            .app_config
+            .historical_candle_data
+            .max_concurrency
+            .max(1);
+        let processed = AtomicUsize::new(0);
+
+        stream::iter(figis.iter())
+            .for_each_concurrent(concurrency, |figi| {
+                let collection = &collection;
+                let processed = &processed;
+                async move {
+                    self.load_figi(figi, target, collection, start_date, end_date)
+                        .await;
+
+                    // Простой прогресс
+                    let done = processed.fetch_add(1, Ordering::Relaxed) + 1;
+                    info!("Progress {}: {}/{}", target.label(), done, total_figis);
+                }
+            })
+            .await;
 
-            // Check if we already have data for this FIGI and date range
-            let should_fetch = self.check_historical_data_needed(figi, start_date, end_date).await;
-            if !should_fetch && !self.settings.app_config.historical_candle_data.force_update {
-                info!("Skipping {} - already have data for the requested period", figi);
-                continue;
-            }
+        info!("Historical candles for target {} completed", target.label());
+    }
 
-            info!(
-                "Fetching historical data for {} from {} to {}",
-                figi,
-                start_date.format("%Y-%m-%d %H:%M:%S"),
-                end_date.format("%Y-%m-%d %H:%M:%S")
-            );
+    /// Загрузка истории одного FIGI для набора: повторно запрашиваются только дни
+    /// без отметки о полной загрузке (ошибки прошлых запусков и найденные пропуски)
+    async fn load_figi(
+        &self,
+        figi: &str,
+        target: &CandleLoadTarget,
+        collection: &Collection<Document>,
+        start_date: DateTime<Utc>,
+        end_date: DateTime<Utc>,
+    ) {
+        info!("Processing historical data for {}", figi);
+
+        let (start_day, end_day) = (start_date.date_naive(), end_date.date_naive());
+
+        // Торговые дни считаем по расписанию площадки инструмента
+        let exchange = self
+            .mongo_db
+            .find_instrument_by_figi(figi)
+            .await
+            .map(|instrument| instrument.exchange().to_string())
+            .unwrap_or_default();
+
+        // Дни внутри уже сохранённого диапазона без свечей помечаются как пропуски
+        self.scan_gaps(figi, &exchange, target, collection, start_day, end_day)
+            .await;
+
+        let days = self
+            .days_to_load(figi, &exchange, target, start_day, end_day)
+            .await;
+        if days.is_empty() {
+            info!("Skipping {} - already have data for the requested period", figi);
+            return;
+        }
+
+        let windows = day_windows(
+            &days,
+            target.interval.max_request_window().num_days(),
+            |day| self.calendar.is_trading_day(&exchange, day),
+        );
+        info!(
+            "Fetching historical data for {}: {} days in {} requests",
+            figi,
+            days.len(),
+            windows.len()
+        );
+
+        for window in windows {
+            match self.fetch_window(figi, target, collection, window).await {
+                Ok(counts) => {
+                    self.mark_window_complete(figi, target, window, &counts)
+                        .await;
+                    if target.interval == MyCandleInterval::OneMin && self.rollup.is_enabled() {
+                        self.rollup_window(figi, window).await;
+                    }
+                }
+                Err(reason) => {
+                    self.mark_window_failed(figi, target, window, &reason)
+                        .await
+                }
+            }
 
-            // Fetch data day by day
-            self.fetch_historical_data_by_day(&figi, start_date, end_date)
-                .await;
-                
-            // Update status after fetching
-            if let Err(e) = self.update_candle_history_status(figi).await {
-                error!("Failed to update candle history status for {}: {}", figi, e);
+            // Optional extra pause per worker on top of the rate limiter
+            let request_delay_ms = self.settings.app_config.historical_candle_data.request_delay_ms;
+            if request_delay_ms > 0 {
+                tokio::time::sleep(tokio::time::Duration::from_millis(request_delay_ms)).await;
             }
         }
 
-        info!("Historical candle data service completed");
+        // Update status after fetching
+        if let Err(e) = self.update_candle_history_status(figi, target).await {
+            error!("Failed to update candle history status for {}: {}", figi, e);
+        }
+
+        self.report_open_days(figi, target).await;
+    }
+
+    /// Пересобирает старшие интервалы по минутам, загруженным за окно дней
+    async fn rollup_window(&self, figi: &str, (from_day, to_day): (NaiveDate, NaiveDate)) {
+        if let Err(e) = self
+            .rollup
+            .rollup_range(figi, day_start(from_day).timestamp(), day_start(to_day).timestamp())
+            .await
+        {
+            error!("Failed to roll up candles for {} from {}: {}", figi, from_day, e);
+        }
     }
 
     async fn ensure_status_collection_indexes(&self) {
         let status_collection = self.mongo_db.market_candles_status_collection();
-        
-        // Create index on FIGI for quick lookups
+
+        // Раньше статус хранился только по FIGI (минутные свечи акций):
+        // проставляем интервал старым записям и убираем уникальный индекс по одному FIGI
+        match status_collection
+            .update_many(
+                doc! { "figi": { "$exists": true }, "interval": { "$exists": false } },
+                doc! { "$set": { "interval": "1m", "instrument_type": "share" } },
+            )
+            .await
+        {
+            Ok(result) if result.modified_count > 0 => info!(
+                "Migrated {} candle history status documents to per-interval keys",
+                result.modified_count
+            ),
+            Ok(_) => {}
+            Err(e) => error!("Failed to migrate candle history status documents: {}", e),
+        }
+        if status_collection.drop_index("figi_1").await.is_ok() {
+            info!("Dropped legacy FIGI index from candle history status collection");
+        }
+
+        // Create index on FIGI and interval for quick lookups
         match status_collection
             .create_index(
                 mongodb::IndexModel::builder()
-                    .keys(doc! { "figi": 1 })
+                    .keys(doc! { "figi": 1, "interval": 1 })
                     .options(IndexOptions::builder().unique(true).build())
                     .build(),
             )
             .await
         {
-            Ok(_) => info!("Created FIGI index for candle history status collection"),
-            Err(e) => error!("Failed to create FIGI index for status collection: {}", e),
+            Ok(_) => info!("Created FIGI/interval index for candle history status collection"),
+            Err(e) => error!("Failed to create FIGI/interval index for status collection: {}", e),
+        }
+    }
+
+    async fn ensure_historical_collection_indexes(&self, collection: &Collection<Document>) {
+        match collection
+            .create_index(
+                mongodb::IndexModel::builder()
+                    .keys(doc! { "figi": 1, "time.seconds": 1 })
+                    .build(),
+            )
+            .await
+        {
+            Ok(_) => info!(
+                "Created FIGI/time index for historical candles collection {}",
+                collection.name()
+            ),
+            Err(e) => error!(
+                "Failed to create FIGI/time index for {}: {}",
+                collection.name(),
+                e
+            ),
+        }
+
+        // Uniqueness of (figi, interval, time) backs the upserts
+        if let Err(e) = collection.create_index(candle_unique_index()).await {
+            error!(
+                "Failed to create unique candle index for {}: {}",
+                collection.name(),
+                e
+            );
         }
     }
 
     // Упрощенный метод расчета периода для запроса данных
-    fn calculate_fetch_period(&self) -> (chrono::DateTime<Utc>, chrono::DateTime<Utc>) {
-        // Конечная дата - вчерашний день (чтобы избежать неполных данных за сегодня)
+    fn calculate_fetch_period(&self, max_days_history: u32) -> (DateTime<Utc>, DateTime<Utc>) {
+        // Конечная граница - начало сегодняшнего дня (чтобы избежать неполных данных за сегодня)
         let end_date = Utc::now()
-            .date()
+            .date_naive()
             .and_hms_opt(0, 0, 0)
             .unwrap()
-            .with_timezone(&Utc)
-            - Duration::days(1);
-        
+            .and_utc();
+
         // Начальная дата - отступаем назад на max_days_history от конечной даты
-        let start_date = end_date - Duration::days(
-            self.settings
-                .app_config
-                .historical_candle_data
-                .max_days_history as i64,
-        );
-        
+        let start_date = end_date - Duration::days(max_days_history as i64);
+
         info!(
             "Setting historical data fetch period to {} days (from {} to {})",
-            self.settings.app_config.historical_candle_data.max_days_history,
+            max_days_history,
             start_date.format("%Y-%m-%d"),
             end_date.format("%Y-%m-%d"),
         );
-        
+
         (start_date, end_date)
     }
 
-    async fn fetch_historical_data_by_day(
+    /// Загружает свечи за окно дней [from, to) и возвращает число свечей по дням
+    async fn fetch_window(
         &self,
         figi: &str,
-    mut start_date: chrono::DateTime<Utc>,
-        end_date: chrono::DateTime<Utc>,
-    ) {
-        let collection = self.mongo_db.get_historical_collection();
-    // Check if we already have some data for this FIGI
-    if let Some(status) = self.get_candle_history_status(figi).await {
-        // If we already have data, we can optimize by only fetching what we're missing
-        
-        // If our existing data starts earlier than requested start_date, 
-        // we can start from the day after our existing latest data
-        if status.first_candle_date_seconds <= start_date.timestamp() {
-            let existing_end = Utc.timestamp_opt(status.last_candle_date_seconds, 0).unwrap();
-            
-            // Start from the day after our existing latest data
-            start_date = (existing_end + Duration::days(1))
-                .date()
-                .and_hms_opt(0, 0, 0)
-                .unwrap()
-                .with_timezone(&Utc);
-            
+        target: &CandleLoadTarget,
+        collection: &Collection<Document>,
+        (from_day, to_day): (NaiveDate, NaiveDate),
+    ) -> Result<HashMap<NaiveDate, i64>, String> {
+        let request = GetCandlesRequest {
+            from: Some(Timestamp {
+                seconds: day_start(from_day).timestamp(),
+                nanos: 0,
+            }),
+            to: Some(Timestamp {
+                seconds: day_start(to_day).timestamp(),
+                nanos: 0,
+            }),
+            interval: target.interval as i32,
+            instrument_id: figi.to_string(),
+            #[allow(deprecated)]
+            figi: figi.to_string(),
+        };
+
+        // Make the gRPC call through the rate limiter
+        let candles = match self
+            .client
+            .call_limited(TinkoffMethod::GetCandles, || {
+                let mut market_data_client = self.client.market_data.clone();
+                let request = self.client.create_request(request.clone());
+                async move {
+                    let request = request.map_err(|e| Status::internal(e.to_string()))?;
+                    market_data_client.get_candles(request).await
+                }
+            })
+            .await
+        {
+            Ok(response) => response.candles,
+            Err(e) => {
+                error!(
+                    "Failed to get historical candles for {} from {}: {}",
+                    figi, from_day, e
+                );
+                return Err(e.message().to_string());
+            }
+        };
+
+        let mut counts: HashMap<NaiveDate, i64> = HashMap::new();
+        if candles.is_empty() {
             info!(
-                "Optimizing fetch for {}: Starting from {} (after existing data end)",
+                "No historical {} candles found for {} from {} to {}",
+                target.interval.short_name(),
                 figi,
-                start_date.format("%Y-%m-%d")
+                from_day,
+                to_day
             );
-            
-            // If optimized start_date is already beyond our end_date, we have nothing to fetch
-            if start_date >= end_date {
+            return Ok(counts);
+        }
+
+        let mut documents = Vec::with_capacity(candles.len());
+        for candle in candles {
+            let seconds = candle.time.as_ref().map_or(0, |t| t.seconds);
+            if let Some(time) = DateTime::from_timestamp(seconds, 0) {
+                *counts.entry(time.date_naive()).or_default() += 1;
+            }
+            documents.push(self.historic_candle_to_document(figi, target.interval, candle));
+        }
+
+        // Upsert by (figi, interval, time): reruns don't duplicate candles
+        match self.mongo_db.upsert_candles(collection, documents).await {
+            Ok(result) => {
                 info!(
-                    "No new data needed for {}: already have data up to {}",
+                    "Saved historical {} candles for {} from {}: {} new, {} updated",
+                    target.interval.short_name(),
                     figi,
-                    existing_end.format("%Y-%m-%d")
+                    from_day,
+                    result.inserted,
+                    result.updated
                 );
-                return;
+                Ok(counts)
             }
-        }
-    }
-        let mut current_date = start_date;
-
-        // Process one day at a time
-        while current_date < end_date {
-            // Calculate the end of this day
-            let day_end =
-                (current_date.date().and_hms_opt(23, 59, 59).unwrap()).with_timezone(&Utc);
-
-            // Convert dates to Timestamp for the gRPC request
-            let from_ts = Timestamp {
-                seconds: current_date.timestamp(),
-                nanos: 0,
-            };
-
-            let to_ts = Timestamp {
-                seconds: day_end.timestamp(),
-                nanos: 0,
-            };
-
-            // Create the request
-            let request = GetCandlesRequest {
-                from: Some(from_ts),
-                to: Some(to_ts),
-                interval: CandleInterval::CandleInterval1Min as i32,
-                instrument_id: figi.to_string(),
-                #[allow(deprecated)]
-                figi: figi.to_string(),
-            };
-
-            // Make the gRPC call
-            match self.client.create_request(request) {
-                Ok(grpc_request) => {
-                    let mut market_data_client = self.client.market_data.clone();
-                    match market_data_client.get_candles(grpc_request).await {
-                        Ok(response) => {
-                            let candles_response = response.into_inner();
-                            let candle_count = candles_response.candles.len();
-
-                            if candle_count > 0 {
-                                // Convert candles to MongoDB documents
-                                let mut documents = Vec::with_capacity(candle_count);
-
-                                for candle in candles_response.candles {
-                                    let doc = self.historic_candle_to_document(figi, candle);
-                                    documents.push(doc);
-                                }
-
-                                // Batch insert the documents
-                                match collection.insert_many(documents).await {
-                                    Ok(result) => {
-                                        info!(
-                                            "Inserted {} historical candles for {} on {}",
-                                            result.inserted_ids.len(),
-                                            figi,
-                                            current_date.format("%Y-%m-%d")
-                                        );
-                                    }
-                                    Err(e) => {
-                                        error!(
-                                            "Failed to insert historical candles for {}: {}",
-                                            figi, e
-                                        );
-                                    }
-                                }
-                            } else {
-                                info!(
-                                    "No historical candles found for {} on {}",
-                                    figi,
-                                    current_date.format("%Y-%m-%d")
-                                );
-                            }
-                        }
-                        Err(e) => {
-                            error!(
-                                "Failed to get historical candles for {} on {}: {}",
-                                figi,
-                                current_date.format("%Y-%m-%d"),
-                                e
-                            );
-                        }
-                    }
-                }
-                Err(e) => {
-                    error!("Failed to create request for historical candles: {}", e);
-                }
+            Err(e) => {
+                error!("Failed to save historical candles for {}: {}", figi, e);
+                Err(e.to_string())
             }
-
-            // Add a delay to avoid rate limiting
-            tokio::time::sleep(tokio::time::Duration::from_millis(
-                self.settings
-                    .app_config
-                    .historical_candle_data
-                    .request_delay_ms,
-            ))
-            .await;
-
-            // Move to the next day
-            current_date = (current_date + Duration::days(1))
-                .date()
-                .and_hms_opt(0, 0, 0)
-                .unwrap()
-                .with_timezone(&Utc);
         }
     }
 
-    fn historic_candle_to_document(&self, figi: &str, candle: HistoricCandle) -> Document {
+    fn historic_candle_to_document(
+        &self,
+        figi: &str,
+        interval: MyCandleInterval,
+        candle: HistoricCandle,
+    ) -> Document {
         // Extract seconds and nanos from the candle time
         let seconds = candle.time.as_ref().map_or(0, |t| t.seconds);
         let nanos = candle.time.as_ref().map_or(0, |t| t.nanos);
@@ -354,24 +488,13 @@ impl HistoricalCandleDataService {
 
         doc! {
             "figi": figi,
+            "interval": interval.short_name(),
             "volume": candle.volume,
             "display_time": moscow_time_str,
-            "open": {
-                "units": candle.open.as_ref().map_or(0, |q| q.units),
-                "nano": candle.open.as_ref().map_or(0, |q| q.nano)
-            },
-            "high": {
-                "units": candle.high.as_ref().map_or(0, |q| q.units),
-                "nano": candle.high.as_ref().map_or(0, |q| q.nano)
-            },
-            "low": {
-                "units": candle.low.as_ref().map_or(0, |q| q.units),
-                "nano": candle.low.as_ref().map_or(0, |q| q.nano)
-            },
-            "close": {
-                "units": candle.close.as_ref().map_or(0, |q| q.units),
-                "nano": candle.close.as_ref().map_or(0, |q| q.nano)
-            },
+            "open": candle_price_bson(candle.open.as_ref()),
+            "high": candle_price_bson(candle.high.as_ref()),
+            "low": candle_price_bson(candle.low.as_ref()),
+            "close": candle_price_bson(candle.close.as_ref()),
             "time": {
                 "seconds": candle.time.as_ref().map_or(0, |t| t.seconds),
                 "nanos": candle.time.as_ref().map_or(0, |t| t.nanos)
@@ -6,9 +6,18 @@ use serde::{Deserialize, Serialize};
 use tracing::{error, info};
 use futures::TryStreamExt;
 
+use crate::features::core::models::candle_interval::MyCandleInterval;
+
+use super::targets::CandleLoadTarget;
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CandleHistoryStatus {
     pub figi: String,
+    /// Интервал свечей в короткой форме (1m, 1d ...); статус хранится по паре FIGI + интервал
+    #[serde(default)]
+    pub interval: String,
+    #[serde(default)]
+    pub instrument_type: String,
     pub first_candle_date_seconds: i64,
     pub last_candle_date_seconds: i64,
     pub first_candle_date_moscow: String,
@@ -18,12 +27,18 @@ pub struct CandleHistoryStatus {
 }
 
 impl super::service::HistoricalCandleDataService {
-    /// Updates the candle history status for a specific FIGI
-    pub async fn update_candle_history_status(&self, figi: &str) -> Result<(), Box<dyn std::error::Error>> {
-        info!("Updating candle history status for {}", figi);
+    /// Updates the candle history status for a specific FIGI and interval
+    pub async fn update_candle_history_status(
+        &self,
+        figi: &str,
+        target: &CandleLoadTarget,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        info!("Updating candle history status for {} ({})", figi, target.label());
         
         // Get the historical collection
-        let historical_collection = self.get_historical_collection();
+        let historical_collection = self
+            .mongo_db
+            .historical_candles_collection(target.kind, target.interval);
         let status_collection = self.get_status_collection();
         
         // Find the min and max dates for this FIGI
@@ -130,6 +145,8 @@ impl super::service::HistoricalCandleDataService {
             // Create status document
             let status = CandleHistoryStatus {
                 figi: figi.to_string(),
+                interval: target.interval.short_name().to_string(),
+                instrument_type: target.kind.as_str().to_string(),
                 first_candle_date_seconds,
                 last_candle_date_seconds,
                 first_candle_date_moscow: first_moscow_str.clone(),
@@ -145,26 +162,33 @@ impl super::service::HistoricalCandleDataService {
             let options = mongodb::options::UpdateOptions::builder().upsert(true).build();
             status_collection
                 .update_one(
-                    doc! { "figi": figi },
+                    doc! { "figi": figi, "interval": target.interval.short_name() },
                     doc! { "$set": status_doc },
                  
                 ).with_options(   options)
                 .await?;
             
             info!(
-                "Updated candle history status for {}: {} candles from {} to {}",
-                figi, candle_count, first_moscow_str, last_moscow_str
+                "Updated candle history status for {} ({}): {} candles from {} to {}",
+                figi, target.label(), candle_count, first_moscow_str, last_moscow_str
             );
         }
         
         Ok(())
     }
     
-    /// Get the existing candle history status for a FIGI
-    pub async fn get_candle_history_status(&self, figi: &str) -> Option<CandleHistoryStatus> {
+    /// Get the existing candle history status for a FIGI and interval
+    pub async fn get_candle_history_status(
+        &self,
+        figi: &str,
+        interval: MyCandleInterval,
+    ) -> Option<CandleHistoryStatus> {
         let status_collection = self.get_status_collection();
         
-        match status_collection.find_one(doc! { "figi": figi }).await {
+        match status_collection
+            .find_one(doc! { "figi": figi, "interval": interval.short_name() })
+            .await
+        {
             Ok(Some(doc)) => {
                 match mongodb::bson::from_document::<CandleHistoryStatus>(doc) {
                     Ok(status) => Some(status),
@@ -182,72 +206,7 @@ impl super::service::HistoricalCandleDataService {
         }
     }
     
-    /// Check if we need to fetch historical data for a specific FIGI and date range
-    pub async fn check_historical_data_needed(
-        &self,
-        figi: &str,
-        start_date: chrono::DateTime<Utc>,
-        end_date: chrono::DateTime<Utc>,
-    ) -> bool {
-    if let Some(status) = self.get_candle_history_status(figi).await {
-        // For historical data that never changes at the beginning:
-        // If we already have data starting from or before the requested start date,
-        // we only need to fetch if the end date is beyond what we have
-
-        // Check if we already have data that covers the start date
-        let have_start_covered = status.first_candle_date_seconds <= start_date.timestamp();
-        
-        // Check if we need to extend to a later end date
-        let need_to_extend_end = end_date.timestamp() > status.last_candle_date_seconds;
-        
-        if have_start_covered && !need_to_extend_end {
-            // We already have all the data we need for this range
-            info!(
-                "Skipping historical data fetch for {} - already have data from {} to {} which covers requested period ({} to {})",
-                figi, 
-                status.first_candle_date_moscow, 
-                status.last_candle_date_moscow,
-                start_date.format("%Y-%m-%d %H:%M:%S"),
-                end_date.format("%Y-%m-%d %H:%M:%S")
-            );
-            return false;
-        }
-        
-        // Log the reason we're fetching
-        if !have_start_covered {
-            info!(
-                "Need to fetch earlier data for {}: current earliest is {}, requested {}",
-                figi,
-                status.first_candle_date_moscow,
-                start_date.format("%Y-%m-%d %H:%M:%S")
-            );
-        }
-        
-        if need_to_extend_end {
-            info!(
-                "Need to fetch more recent data for {}: current latest is {}, requested {}",
-                figi,
-                status.last_candle_date_moscow,
-                end_date.format("%Y-%m-%d %H:%M:%S")
-            );
-        }
-        
-        return !have_start_covered || need_to_extend_end;
-    }
-    
-    // No status found, so we need to fetch data
-    info!(
-        "No existing history status for {}, need to fetch full range",
-        figi
-    );
-        true
-    }
-    
     // Helper functions to avoid direct access to private fields
-    fn get_historical_collection(&self) -> mongodb::Collection<Document> {
-        self.mongo_db.get_historical_collection()
-    }
-    
     fn get_status_collection(&self) -> mongodb::Collection<Document> {
         self.mongo_db.market_candles_status_collection()
     }
@@ -0,0 +1,127 @@
+// src/features/market_candles/tinkoff_shares_1m_historical/targets.rs
+
+use tracing::{error, info};
+
+use crate::env_config::models::app_config::{HistoricalCandleScope, HistoricalCandleTarget};
+use crate::features::core::models::{candle_interval::MyCandleInterval, instrument::InstrumentKind};
+
+use super::service::HistoricalCandleDataService;
+
+/// Набор свечей для загрузки: тип инструмента, интервал и охват
+#[derive(Debug, Clone, Copy)]
+pub struct CandleLoadTarget {
+    pub kind: InstrumentKind,
+    pub interval: MyCandleInterval,
+    pub scope: HistoricalCandleScope,
+    pub max_days_history: u32,
+}
+
+impl CandleLoadTarget {
+    /// Разбирает набор из конфигурации; глубина истории по умолчанию берётся из max_days_history
+    pub fn from_config(
+        target: &HistoricalCandleTarget,
+        default_days_history: u32,
+    ) -> Result<Self, String> {
+        let kind = InstrumentKind::parse(&target.instrument_type)
+            .ok_or_else(|| format!("unknown instrument type '{}'", target.instrument_type))?;
+        // Загрузка и чекпоинты ведутся по FIGI, а у опционов его нет
+        if kind == InstrumentKind::Option {
+            return Err("historical candles are not supported for options".to_string());
+        }
+        let interval = MyCandleInterval::parse(&target.interval)
+            .ok_or_else(|| format!("unknown candle interval '{}'", target.interval))?;
+
+        Ok(Self {
+            kind,
+            interval,
+            scope: target.scope,
+            max_days_history: target.max_days_history.unwrap_or(default_days_history),
+        })
+    }
+
+    /// Короткая подпись для логов, например `share/1m`
+    pub fn label(&self) -> String {
+        format!("{}/{}", self.kind.as_str(), self.interval.short_name())
+    }
+}
+
+impl HistoricalCandleDataService {
+    /// Наборы из конфигурации; некорректные записи пропускаются с ошибкой в логе
+    pub(crate) fn load_targets(&self) -> Vec<CandleLoadTarget> {
+        let config = &self.settings.app_config.historical_candle_data;
+
+        config
+            .targets
+            .iter()
+            .filter_map(|target| {
+                match CandleLoadTarget::from_config(target, config.max_days_history) {
+                    Ok(target) => Some(target),
+                    Err(e) => {
+                        error!("Skipping historical candle target {:?}: {}", target, e);
+                        None
+                    }
+                }
+            })
+            .collect()
+    }
+
+    /// FIGI, по которым нужно загружать свечи для набора
+    pub(crate) async fn target_figis(&self, target: &CandleLoadTarget) -> Vec<String> {
+        match target.scope {
+            HistoricalCandleScope::All => self.mongo_db.get_unique_figis(target.kind).await,
+            HistoricalCandleScope::Watchlist => {
+                let mut figis = Vec::new();
+                for watchlist in self.mongo_db.get_watchlists().await {
+                    if !watchlist.enabled {
+                        continue;
+                    }
+                    // Тип инструмента в watchlist не хранится, берём его из каталога
+                    match self.mongo_db.find_instrument_by_figi(&watchlist.figi).await {
+                        Some(instrument) if instrument.kind() == target.kind => {
+                            figis.push(watchlist.figi)
+                        }
+                        Some(_) => {}
+                        None => info!(
+                            "Watchlist FIGI {} not found in instrument catalogue",
+                            watchlist.figi
+                        ),
+                    }
+                }
+                figis
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_target_from_config() {
+        let config = HistoricalCandleTarget {
+            instrument_type: "bonds".to_string(),
+            interval: "1d".to_string(),
+            scope: HistoricalCandleScope::Watchlist,
+            max_days_history: None,
+        };
+        let target = CandleLoadTarget::from_config(&config, 30).unwrap();
+        assert_eq!(target.kind, InstrumentKind::Bond);
+        assert_eq!(target.interval, MyCandleInterval::Day);
+        assert_eq!(target.max_days_history, 30);
+        assert_eq!(target.label(), "bond/1d");
+
+        let config = HistoricalCandleTarget {
+            interval: "7m".to_string(),
+            ..config
+        };
+        assert!(CandleLoadTarget::from_config(&config, 30).is_err());
+
+        let config = HistoricalCandleTarget {
+            instrument_type: "option".to_string(),
+            interval: "1d".to_string(),
+            ..config
+        };
+        assert!(CandleLoadTarget::from_config(&config, 30).is_err());
+    }
+}
@@ -2,14 +2,22 @@
 use std::sync::Arc;
 use tokio::time;
 use std::time::Duration;
-use chrono::Timelike;
-use tracing::{error, info};
+use chrono::{DateTime, NaiveDate, Utc};
+use tracing::info;
 
 use super::service::HistoricalCandleDataService;
 use crate::env_config::models::app_setting::AppSettings;
 use crate::features::db::MongoDb;
 use crate::services::tinkoff::client_grpc::TinkoffClient;
 
+enum SessionCheck {
+    /// Торги за день закончились, можно загружать
+    Closed,
+    /// Сессия ещё идёт (вечерняя сессия после 00:00 UTC)
+    Open,
+    NotTradingDay,
+}
+
 pub struct HistoricalCandleUpdater {
     service: Arc<HistoricalCandleDataService>,
     settings: Arc<AppSettings>,
@@ -42,10 +50,8 @@ impl HistoricalCandleUpdater {
         }
         
         info!(
-            "Starting historical candle update service (scheduled from {} to {}, timezone: {})",
-            self.settings.app_config.historical_candle_updater.update_start_time,
-            self.settings.app_config.historical_candle_updater.update_end_time,
-            self.settings.app_config.historical_candle_updater.timezone
+            "Starting historical candle update service (once a day after the {} trading session closes)",
+            self.service.calendar.default_exchange()
         );
         
         // Run immediately on startup if configured
@@ -54,102 +60,53 @@ impl HistoricalCandleUpdater {
             self.update_historical_candles().await;
         }
         
-        // Проверка каждую минуту, не пора ли запускать обновление
+        // Загрузчик берёт только завершённые дни (до 00:00 UTC): торговый день
+        // загружается один раз, когда он закончился и по расписанию закрылась его
+        // сессия. Вчерашний день на старте считаем уже обработанным — его загружает
+        // run_on_startup или загрузил предыдущий запуск
         let mut interval = time::interval(Duration::from_secs(60));
+        let mut last_handled_day = Utc::now().date_naive() - chrono::Duration::days(1);
         
         loop {
             interval.tick().await;
             
-            // Проверяем, находимся ли мы в начале временного окна для запуска
-            let should_start = self.should_start_update();
+            let now = Utc::now();
+            let day = now.date_naive() - chrono::Duration::days(1);
+            if day <= last_handled_day {
+                continue;
+            }
             
-            if should_start {
-                info!("Starting scheduled historical candle update");
-                self.update_historical_candles().await;
+            match self.check_session_closed(day, now).await {
+                SessionCheck::Closed => {
+                    last_handled_day = day;
+                    info!("Starting scheduled historical candle update for {}", day);
+                    self.update_historical_candles().await;
+                }
+                SessionCheck::NotTradingDay => last_handled_day = day,
+                SessionCheck::Open => {}
             }
         }
     }
     
-    // Проверяет, нужно ли начинать обновление прямо сейчас
-    fn should_start_update(&self) -> bool {
-        let update_config = &self.settings.app_config.historical_candle_updater;
-        
-        // Парсим временную зону и текущее время
-        let timezone: chrono_tz::Tz = update_config.timezone.parse().expect("Invalid timezone");
-        let current_time = chrono::Utc::now().with_timezone(&timezone);
-        
-        // Парсим время начала и конца обновления
-        let start_parts: Vec<&str> = update_config.update_start_time.split(':').collect();
-        let end_parts: Vec<&str> = update_config.update_end_time.split(':').collect();
-        
-        if start_parts.len() != 2 || end_parts.len() != 2 {
-            error!("Invalid time format in configuration");
-            return false;
-        }
-        
-        let start_hour: u32 = match start_parts[0].parse() {
-            Ok(h) => h,
-            Err(e) => {
-                error!("Failed to parse start hour: {}", e);
-                return false;
-            }
-        };
-        
-        let start_minute: u32 = match start_parts[1].parse() {
-            Ok(m) => m,
-            Err(e) => {
-                error!("Failed to parse start minute: {}", e);
-                return false;
-            }
-        };
-        
-        let end_hour: u32 = match end_parts[0].parse() {
-            Ok(h) => h,
-            Err(e) => {
-                error!("Failed to parse end hour: {}", e);
-                return false;
-            }
-        };
-        
-        let end_minute: u32 = match end_parts[1].parse() {
-            Ok(m) => m,
-            Err(e) => {
-                error!("Failed to parse end minute: {}", e);
-                return false;
-            }
-        };
+    // Обновлять есть смысл, только если день был торговым и его сессия закрылась
+    async fn check_session_closed(&self, day: NaiveDate, now: DateTime<Utc>) -> SessionCheck {
+        let calendar = &self.service.calendar;
+        calendar.ensure_range(day, day + chrono::Duration::days(1)).await;
         
-        // Преобразуем все времена в минуты для простоты сравнения
-        let current_time_minutes = current_time.hour() * 60 + current_time.minute();
-        let start_time_minutes = start_hour * 60 + start_minute;
-        let end_time_minutes = end_hour * 60 + end_minute;
-        
-        // Проверка, находится ли текущее время в диапазоне обновления
-        let in_update_window = if start_time_minutes <= end_time_minutes {
-            // Обычный случай: 00:00 - 05:00
-            current_time_minutes >= start_time_minutes && current_time_minutes <= end_time_minutes
-        } else {
-            // Случай перехода через полночь: 22:00 - 03:00
-            current_time_minutes >= start_time_minutes || current_time_minutes <= end_time_minutes
-        };
-        
-        // Проверяем, прошла ли минута с последнего запуска
-        // Можно добавить состояние для отслеживания последнего запуска
-        if in_update_window {
-            // Добавьте дополнительную логику здесь, чтобы не запускать слишком часто
-            // Например, сохраняйте время последнего запуска и проверяйте, прошло ли хотя бы 60 минут
-            
+        let exchange = calendar.default_exchange();
+        if !calendar.is_trading_day(exchange, day) {
             info!(
-                "Current time ({:02}:{:02}) is within update window ({:02}:{:02} - {:02}:{:02})",
-                current_time.hour(), current_time.minute(),
-                start_hour, start_minute,
-                end_hour, end_minute
+                "Skipping historical candle update: {} was not a trading day on {}",
+                day, exchange
             );
-            
-            return true;
+            return SessionCheck::NotTradingDay;
         }
         
-        false
+        // Без расписания сессия считается закрытой к концу дня
+        match calendar.session_end(exchange, day) {
+            Some(session_end) if now < session_end => SessionCheck::Open,
+            _ => SessionCheck::Closed,
+        }
     }
     
     async fn update_historical_candles(&self) {
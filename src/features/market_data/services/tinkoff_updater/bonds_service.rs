@@ -1,9 +1,8 @@
-use mongodb::bson::doc;
 use tracing::{error, info};
 
 
 
-use crate::features::db::mongo_db::Collections;
+use crate::features::{core::models::instrument::InstrumentKind, db::mongo_db::Collections};
 
 use super::TinkoffInstrumentsUpdater;
 
@@ -14,16 +13,6 @@ impl TinkoffInstrumentsUpdater {
         let total_bonds = bonds_response.instruments.len();
         info!("Starting bonds update: total {} records", total_bonds);
 
-        // Get collections
-        let collection = self.mongo_db.bonds_collection();
-
-        // Set status to updating
-        self.set_status_updating(Collections::TINKOFF_BONDS).await?;
-
-        // Clear existing data
-        collection.delete_many(doc! {}).await?;
-        info!("Previous records deleted from bonds collection");
-
         // Create documents for batch insertion
         let mut documents = Vec::with_capacity(total_bonds);
 
@@ -42,14 +31,23 @@ impl TinkoffInstrumentsUpdater {
             return Err("No valid bond documents to insert".into());
         }
 
-        // Batch insert documents
-        collection.insert_many(documents).await?;
+        // Set status to updating
+        self.set_status_updating(Collections::TINKOFF_BONDS).await?;
+
+        // Diff by uid into a staging collection, then swap it in atomically
+        let mut stats = self
+            .mongo_db
+            .sync_instruments(InstrumentKind::Bond, documents)
+            .await?;
+        self.record_changes(std::mem::take(&mut stats.changes)).await;
 
         // Update status to ready
         self.set_status_ready(Collections::TINKOFF_BONDS).await?;
         info!(
-            "Update completed: {} bond records successfully processed",
-            total_bonds
+            "Update completed: {} bond records successfully processed, {} in catalogue ({} delisted)",
+            total_bonds,
+            stats.total,
+            stats.delisted
         );
 
         Ok(())
@@ -0,0 +1,38 @@
+use tracing::{error, info};
+
+use crate::features::db::mongo_extensions::instrument_changelog::models::{
+    DbInstrumentChange, InstrumentChangeKind,
+};
+
+use super::TinkoffInstrumentsUpdater;
+
+impl TinkoffInstrumentsUpdater {
+    /// Записывает изменения каталога в журнал без полей из `ignored_fields`.
+    /// Ошибка записи журнала не отменяет уже выполненную синхронизацию
+    pub(super) async fn record_changes(&self, changes: Vec<DbInstrumentChange>) {
+        let config = &self.settings.app_config.instrument_changelog;
+        if !config.enabled {
+            return;
+        }
+
+        let changes: Vec<DbInstrumentChange> = changes
+            .into_iter()
+            .filter_map(|mut change| {
+                change
+                    .fields
+                    .retain(|field| !config.ignored_fields.contains(&field.field));
+                let empty_update =
+                    change.change == InstrumentChangeKind::Updated && change.fields.is_empty();
+                (!empty_update).then_some(change)
+            })
+            .collect();
+        if changes.is_empty() {
+            return;
+        }
+
+        match self.mongo_db.insert_instrument_changes(&changes).await {
+            Ok(()) => info!("Recorded {} instrument changes", changes.len()),
+            Err(e) => error!("Failed to record instrument changes: {}", e),
+        }
+    }
+}
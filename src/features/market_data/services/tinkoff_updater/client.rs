@@ -1,5 +1,6 @@
 use crate::gen::tinkoff_public_invest_api_contract_v1::{
-    BondsResponse, EtfsResponse, FuturesResponse, InstrumentStatus, InstrumentsRequest, SharesResponse
+    BondsResponse, CurrenciesResponse, EtfsResponse, FuturesResponse, InstrumentStatus,
+    InstrumentsRequest, OptionsResponse, SharesResponse,
 };
 use tracing::info;
 
@@ -75,4 +76,39 @@ impl TinkoffInstrumentsUpdater {
     
         response
     }
+
+    /// Опционы всех базовых активов одним запросом (InstrumentsService.Options).
+    /// Метод помечен устаревшим в пользу OptionsBy, но тот требует uid базового актива
+    /// и полный справочник им не получить. В отличие от остальных справочников ошибка
+    /// не роняет цикл обновления
+    #[allow(deprecated)]
+    pub(super) async fn fetch_options(&self) -> Result<OptionsResponse, tonic::Status> {
+        let request = self
+            .client
+            .create_request(InstrumentsRequest {
+                instrument_status: InstrumentStatus::All as i32,
+            })
+            .map_err(|e| tonic::Status::internal(e.to_string()))?;
+
+        let mut instruments_client = self.client.instruments.clone();
+        instruments_client
+            .options(request)
+            .await
+            .map(|response| response.into_inner())
+    }
+
+    pub(super) async fn fetch_currencies(&self) -> Result<CurrenciesResponse, tonic::Status> {
+        let request = self
+            .client
+            .create_request(InstrumentsRequest {
+                instrument_status: InstrumentStatus::All as i32,
+            })
+            .map_err(|e| tonic::Status::internal(e.to_string()))?;
+
+        let mut instruments_client = self.client.instruments.clone();
+        instruments_client
+            .currencies(request)
+            .await
+            .map(|response| response.into_inner())
+    }
 }
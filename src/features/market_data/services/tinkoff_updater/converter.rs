@@ -1,6 +1,6 @@
 use crate::features::core::models::{
-    bond::TinkoffBondModel, etf::TinkoffEtfModel, future::TinkoffFutureModel,
-    share::TinkoffShareModel,
+    bond::TinkoffBondModel, currency::TinkoffCurrencyModel, etf::TinkoffEtfModel,
+    future::TinkoffFutureModel, option::TinkoffOptionModel, share::TinkoffShareModel,
 };
 use mongodb::bson::{doc, Document};
 use tracing::error;
@@ -88,4 +88,48 @@ impl TinkoffInstrumentsUpdater {
             }
         }
     }
+
+    pub(super) fn convert_option_to_document(
+        &self,
+        option: &crate::gen::tinkoff_public_invest_api_contract_v1::Option,
+    ) -> Document {
+        // Convert to the human-readable model first
+        let human_option = TinkoffOptionModel::from(option);
+
+        // Convert to BSON Document using serde
+        match bson::to_document(&human_option) {
+            Ok(doc) => doc,
+            Err(e) => {
+                error!(
+                    "Failed to convert option {} to document: {}",
+                    option.ticker, e
+                );
+                // Return empty document when conversion fails
+                // This will be skipped during insertion
+                doc! {}
+            }
+        }
+    }
+
+    pub(super) fn convert_currency_to_document(
+        &self,
+        currency: &crate::gen::tinkoff_public_invest_api_contract_v1::Currency,
+    ) -> Document {
+        // Convert to the human-readable model first
+        let human_currency = TinkoffCurrencyModel::from(currency);
+
+        // Convert to BSON Document using serde
+        match bson::to_document(&human_currency) {
+            Ok(doc) => doc,
+            Err(e) => {
+                error!(
+                    "Failed to convert currency {} to document: {}",
+                    currency.ticker, e
+                );
+                // Return empty document when conversion fails
+                // This will be skipped during insertion
+                doc! {}
+            }
+        }
+    }
 }
@@ -0,0 +1,58 @@
+use tracing::{error, info};
+
+use super::TinkoffInstrumentsUpdater;
+use crate::features::{core::models::instrument::InstrumentKind, db::mongo_db::Collections};
+
+impl TinkoffInstrumentsUpdater {
+    pub(super) async fn update_currencies(&self) -> Result<(), Box<dyn std::error::Error>> {
+        // Fetch currencies data via gRPC
+        let currencies_response = self.fetch_currencies().await?;
+        let total_currencies = currencies_response.instruments.len();
+        info!(
+            "Starting currencies update: total {} records",
+            total_currencies
+        );
+
+        // Create documents for batch insertion
+        let mut documents = Vec::with_capacity(total_currencies);
+
+        // Convert each currency to MongoDB document
+        for currency in &currencies_response.instruments {
+            let doc = self.convert_currency_to_document(currency);
+            // Only add non-empty documents
+            if !doc.is_empty() {
+                documents.push(doc);
+            }
+        }
+
+        // Skip insertion if all documents failed to convert
+        if documents.is_empty() {
+            error!("Failed to convert any currencies to documents, skipping database update");
+            return Err("No valid currency documents to insert".into());
+        }
+
+        // Set status to updating
+        self.set_status_updating(Collections::TINKOFF_CURRENCIES)
+            .await?;
+
+        // Diff by uid into a staging collection, then swap it in atomically
+        let mut stats = self
+            .mongo_db
+            .sync_instruments(InstrumentKind::Currency, documents)
+            .await?;
+        self.record_changes(std::mem::take(&mut stats.changes))
+            .await;
+
+        // Update status to ready
+        self.set_status_ready(Collections::TINKOFF_CURRENCIES)
+            .await?;
+        info!(
+            "Update completed: {} currency records successfully processed, {} in catalogue ({} delisted)",
+            total_currencies,
+            stats.total,
+            stats.delisted
+        );
+
+        Ok(())
+    }
+}
@@ -1,8 +1,7 @@
-use mongodb::bson::doc;
 use tracing::{error, info};
 
 use super::TinkoffInstrumentsUpdater;
-use crate::features::db::mongo_db::Collections;
+use crate::features::{core::models::instrument::InstrumentKind, db::mongo_db::Collections};
 
 
 impl TinkoffInstrumentsUpdater {
@@ -12,16 +11,6 @@ impl TinkoffInstrumentsUpdater {
         let total_etfs = etfs_response.instruments.len();
         info!("Starting ETFs update: total {} records", total_etfs);
 
-        // Get collections
-        let collection = self.mongo_db.etfs_collection();
-
-        // Set status to updating
-        self.set_status_updating(Collections::TINKOFF_ETFS).await?;
-
-        // Clear existing data
-        collection.delete_many(doc! {}).await?;
-        info!("Previous records deleted from ETFs collection");
-
         // Create documents for batch insertion
         let mut documents = Vec::with_capacity(total_etfs);
 
@@ -40,14 +29,23 @@ impl TinkoffInstrumentsUpdater {
             return Err("No valid ETF documents to insert".into());
         }
 
-        // Batch insert documents
-        collection.insert_many(documents).await?;
+        // Set status to updating
+        self.set_status_updating(Collections::TINKOFF_ETFS).await?;
+
+        // Diff by uid into a staging collection, then swap it in atomically
+        let mut stats = self
+            .mongo_db
+            .sync_instruments(InstrumentKind::Etf, documents)
+            .await?;
+        self.record_changes(std::mem::take(&mut stats.changes)).await;
 
         // Update status to ready
         self.set_status_ready(Collections::TINKOFF_ETFS).await?;
         info!(
-            "Update completed: {} ETF records successfully processed",
-            total_etfs
+            "Update completed: {} ETF records successfully processed, {} in catalogue ({} delisted)",
+            total_etfs,
+            stats.total,
+            stats.delisted
         );
 
         Ok(())
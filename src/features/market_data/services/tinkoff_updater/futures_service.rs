@@ -1,7 +1,6 @@
-use mongodb::bson::doc;
 use tracing::{error, info};
 
-use crate::features::db::mongo_db::Collections;
+use crate::features::{core::models::instrument::InstrumentKind, db::mongo_db::Collections};
 
 use super::TinkoffInstrumentsUpdater;
 
@@ -12,17 +11,6 @@ impl TinkoffInstrumentsUpdater {
         let total_futures = futures_response.instruments.len();
         info!("Starting futures update: total {} records", total_futures);
 
-        // Get collections
-        let collection = self.mongo_db.futures_collection();
-
-        // Set status to updating
-        self.set_status_updating(Collections::TINKOFF_FUTURES)
-            .await?;
-
-        // Clear existing data
-        collection.delete_many(doc! {}).await?;
-        info!("Previous records deleted from futures collection");
-
         // Create documents for batch insertion
         let mut documents = Vec::with_capacity(total_futures);
 
@@ -41,14 +29,23 @@ impl TinkoffInstrumentsUpdater {
             return Err("No valid future documents to insert".into());
         }
 
-        // Batch insert documents
-        collection.insert_many(documents).await?;
+        // Set status to updating
+        self.set_status_updating(Collections::TINKOFF_FUTURES).await?;
+
+        // Diff by uid into a staging collection, then swap it in atomically
+        let mut stats = self
+            .mongo_db
+            .sync_instruments(InstrumentKind::Future, documents)
+            .await?;
+        self.record_changes(std::mem::take(&mut stats.changes)).await;
 
         // Update status to ready
         self.set_status_ready(Collections::TINKOFF_FUTURES).await?;
         info!(
-            "Update completed: {} futures records successfully processed",
-            total_futures
+            "Update completed: {} futures records successfully processed, {} in catalogue ({} delisted)",
+            total_futures,
+            stats.total,
+            stats.delisted
         );
 
         Ok(())
@@ -1,8 +1,11 @@
 mod bonds_service;
+mod changelog;
 mod client;
 mod converter;
+mod currencies_service;
 mod etfs_service;
 mod futures_service;
+mod options_service;
 mod scheduler;
 mod shares_service;
 mod status;
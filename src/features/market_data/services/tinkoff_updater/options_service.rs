@@ -0,0 +1,54 @@
+use tracing::{error, info};
+
+use super::TinkoffInstrumentsUpdater;
+use crate::features::{core::models::instrument::InstrumentKind, db::mongo_db::Collections};
+
+impl TinkoffInstrumentsUpdater {
+    pub(super) async fn update_options(&self) -> Result<(), Box<dyn std::error::Error>> {
+        // Fetch options data via gRPC
+        let options_response = self.fetch_options().await?;
+        let total_options = options_response.instruments.len();
+        info!("Starting options update: total {} records", total_options);
+
+        // Create documents for batch insertion
+        let mut documents = Vec::with_capacity(total_options);
+
+        // Convert each option to MongoDB document
+        for option in &options_response.instruments {
+            let doc = self.convert_option_to_document(option);
+            // Only add non-empty documents
+            if !doc.is_empty() {
+                documents.push(doc);
+            }
+        }
+
+        // Skip insertion if all documents failed to convert
+        if documents.is_empty() {
+            error!("Failed to convert any options to documents, skipping database update");
+            return Err("No valid option documents to insert".into());
+        }
+
+        // Set status to updating
+        self.set_status_updating(Collections::TINKOFF_OPTIONS)
+            .await?;
+
+        // Diff by uid into a staging collection, then swap it in atomically
+        let mut stats = self
+            .mongo_db
+            .sync_instruments(InstrumentKind::Option, documents)
+            .await?;
+        self.record_changes(std::mem::take(&mut stats.changes))
+            .await;
+
+        // Update status to ready
+        self.set_status_ready(Collections::TINKOFF_OPTIONS).await?;
+        info!(
+            "Update completed: {} option records successfully processed, {} in catalogue ({} delisted)",
+            total_options,
+            stats.total,
+            stats.delisted
+        );
+
+        Ok(())
+    }
+}
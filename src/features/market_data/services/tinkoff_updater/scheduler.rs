@@ -74,6 +74,16 @@ impl TinkoffInstrumentsUpdater {
                 Ok(_) => info!("Successfully updated futures data"),
                 Err(e) => error!("Failed to update futures: {}", e),
             }
+            // Update options
+            match self.update_options().await {
+                Ok(_) => info!("Successfully updated options data"),
+                Err(e) => error!("Failed to update options: {}", e),
+            }
+            // Update currencies
+            match self.update_currencies().await {
+                Ok(_) => info!("Successfully updated currencies data"),
+                Err(e) => error!("Failed to update currencies: {}", e),
+            }
         }
     }
 }
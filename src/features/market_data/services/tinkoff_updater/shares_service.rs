@@ -1,7 +1,6 @@
-use mongodb::bson::doc;
 use tracing::{error, info};
 
-use crate::features::db::mongo_db::Collections;
+use crate::features::{core::models::instrument::InstrumentKind, db::mongo_db::Collections};
 
 use super::TinkoffInstrumentsUpdater;
 
@@ -12,17 +11,6 @@ impl TinkoffInstrumentsUpdater {
         let total_shares = shares_response.instruments.len();
         info!("Starting shares update: total {} records", total_shares);
 
-        // Get collections
-        let collection = self.mongo_db.shares_collection();
-
-        // Set status to updating
-        self.set_status_updating(Collections::TINKOFF_SHARES)
-            .await?;
-
-        // Clear existing data
-        collection.delete_many(doc! {}).await?;
-        info!("Previous records deleted from shares collection");
-
         // Create documents for batch insertion
         let mut documents = Vec::with_capacity(total_shares);
 
@@ -41,14 +29,23 @@ impl TinkoffInstrumentsUpdater {
             return Err("No valid share documents to insert".into());
         }
 
-        // Batch insert documents
-        collection.insert_many(documents).await?;
+        // Set status to updating
+        self.set_status_updating(Collections::TINKOFF_SHARES).await?;
+
+        // Diff by uid into a staging collection, then swap it in atomically
+        let mut stats = self
+            .mongo_db
+            .sync_instruments(InstrumentKind::Share, documents)
+            .await?;
+        self.record_changes(std::mem::take(&mut stats.changes)).await;
 
         // Update status to ready
         self.set_status_ready(Collections::TINKOFF_SHARES).await?;
         info!(
-            "Update completed: {} share records successfully processed",
-            total_shares
+            "Update completed: {} share records successfully processed, {} in catalogue ({} delisted)",
+            total_shares,
+            stats.total,
+            stats.delisted
         );
 
         Ok(())
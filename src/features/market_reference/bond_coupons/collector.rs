@@ -0,0 +1,204 @@
+// src/features/market_reference/bond_coupons/collector.rs
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, Utc};
+use prost_types::Timestamp;
+use tonic::Status;
+use tracing::{error, info, warn};
+
+use crate::env_config::models::app_setting::AppSettings;
+use crate::features::core::models::{
+    bond::TinkoffBondModel,
+    instrument::{InstrumentKind, TinkoffInstrumentEnum},
+};
+use crate::features::db::MongoDb;
+use crate::gen::tinkoff_public_invest_api_contract_v1::{
+    AccruedInterest, Coupon, GetAccruedInterestsRequest, GetBondCouponsRequest,
+};
+use crate::services::tinkoff::{client_grpc::TinkoffClient, rate_limiter::TinkoffMethod};
+
+use super::mappers::{map_accrued_interest, map_coupon};
+
+/// Горизонт купонного календаря для облигаций без даты погашения (бессрочные)
+const PERPETUAL_HORIZON_DAYS: i64 = 365 * 30;
+
+fn timestamp(at: DateTime<Utc>) -> Timestamp {
+    Timestamp {
+        seconds: at.timestamp(),
+        nanos: 0,
+    }
+}
+
+/// Обходит облигации каталога: купонный календарь из InstrumentsService.GetBondCoupons
+/// и НКД за последние дни из InstrumentsService.GetAccruedInterests
+pub struct BondCouponCollector {
+    client: Arc<TinkoffClient>,
+    mongo_db: Arc<MongoDb>,
+    settings: Arc<AppSettings>,
+}
+
+impl BondCouponCollector {
+    pub fn new(
+        client: Arc<TinkoffClient>,
+        mongo_db: Arc<MongoDb>,
+        settings: Arc<AppSettings>,
+    ) -> Self {
+        Self {
+            client,
+            mongo_db,
+            settings,
+        }
+    }
+
+    /// Цикл обхода каталога облигаций
+    pub async fn start_update_loop(self) {
+        let config = &self.settings.app_config.bond_coupons;
+        if !config.enabled {
+            info!("Bond coupon collector is disabled in configuration");
+            return;
+        }
+
+        info!(
+            "Starting bond coupon collector with {} second interval",
+            config.interval_seconds
+        );
+
+        let mut interval =
+            tokio::time::interval(tokio::time::Duration::from_secs(config.interval_seconds.max(1)));
+        loop {
+            interval.tick().await;
+            self.collect().await;
+        }
+    }
+
+    /// Один обход всех облигаций каталога
+    pub async fn collect(&self) {
+        let config = &self.settings.app_config.bond_coupons;
+        let now = Utc::now();
+        let history_from = now - Duration::days(config.history_days as i64);
+
+        let mut bonds = 0;
+        let mut coupons = 0;
+        let mut inserted = 0;
+        for figi in self.mongo_db.get_unique_figis(InstrumentKind::Bond).await {
+            let Some(TinkoffInstrumentEnum::Bond(bond)) =
+                self.mongo_db.find_instrument_by_figi(&figi).await
+            else {
+                continue;
+            };
+
+            let maturity = bond
+                .maturity_date
+                .as_ref()
+                .filter(|date| date.seconds > 0)
+                .and_then(|date| DateTime::from_timestamp(date.seconds, 0));
+            // Погашенные до начала окна истории облигации не запрашиваем
+            if maturity.is_some_and(|maturity| maturity < history_from) {
+                continue;
+            }
+            let to = maturity.unwrap_or(now + Duration::days(PERPETUAL_HORIZON_DAYS)) + Duration::days(1);
+            bonds += 1;
+
+            match self.fetch_coupons(&figi, history_from, to).await {
+                Ok(events) => {
+                    let records: Vec<_> = events
+                        .iter()
+                        .filter_map(|coupon| map_coupon(&bond, coupon, now))
+                        .collect();
+                    match self.mongo_db.upsert_bond_coupons(&records).await {
+                        Ok(count) => {
+                            coupons += records.len();
+                            inserted += count;
+                        }
+                        Err(e) => error!("Failed to save coupons for {}: {}", figi, e),
+                    }
+                }
+                Err(e) => warn!("Failed to load coupons for {}: {}", figi, e),
+            }
+
+            self.collect_accrued_interests(&bond, now).await;
+        }
+
+        info!(
+            "Bond coupon collection finished: {} bonds, {} coupons stored ({} new)",
+            bonds, coupons, inserted
+        );
+    }
+
+    async fn collect_accrued_interests(&self, bond: &TinkoffBondModel, now: DateTime<Utc>) {
+        let days = self.settings.app_config.bond_coupons.accrued_interest_days as i64;
+        let interests = match self
+            .fetch_accrued_interests(&bond.figi, now - Duration::days(days), now)
+            .await
+        {
+            Ok(interests) => interests,
+            Err(e) => {
+                warn!("Failed to load accrued interest for {}: {}", bond.figi, e);
+                return;
+            }
+        };
+
+        let records: Vec<_> = interests
+            .iter()
+            .filter_map(|interest| map_accrued_interest(&bond.figi, interest, now))
+            .collect();
+        if let Err(e) = self.mongo_db.upsert_bond_accrued_interests(&records).await {
+            error!("Failed to save accrued interest for {}: {}", bond.figi, e);
+        }
+    }
+
+    async fn fetch_coupons(
+        &self,
+        figi: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<Coupon>, Status> {
+        let request = GetBondCouponsRequest {
+            figi: figi.to_string(),
+            from: Some(timestamp(from)),
+            to: Some(timestamp(to)),
+        };
+
+        let response = self
+            .client
+            .call_limited(TinkoffMethod::GetBondCoupons, || {
+                let mut instruments_client = self.client.instruments.clone();
+                let request = self.client.create_request(request.clone());
+                async move {
+                    let request = request.map_err(|e| Status::internal(e.to_string()))?;
+                    instruments_client.get_bond_coupons(request).await
+                }
+            })
+            .await?;
+
+        Ok(response.events)
+    }
+
+    async fn fetch_accrued_interests(
+        &self,
+        figi: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<AccruedInterest>, Status> {
+        let request = GetAccruedInterestsRequest {
+            figi: figi.to_string(),
+            from: Some(timestamp(from)),
+            to: Some(timestamp(to)),
+        };
+
+        let response = self
+            .client
+            .call_limited(TinkoffMethod::GetAccruedInterests, || {
+                let mut instruments_client = self.client.instruments.clone();
+                let request = self.client.create_request(request.clone());
+                async move {
+                    let request = request.map_err(|e| Status::internal(e.to_string()))?;
+                    instruments_client.get_accrued_interests(request).await
+                }
+            })
+            .await?;
+
+        Ok(response.accrued_interests)
+    }
+}
@@ -0,0 +1,130 @@
+// src/features/market_reference/bond_coupons/mappers.rs
+
+use chrono::{DateTime, Utc};
+use prost_types::Timestamp;
+
+use crate::features::core::models::{
+    bond::TinkoffBondModel, money_value::TinkoffMoneyValueModel, quotation::TinkoffQuotationModel,
+};
+use crate::features::db::mongo_extensions::bond_coupons::models::{
+    DbBondAccruedInterest, DbBondCoupon,
+};
+use crate::gen::tinkoff_public_invest_api_contract_v1::{AccruedInterest, Coupon, CouponType};
+
+/// Дата из Timestamp; нулевой Timestamp в ответе означает, что дата не задана
+fn day(timestamp: &Option<Timestamp>) -> Option<String> {
+    let timestamp = timestamp.as_ref().filter(|ts| ts.seconds > 0)?;
+    DateTime::from_timestamp(timestamp.seconds, 0).map(|dt| dt.format("%Y-%m-%d").to_string())
+}
+
+/// Купон без даты выплаты не сохраняется
+pub fn map_coupon(bond: &TinkoffBondModel, coupon: &Coupon, now: DateTime<Utc>) -> Option<DbBondCoupon> {
+    let coupon_type = CouponType::try_from(coupon.coupon_type).unwrap_or(CouponType::Unspecified);
+    let pay_one_bond = coupon.pay_one_bond.as_ref().map(TinkoffMoneyValueModel::from);
+
+    Some(DbBondCoupon {
+        figi: bond.figi.clone(),
+        ticker: bond.ticker.clone(),
+        coupon_number: coupon.coupon_number,
+        coupon_date: day(&coupon.coupon_date)?,
+        fix_date: day(&coupon.fix_date),
+        coupon_start_date: day(&coupon.coupon_start_date),
+        coupon_end_date: day(&coupon.coupon_end_date),
+        coupon_period: coupon.coupon_period,
+        coupon_type: coupon_type.as_str_name().to_string(),
+        amount_known: pay_one_bond
+            .as_ref()
+            .is_some_and(|amount| !amount.value.is_zero()),
+        pay_one_bond,
+        floating: bond.floating_coupon_flag
+            || matches!(coupon_type, CouponType::Floating | CouponType::Variable),
+        amortization: bond.amortization_flag,
+        updated_at: now.to_rfc3339(),
+    })
+}
+
+pub fn map_accrued_interest(
+    figi: &str,
+    interest: &AccruedInterest,
+    now: DateTime<Utc>,
+) -> Option<DbBondAccruedInterest> {
+    Some(DbBondAccruedInterest {
+        figi: figi.to_string(),
+        date: day(&interest.date)?,
+        value: interest.value.as_ref().map(TinkoffQuotationModel::from),
+        value_percent: interest.value_percent.as_ref().map(TinkoffQuotationModel::from),
+        nominal: interest.nominal.as_ref().map(TinkoffQuotationModel::from),
+        updated_at: now.to_rfc3339(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gen::tinkoff_public_invest_api_contract_v1::{Bond, MoneyValue};
+
+    fn timestamp(seconds: i64) -> Option<Timestamp> {
+        Some(Timestamp { seconds, nanos: 0 })
+    }
+
+    fn bond(floating: bool, amortization: bool) -> TinkoffBondModel {
+        TinkoffBondModel::from(&Bond {
+            figi: "BBG00R05JT04".to_string(),
+            ticker: "RU000A1013N6".to_string(),
+            floating_coupon_flag: floating,
+            amortization_flag: amortization,
+            ..Default::default()
+        })
+    }
+
+    fn coupon(coupon_type: CouponType, units: Option<i64>) -> Coupon {
+        Coupon {
+            coupon_number: 3,
+            // 2025-06-01 и 2025-05-20
+            coupon_date: timestamp(1_748_736_000),
+            fix_date: timestamp(1_747_699_200),
+            coupon_type: coupon_type as i32,
+            pay_one_bond: units.map(|units| MoneyValue {
+                currency: "rub".to_string(),
+                units,
+                nano: 0,
+            }),
+            coupon_period: 91,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_map_coupon() {
+        let now = Utc::now();
+
+        let fixed = map_coupon(&bond(false, false), &coupon(CouponType::Constant, Some(25)), now).unwrap();
+        assert_eq!(fixed.coupon_date, "2025-06-01");
+        assert_eq!(fixed.fix_date.as_deref(), Some("2025-05-20"));
+        assert_eq!(fixed.coupon_start_date, None);
+        assert_eq!(fixed.coupon_type, "COUPON_TYPE_CONSTANT");
+        assert!(fixed.amount_known);
+        assert!(!fixed.floating);
+        assert!(!fixed.amortization);
+
+        // Будущий плавающий купон приходит с нулевой выплатой
+        let floating = map_coupon(&bond(false, false), &coupon(CouponType::Floating, Some(0)), now).unwrap();
+        assert!(floating.floating);
+        assert!(!floating.amount_known);
+
+        let flagged = map_coupon(&bond(true, false), &coupon(CouponType::Constant, None), now).unwrap();
+        assert!(flagged.floating);
+        assert!(!flagged.amount_known);
+        assert!(flagged.pay_one_bond.is_none());
+
+        let amortizing = map_coupon(&bond(false, true), &coupon(CouponType::Constant, Some(12)), now).unwrap();
+        assert!(amortizing.amortization);
+        assert!(amortizing.amount_known);
+
+        let undated = Coupon {
+            coupon_date: None,
+            ..coupon(CouponType::Constant, Some(25))
+        };
+        assert!(map_coupon(&bond(false, false), &undated, now).is_none());
+    }
+}
@@ -0,0 +1,4 @@
+pub mod collector;
+mod mappers;
+
+pub use collector::BondCouponCollector;
@@ -0,0 +1,147 @@
+// src/features/market_reference/dividends/collector.rs
+
+use std::sync::Arc;
+
+use chrono::{Duration, Utc};
+use prost_types::Timestamp;
+use tonic::Status;
+use tracing::{error, info, warn};
+
+use crate::env_config::models::app_setting::AppSettings;
+use crate::features::core::models::instrument::InstrumentKind;
+use crate::features::db::MongoDb;
+use crate::gen::tinkoff_public_invest_api_contract_v1::{Dividend, GetDividendsRequest};
+use crate::services::tinkoff::{client_grpc::TinkoffClient, rate_limiter::TinkoffMethod};
+
+use super::mappers::map_dividend;
+
+/// Типы инструментов, по которым бывают дивиденды
+const DIVIDEND_KINDS: [InstrumentKind; 2] = [InstrumentKind::Share, InstrumentKind::Etf];
+
+/// Обходит акции и ETF каталога и сохраняет прошедшие и объявленные дивиденды
+/// из InstrumentsService.GetDividends
+pub struct DividendCollector {
+    client: Arc<TinkoffClient>,
+    mongo_db: Arc<MongoDb>,
+    settings: Arc<AppSettings>,
+}
+
+impl DividendCollector {
+    pub fn new(
+        client: Arc<TinkoffClient>,
+        mongo_db: Arc<MongoDb>,
+        settings: Arc<AppSettings>,
+    ) -> Self {
+        Self {
+            client,
+            mongo_db,
+            settings,
+        }
+    }
+
+    /// Цикл обхода каталога
+    pub async fn start_update_loop(self) {
+        let config = &self.settings.app_config.dividends;
+        if !config.enabled {
+            info!("Dividend collector is disabled in configuration");
+            return;
+        }
+
+        info!(
+            "Starting dividend collector with {} second interval ({} days back, {} days ahead)",
+            config.interval_seconds, config.history_days, config.days_ahead
+        );
+
+        let mut interval =
+            tokio::time::interval(tokio::time::Duration::from_secs(config.interval_seconds.max(1)));
+        loop {
+            interval.tick().await;
+            self.collect().await;
+        }
+    }
+
+    /// Один обход всех акций и ETF каталога
+    pub async fn collect(&self) {
+        let config = &self.settings.app_config.dividends;
+        let now = Utc::now();
+        let from = now - Duration::days(config.history_days as i64);
+        let to = now + Duration::days(config.days_ahead as i64);
+
+        let mut instruments = 0;
+        let mut stored = 0;
+        let mut inserted = 0;
+        for kind in DIVIDEND_KINDS {
+            for figi in self.mongo_db.get_unique_figis(kind).await {
+                let ticker = self
+                    .mongo_db
+                    .find_instrument_by_figi(&figi)
+                    .await
+                    .map(|instrument| instrument.ticker().to_string())
+                    .unwrap_or_default();
+
+                let dividends = match self.fetch(&figi, from.timestamp(), to.timestamp()).await {
+                    Ok(dividends) => dividends,
+                    Err(e) => {
+                        warn!("Failed to load dividends for {}: {}", figi, e);
+                        continue;
+                    }
+                };
+                instruments += 1;
+
+                let records: Vec<_> = dividends
+                    .iter()
+                    .filter_map(|dividend| map_dividend(&figi, kind.as_str(), &ticker, dividend, now))
+                    .collect();
+                if records.is_empty() {
+                    continue;
+                }
+
+                match self.mongo_db.upsert_dividends(&records).await {
+                    Ok(count) => {
+                        stored += records.len();
+                        inserted += count;
+                    }
+                    Err(e) => error!("Failed to save dividends for {}: {}", figi, e),
+                }
+            }
+        }
+
+        info!(
+            "Dividend collection finished: {} instruments, {} dividends stored ({} new)",
+            instruments, stored, inserted
+        );
+    }
+
+    async fn fetch(
+        &self,
+        figi: &str,
+        from_seconds: i64,
+        to_seconds: i64,
+    ) -> Result<Vec<Dividend>, Status> {
+        let request = GetDividendsRequest {
+            figi: figi.to_string(),
+            from: Some(Timestamp {
+                seconds: from_seconds,
+                nanos: 0,
+            }),
+            to: Some(Timestamp {
+                seconds: to_seconds,
+                nanos: 0,
+            }),
+        };
+
+        let response = self
+            .client
+            .call_limited(TinkoffMethod::GetDividends, || {
+                let mut instruments_client = self.client.instruments.clone();
+                let request = self.client.create_request(request.clone());
+                async move {
+                    let request = request.map_err(|e| Status::internal(e.to_string()))?;
+                    instruments_client.get_dividends(request).await
+                }
+            })
+            .await?;
+
+        Ok(response.dividends)
+    }
+}
@@ -0,0 +1,46 @@
+// src/features/market_reference/dividends/mappers.rs
+
+use chrono::{DateTime, Utc};
+use prost_types::Timestamp;
+
+use crate::features::core::models::{
+    money_value::TinkoffMoneyValueModel, quotation::TinkoffQuotationModel,
+};
+use crate::features::db::mongo_extensions::dividends::models::DbDividend;
+use crate::gen::tinkoff_public_invest_api_contract_v1::Dividend;
+
+/// Дата из Timestamp; нулевой Timestamp в ответе означает, что дата не задана
+fn day(timestamp: &Option<Timestamp>) -> Option<String> {
+    let timestamp = timestamp.as_ref().filter(|ts| ts.seconds > 0)?;
+    DateTime::from_timestamp(timestamp.seconds, 0).map(|dt| dt.format("%Y-%m-%d").to_string())
+}
+
+/// Дивиденд без даты фиксации реестра не сохраняется: по ней строится ключ записи
+pub fn map_dividend(
+    figi: &str,
+    instrument_type: &str,
+    ticker: &str,
+    dividend: &Dividend,
+    now: DateTime<Utc>,
+) -> Option<DbDividend> {
+    Some(DbDividend {
+        figi: figi.to_string(),
+        instrument_type: instrument_type.to_string(),
+        ticker: ticker.to_string(),
+        record_date: day(&dividend.record_date)?,
+        last_buy_date: day(&dividend.last_buy_date),
+        payment_date: day(&dividend.payment_date),
+        declared_date: day(&dividend.declared_date),
+        dividend_net: dividend.dividend_net.as_ref().map(TinkoffMoneyValueModel::from),
+        close_price: dividend.close_price.as_ref().map(TinkoffMoneyValueModel::from),
+        yield_value: dividend.yield_value.as_ref().map(TinkoffQuotationModel::from),
+        dividend_type: dividend.dividend_type.clone(),
+        regularity: dividend.regularity.clone(),
+        created_at: dividend
+            .created_at
+            .as_ref()
+            .and_then(|ts| DateTime::from_timestamp(ts.seconds, ts.nanos as u32))
+            .map(|dt| dt.to_rfc3339()),
+        updated_at: now.to_rfc3339(),
+    })
+}
@@ -0,0 +1,3 @@
+pub mod bond_coupons;
+pub mod dividends;
+pub mod trading_calendar;
@@ -1,11 +1,7 @@
 use std::collections::HashSet;
 use std::vec;
 
-use crate::features::core::models::bond::TinkoffBondModel;
-use crate::features::core::models::etf::TinkoffEtfModel;
-use crate::features::core::models::future::TinkoffFutureModel;
 use crate::features::core::models::instrument::TinkoffInstrumentEnum;
-use crate::features::core::models::share::TinkoffShareModel;
 use crate::features::db::mongo_db::{Collections, DbNames};
 
 use super::CandlesTrackingUpdater;
@@ -25,7 +21,23 @@ use tracing::{error, info};
 struct TrackingDocument {
     #[serde(rename = "_id")]
     id: ObjectId,
-    figi: String,
+    /// Ключ настройки: uid, если задан (у опционов FIGI нет), иначе FIGI
+    key: InstrumentKey,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+enum InstrumentKey {
+    Figi(String),
+    Uid(String),
+}
+
+impl std::fmt::Display for InstrumentKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InstrumentKey::Figi(figi) => write!(f, "FIGI {}", figi),
+            InstrumentKey::Uid(uid) => write!(f, "uid {}", uid),
+        }
+    }
 }
 impl CandlesTrackingUpdater {
     pub(super) async fn update_candles_tracking(&self) -> Result<(), Box<dyn std::error::Error>> {
@@ -63,15 +75,18 @@ impl CandlesTrackingUpdater {
                 doc! {
                     "$match": doc! {
                         "user_setting.enabled": true,
-                        "user_setting.figi": doc! {
-                            "$exists": true,
-                            "$type": "string"
-                        }
+                        "$or": [
+                            { "user_setting.figi": { "$type": "string" } },
+                            { "user_setting.uid": { "$type": "string" } }
+                        ]
                     }
                 },
                 doc! {
                     "$group": doc! {
-                        "_id": "$user_setting.figi",
+                        "_id": doc! {
+                            "figi": "$user_setting.figi",
+                            "uid": "$user_setting.uid"
+                        },
                         "original_id": doc! {
                             "$first": "$_id"
                         }
@@ -79,7 +94,8 @@ impl CandlesTrackingUpdater {
                 },
                 doc! {
                     "$addFields": doc! {
-                        "figi": "$_id",
+                        "figi": "$_id.figi",
+                        "uid": "$_id.uid",
                         "_id": "$original_id"
                     }
                 },
@@ -98,8 +114,12 @@ impl CandlesTrackingUpdater {
             .into_iter()
             .filter_map(|doc| {
                 let id = doc.get_object_id("_id").ok()?;
-                let figi = doc.get_str("figi").ok()?.to_string();
-                Some(TrackingDocument { id, figi })
+                let key = match (doc.get_str("uid"), doc.get_str("figi")) {
+                    (Ok(uid), _) if !uid.is_empty() => InstrumentKey::Uid(uid.to_string()),
+                    (_, Ok(figi)) if !figi.is_empty() => InstrumentKey::Figi(figi.to_string()),
+                    _ => return None,
+                };
+                Some(TrackingDocument { id, key })
             })
             .collect();
 
@@ -116,14 +136,13 @@ impl CandlesTrackingUpdater {
         for doc in tracking_documents {
             // Get the original document first
             if let Ok(Some(original_doc)) = collection.find_one(doc! { "_id": doc.id }).await {
-                let figi = doc.figi.clone(); // Clone the figi before it's moved
-                                             // Try to update the document with instrument data
+                // Try to update the document with instrument data
                 match self
-                    .update_document_with_instrument_data(&original_doc, doc.figi, collection)
+                    .update_document_with_instrument_data(&original_doc, &doc.key, collection)
                     .await
                 {
                     Ok(_) => updated_count += 1,
-                    Err(e) => error!("Failed to update document for FIGI {}: {}", figi, e),
+                    Err(e) => error!("Failed to update document for {}: {}", doc.key, e),
                 }
             }
         }
@@ -134,11 +153,18 @@ impl CandlesTrackingUpdater {
     async fn update_document_with_instrument_data(
         &self,
         doc: &Document,
-        figi: String,
+        key: &InstrumentKey,
         collection: &Collection<Document>,
     ) -> Result<(), Box<dyn std::error::Error>> {
-        // Find instrument by FIGI
-        if let Some(instrument) = self.find_instrument_by_figi(&figi).await {
+        // Find instrument by uid or FIGI; a FIGI setting may hold a uid of an option
+        let instrument = match key {
+            InstrumentKey::Uid(uid) => self.mongo_db.find_instrument_by_uid(uid).await,
+            InstrumentKey::Figi(figi) => match self.mongo_db.find_instrument_by_figi(figi).await {
+                Some(instrument) => Some(instrument),
+                None => self.mongo_db.find_instrument_by_uid(figi).await,
+            },
+        };
+        if let Some(instrument) = instrument {
             // Create updated document
             match self
                 .create_updated_tracking_document(doc.clone(), instrument)
@@ -151,96 +177,24 @@ impl CandlesTrackingUpdater {
                         .await
                     {
                         Ok(_) => {
-                            info!("Updated tracking data for instrument {}", figi);
+                            info!("Updated tracking data for instrument {}", key);
                             Ok(())
                         }
                         Err(e) => {
-                            error!("Failed to update document for {}: {}", figi, e);
+                            error!("Failed to update document for {}: {}", key, e);
                             Err(Box::new(e))
                         }
                     }
                 }
                 Err(e) => {
-                    error!("Failed to create updated document for {}: {}", figi, e);
+                    error!("Failed to create updated document for {}: {}", key, e);
                     Err(Box::new(e))
                 }
             }
         } else {
-            error!("Instrument with FIGI {} not found in any collection", figi);
-            Err(format!("Instrument with FIGI {} not found", figi).into())
-        }
-    }
-
-    /// Поиск инструмента по UID во всех коллекциях
-    ///
-    /// Последовательно проверяет коллекции акций, облигаций, ETF и фьючерсов
-    async fn find_instrument_by_figi(&self, figi: &str) -> Option<TinkoffInstrumentEnum> {
-        // Filter for searching by FIGI
-        let filter = doc! { "figi": figi };
-
-        // Check shares collection
-        if let Ok(Some(doc)) = self
-            .mongo_db
-            .shares_collection()
-            .find_one(filter.clone())
-            .await
-        {
-            // Convert BSON document to HumanShare
-            match bson::from_document::<TinkoffShareModel>(doc) {
-                Ok(share) => return Some(TinkoffInstrumentEnum::Share(share)),
-                Err(e) => {
-                    error!("Failed to deserialize Share document: {}", e);
-                    // Continue checking other collections
-                }
-            }
-        }
-
-        // Check bonds collection
-        if let Ok(Some(doc)) = self
-            .mongo_db
-            .bonds_collection()
-            .find_one(filter.clone())
-            .await
-        {
-            match bson::from_document::<TinkoffBondModel>(doc) {
-                Ok(bond) => return Some(TinkoffInstrumentEnum::Bond(bond)),
-                Err(e) => {
-                    error!("Failed to deserialize Bond document: {}", e);
-                }
-            }
+            error!("Instrument with {} not found in any collection", key);
+            Err(format!("Instrument with {} not found", key).into())
         }
-
-        // Check ETFs collection
-        if let Ok(Some(doc)) = self
-            .mongo_db
-            .etfs_collection()
-            .find_one(filter.clone())
-            .await
-        {
-            match bson::from_document::<TinkoffEtfModel>(doc) {
-                Ok(etf) => return Some(TinkoffInstrumentEnum::Etf(etf)),
-                Err(e) => {
-                    error!("Failed to deserialize ETF document: {}", e);
-                }
-            }
-        }
-
-        // Check futures collection
-        if let Ok(Some(doc)) = self
-            .mongo_db
-            .futures_collection()
-            .find_one(filter.clone())
-            .await
-        {
-            match bson::from_document::<TinkoffFutureModel>(doc) {
-                Ok(future) => return Some(TinkoffInstrumentEnum::Future(future)),
-                Err(e) => {
-                    error!("Failed to deserialize Future document: {}", e);
-                }
-            }
-        }
-
-        None
     }
 
     // Then you would need to update the create_updated_tracking_document method to work with the enum
@@ -254,6 +208,7 @@ impl CandlesTrackingUpdater {
             TinkoffInstrumentEnum::Share(share) => {
                 doc! {
                     "figi": &share.figi,
+                    "uid": &share.uid,
                     "ticker": &share.ticker,
                     "name": &share.name,
                     "instrument_type": "share",
@@ -265,6 +220,7 @@ impl CandlesTrackingUpdater {
             TinkoffInstrumentEnum::Bond(bond) => {
                 doc! {
                     "figi": &bond.figi,
+                    "uid": &bond.uid,
                     "ticker": &bond.ticker,
                     "name": &bond.name,
                     "instrument_type": "bond",
@@ -276,6 +232,7 @@ impl CandlesTrackingUpdater {
             TinkoffInstrumentEnum::Etf(etf) => {
                 doc! {
                     "figi": &etf.figi,
+                    "uid": &etf.uid,
                     "ticker": &etf.ticker,
                     "name": &etf.name,
                     "instrument_type": "etf",
@@ -287,6 +244,7 @@ impl CandlesTrackingUpdater {
             TinkoffInstrumentEnum::Future(future) => {
                 doc! {
                     "figi": &future.figi,
+                    "uid": &future.uid,
                     "ticker": &future.ticker,
                     "name": &future.name,
                     "instrument_type": "future",
@@ -295,6 +253,29 @@ impl CandlesTrackingUpdater {
                     "lot": future.lot,
                 }
             }
+            TinkoffInstrumentEnum::Option(option) => {
+                doc! {
+                    "uid": &option.uid,
+                    "ticker": &option.ticker,
+                    "name": &option.name,
+                    "instrument_type": "option",
+                    "first_available_date": option.first_1day_candle_date.as_ref().map(|d| d.timestamp_utc.clone()),
+                    "currency": &option.currency,
+                    "lot": option.lot,
+                }
+            }
+            TinkoffInstrumentEnum::Currency(currency) => {
+                doc! {
+                    "figi": &currency.figi,
+                    "uid": &currency.uid,
+                    "ticker": &currency.ticker,
+                    "name": &currency.name,
+                    "instrument_type": "currency",
+                    "first_available_date": currency.first_1day_candle_date.as_ref().map(|d| d.timestamp_utc.clone()),
+                    "currency": &currency.currency,
+                    "lot": currency.lot,
+                }
+            }
         };
 
         // Current time for tracking updates
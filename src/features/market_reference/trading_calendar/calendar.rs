@@ -0,0 +1,274 @@
+// src/features/market_reference/trading_calendar/calendar.rs
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc, Weekday};
+use prost_types::Timestamp;
+use tonic::Status;
+use tracing::{info, warn};
+
+use crate::env_config::models::app_setting::AppSettings;
+use crate::features::db::mongo_extensions::trading_schedules::models::DbTradingDay;
+use crate::features::db::MongoDb;
+use crate::gen::tinkoff_public_invest_api_contract_v1::{TradingDay, TradingSchedulesRequest};
+use crate::services::tinkoff::{client_grpc::TinkoffClient, rate_limiter::TinkoffMethod};
+
+use super::session::{session_state, SessionState};
+
+/// Максимальный период одного запроса TradingSchedules
+const SCHEDULE_REQUEST_DAYS: i64 = 14;
+
+const DAY_FORMAT: &str = "%Y-%m-%d";
+
+/// Календарь торгов по площадкам: расписания из InstrumentsService.TradingSchedules,
+/// кэшируются в MongoDB и в памяти. Если расписание площадки за день неизвестно,
+/// используется расписание площадки по умолчанию, а без него — будние дни
+pub struct TradingCalendar {
+    client: Arc<TinkoffClient>,
+    mongo_db: Arc<MongoDb>,
+    default_exchange: String,
+    days: Mutex<HashMap<(String, NaiveDate), DbTradingDay>>,
+    /// Дни, расписания которых уже загружены в память (или запрос за них не удался)
+    loaded: Mutex<HashSet<NaiveDate>>,
+}
+
+impl TradingCalendar {
+    pub fn new(
+        client: Arc<TinkoffClient>,
+        mongo_db: Arc<MongoDb>,
+        settings: Arc<AppSettings>,
+    ) -> Self {
+        Self {
+            client,
+            mongo_db,
+            default_exchange: settings.app_config.trading_calendar.default_exchange.clone(),
+            days: Mutex::new(HashMap::new()),
+            loaded: Mutex::new(HashSet::new()),
+        }
+    }
+
+    pub fn default_exchange(&self) -> &str {
+        &self.default_exchange
+    }
+
+    /// Подгружает расписания за дни [from, to). Прошедшие дни берутся из MongoDB,
+    /// недостающие и будущие (расписание может измениться) запрашиваются у API
+    pub async fn ensure_range(&self, from: NaiveDate, to: NaiveDate) {
+        let pending: Vec<NaiveDate> = {
+            let loaded = self.loaded.lock().unwrap();
+            from.iter_days()
+                .take_while(|day| *day < to)
+                .filter(|day| !loaded.contains(day))
+                .collect()
+        };
+        let (Some(first), Some(last)) = (pending.first().copied(), pending.last().copied()) else {
+            return;
+        };
+
+        let stored = match self
+            .mongo_db
+            .get_trading_days(
+                &first.format(DAY_FORMAT).to_string(),
+                &last.format(DAY_FORMAT).to_string(),
+            )
+            .await
+        {
+            Ok(stored) => stored,
+            Err(e) => {
+                warn!("Failed to read cached trading schedules: {}", e);
+                Vec::new()
+            }
+        };
+
+        let today = Utc::now().date_naive();
+        let mut covered = HashSet::new();
+        for day in stored {
+            if let Ok(date) = NaiveDate::parse_from_str(&day.day, DAY_FORMAT) {
+                if date < today {
+                    covered.insert(date);
+                }
+                self.remember(date, day);
+            }
+        }
+
+        let missing: Vec<NaiveDate> = pending
+            .iter()
+            .copied()
+            .filter(|day| !covered.contains(day))
+            .collect();
+        for (chunk_start, chunk_end) in request_chunks(&missing) {
+            if let Err(e) = self.fetch_schedules(chunk_start, chunk_end).await {
+                warn!(
+                    "Failed to load trading schedules for {} .. {}, falling back to weekdays: {}",
+                    chunk_start, chunk_end, e
+                );
+            }
+        }
+
+        self.loaded.lock().unwrap().extend(pending);
+    }
+
+    /// Запрашивает расписания всех площадок за [from, to) и сохраняет их
+    async fn fetch_schedules(&self, from: NaiveDate, to: NaiveDate) -> Result<(), Status> {
+        let request = TradingSchedulesRequest {
+            exchange: String::new(),
+            from: Some(day_timestamp(from)),
+            to: Some(day_timestamp(to)),
+        };
+
+        let response = self
+            .client
+            .call_limited(TinkoffMethod::TradingSchedules, || {
+                let mut instruments_client = self.client.instruments.clone();
+                let request = self.client.create_request(request.clone());
+                async move {
+                    let request = request.map_err(|e| Status::internal(e.to_string()))?;
+                    instruments_client.trading_schedules(request).await
+                }
+            })
+            .await?;
+
+        let days: Vec<DbTradingDay> = response
+            .exchanges
+            .iter()
+            .flat_map(|schedule| {
+                schedule
+                    .days
+                    .iter()
+                    .filter_map(|day| trading_day_to_db(&schedule.exchange, day))
+            })
+            .collect();
+
+        if let Err(e) = self.mongo_db.upsert_trading_days(&days).await {
+            warn!("Failed to cache trading schedules: {}", e);
+        }
+
+        info!(
+            "Loaded {} trading schedule days for {} exchanges ({} .. {})",
+            days.len(),
+            response.exchanges.len(),
+            from,
+            to
+        );
+
+        for day in days {
+            if let Ok(date) = NaiveDate::parse_from_str(&day.day, DAY_FORMAT) {
+                self.remember(date, day);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn remember(&self, date: NaiveDate, day: DbTradingDay) {
+        self.days
+            .lock()
+            .unwrap()
+            .insert((day.exchange.clone(), date), day);
+    }
+
+    /// Расписание дня: площадки, иначе площадки по умолчанию
+    pub fn schedule(&self, exchange: &str, day: NaiveDate) -> Option<DbTradingDay> {
+        let days = self.days.lock().unwrap();
+        days.get(&(exchange.to_string(), day))
+            .or_else(|| days.get(&(self.default_exchange.clone(), day)))
+            .cloned()
+    }
+
+    /// Торговый ли день на площадке; без расписания — будние дни
+    pub fn is_trading_day(&self, exchange: &str, day: NaiveDate) -> bool {
+        match self.schedule(exchange, day) {
+            Some(schedule) => schedule.is_trading_day,
+            None => !matches!(day.weekday(), Weekday::Sat | Weekday::Sun),
+        }
+    }
+
+    /// Окончание торгов площадки за день с учётом вечерней сессии.
+    /// None — день неторговый или расписание неизвестно
+    pub fn session_end(&self, exchange: &str, day: NaiveDate) -> Option<DateTime<Utc>> {
+        let (_, end) = self.schedule(exchange, day)?.session_bounds()?;
+        DateTime::from_timestamp(end, 0)
+    }
+
+    /// Состояние торгов площадки по умолчанию на момент `now`.
+    /// None — расписание на сегодня неизвестно
+    pub fn session_state(&self, now: DateTime<Utc>, days_ahead: i64) -> Option<SessionState> {
+        let today = now.date_naive();
+        self.schedule(&self.default_exchange, today)?;
+
+        let sessions: Vec<(i64, i64)> = (0..=days_ahead)
+            .filter_map(|offset| self.schedule(&self.default_exchange, today + Duration::days(offset)))
+            .filter_map(|schedule| schedule.session_bounds())
+            .collect();
+
+        Some(session_state(now.timestamp(), &sessions))
+    }
+}
+
+fn day_timestamp(day: NaiveDate) -> Timestamp {
+    Timestamp {
+        seconds: day.and_hms_opt(0, 0, 0).unwrap().and_utc().timestamp(),
+        nanos: 0,
+    }
+}
+
+/// Разбивает дни на периоды запроса [start, end) не длиннее SCHEDULE_REQUEST_DAYS
+fn request_chunks(days: &[NaiveDate]) -> Vec<(NaiveDate, NaiveDate)> {
+    let mut chunks: Vec<(NaiveDate, NaiveDate)> = Vec::new();
+    for day in days {
+        match chunks.last_mut() {
+            Some((start, end)) if (*day - *start).num_days() < SCHEDULE_REQUEST_DAYS => {
+                *end = *day + Duration::days(1);
+            }
+            _ => chunks.push((*day, *day + Duration::days(1))),
+        }
+    }
+    chunks
+}
+
+fn trading_day_to_db(exchange: &str, day: &TradingDay) -> Option<DbTradingDay> {
+    let seconds = |ts: &Option<Timestamp>| ts.as_ref().map(|ts| ts.seconds).filter(|s| *s > 0);
+    let date = DateTime::from_timestamp(day.date.as_ref()?.seconds, 0)?.date_naive();
+
+    Some(DbTradingDay {
+        exchange: exchange.to_string(),
+        day: date.format(DAY_FORMAT).to_string(),
+        is_trading_day: day.is_trading_day,
+        start_time: seconds(&day.start_time),
+        end_time: seconds(&day.end_time),
+        evening_start_time: seconds(&day.evening_start_time),
+        evening_end_time: seconds(&day.evening_end_time),
+        updated_at: Utc::now().to_rfc3339(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trading_day_to_db() {
+        let ts = |seconds| Some(Timestamp { seconds, nanos: 0 });
+        // 2024-03-08, сессия 07:00 - 15:50 UTC, вечерняя до 20:50 UTC
+        let day = TradingDay {
+            date: ts(1_709_856_000),
+            is_trading_day: true,
+            start_time: ts(1_709_881_200),
+            end_time: ts(1_709_913_000),
+            evening_end_time: ts(1_709_931_000),
+            ..Default::default()
+        };
+
+        let db = trading_day_to_db("MOEX", &day).unwrap();
+        assert_eq!(db.day, "2024-03-08");
+        assert_eq!(db.evening_start_time, None);
+        assert_eq!(db.session_bounds(), Some((1_709_881_200, 1_709_931_000)));
+
+        let holiday = TradingDay {
+            is_trading_day: false,
+            ..day
+        };
+        assert_eq!(trading_day_to_db("MOEX", &holiday).unwrap().session_bounds(), None);
+    }
+}
@@ -0,0 +1,5 @@
+mod calendar;
+mod session;
+
+pub use calendar::TradingCalendar;
+pub use session::SessionState;
@@ -0,0 +1,50 @@
+// src/features/market_reference/trading_calendar/session.rs
+
+/// Состояние торговой сессии на текущий момент
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionState {
+    /// Идут торги до указанного момента (секунды UTC)
+    Open { until: i64 },
+    /// Торгов нет; следующая сессия начнётся в указанный момент, если она известна
+    Closed { opens_at: Option<i64> },
+}
+
+/// Состояние по списку сессий (начало, конец), отсортированному по времени
+pub(super) fn session_state(now: i64, sessions: &[(i64, i64)]) -> SessionState {
+    if let Some((_, end)) = sessions
+        .iter()
+        .find(|(start, end)| *start <= now && now < *end)
+    {
+        return SessionState::Open { until: *end };
+    }
+
+    SessionState::Closed {
+        opens_at: sessions
+            .iter()
+            .map(|(start, _)| *start)
+            .find(|start| *start > now),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_state() {
+        let sessions = [(100, 200), (1100, 1200)];
+        assert_eq!(session_state(150, &sessions), SessionState::Open { until: 200 });
+        assert_eq!(
+            session_state(200, &sessions),
+            SessionState::Closed { opens_at: Some(1100) }
+        );
+        assert_eq!(
+            session_state(50, &sessions),
+            SessionState::Closed { opens_at: Some(100) }
+        );
+        assert_eq!(
+            session_state(1300, &sessions),
+            SessionState::Closed { opens_at: None }
+        );
+    }
+}
@@ -3,8 +3,12 @@ pub mod market_data;
 pub mod market_reference;
 pub mod market_candles;
 pub mod moex_api;
+pub mod cbr_api;
 pub mod tinkoff_market_data_stream;
 pub mod update;
+pub mod portfolio;
+pub mod tax;
+pub mod bond_analytics;
 
 
 pub mod core;
\ No newline at end of file
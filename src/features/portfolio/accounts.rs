@@ -0,0 +1,50 @@
+// src/features/portfolio/accounts.rs
+
+use chrono::Utc;
+use tonic::Status;
+use tracing::error;
+
+use crate::features::db::mongo_extensions::portfolio::models::DbAccount;
+use crate::features::db::MongoDb;
+use crate::gen::tinkoff_public_invest_api_contract_v1::{Account, GetAccountsRequest};
+use crate::services::tinkoff::{client_grpc::TinkoffClient, rate_limiter::TinkoffMethod};
+
+use super::mappers::{is_trackable, map_account};
+
+/// Запрашивает счета токена (UsersService.GetAccounts) и сохраняет их в MongoDB
+pub async fn sync_accounts(
+    client: &TinkoffClient,
+    mongo_db: &MongoDb,
+) -> Result<Vec<Account>, Status> {
+    let response = client
+        .call_limited(TinkoffMethod::GetAccounts, || {
+            let mut users_client = client.users.clone();
+            let request = client.create_request(GetAccountsRequest {});
+            async move {
+                let request = request.map_err(|e| Status::internal(e.to_string()))?;
+                users_client.get_accounts(request).await
+            }
+        })
+        .await?;
+
+    let now = Utc::now();
+    let records: Vec<DbAccount> = response
+        .accounts
+        .iter()
+        .map(|account| map_account(account, now))
+        .collect();
+    if let Err(e) = mongo_db.upsert_accounts(&records).await {
+        error!("Failed to save accounts: {}", e);
+    }
+
+    Ok(response.accounts)
+}
+
+/// Открытые счета из `[portfolio] accounts` (пустой список — все счета токена)
+pub fn tracked_accounts(accounts: Vec<Account>, configured: &[String]) -> Vec<Account> {
+    accounts
+        .into_iter()
+        .filter(|account| configured.is_empty() || configured.contains(&account.id))
+        .filter(is_trackable)
+        .collect()
+}
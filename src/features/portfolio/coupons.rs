@@ -0,0 +1,225 @@
+// src/features/portfolio/coupons.rs
+
+use std::collections::BTreeMap;
+
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+use crate::features::db::mongo_extensions::{
+    bond_coupons::models::DbBondCoupon, portfolio::models::DbPortfolioSnapshot,
+};
+
+use super::dividends::CurrencyAmount;
+
+/// Ожидаемый купон по облигации счёта
+#[derive(Debug, Clone, Serialize)]
+pub struct ExpectedCoupon {
+    pub figi: String,
+    pub ticker: String,
+    pub coupon_number: i64,
+    pub coupon_date: String,
+    pub fix_date: Option<String>,
+    pub coupon_type: String,
+    pub quantity: Decimal,
+    /// Выплата на одну облигацию; None — ставка плавающего купона ещё не объявлена
+    pub pay_one_bond: Option<Decimal>,
+    pub currency: Option<String>,
+    pub expected_amount: Option<Decimal>,
+    pub floating: bool,
+    pub amortization: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CouponProjection {
+    pub account_id: String,
+    /// День снимка портфеля, по которому взято количество облигаций
+    pub snapshot_day: String,
+    pub from: String,
+    pub to: String,
+    pub coupons: Vec<ExpectedCoupon>,
+    /// Итоги по валютам только по купонам с известной выплатой
+    pub totals: Vec<CurrencyAmount>,
+    /// Сколько купонов периода ещё без объявленной выплаты
+    pub unknown_amounts: usize,
+}
+
+/// Ожидаемые купоны: текущее количество облигаций по снимку, умноженное на выплату
+/// купонов с датой выплаты в периоде
+pub fn project_coupons(
+    snapshot: &DbPortfolioSnapshot,
+    coupons: &[DbBondCoupon],
+    from: &str,
+    to: &str,
+) -> CouponProjection {
+    let mut quantities: BTreeMap<&str, Decimal> = BTreeMap::new();
+    for position in &snapshot.positions {
+        let quantity = position
+            .quantity
+            .as_ref()
+            .map(|quantity| quantity.value)
+            .unwrap_or_default();
+        *quantities.entry(position.figi.as_str()).or_default() += quantity;
+    }
+
+    let mut expected = Vec::new();
+    let mut totals: BTreeMap<String, Decimal> = BTreeMap::new();
+    let mut unknown_amounts = 0;
+    for coupon in coupons {
+        if coupon.coupon_date.as_str() < from || coupon.coupon_date.as_str() > to {
+            continue;
+        }
+        let Some(&quantity) = quantities.get(coupon.figi.as_str()) else {
+            continue;
+        };
+        if quantity <= Decimal::ZERO {
+            continue;
+        }
+
+        let amount = coupon.pay_one_bond.as_ref().filter(|_| coupon.amount_known);
+        let expected_amount = amount.map(|amount| (quantity * amount.value).round_dp(2));
+        match (amount, expected_amount) {
+            (Some(amount), Some(value)) => {
+                *totals.entry(amount.currency.to_uppercase()).or_default() += value;
+            }
+            _ => unknown_amounts += 1,
+        }
+
+        expected.push(ExpectedCoupon {
+            figi: coupon.figi.clone(),
+            ticker: coupon.ticker.clone(),
+            coupon_number: coupon.coupon_number,
+            coupon_date: coupon.coupon_date.clone(),
+            fix_date: coupon.fix_date.clone(),
+            coupon_type: coupon.coupon_type.clone(),
+            quantity,
+            pay_one_bond: amount.map(|amount| amount.value),
+            currency: amount.map(|amount| amount.currency.to_uppercase()),
+            expected_amount,
+            floating: coupon.floating,
+            amortization: coupon.amortization,
+        });
+    }
+
+    CouponProjection {
+        account_id: snapshot.account_id.clone(),
+        snapshot_day: snapshot.day.clone(),
+        from: from.to_string(),
+        to: to.to_string(),
+        coupons: expected,
+        totals: totals
+            .into_iter()
+            .map(|(currency, amount)| CurrencyAmount { currency, amount })
+            .collect(),
+        unknown_amounts,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::core::models::{
+        money_value::TinkoffMoneyValueModel, quotation::TinkoffQuotationModel,
+    };
+    use crate::features::db::mongo_extensions::portfolio::models::{
+        DbPortfolioPosition, DbPortfolioTotals,
+    };
+
+    fn position(figi: &str, quantity: i64) -> DbPortfolioPosition {
+        DbPortfolioPosition {
+            figi: figi.to_string(),
+            instrument_uid: String::new(),
+            position_uid: String::new(),
+            instrument_type: "bond".to_string(),
+            quantity: Some(TinkoffQuotationModel::new(quantity, 0)),
+            average_position_price: None,
+            average_position_price_fifo: None,
+            current_price: None,
+            current_nkd: None,
+            expected_yield: None,
+            expected_yield_fifo: None,
+            var_margin: None,
+            blocked: false,
+            blocked_lots: None,
+            balance: None,
+            blocked_balance: None,
+        }
+    }
+
+    fn coupon(figi: &str, coupon_date: &str, pay_one_bond: Option<i64>) -> DbBondCoupon {
+        DbBondCoupon {
+            figi: figi.to_string(),
+            ticker: figi.to_string(),
+            coupon_number: 1,
+            coupon_date: coupon_date.to_string(),
+            fix_date: None,
+            coupon_start_date: None,
+            coupon_end_date: None,
+            coupon_period: 182,
+            coupon_type: "COUPON_TYPE_CONSTANT".to_string(),
+            amount_known: pay_one_bond.is_some_and(|units| units != 0),
+            pay_one_bond: pay_one_bond.map(|units| TinkoffMoneyValueModel::new("rub", units, 0)),
+            floating: false,
+            amortization: false,
+            updated_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_project_coupons() {
+        let snapshot = DbPortfolioSnapshot {
+            account_id: "2000000001".to_string(),
+            day: "2025-03-14".to_string(),
+            taken_at: String::new(),
+            currency: "RUB".to_string(),
+            totals: DbPortfolioTotals::default(),
+            expected_yield: None,
+            positions: vec![
+                position("OFZ26238", 10),
+                position("FLOAT", 5),
+                position("AMORT", 2),
+            ],
+            money: Vec::new(),
+            blocked_money: Vec::new(),
+        };
+        // Ставка будущего плавающего купона не объявлена: выплата приходит нулевой
+        let mut floating = coupon("FLOAT", "2025-06-01", Some(0));
+        floating.coupon_type = "COUPON_TYPE_FLOATING".to_string();
+        floating.floating = true;
+        let mut amortizing = coupon("AMORT", "2025-05-01", Some(12));
+        amortizing.amortization = true;
+        let coupons = vec![
+            coupon("OFZ26238", "2025-04-16", Some(35)),
+            coupon("OFZ26238", "2025-10-15", None),
+            coupon("OFZ26238", "2026-04-15", Some(35)),
+            floating,
+            amortizing,
+            coupon("NOTHELD", "2025-04-01", Some(50)),
+        ];
+
+        let projection = project_coupons(&snapshot, &coupons, "2025-03-14", "2026-03-14");
+
+        assert_eq!(projection.coupons.len(), 4);
+        assert_eq!(projection.unknown_amounts, 2);
+
+        let fixed = &projection.coupons[0];
+        assert_eq!(fixed.expected_amount, Some(Decimal::from(350)));
+        assert_eq!(fixed.currency.as_deref(), Some("RUB"));
+
+        let unknown = &projection.coupons[1];
+        assert_eq!(unknown.pay_one_bond, None);
+        assert_eq!(unknown.expected_amount, None);
+
+        let floating = &projection.coupons[2];
+        assert!(floating.floating);
+        assert_eq!(floating.pay_one_bond, None);
+        assert_eq!(floating.expected_amount, None);
+
+        let amortizing = &projection.coupons[3];
+        assert!(amortizing.amortization);
+        assert_eq!(amortizing.expected_amount, Some(Decimal::from(24)));
+
+        assert_eq!(projection.totals.len(), 1);
+        assert_eq!(projection.totals[0].currency, "RUB");
+        assert_eq!(projection.totals[0].amount, Decimal::from(374));
+    }
+}
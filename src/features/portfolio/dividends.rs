@@ -0,0 +1,184 @@
+// src/features/portfolio/dividends.rs
+
+use std::collections::BTreeMap;
+
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+use crate::features::db::mongo_extensions::{
+    dividends::models::DbDividend, portfolio::models::DbPortfolioSnapshot,
+};
+
+/// Ожидаемая выплата по позиции счёта
+#[derive(Debug, Clone, Serialize)]
+pub struct ExpectedDividend {
+    pub figi: String,
+    pub ticker: String,
+    pub record_date: String,
+    pub last_buy_date: Option<String>,
+    pub payment_date: Option<String>,
+    pub quantity: Decimal,
+    pub amount_per_share: Decimal,
+    pub currency: String,
+    /// Выплата до удержания налога
+    pub expected_amount: Decimal,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CurrencyAmount {
+    pub currency: String,
+    pub amount: Decimal,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DividendProjection {
+    pub account_id: String,
+    /// День снимка портфеля, по которому взято количество бумаг
+    pub snapshot_day: String,
+    pub from: String,
+    pub to: String,
+    pub dividends: Vec<ExpectedDividend>,
+    /// Итоги по валютам выплат
+    pub totals: Vec<CurrencyAmount>,
+}
+
+/// Ожидаемый доход: текущее количество бумаг по снимку, умноженное на объявленные выплаты
+/// с датой фиксации в периоде. Считается, что позиция продержится до даты фиксации
+pub fn project_dividends(
+    snapshot: &DbPortfolioSnapshot,
+    dividends: &[DbDividend],
+    from: &str,
+    to: &str,
+) -> DividendProjection {
+    let mut quantities: BTreeMap<&str, Decimal> = BTreeMap::new();
+    for position in &snapshot.positions {
+        let quantity = position
+            .quantity
+            .as_ref()
+            .map(|quantity| quantity.value)
+            .unwrap_or_default();
+        *quantities.entry(position.figi.as_str()).or_default() += quantity;
+    }
+
+    let mut expected = Vec::new();
+    let mut totals: BTreeMap<String, Decimal> = BTreeMap::new();
+    for dividend in dividends {
+        if dividend.record_date.as_str() < from || dividend.record_date.as_str() > to {
+            continue;
+        }
+        let Some(&quantity) = quantities.get(dividend.figi.as_str()) else {
+            continue;
+        };
+        let Some(amount) = &dividend.dividend_net else {
+            continue;
+        };
+        if quantity <= Decimal::ZERO || amount.value.is_zero() {
+            continue;
+        }
+
+        let currency = amount.currency.to_uppercase();
+        let expected_amount = (quantity * amount.value).round_dp(2);
+        *totals.entry(currency.clone()).or_default() += expected_amount;
+        expected.push(ExpectedDividend {
+            figi: dividend.figi.clone(),
+            ticker: dividend.ticker.clone(),
+            record_date: dividend.record_date.clone(),
+            last_buy_date: dividend.last_buy_date.clone(),
+            payment_date: dividend.payment_date.clone(),
+            quantity,
+            amount_per_share: amount.value,
+            currency,
+            expected_amount,
+        });
+    }
+
+    DividendProjection {
+        account_id: snapshot.account_id.clone(),
+        snapshot_day: snapshot.day.clone(),
+        from: from.to_string(),
+        to: to.to_string(),
+        dividends: expected,
+        totals: totals
+            .into_iter()
+            .map(|(currency, amount)| CurrencyAmount { currency, amount })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::core::models::{
+        money_value::TinkoffMoneyValueModel, quotation::TinkoffQuotationModel,
+    };
+    use crate::features::db::mongo_extensions::portfolio::models::{
+        DbPortfolioPosition, DbPortfolioTotals,
+    };
+
+    fn position(figi: &str, quantity: i64) -> DbPortfolioPosition {
+        DbPortfolioPosition {
+            figi: figi.to_string(),
+            instrument_uid: String::new(),
+            position_uid: String::new(),
+            instrument_type: "share".to_string(),
+            quantity: Some(TinkoffQuotationModel::new(quantity, 0)),
+            average_position_price: None,
+            average_position_price_fifo: None,
+            current_price: None,
+            current_nkd: None,
+            expected_yield: None,
+            expected_yield_fifo: None,
+            var_margin: None,
+            blocked: false,
+            blocked_lots: None,
+            balance: None,
+            blocked_balance: None,
+        }
+    }
+
+    fn dividend(figi: &str, record_date: &str, units: i64) -> DbDividend {
+        DbDividend {
+            figi: figi.to_string(),
+            instrument_type: "share".to_string(),
+            ticker: figi.to_string(),
+            record_date: record_date.to_string(),
+            last_buy_date: None,
+            payment_date: None,
+            declared_date: None,
+            dividend_net: Some(TinkoffMoneyValueModel::new("rub", units, 500_000_000)),
+            close_price: None,
+            yield_value: None,
+            dividend_type: String::new(),
+            regularity: String::new(),
+            created_at: None,
+            updated_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_project_dividends() {
+        let snapshot = DbPortfolioSnapshot {
+            account_id: "2000000001".to_string(),
+            day: "2025-03-14".to_string(),
+            taken_at: String::new(),
+            currency: "RUB".to_string(),
+            totals: DbPortfolioTotals::default(),
+            expected_yield: None,
+            positions: vec![position("SBER", 100), position("GAZP", 10)],
+            money: Vec::new(),
+            blocked_money: Vec::new(),
+        };
+        let dividends = vec![
+            dividend("SBER", "2025-07-18", 34),
+            dividend("SBER", "2024-07-11", 33),
+            dividend("LKOH", "2025-06-03", 541),
+        ];
+
+        let projection = project_dividends(&snapshot, &dividends, "2025-03-14", "2026-03-14");
+        assert_eq!(projection.dividends.len(), 1);
+        assert_eq!(projection.dividends[0].expected_amount, Decimal::from(3450));
+        assert_eq!(projection.totals.len(), 1);
+        assert_eq!(projection.totals[0].currency, "RUB");
+        assert_eq!(projection.totals[0].amount, Decimal::from(3450));
+    }
+}
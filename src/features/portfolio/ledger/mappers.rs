@@ -0,0 +1,144 @@
+// src/features/portfolio/ledger/mappers.rs
+
+use chrono::{DateTime, Utc};
+
+use crate::features::core::models::{
+    money_value::TinkoffMoneyValueModel, quotation::TinkoffQuotationModel,
+};
+use crate::features::db::mongo_extensions::operations::models::{
+    DbOperation, DbOperationTrade, DbOperationsSyncState,
+};
+use crate::gen::tinkoff_public_invest_api_contract_v1::{
+    MoneyValue, OperationItem, OperationState, OperationType,
+};
+
+fn money(value: &Option<MoneyValue>) -> Option<TinkoffMoneyValueModel> {
+    value.as_ref().map(TinkoffMoneyValueModel::from)
+}
+
+pub fn map_operation(account_id: &str, item: &OperationItem, now: DateTime<Utc>) -> DbOperation {
+    let date_seconds = item.date.as_ref().map(|ts| ts.seconds).unwrap_or_default();
+    let date = item
+        .date
+        .as_ref()
+        .and_then(|ts| DateTime::from_timestamp(ts.seconds, ts.nanos as u32))
+        .map(|dt| dt.to_rfc3339())
+        .unwrap_or_default();
+
+    let trades = item
+        .trades_info
+        .as_ref()
+        .map(|info| {
+            info.trades
+                .iter()
+                .map(|trade| DbOperationTrade {
+                    num: trade.num.clone(),
+                    date_seconds: trade.date.as_ref().map(|ts| ts.seconds).unwrap_or_default(),
+                    quantity: trade.quantity,
+                    price: money(&trade.price),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    DbOperation {
+        account_id: account_id.to_string(),
+        operation_id: item.id.clone(),
+        parent_operation_id: item.parent_operation_id.clone(),
+        operation_type: OperationType::try_from(item.r#type)
+            .unwrap_or(OperationType::Unspecified)
+            .as_str_name()
+            .to_string(),
+        state: OperationState::try_from(item.state)
+            .unwrap_or(OperationState::Unspecified)
+            .as_str_name()
+            .to_string(),
+        name: item.name.clone(),
+        description: item.description.clone(),
+        date,
+        date_seconds,
+        figi: item.figi.clone(),
+        instrument_uid: item.instrument_uid.clone(),
+        instrument_type: item.instrument_type.clone(),
+        position_uid: item.position_uid.clone(),
+        asset_uid: item.asset_uid.clone(),
+        payment: money(&item.payment),
+        price: money(&item.price),
+        commission: money(&item.commission),
+        yield_amount: money(&item.r#yield),
+        yield_relative: item.yield_relative.as_ref().map(TinkoffQuotationModel::from),
+        accrued_int: money(&item.accrued_int),
+        quantity: item.quantity,
+        quantity_done: item.quantity_done,
+        quantity_rest: item.quantity_rest,
+        trades,
+        imported_at: now.to_rfc3339(),
+    }
+}
+
+/// Окно загрузки и курсор первой страницы.
+///
+/// Незавершённое окно продолжается с сохранённого курсора. Иначе новое окно начинается
+/// за `overlap_seconds` до уже загруженной границы (или с начала истории счёта)
+/// и заканчивается в `now`
+pub fn next_window(
+    state: Option<&DbOperationsSyncState>,
+    history_start: i64,
+    now: i64,
+    overlap_seconds: i64,
+) -> (i64, i64, Option<String>) {
+    if let Some(DbOperationsSyncState {
+        window_from: Some(from),
+        window_to: Some(to),
+        cursor,
+        ..
+    }) = state
+    {
+        return (*from, *to, cursor.clone());
+    }
+
+    let from = state
+        .and_then(|state| state.synced_until)
+        .map(|synced| (synced - overlap_seconds).max(history_start))
+        .unwrap_or(history_start);
+    (from, now, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(
+        synced_until: Option<i64>,
+        window: Option<(i64, i64)>,
+        cursor: Option<&str>,
+    ) -> DbOperationsSyncState {
+        DbOperationsSyncState {
+            account_id: "2000000001".to_string(),
+            synced_until,
+            window_from: window.map(|w| w.0),
+            window_to: window.map(|w| w.1),
+            cursor: cursor.map(str::to_string),
+            updated_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_next_window() {
+        // Первая загрузка — с начала истории
+        assert_eq!(next_window(None, 100, 1_000, 50), (100, 1_000, None));
+
+        // Следующая — с перекрытием, но не раньше начала истории
+        let synced = state(Some(800), None, None);
+        assert_eq!(next_window(Some(&synced), 100, 1_000, 50), (750, 1_000, None));
+        let synced = state(Some(120), None, None);
+        assert_eq!(next_window(Some(&synced), 100, 1_000, 50), (100, 1_000, None));
+
+        // Прерванная загрузка продолжается со своего окна и курсора
+        let interrupted = state(Some(500), Some((450, 900)), Some("cursor-2"));
+        assert_eq!(
+            next_window(Some(&interrupted), 100, 1_000, 50),
+            (450, 900, Some("cursor-2".to_string()))
+        );
+    }
+}
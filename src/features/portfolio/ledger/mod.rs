@@ -0,0 +1,4 @@
+mod mappers;
+pub mod service;
+
+pub use service::OperationsLedgerService;
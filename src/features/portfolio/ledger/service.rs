@@ -0,0 +1,222 @@
+// src/features/portfolio/ledger/service.rs
+
+use std::sync::Arc;
+
+use chrono::{NaiveDate, Utc};
+use prost_types::Timestamp;
+use tonic::Status;
+use tracing::{error, info, warn};
+
+use crate::env_config::models::app_setting::AppSettings;
+use crate::features::db::mongo_extensions::operations::models::{
+    DbOperation, DbOperationsSyncState,
+};
+use crate::features::db::MongoDb;
+use crate::gen::tinkoff_public_invest_api_contract_v1::{
+    Account, GetOperationsByCursorRequest, GetOperationsByCursorResponse, OperationState,
+};
+use crate::services::tinkoff::{client_grpc::TinkoffClient, rate_limiter::TinkoffMethod};
+
+use super::super::accounts::{sync_accounts, tracked_accounts};
+use super::mappers::{map_operation, next_window};
+
+/// Журнал операций: инкрементально выгружает исполненные операции счетов через
+/// GetOperationsByCursor и дописывает их в коллекцию `portfolio.operations`.
+/// Курсор незавершённой выгрузки хранится по счёту, после перезапуска выгрузка продолжается
+pub struct OperationsLedgerService {
+    client: Arc<TinkoffClient>,
+    mongo_db: Arc<MongoDb>,
+    settings: Arc<AppSettings>,
+}
+
+impl OperationsLedgerService {
+    pub fn new(
+        client: Arc<TinkoffClient>,
+        mongo_db: Arc<MongoDb>,
+        settings: Arc<AppSettings>,
+    ) -> Self {
+        Self {
+            client,
+            mongo_db,
+            settings,
+        }
+    }
+
+    /// Цикл догрузки операций
+    pub async fn start_update_loop(self) {
+        let config = &self.settings.app_config.operations_ledger;
+        if !config.enabled {
+            info!("Operations ledger is disabled in configuration");
+            return;
+        }
+
+        info!(
+            "Starting operations ledger sync with {} second interval",
+            config.interval_seconds
+        );
+
+        let mut interval =
+            tokio::time::interval(tokio::time::Duration::from_secs(config.interval_seconds.max(1)));
+        loop {
+            interval.tick().await;
+            self.sync_all().await;
+        }
+    }
+
+    /// Догружает операции всех отслеживаемых счетов
+    pub async fn sync_all(&self) {
+        let accounts = match sync_accounts(&self.client, &self.mongo_db).await {
+            Ok(accounts) => tracked_accounts(accounts, &self.settings.app_config.portfolio.accounts),
+            Err(e) => {
+                error!("Failed to load accounts: {}", e);
+                return;
+            }
+        };
+
+        for account in &accounts {
+            match self.sync_account(account).await {
+                Ok(0) => info!("No new operations for account {}", account.id),
+                Ok(inserted) => info!(
+                    "Imported {} new operations for account {}",
+                    inserted, account.id
+                ),
+                Err(e) => error!("Failed to sync operations for account {}: {}", account.id, e),
+            }
+        }
+    }
+
+    /// Выгружает операции счёта окном из `next_window`; после каждой страницы
+    /// сохраняет курсор, после последней — отметку о загруженном периоде
+    async fn sync_account(&self, account: &Account) -> Result<u64, String> {
+        let config = &self.settings.app_config.operations_ledger;
+        let state = self
+            .mongo_db
+            .get_operations_sync_state(&account.id)
+            .await
+            .map_err(|e| format!("Failed to read sync state: {}", e))?;
+
+        let (from, to, mut cursor) = next_window(
+            state.as_ref(),
+            self.history_start(account),
+            Utc::now().timestamp(),
+            config.overlap_days as i64 * 24 * 60 * 60,
+        );
+        if cursor.is_some() {
+            info!(
+                "Resuming operations sync for account {} from saved cursor",
+                account.id
+            );
+        }
+
+        let synced_until = state.as_ref().and_then(|state| state.synced_until);
+        let mut inserted = 0;
+        loop {
+            let page = self.fetch_page(&account.id, from, to, cursor.clone()).await?;
+
+            let now = Utc::now();
+            let operations: Vec<DbOperation> = page
+                .items
+                .iter()
+                .map(|item| map_operation(&account.id, item, now))
+                .collect();
+            inserted += self
+                .mongo_db
+                .append_operations(&operations)
+                .await
+                .map_err(|e| format!("Failed to save operations: {}", e))?;
+
+            let next_cursor = Some(page.next_cursor).filter(|c| !c.is_empty());
+            if !page.has_next || next_cursor.is_none() {
+                break;
+            }
+            cursor = next_cursor;
+
+            self.save_state(&account.id, synced_until, Some((from, to)), cursor.clone())
+                .await?;
+        }
+
+        self.save_state(&account.id, Some(to), None, None).await?;
+        Ok(inserted)
+    }
+
+    async fn fetch_page(
+        &self,
+        account_id: &str,
+        from: i64,
+        to: i64,
+        cursor: Option<String>,
+    ) -> Result<GetOperationsByCursorResponse, String> {
+        let request = GetOperationsByCursorRequest {
+            account_id: account_id.to_string(),
+            instrument_id: String::new(),
+            from: Some(Timestamp {
+                seconds: from,
+                nanos: 0,
+            }),
+            to: Some(Timestamp {
+                seconds: to,
+                nanos: 0,
+            }),
+            cursor: cursor.unwrap_or_default(),
+            limit: self.settings.app_config.operations_ledger.page_size.clamp(1, 1000),
+            operation_types: Vec::new(),
+            // В журнал попадают только исполненные операции: они больше не меняются
+            state: OperationState::Executed as i32,
+            without_commissions: false,
+            without_trades: false,
+            without_overnights: false,
+        };
+
+        self.client
+            .call_limited(TinkoffMethod::GetOperationsByCursor, || {
+                let mut operations_client = self.client.operations.clone();
+                let request = self.client.create_request(request.clone());
+                async move {
+                    let request = request.map_err(|e| Status::internal(e.to_string()))?;
+                    operations_client.get_operations_by_cursor(request).await
+                }
+            })
+            .await
+            .map_err(|e| format!("GetOperationsByCursor failed: {}", e))
+    }
+
+    async fn save_state(
+        &self,
+        account_id: &str,
+        synced_until: Option<i64>,
+        window: Option<(i64, i64)>,
+        cursor: Option<String>,
+    ) -> Result<(), String> {
+        let state = DbOperationsSyncState {
+            account_id: account_id.to_string(),
+            synced_until,
+            window_from: window.map(|window| window.0),
+            window_to: window.map(|window| window.1),
+            cursor,
+            updated_at: Utc::now().to_rfc3339(),
+        };
+        self.mongo_db
+            .save_operations_sync_state(&state)
+            .await
+            .map_err(|e| format!("Failed to save sync state: {}", e))
+    }
+
+    /// Начало истории счёта: дата открытия, иначе `history_start` из конфигурации
+    fn history_start(&self, account: &Account) -> i64 {
+        if let Some(opened) = &account.opened_date {
+            if opened.seconds > 0 {
+                return opened.seconds;
+            }
+        }
+
+        let configured = &self.settings.app_config.operations_ledger.history_start;
+        let day = NaiveDate::parse_from_str(configured, "%Y-%m-%d").unwrap_or_else(|_| {
+            warn!(
+                "Invalid operations_ledger.history_start '{}', loading from 2016-01-01",
+                configured
+            );
+            NaiveDate::from_ymd_opt(2016, 1, 1).unwrap()
+        });
+        day.and_hms_opt(0, 0, 0).unwrap().and_utc().timestamp()
+    }
+}
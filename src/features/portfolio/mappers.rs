@@ -0,0 +1,202 @@
+// src/features/portfolio/mappers.rs
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use prost_types::Timestamp;
+
+use crate::features::core::models::{
+    money_value::TinkoffMoneyValueModel, quotation::TinkoffQuotationModel,
+};
+use crate::features::db::mongo_extensions::portfolio::models::{
+    DbAccount, DbPortfolioPosition, DbPortfolioSnapshot, DbPortfolioTotals,
+};
+use crate::gen::tinkoff_public_invest_api_contract_v1::{
+    AccessLevel, Account, AccountStatus, AccountType, MoneyValue, PortfolioPosition,
+    PortfolioResponse, PositionsResponse, Quotation,
+};
+
+pub const DAY_FORMAT: &str = "%Y-%m-%d";
+
+fn timestamp_to_rfc3339(ts: &Timestamp) -> Option<String> {
+    DateTime::from_timestamp(ts.seconds, ts.nanos as u32).map(|dt| dt.to_rfc3339())
+}
+
+fn money(value: &Option<MoneyValue>) -> Option<TinkoffMoneyValueModel> {
+    value.as_ref().map(TinkoffMoneyValueModel::from)
+}
+
+fn quotation(value: &Option<Quotation>) -> Option<TinkoffQuotationModel> {
+    value.as_ref().map(TinkoffQuotationModel::from)
+}
+
+pub fn map_account(account: &Account, now: DateTime<Utc>) -> DbAccount {
+    DbAccount {
+        account_id: account.id.clone(),
+        name: account.name.clone(),
+        account_type: AccountType::try_from(account.r#type)
+            .unwrap_or(AccountType::Unspecified)
+            .as_str_name()
+            .to_string(),
+        status: AccountStatus::try_from(account.status)
+            .unwrap_or(AccountStatus::Unspecified)
+            .as_str_name()
+            .to_string(),
+        access_level: AccessLevel::try_from(account.access_level)
+            .unwrap_or(AccessLevel::AccountAccessLevelUnspecified)
+            .as_str_name()
+            .to_string(),
+        opened_date: account.opened_date.as_ref().and_then(timestamp_to_rfc3339),
+        closed_date: account.closed_date.as_ref().and_then(timestamp_to_rfc3339),
+        updated_at: now.to_rfc3339(),
+    }
+}
+
+/// Счёт открыт и токен может читать по нему данные
+pub fn is_trackable(account: &Account) -> bool {
+    account.status == AccountStatus::Open as i32
+        && account.access_level != AccessLevel::AccountAccessLevelNoAccess as i32
+}
+
+#[allow(deprecated)]
+fn map_position(position: &PortfolioPosition) -> DbPortfolioPosition {
+    DbPortfolioPosition {
+        figi: position.figi.clone(),
+        instrument_uid: position.instrument_uid.clone(),
+        position_uid: position.position_uid.clone(),
+        instrument_type: position.instrument_type.clone(),
+        quantity: quotation(&position.quantity),
+        average_position_price: money(&position.average_position_price),
+        average_position_price_fifo: money(&position.average_position_price_fifo),
+        current_price: money(&position.current_price),
+        current_nkd: money(&position.current_nkd),
+        expected_yield: quotation(&position.expected_yield),
+        expected_yield_fifo: quotation(&position.expected_yield_fifo),
+        var_margin: money(&position.var_margin),
+        blocked: position.blocked,
+        blocked_lots: quotation(&position.blocked_lots),
+        balance: None,
+        blocked_balance: None,
+    }
+}
+
+/// Собирает снимок из ответов GetPortfolio и GetPositions.
+/// Балансы бумаг и фьючерсов из GetPositions сопоставляются с позициями портфеля по instrument_uid
+pub fn build_snapshot(
+    portfolio: &PortfolioResponse,
+    positions: &PositionsResponse,
+    currency: &str,
+    taken_at: DateTime<Utc>,
+) -> DbPortfolioSnapshot {
+    let balances: HashMap<&str, (i64, i64)> = positions
+        .securities
+        .iter()
+        .map(|s| (s.instrument_uid.as_str(), (s.balance, s.blocked)))
+        .chain(
+            positions
+                .futures
+                .iter()
+                .map(|f| (f.instrument_uid.as_str(), (f.balance, f.blocked))),
+        )
+        .chain(
+            positions
+                .options
+                .iter()
+                .map(|o| (o.instrument_uid.as_str(), (o.balance, o.blocked))),
+        )
+        .collect();
+
+    let positions_list = portfolio
+        .positions
+        .iter()
+        .map(|position| {
+            let mut mapped = map_position(position);
+            if let Some((balance, blocked)) = balances.get(position.instrument_uid.as_str()) {
+                mapped.balance = Some(*balance);
+                mapped.blocked_balance = Some(*blocked);
+            }
+            mapped
+        })
+        .collect();
+
+    DbPortfolioSnapshot {
+        account_id: portfolio.account_id.clone(),
+        day: taken_at.format(DAY_FORMAT).to_string(),
+        taken_at: taken_at.to_rfc3339(),
+        currency: currency.to_string(),
+        totals: DbPortfolioTotals {
+            portfolio: money(&portfolio.total_amount_portfolio),
+            shares: money(&portfolio.total_amount_shares),
+            bonds: money(&portfolio.total_amount_bonds),
+            etf: money(&portfolio.total_amount_etf),
+            currencies: money(&portfolio.total_amount_currencies),
+            futures: money(&portfolio.total_amount_futures),
+            options: money(&portfolio.total_amount_options),
+            structured_notes: money(&portfolio.total_amount_sp),
+        },
+        expected_yield: quotation(&portfolio.expected_yield),
+        positions: positions_list,
+        money: positions.money.iter().map(TinkoffMoneyValueModel::from).collect(),
+        blocked_money: positions.blocked.iter().map(TinkoffMoneyValueModel::from).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gen::tinkoff_public_invest_api_contract_v1::PositionsSecurities;
+    use rust_decimal::Decimal;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_build_snapshot_merges_balances() {
+        let portfolio = PortfolioResponse {
+            account_id: "2000000001".to_string(),
+            total_amount_portfolio: Some(MoneyValue {
+                currency: "rub".to_string(),
+                units: 10500,
+                nano: 250_000_000,
+            }),
+            positions: vec![
+                PortfolioPosition {
+                    figi: "BBG004730N88".to_string(),
+                    instrument_uid: "sber-uid".to_string(),
+                    instrument_type: "share".to_string(),
+                    quantity: Some(Quotation { units: 30, nano: 0 }),
+                    ..Default::default()
+                },
+                PortfolioPosition {
+                    figi: "RUB000UTSTOM".to_string(),
+                    instrument_uid: "rub-uid".to_string(),
+                    instrument_type: "currency".to_string(),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+        let positions = PositionsResponse {
+            securities: vec![PositionsSecurities {
+                figi: "BBG004730N88".to_string(),
+                instrument_uid: "sber-uid".to_string(),
+                balance: 20,
+                blocked: 10,
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let taken_at = DateTime::parse_from_rfc3339("2025-03-14T10:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        let snapshot = build_snapshot(&portfolio, &positions, "RUB", taken_at);
+
+        assert_eq!(snapshot.day, "2025-03-14");
+        assert_eq!(
+            snapshot.totals.portfolio.unwrap().value,
+            Decimal::from_str("10500.25").unwrap()
+        );
+        assert_eq!(snapshot.positions[0].balance, Some(20));
+        assert_eq!(snapshot.positions[0].blocked_balance, Some(10));
+        assert_eq!(snapshot.positions[1].balance, None);
+    }
+}
@@ -0,0 +1,13 @@
+mod accounts;
+pub mod coupons;
+pub mod dividends;
+pub mod ledger;
+mod mappers;
+pub mod pnl;
+pub mod service;
+pub mod valuation;
+
+pub use ledger::OperationsLedgerService;
+pub use pnl::PnlService;
+pub use service::PortfolioService;
+pub use valuation::ValuationService;
@@ -0,0 +1,78 @@
+// src/features/portfolio/pnl/events.rs
+
+use rust_decimal::Decimal;
+
+use crate::features::core::models::money_value::TinkoffMoneyValueModel;
+use crate::features::db::mongo_extensions::operations::models::DbOperation;
+
+use super::fifo::LedgerEvent;
+
+/// Исполненное количество: для частично исполненных заявок — только исполненная часть
+fn executed_quantity(operation: &DbOperation) -> Decimal {
+    let quantity = if operation.quantity_done > 0 {
+        operation.quantity_done
+    } else if operation.quantity_rest > 0 {
+        operation.quantity - operation.quantity_rest
+    } else {
+        operation.quantity
+    };
+    Decimal::from(quantity.max(0))
+}
+
+fn abs_value(value: &Option<TinkoffMoneyValueModel>) -> Decimal {
+    value.as_ref().map(|money| money.value.abs()).unwrap_or_default()
+}
+
+/// Сумма сделки без НКД
+fn trade_amount(operation: &DbOperation) -> Decimal {
+    (abs_value(&operation.payment) - abs_value(&operation.accrued_int)).max(Decimal::ZERO)
+}
+
+/// Событие для книги лотов; операции, не меняющие лоты (купоны, дивиденды, налоги,
+/// комиссии отдельными операциями, движение денег), пропускаются.
+/// Комиссия сделки берётся из поля `commission` самой сделки
+pub fn ledger_event(operation: &DbOperation) -> Option<LedgerEvent> {
+    let event = match operation.operation_type.as_str() {
+        "OPERATION_TYPE_BUY"
+        | "OPERATION_TYPE_BUY_CARD"
+        | "OPERATION_TYPE_BUY_MARGIN"
+        | "OPERATION_TYPE_DELIVERY_BUY" => LedgerEvent::Buy {
+            quantity: executed_quantity(operation),
+            amount: trade_amount(operation),
+            commission: abs_value(&operation.commission),
+        },
+        "OPERATION_TYPE_SELL"
+        | "OPERATION_TYPE_SELL_CARD"
+        | "OPERATION_TYPE_SELL_MARGIN"
+        | "OPERATION_TYPE_DELIVERY_SELL" => LedgerEvent::Sell {
+            quantity: executed_quantity(operation),
+            amount: trade_amount(operation),
+            commission: abs_value(&operation.commission),
+        },
+        "OPERATION_TYPE_OUTPUT_SECURITIES" => LedgerEvent::SecuritiesOut {
+            quantity: executed_quantity(operation),
+        },
+        "OPERATION_TYPE_INPUT_SECURITIES" => LedgerEvent::SecuritiesIn {
+            quantity: executed_quantity(operation),
+            amount: abs_value(&operation.payment),
+        },
+        "OPERATION_TYPE_BOND_REPAYMENT" => LedgerEvent::CapitalReturn {
+            amount: abs_value(&operation.payment),
+        },
+        "OPERATION_TYPE_BOND_REPAYMENT_FULL" => LedgerEvent::Redemption {
+            amount: abs_value(&operation.payment),
+        },
+        _ => return None,
+    };
+    Some(event)
+}
+
+/// Порядок применения операций с одинаковым временем: списание бумаг раньше зачисления,
+/// чтобы сплит применялся к уже открытым лотам
+pub fn event_order(event: &LedgerEvent) -> u8 {
+    match event {
+        LedgerEvent::SecuritiesOut { .. } => 0,
+        LedgerEvent::SecuritiesIn { .. } => 1,
+        _ => 2,
+    }
+}
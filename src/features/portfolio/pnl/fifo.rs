@@ -0,0 +1,424 @@
+// src/features/portfolio/pnl/fifo.rs
+
+use std::collections::VecDeque;
+
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+/// Событие журнала, влияющее на лоты позиции. Суммы — положительные, в валюте расчётов
+#[derive(Debug, Clone, PartialEq)]
+pub enum LedgerEvent {
+    /// Покупка: `amount` без НКД, комиссия увеличивает себестоимость
+    Buy {
+        quantity: Decimal,
+        amount: Decimal,
+        commission: Decimal,
+    },
+    /// Продажа: комиссия уменьшает выручку
+    Sell {
+        quantity: Decimal,
+        amount: Decimal,
+        commission: Decimal,
+    },
+    /// Списание бумаг без сделки (сплит, конвертация): себестоимость ждёт зачисления
+    SecuritiesOut { quantity: Decimal },
+    /// Зачисление бумаг без сделки; `amount` — стоимость, если известна
+    SecuritiesIn { quantity: Decimal, amount: Decimal },
+    /// Частичное погашение номинала (амортизация): уменьшает себестоимость
+    CapitalReturn { amount: Decimal },
+    /// Полное погашение: закрывает всю позицию по сумме погашения
+    Redemption { amount: Decimal },
+}
+
+/// Лот: количество со знаком (больше нуля — длинная позиция, меньше — короткая)
+/// и себестоимость единицы
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Lot {
+    pub quantity: Decimal,
+    pub unit_cost: Decimal,
+    /// Время открытия лота, секунды UTC
+    pub opened_at: i64,
+}
+
+/// Закрытая часть лота
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ClosedLot {
+    /// Закрытое количество, всегда положительное
+    pub quantity: Decimal,
+    /// Закрыта короткая позиция
+    pub short: bool,
+    pub unit_cost: Decimal,
+    /// Цена закрытия за единицу (для короткой позиции — цена выкупа)
+    pub unit_price: Decimal,
+    pub opened_at: i64,
+    pub closed_at: i64,
+}
+
+impl ClosedLot {
+    pub fn realized(&self) -> Decimal {
+        if self.short {
+            self.quantity * (self.unit_cost - self.unit_price)
+        } else {
+            self.quantity * (self.unit_price - self.unit_cost)
+        }
+    }
+}
+
+/// Книга лотов одной позиции с учётом FIFO
+#[derive(Debug, Default, Clone)]
+pub struct FifoBook {
+    lots: VecDeque<Lot>,
+    pub realized: Decimal,
+    pub commissions: Decimal,
+    /// Закрытые части лотов в порядке закрытия
+    pub closed: Vec<ClosedLot>,
+    /// Лоты, выведенные без сделки и не ставшие частью сплита или конвертации
+    pub transferred_out: Vec<ClosedLot>,
+    /// Лоты, списанные без сделки, до зачисления новых бумаг
+    pending: Vec<Lot>,
+    /// Время и родительская операция списания, к которому относится `pending`
+    pending_key: Option<(i64, String)>,
+}
+
+impl FifoBook {
+    /// Применяет событие операции с родительской операцией `group` (может быть пустой).
+    /// Списание и зачисление бумаг считаются одной конвертацией, только если у них
+    /// совпадает время или непустая родительская операция; любое другое событие
+    /// сначала выводит ожидающие лоты из позиции
+    pub fn apply(&mut self, event: &LedgerEvent, at: i64, group: &str) {
+        let paired = matches!(
+            event,
+            LedgerEvent::SecuritiesOut { .. } | LedgerEvent::SecuritiesIn { .. }
+        ) && self.pending_key.as_ref().is_some_and(|(pending_at, pending_group)| {
+            *pending_at == at || (!group.is_empty() && pending_group == group)
+        });
+        if !paired {
+            self.flush_pending(at);
+        }
+
+        match event {
+            LedgerEvent::Buy {
+                quantity,
+                amount,
+                commission,
+            } => {
+                if quantity.is_zero() {
+                    return;
+                }
+                self.commissions += commission;
+                let unit = (amount + commission) / quantity;
+                let rest = self.close(*quantity, unit, false, at);
+                self.open(rest, unit, at);
+            }
+            LedgerEvent::Sell {
+                quantity,
+                amount,
+                commission,
+            } => {
+                if quantity.is_zero() {
+                    return;
+                }
+                self.commissions += commission;
+                let unit = (amount - commission) / quantity;
+                let rest = self.close(*quantity, unit, true, at);
+                self.open(-rest, unit, at);
+            }
+            LedgerEvent::SecuritiesOut { quantity } => {
+                if self.pending_key.is_none() {
+                    self.pending_key = Some((at, group.to_string()));
+                }
+                let mut remaining = *quantity;
+                while remaining > Decimal::ZERO {
+                    let Some(lot) = self.lots.front_mut() else {
+                        break;
+                    };
+                    if lot.quantity <= Decimal::ZERO {
+                        break;
+                    }
+                    let taken = remaining.min(lot.quantity);
+                    self.pending.push(Lot {
+                        quantity: taken,
+                        unit_cost: lot.unit_cost,
+                        opened_at: lot.opened_at,
+                    });
+                    lot.quantity -= taken;
+                    remaining -= taken;
+                    if lot.quantity.is_zero() {
+                        self.lots.pop_front();
+                    }
+                }
+            }
+            LedgerEvent::SecuritiesIn { quantity, amount } => {
+                if quantity.is_zero() {
+                    return;
+                }
+                let pending_quantity: Decimal = self.pending.iter().map(|lot| lot.quantity).sum();
+                if pending_quantity.is_zero() {
+                    self.open(*quantity, amount / quantity, at);
+                    return;
+                }
+
+                // Сплит или конвертация: лоты сохраняют даты и общую себестоимость
+                self.pending_key = None;
+                let ratio = quantity / pending_quantity;
+                let converted: Vec<Lot> = self
+                    .pending
+                    .drain(..)
+                    .map(|lot| Lot {
+                        quantity: lot.quantity * ratio,
+                        unit_cost: lot.unit_cost / ratio,
+                        opened_at: lot.opened_at,
+                    })
+                    .collect();
+                for lot in converted.into_iter().rev() {
+                    self.lots.push_front(lot);
+                }
+            }
+            LedgerEvent::CapitalReturn { amount } => {
+                let quantity = self.quantity();
+                if quantity <= Decimal::ZERO {
+                    return;
+                }
+                let per_unit = amount / quantity;
+                for lot in self.lots.iter_mut() {
+                    lot.unit_cost -= per_unit;
+                }
+            }
+            LedgerEvent::Redemption { amount } => {
+                let quantity = self.quantity();
+                if quantity <= Decimal::ZERO {
+                    return;
+                }
+                self.close(quantity, amount / quantity, true, at);
+            }
+        }
+    }
+
+    /// Списанные лоты, для которых не пришло зачисление, — вывод бумаг со счёта:
+    /// позиция уменьшается по себестоимости, реализованного результата нет
+    fn flush_pending(&mut self, at: i64) {
+        self.pending_key = None;
+        let flushed: Vec<ClosedLot> = self
+            .pending
+            .drain(..)
+            .map(|lot| ClosedLot {
+                quantity: lot.quantity,
+                short: false,
+                unit_cost: lot.unit_cost,
+                unit_price: lot.unit_cost,
+                opened_at: lot.opened_at,
+                closed_at: at,
+            })
+            .collect();
+        self.transferred_out.extend(flushed);
+    }
+
+    /// Закрывает встречные лоты по цене `unit` и возвращает незакрытый остаток.
+    /// `selling` — закрываются длинные лоты, иначе короткие
+    fn close(&mut self, quantity: Decimal, unit: Decimal, selling: bool, at: i64) -> Decimal {
+        let mut remaining = quantity;
+        while remaining > Decimal::ZERO {
+            let Some(lot) = self.lots.front_mut() else {
+                break;
+            };
+            let open = if selling { lot.quantity } else { -lot.quantity };
+            if open <= Decimal::ZERO {
+                break;
+            }
+
+            let matched = remaining.min(open);
+            let closed = ClosedLot {
+                quantity: matched,
+                short: !selling,
+                unit_cost: lot.unit_cost,
+                unit_price: unit,
+                opened_at: lot.opened_at,
+                closed_at: at,
+            };
+            self.realized += closed.realized();
+            self.closed.push(closed);
+            lot.quantity += if selling { -matched } else { matched };
+            remaining -= matched;
+            if lot.quantity.is_zero() {
+                self.lots.pop_front();
+            }
+        }
+        remaining
+    }
+
+    fn open(&mut self, quantity: Decimal, unit_cost: Decimal, at: i64) {
+        if !quantity.is_zero() {
+            self.lots.push_back(Lot {
+                quantity,
+                unit_cost,
+                opened_at: at,
+            });
+        }
+    }
+
+    pub fn lots(&self) -> &VecDeque<Lot> {
+        &self.lots
+    }
+
+    /// Количество в позиции со знаком
+    pub fn quantity(&self) -> Decimal {
+        self.lots.iter().map(|lot| lot.quantity).sum()
+    }
+
+    /// Себестоимость открытых лотов (со знаком позиции)
+    pub fn cost_basis(&self) -> Decimal {
+        self.lots.iter().map(|lot| lot.quantity * lot.unit_cost).sum()
+    }
+
+    /// Средняя цена открытой позиции
+    pub fn average_price(&self) -> Option<Decimal> {
+        let quantity = self.quantity();
+        (!quantity.is_zero()).then(|| self.cost_basis() / quantity)
+    }
+
+    /// Нереализованный результат по цене `price`
+    pub fn unrealized(&self, price: Decimal) -> Decimal {
+        self.quantity() * price - self.cost_basis()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(value: i64) -> Decimal {
+        Decimal::from(value)
+    }
+
+    fn buy(quantity: i64, amount: i64) -> LedgerEvent {
+        LedgerEvent::Buy {
+            quantity: d(quantity),
+            amount: d(amount),
+            commission: Decimal::ZERO,
+        }
+    }
+
+    fn sell(quantity: i64, amount: i64) -> LedgerEvent {
+        LedgerEvent::Sell {
+            quantity: d(quantity),
+            amount: d(amount),
+            commission: Decimal::ZERO,
+        }
+    }
+
+    #[test]
+    fn test_fifo_partial_sells() {
+        let mut book = FifoBook::default();
+        book.apply(&buy(10, 1000), 1, ""); // 10 по 100
+        book.apply(&buy(10, 1200), 2, ""); // 10 по 120
+        book.apply(&sell(15, 2250), 3, ""); // 15 по 150
+
+        // 10 * (150 - 100) + 5 * (150 - 120)
+        assert_eq!(book.realized, d(650));
+        assert_eq!(book.closed.len(), 2);
+        assert_eq!(book.closed[1].opened_at, 2);
+        assert_eq!(book.quantity(), d(5));
+        assert_eq!(book.average_price(), Some(d(120)));
+        assert_eq!(book.unrealized(d(130)), d(50));
+    }
+
+    #[test]
+    fn test_commissions_and_short() {
+        let mut book = FifoBook::default();
+        book.apply(
+            &LedgerEvent::Buy {
+                quantity: d(2),
+                amount: d(200),
+                commission: d(2),
+            },
+            1,
+            "",
+        );
+        book.apply(
+            &LedgerEvent::Sell {
+                quantity: d(3),
+                amount: d(330),
+                commission: d(3),
+            },
+            2,
+            "",
+        );
+        // 2 * (109 - 101), остаток 1 — короткая позиция по 109
+        assert_eq!(book.realized, d(16));
+        assert_eq!(book.quantity(), d(-1));
+        assert_eq!(book.commissions, d(5));
+
+        book.apply(&buy(1, 100), 3, "");
+        assert_eq!(book.realized, d(25));
+        assert!(book.lots().is_empty());
+    }
+
+    #[test]
+    fn test_split_keeps_cost_basis() {
+        let mut book = FifoBook::default();
+        book.apply(&buy(10, 1000), 1, "");
+        book.apply(&buy(10, 2000), 2, "");
+        book.apply(&LedgerEvent::SecuritiesOut { quantity: d(20) }, 3, "");
+        book.apply(
+            &LedgerEvent::SecuritiesIn {
+                quantity: d(200),
+                amount: Decimal::ZERO,
+            },
+            3,
+            "",
+        );
+
+        assert_eq!(book.quantity(), d(200));
+        assert_eq!(book.cost_basis(), d(3000));
+        assert_eq!(book.lots()[0].unit_cost, d(10));
+        assert_eq!(book.lots()[0].opened_at, 1);
+
+        book.apply(&sell(100, 1500), 4, "");
+        assert_eq!(book.realized, d(500));
+        assert!(book.transferred_out.is_empty());
+    }
+
+    #[test]
+    fn test_out_then_unrelated_in_is_not_a_split() {
+        let day = 86_400;
+        let mut book = FifoBook::default();
+        book.apply(&buy(10, 1000), 1, "");
+        book.apply(&LedgerEvent::SecuritiesOut { quantity: d(10) }, 2, "");
+        // Зачисление с другого брокера спустя несколько дней
+        book.apply(
+            &LedgerEvent::SecuritiesIn {
+                quantity: d(5),
+                amount: d(600),
+            },
+            2 + 5 * day,
+            "",
+        );
+
+        assert_eq!(book.quantity(), d(5));
+        assert_eq!(book.cost_basis(), d(600));
+        assert_eq!(book.lots()[0].opened_at, 2 + 5 * day);
+        assert_eq!(book.realized, Decimal::ZERO);
+        assert_eq!(book.transferred_out.len(), 1);
+        assert_eq!(book.transferred_out[0].quantity, d(10));
+        assert_eq!(book.transferred_out[0].unit_cost, d(100));
+    }
+
+    #[test]
+    fn test_split_legs_paired_by_parent_operation() {
+        let mut book = FifoBook::default();
+        book.apply(&buy(10, 1000), 1, "");
+        book.apply(&LedgerEvent::SecuritiesOut { quantity: d(10) }, 100, "split-1");
+        book.apply(
+            &LedgerEvent::SecuritiesIn {
+                quantity: d(20),
+                amount: Decimal::ZERO,
+            },
+            160,
+            "split-1",
+        );
+
+        assert_eq!(book.quantity(), d(20));
+        assert_eq!(book.cost_basis(), d(1000));
+        assert_eq!(book.lots()[0].opened_at, 1);
+    }
+}
@@ -0,0 +1,5 @@
+pub mod events;
+pub mod fifo;
+pub mod service;
+
+pub use service::{AccountPnl, AggregatePnl, PnlService};
@@ -0,0 +1,335 @@
+// src/features/portfolio/pnl/service.rs
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use rust_decimal::Decimal;
+use serde::Serialize;
+use tonic::Status;
+use tracing::warn;
+
+use crate::features::core::models::{
+    instrument::TinkoffInstrumentEnum, quotation::units_nano_to_decimal,
+};
+use crate::features::db::mongo_extensions::operations::models::DbOperation;
+use crate::features::db::MongoDb;
+use crate::gen::tinkoff_public_invest_api_contract_v1::GetLastPricesRequest;
+use crate::services::tinkoff::{client_grpc::TinkoffClient, rate_limiter::TinkoffMethod};
+
+use super::events::{event_order, ledger_event};
+use super::fifo::{FifoBook, Lot};
+
+/// Результат по позиции (счёт или сумма по счетам), суммы в валюте расчётов позиции
+#[derive(Debug, Clone, Serialize)]
+pub struct PositionPnl {
+    pub figi: String,
+    pub ticker: String,
+    pub name: String,
+    pub instrument_type: String,
+    pub currency: String,
+    pub quantity: Decimal,
+    pub average_price: Option<Decimal>,
+    pub cost_basis: Decimal,
+    /// Последняя цена в валюте (для облигаций — с пересчётом из процента номинала)
+    pub last_price: Option<Decimal>,
+    pub market_value: Option<Decimal>,
+    pub realized: Decimal,
+    /// Нет, если цена неизвестна или не переводится в валюту (фьючерсы)
+    pub unrealized: Option<Decimal>,
+    pub commissions: Decimal,
+    /// Открытые лоты в порядке FIFO
+    pub lots: Vec<Lot>,
+}
+
+/// Итоги по валюте
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CurrencyPnl {
+    pub currency: String,
+    pub realized: Decimal,
+    pub unrealized: Decimal,
+    pub cost_basis: Decimal,
+    pub market_value: Decimal,
+    pub commissions: Decimal,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountPnl {
+    pub account_id: String,
+    pub positions: Vec<PositionPnl>,
+    pub totals: Vec<CurrencyPnl>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AggregatePnl {
+    pub accounts: Vec<AccountPnl>,
+    /// Позиции, сложенные по всем счетам
+    pub positions: Vec<PositionPnl>,
+    pub totals: Vec<CurrencyPnl>,
+}
+
+/// Ключ позиции: FIGI и валюта расчётов
+type PositionKey = (String, String);
+
+/// Расчёт реализованного и нереализованного результата по журналу операций:
+/// лоты сопоставляются FIFO по счёту и инструменту, текущая оценка — по GetLastPrices
+pub struct PnlService {
+    client: Arc<TinkoffClient>,
+    mongo_db: Arc<MongoDb>,
+}
+
+impl PnlService {
+    pub fn new(client: Arc<TinkoffClient>, mongo_db: Arc<MongoDb>) -> Self {
+        Self { client, mongo_db }
+    }
+
+    pub async fn account_pnl(&self, account_id: &str) -> Result<AccountPnl, String> {
+        let books = self.account_books(account_id).await?;
+        let prices = self.last_prices(position_figis(&books)).await;
+
+        let mut instruments = HashMap::new();
+        let positions = self.position_reports(&books, &prices, &mut instruments).await;
+        Ok(AccountPnl {
+            account_id: account_id.to_string(),
+            totals: currency_totals(&positions),
+            positions,
+        })
+    }
+
+    /// Результат по всем счетам из журнала: по каждому счёту и суммарно
+    pub async fn aggregate_pnl(&self) -> Result<AggregatePnl, String> {
+        let accounts = self
+            .mongo_db
+            .get_accounts()
+            .await
+            .map_err(|e| format!("Failed to read accounts: {}", e))?;
+
+        let mut books_by_account = Vec::new();
+        for account in &accounts {
+            books_by_account.push((
+                account.account_id.clone(),
+                self.account_books(&account.account_id).await?,
+            ));
+        }
+
+        // Цены запрашиваются одним вызовом на все счета
+        let figis = books_by_account
+            .iter()
+            .flat_map(|(_, books)| position_figis(books))
+            .collect();
+        let prices = self.last_prices(figis).await;
+
+        let mut instruments = HashMap::new();
+        let mut reports = Vec::new();
+        for (account_id, books) in &books_by_account {
+            let positions = self.position_reports(books, &prices, &mut instruments).await;
+            reports.push(AccountPnl {
+                account_id: account_id.clone(),
+                totals: currency_totals(&positions),
+                positions,
+            });
+        }
+
+        let positions = merge_positions(reports.iter().flat_map(|report| report.positions.iter()));
+        Ok(AggregatePnl {
+            totals: currency_totals(&positions),
+            positions,
+            accounts: reports,
+        })
+    }
+
+    /// Книги лотов счёта по (FIGI, валюта), с типом инструмента из журнала
+    async fn account_books(
+        &self,
+        account_id: &str,
+    ) -> Result<BTreeMap<PositionKey, (String, FifoBook)>, String> {
+        let operations = self
+            .mongo_db
+            .find_account_operations(account_id)
+            .await
+            .map_err(|e| format!("Failed to read operations: {}", e))?;
+        Ok(build_books(&operations))
+    }
+
+    async fn position_reports(
+        &self,
+        books: &BTreeMap<PositionKey, (String, FifoBook)>,
+        prices: &HashMap<String, Decimal>,
+        instruments: &mut HashMap<String, Option<TinkoffInstrumentEnum>>,
+    ) -> Vec<PositionPnl> {
+        let mut positions = Vec::new();
+        for ((figi, currency), (instrument_type, book)) in books {
+            if !instruments.contains_key(figi) {
+                let instrument = self.mongo_db.find_instrument_by_figi(figi).await;
+                instruments.insert(figi.clone(), instrument);
+            }
+            let instrument = instruments.get(figi).and_then(|i| i.as_ref());
+
+            let quantity = book.quantity();
+            let last_price = prices
+                .get(figi)
+                .and_then(|price| price_in_currency(instrument, currency, *price));
+            let market_value = last_price.map(|price| quantity * price);
+
+            positions.push(PositionPnl {
+                figi: figi.clone(),
+                ticker: instrument.map(|i| i.ticker().to_string()).unwrap_or_default(),
+                name: instrument.map(|i| i.name().to_string()).unwrap_or_default(),
+                instrument_type: instrument_type.clone(),
+                currency: currency.clone(),
+                quantity,
+                average_price: book.average_price(),
+                cost_basis: book.cost_basis(),
+                last_price,
+                market_value,
+                realized: book.realized,
+                unrealized: if quantity.is_zero() {
+                    Some(Decimal::ZERO)
+                } else {
+                    last_price.map(|price| book.unrealized(price))
+                },
+                commissions: book.commissions,
+                lots: book.lots().iter().cloned().collect(),
+            });
+        }
+        positions
+    }
+
+    /// Последние цены по FIGI (GetLastPrices); при ошибке — без цен
+    async fn last_prices(&self, mut figis: Vec<String>) -> HashMap<String, Decimal> {
+        figis.sort();
+        figis.dedup();
+        if figis.is_empty() {
+            return HashMap::new();
+        }
+
+        let request = GetLastPricesRequest {
+            #[allow(deprecated)]
+            figi: Vec::new(),
+            instrument_id: figis,
+        };
+        let response = self
+            .client
+            .call_limited(TinkoffMethod::GetLastPrices, || {
+                let mut market_data_client = self.client.market_data.clone();
+                let request = self.client.create_request(request.clone());
+                async move {
+                    let request = request.map_err(|e| Status::internal(e.to_string()))?;
+                    market_data_client.get_last_prices(request).await
+                }
+            })
+            .await;
+
+        match response {
+            Ok(response) => response
+                .last_prices
+                .iter()
+                .filter_map(|last| {
+                    let price = last.price.as_ref()?;
+                    Some((last.figi.clone(), units_nano_to_decimal(price.units, price.nano)))
+                })
+                .collect(),
+            Err(e) => {
+                warn!("Failed to load last prices, unrealized P&L is unavailable: {}", e);
+                HashMap::new()
+            }
+        }
+    }
+}
+
+fn position_figis(books: &BTreeMap<PositionKey, (String, FifoBook)>) -> Vec<String> {
+    books.keys().map(|(figi, _)| figi.clone()).collect()
+}
+
+/// Прогоняет операции через книги лотов
+fn build_books(operations: &[DbOperation]) -> BTreeMap<PositionKey, (String, FifoBook)> {
+    let mut events: Vec<_> = operations
+        .iter()
+        .filter(|operation| !operation.figi.is_empty())
+        .filter_map(|operation| ledger_event(operation).map(|event| (operation, event)))
+        .collect();
+    events.sort_by_key(|(operation, event)| (operation.date_seconds, event_order(event)));
+
+    let mut books: BTreeMap<PositionKey, (String, FifoBook)> = BTreeMap::new();
+    for (operation, event) in events {
+        let currency = operation
+            .payment
+            .as_ref()
+            .map(|payment| payment.currency.to_lowercase())
+            .unwrap_or_default();
+        let (_, book) = books
+            .entry((operation.figi.clone(), currency))
+            .or_insert_with(|| (operation.instrument_type.clone(), FifoBook::default()));
+        book.apply(&event, operation.date_seconds, &operation.parent_operation_id);
+    }
+    books
+}
+
+/// Цена из GetLastPrices или свечи в валюте позиции: облигации котируются в процентах номинала,
+/// цена фьючерса и опциона в пунктах в валюту не переводится
+pub(crate) fn price_in_currency(
+    instrument: Option<&TinkoffInstrumentEnum>,
+    currency: &str,
+    price: Decimal,
+) -> Option<Decimal> {
+    match instrument {
+        Some(TinkoffInstrumentEnum::Bond(bond)) => {
+            let nominal = bond.nominal.as_ref()?;
+            if !nominal.currency.eq_ignore_ascii_case(currency) {
+                return None;
+            }
+            Some(price * nominal.value / Decimal::ONE_HUNDRED)
+        }
+        Some(TinkoffInstrumentEnum::Future(_) | TinkoffInstrumentEnum::Option(_)) => None,
+        Some(instrument) if !instrument.currency().eq_ignore_ascii_case(currency) => None,
+        _ => Some(price),
+    }
+}
+
+/// Складывает позиции с одинаковыми FIGI и валютой
+fn merge_positions<'a>(positions: impl Iterator<Item = &'a PositionPnl>) -> Vec<PositionPnl> {
+    let mut merged: BTreeMap<PositionKey, PositionPnl> = BTreeMap::new();
+    for position in positions {
+        let key = (position.figi.clone(), position.currency.clone());
+        match merged.get_mut(&key) {
+            Some(total) => {
+                total.quantity += position.quantity;
+                total.cost_basis += position.cost_basis;
+                total.realized += position.realized;
+                total.commissions += position.commissions;
+                total.lots.extend(position.lots.iter().cloned());
+                total.market_value = total.market_value.zip(position.market_value).map(|(a, b)| a + b);
+                total.unrealized = total.unrealized.zip(position.unrealized).map(|(a, b)| a + b);
+                total.average_price =
+                    (!total.quantity.is_zero()).then(|| total.cost_basis / total.quantity);
+            }
+            None => {
+                merged.insert(key, position.clone());
+            }
+        }
+    }
+    merged
+        .into_values()
+        .map(|mut position| {
+            position.lots.sort_by_key(|lot| lot.opened_at);
+            position
+        })
+        .collect()
+}
+
+fn currency_totals(positions: &[PositionPnl]) -> Vec<CurrencyPnl> {
+    let mut totals: BTreeMap<String, CurrencyPnl> = BTreeMap::new();
+    for position in positions {
+        let total = totals
+            .entry(position.currency.clone())
+            .or_insert_with(|| CurrencyPnl {
+                currency: position.currency.clone(),
+                ..Default::default()
+            });
+        total.realized += position.realized;
+        total.unrealized += position.unrealized.unwrap_or_default();
+        total.cost_basis += position.cost_basis;
+        total.market_value += position.market_value.unwrap_or_default();
+        total.commissions += position.commissions;
+    }
+    totals.into_values().collect()
+}
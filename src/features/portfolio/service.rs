@@ -0,0 +1,147 @@
+// src/features/portfolio/service.rs
+
+use std::sync::Arc;
+
+use chrono::Utc;
+use tonic::Status;
+use tracing::{error, info, warn};
+
+use crate::env_config::models::app_setting::AppSettings;
+use crate::features::db::mongo_extensions::portfolio::models::DbPortfolioSnapshot;
+use crate::features::db::MongoDb;
+use crate::gen::tinkoff_public_invest_api_contract_v1::{
+    portfolio_request::CurrencyRequest, PortfolioRequest, PositionsRequest,
+};
+use crate::services::tinkoff::{client_grpc::TinkoffClient, rate_limiter::TinkoffMethod};
+
+use super::accounts::{sync_accounts, tracked_accounts};
+use super::mappers::build_snapshot;
+
+/// Снимки портфеля: счета из UsersService.GetAccounts, состав и стоимость портфеля
+/// из OperationsService.GetPortfolio/GetPositions. Снимок хранится по дню, в течение дня
+/// перезаписывается последним состоянием
+pub struct PortfolioService {
+    client: Arc<TinkoffClient>,
+    mongo_db: Arc<MongoDb>,
+    settings: Arc<AppSettings>,
+}
+
+impl PortfolioService {
+    pub fn new(
+        client: Arc<TinkoffClient>,
+        mongo_db: Arc<MongoDb>,
+        settings: Arc<AppSettings>,
+    ) -> Self {
+        Self {
+            client,
+            mongo_db,
+            settings,
+        }
+    }
+
+    /// Цикл обновления снимков портфеля
+    pub async fn start_update_loop(self) {
+        let config = &self.settings.app_config.portfolio;
+        if !config.enabled {
+            info!("Portfolio snapshots are disabled in configuration");
+            return;
+        }
+
+        info!(
+            "Starting portfolio snapshot loop with {} second interval (currency: {})",
+            config.interval_seconds, config.currency
+        );
+
+        let mut interval =
+            tokio::time::interval(tokio::time::Duration::from_secs(config.interval_seconds.max(1)));
+        loop {
+            interval.tick().await;
+            self.update_snapshots().await;
+        }
+    }
+
+    /// Обновляет список счетов и снимает портфель по каждому отслеживаемому счёту
+    pub async fn update_snapshots(&self) {
+        let accounts = match sync_accounts(&self.client, &self.mongo_db).await {
+            Ok(accounts) => tracked_accounts(accounts, &self.settings.app_config.portfolio.accounts),
+            Err(e) => {
+                error!("Failed to load accounts: {}", e);
+                return;
+            }
+        };
+        if accounts.is_empty() {
+            warn!("No open accounts available for portfolio snapshots");
+            return;
+        }
+
+        for account in accounts {
+            match self.snapshot_account(&account.id).await {
+                Ok(snapshot) => info!(
+                    "Saved portfolio snapshot for account {} ({}): {} positions",
+                    account.id,
+                    snapshot.day,
+                    snapshot.positions.len()
+                ),
+                Err(e) => error!("Failed to snapshot portfolio for account {}: {}", account.id, e),
+            }
+        }
+    }
+
+    /// Снимок портфеля одного счёта
+    async fn snapshot_account(&self, account_id: &str) -> Result<DbPortfolioSnapshot, String> {
+        let currency = self.currency();
+
+        let portfolio_request = PortfolioRequest {
+            account_id: account_id.to_string(),
+            currency: currency as i32,
+        };
+        let portfolio = self
+            .client
+            .call_limited(TinkoffMethod::GetPortfolio, || {
+                let mut operations_client = self.client.operations.clone();
+                let request = self.client.create_request(portfolio_request.clone());
+                async move {
+                    let request = request.map_err(|e| Status::internal(e.to_string()))?;
+                    operations_client.get_portfolio(request).await
+                }
+            })
+            .await
+            .map_err(|e| format!("GetPortfolio failed: {}", e))?;
+
+        let positions_request = PositionsRequest {
+            account_id: account_id.to_string(),
+        };
+        let positions = self
+            .client
+            .call_limited(TinkoffMethod::GetPositions, || {
+                let mut operations_client = self.client.operations.clone();
+                let request = self.client.create_request(positions_request.clone());
+                async move {
+                    let request = request.map_err(|e| Status::internal(e.to_string()))?;
+                    operations_client.get_positions(request).await
+                }
+            })
+            .await
+            .map_err(|e| format!("GetPositions failed: {}", e))?;
+
+        let mut snapshot = build_snapshot(&portfolio, &positions, currency.as_str_name(), Utc::now());
+        if snapshot.account_id.is_empty() {
+            snapshot.account_id = account_id.to_string();
+        }
+
+        self.mongo_db
+            .upsert_portfolio_snapshot(&snapshot)
+            .await
+            .map_err(|e| format!("Failed to save snapshot: {}", e))?;
+
+        Ok(snapshot)
+    }
+
+    fn currency(&self) -> CurrencyRequest {
+        let configured = &self.settings.app_config.portfolio.currency;
+        CurrencyRequest::from_str_name(&configured.to_uppercase()).unwrap_or_else(|| {
+            warn!("Unsupported portfolio currency '{}', using RUB", configured);
+            CurrencyRequest::Rub
+        })
+    }
+}
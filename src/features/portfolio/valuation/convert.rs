@@ -0,0 +1,63 @@
+// src/features/portfolio/valuation/convert.rs
+
+use std::collections::HashMap;
+
+use rust_decimal::Decimal;
+
+/// Пересчёт сумм в базовую валюту через курсы к рублю
+#[derive(Debug)]
+pub struct CurrencyConverter {
+    base: String,
+    /// Рублей за единицу валюты
+    rub_rates: HashMap<String, Decimal>,
+}
+
+impl CurrencyConverter {
+    pub fn new(base: &str) -> Self {
+        let mut rub_rates = HashMap::new();
+        rub_rates.insert("RUB".to_string(), Decimal::ONE);
+        Self {
+            base: base.to_uppercase(),
+            rub_rates,
+        }
+    }
+
+    pub fn base(&self) -> &str {
+        &self.base
+    }
+
+    pub fn insert_rate(&mut self, currency: &str, rub_per_unit: Decimal) {
+        self.rub_rates.insert(currency.to_uppercase(), rub_per_unit);
+    }
+
+    pub fn has_rate(&self, currency: &str) -> bool {
+        self.rub_rates.contains_key(&currency.to_uppercase())
+    }
+
+    /// Сумма в базовой валюте; None, если нет курса валюты суммы или базовой валюты
+    pub fn convert(&self, amount: Decimal, from: &str) -> Option<Decimal> {
+        let from_rate = self.rub_rates.get(&from.to_uppercase())?;
+        let base_rate = self.rub_rates.get(&self.base)?;
+        if base_rate.is_zero() {
+            return None;
+        }
+        Some(amount * from_rate / base_rate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convert_through_rub() {
+        let mut converter = CurrencyConverter::new("usd");
+        converter.insert_rate("USD", Decimal::from(80));
+        converter.insert_rate("CNY", Decimal::from(11));
+
+        assert_eq!(converter.convert(Decimal::from(800), "RUB"), Some(Decimal::from(10)));
+        assert_eq!(converter.convert(Decimal::from(160), "cny"), Some(Decimal::from(22)));
+        assert_eq!(converter.convert(Decimal::from(5), "USD"), Some(Decimal::from(5)));
+        assert_eq!(converter.convert(Decimal::from(5), "EUR"), None);
+    }
+}
@@ -0,0 +1,4 @@
+mod convert;
+pub mod service;
+
+pub use service::{PortfolioValuation, ValuationService};
@@ -0,0 +1,381 @@
+// src/features/portfolio/valuation/service.rs
+
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+use crate::features::{
+    core::models::instrument::InstrumentKind,
+    db::{
+        mongo_extensions::{
+            currency_rates::models::DbCurrencyRate,
+            portfolio::models::{DbPortfolioPosition, DbPortfolioSnapshot},
+        },
+        MongoDb,
+    },
+    portfolio::pnl::service::price_in_currency,
+};
+
+use super::convert::CurrencyConverter;
+
+/// instrument_type денежных позиций в ответе GetPortfolio
+const CURRENCY_INSTRUMENT_TYPE: &str = "currency";
+
+/// Откуда взята цена позиции
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PriceSource {
+    /// current_price из снимка GetPortfolio
+    Portfolio,
+    /// Закрытие последней сохранённой свечи не позже момента оценки
+    Candle,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PositionValuation {
+    pub figi: String,
+    pub ticker: String,
+    pub instrument_type: String,
+    pub quantity: Decimal,
+    /// Валюта цены позиции
+    pub currency: String,
+    /// Цена за штуку в валюте позиции (для облигаций — без НКД)
+    pub price: Option<Decimal>,
+    pub price_source: Option<PriceSource>,
+    /// Время цены, RFC 3339
+    pub price_time: Option<String>,
+    pub market_value: Option<Decimal>,
+    pub value_in_base: Option<Decimal>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CashValuation {
+    pub currency: String,
+    pub amount: Decimal,
+    pub value_in_base: Option<Decimal>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PortfolioValuation {
+    pub account_id: String,
+    pub base_currency: String,
+    /// Источник курсов: cbrf, exchange или wap
+    pub rate_source: String,
+    /// Момент оценки, RFC 3339
+    pub valued_at: String,
+    /// День снимка, из которого взяты позиции
+    pub snapshot_day: String,
+    /// Позиции без денежных: деньги учитываются в `cash`
+    pub positions: Vec<PositionValuation>,
+    pub cash: Vec<CashValuation>,
+    pub positions_total: Decimal,
+    pub cash_total: Decimal,
+    pub total: Decimal,
+    /// Курсы к рублю, использованные при пересчёте
+    pub rates: Vec<DbCurrencyRate>,
+    /// Позиции и валюты, которые не удалось оценить; в итоги они не входят
+    pub missing: Vec<String>,
+}
+
+/// Оценка портфеля счёта в выбранной валюте по архивным курсам
+pub struct ValuationService {
+    mongo_db: Arc<MongoDb>,
+}
+
+impl ValuationService {
+    pub fn new(mongo_db: Arc<MongoDb>) -> Self {
+        Self { mongo_db }
+    }
+
+    /// Оценка на момент `at` или по последнему снимку, если момент не задан.
+    ///
+    /// Позиции берутся из снимка портфеля за день момента оценки или ближайшего раньше.
+    /// Без `at` цены — из снимка, с `at` — закрытие последней свечи не позже `at`;
+    /// курсы — последние архивные курсы источника на дату оценки
+    pub async fn account_valuation(
+        &self,
+        account_id: &str,
+        base_currency: &str,
+        at: Option<DateTime<Utc>>,
+        rate_source: &str,
+    ) -> Result<Option<PortfolioValuation>, String> {
+        let snapshot = match at {
+            Some(at) => {
+                self.mongo_db
+                    .get_portfolio_snapshot_on_or_before(account_id, &at.format("%Y-%m-%d").to_string())
+                    .await
+            }
+            None => self.mongo_db.get_latest_portfolio_snapshot(account_id).await,
+        }
+        .map_err(|e| format!("Failed to read portfolio snapshot: {}", e))?;
+        let Some(snapshot) = snapshot else {
+            return Ok(None);
+        };
+
+        let valued_at = match at {
+            Some(at) => at,
+            None => DateTime::parse_from_rfc3339(&snapshot.taken_at)
+                .map(|taken_at| taken_at.with_timezone(&Utc))
+                .unwrap_or_else(|_| Utc::now()),
+        };
+
+        let mut positions = Vec::with_capacity(snapshot.positions.len());
+        for position in &snapshot.positions {
+            positions.push(self.value_position(position, at).await);
+        }
+
+        let (converter, rates) = self
+            .converter(base_currency, rate_source, &snapshot, &positions, valued_at)
+            .await?;
+        Ok(Some(build_valuation(
+            &snapshot,
+            positions,
+            &converter,
+            rates,
+            rate_source,
+            valued_at,
+        )))
+    }
+
+    /// Цена и стоимость позиции в её валюте
+    async fn value_position(
+        &self,
+        position: &DbPortfolioPosition,
+        at: Option<DateTime<Utc>>,
+    ) -> PositionValuation {
+        let instrument = self.mongo_db.find_instrument_by_figi(&position.figi).await;
+        let quantity = position
+            .quantity
+            .as_ref()
+            .map(|quantity| quantity.value)
+            .unwrap_or_default();
+        let currency = position
+            .current_price
+            .as_ref()
+            .map(|price| price.currency.to_uppercase())
+            .or_else(|| instrument.as_ref().map(|i| i.currency().to_uppercase()))
+            .unwrap_or_default();
+
+        let mut price = None;
+        if let Some(at) = at {
+            let kind = instrument
+                .as_ref()
+                .map(|instrument| instrument.kind())
+                .unwrap_or(InstrumentKind::Share);
+            if let Some((time, close)) = self
+                .mongo_db
+                .find_last_close(kind, &position.figi, at.timestamp())
+                .await
+            {
+                price = price_in_currency(instrument.as_ref(), &currency, close).map(|price| {
+                    (price, PriceSource::Candle, DateTime::from_timestamp(time, 0))
+                });
+            }
+        }
+        if price.is_none() {
+            price = position
+                .current_price
+                .as_ref()
+                .map(|current| (current.value, PriceSource::Portfolio, None));
+        }
+
+        PositionValuation {
+            figi: position.figi.clone(),
+            ticker: instrument
+                .as_ref()
+                .map(|instrument| instrument.ticker().to_string())
+                .unwrap_or_default(),
+            instrument_type: position.instrument_type.clone(),
+            quantity,
+            currency,
+            price: price.map(|(price, _, _)| price),
+            price_source: price.map(|(_, source, _)| source),
+            price_time: price
+                .and_then(|(_, _, time)| time)
+                .map(|time| time.to_rfc3339()),
+            market_value: price.map(|(price, _, _)| price * quantity),
+            value_in_base: None,
+        }
+    }
+
+    /// Курсы к рублю на дату оценки для всех валют позиций, денег и базовой валюты
+    async fn converter(
+        &self,
+        base_currency: &str,
+        rate_source: &str,
+        snapshot: &DbPortfolioSnapshot,
+        positions: &[PositionValuation],
+        valued_at: DateTime<Utc>,
+    ) -> Result<(CurrencyConverter, Vec<DbCurrencyRate>), String> {
+        let currencies: BTreeSet<String> = positions
+            .iter()
+            .map(|position| position.currency.clone())
+            .chain(snapshot.money.iter().map(|money| money.currency.to_uppercase()))
+            .chain(std::iter::once(base_currency.to_uppercase()))
+            .filter(|currency| !currency.is_empty() && currency != "RUB")
+            .collect();
+
+        let date = valued_at.format("%Y-%m-%d").to_string();
+        let mut converter = CurrencyConverter::new(base_currency);
+        let mut rates = Vec::new();
+        for currency in currencies {
+            let rate = self
+                .mongo_db
+                .get_currency_rate(&currency, rate_source, &date)
+                .await
+                .map_err(|e| format!("Failed to read currency rates: {}", e))?;
+            if let Some(rate) = rate {
+                converter.insert_rate(&currency, rate.rate);
+                rates.push(rate);
+            }
+        }
+        Ok((converter, rates))
+    }
+}
+
+fn build_valuation(
+    snapshot: &DbPortfolioSnapshot,
+    mut positions: Vec<PositionValuation>,
+    converter: &CurrencyConverter,
+    rates: Vec<DbCurrencyRate>,
+    rate_source: &str,
+    valued_at: DateTime<Utc>,
+) -> PortfolioValuation {
+    // Валютные позиции GetPortfolio (RUB000UTSTOM ...) — те же деньги, что и в
+    // snapshot.money; деньги считаются только по snapshot.money
+    positions.retain(|position| position.instrument_type != CURRENCY_INSTRUMENT_TYPE);
+
+    let mut missing = BTreeSet::new();
+    if !converter.has_rate(converter.base()) {
+        missing.insert(format!("{} rate", converter.base()));
+    }
+
+    for position in &mut positions {
+        position.value_in_base = position
+            .market_value
+            .and_then(|value| converter.convert(value, &position.currency))
+            .map(|value| value.round_dp(2));
+        if position.market_value.is_none() {
+            missing.insert(format!("{} price", position.figi));
+        } else if position.value_in_base.is_none() {
+            missing.insert(format!("{} rate", position.currency));
+        }
+    }
+
+    let cash: Vec<CashValuation> = snapshot
+        .money
+        .iter()
+        .map(|money| {
+            let value_in_base = converter
+                .convert(money.value, &money.currency)
+                .map(|value| value.round_dp(2));
+            if value_in_base.is_none() {
+                missing.insert(format!("{} rate", money.currency.to_uppercase()));
+            }
+            CashValuation {
+                currency: money.currency.to_uppercase(),
+                amount: money.value,
+                value_in_base,
+            }
+        })
+        .collect();
+
+    let positions_total: Decimal = positions.iter().filter_map(|p| p.value_in_base).sum();
+    let cash_total: Decimal = cash.iter().filter_map(|c| c.value_in_base).sum();
+
+    PortfolioValuation {
+        account_id: snapshot.account_id.clone(),
+        base_currency: converter.base().to_string(),
+        rate_source: rate_source.to_string(),
+        valued_at: valued_at.to_rfc3339(),
+        snapshot_day: snapshot.day.clone(),
+        positions,
+        cash,
+        positions_total,
+        cash_total,
+        total: positions_total + cash_total,
+        rates,
+        missing: missing.into_iter().collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::core::models::{
+        money_value::TinkoffMoneyValueModel, quotation::TinkoffQuotationModel,
+    };
+
+    fn snapshot_position(figi: &str, instrument_type: &str, quantity: i64, price: i64) -> DbPortfolioPosition {
+        DbPortfolioPosition {
+            figi: figi.to_string(),
+            instrument_uid: String::new(),
+            position_uid: String::new(),
+            instrument_type: instrument_type.to_string(),
+            quantity: Some(TinkoffQuotationModel::new(quantity, 0)),
+            average_position_price: None,
+            average_position_price_fifo: None,
+            current_price: Some(TinkoffMoneyValueModel::new("rub", price, 0)),
+            current_nkd: None,
+            expected_yield: None,
+            expected_yield_fifo: None,
+            var_margin: None,
+            blocked: false,
+            blocked_lots: None,
+            balance: None,
+            blocked_balance: None,
+        }
+    }
+
+    fn position_valuation(position: &DbPortfolioPosition) -> PositionValuation {
+        let quantity = position.quantity.as_ref().unwrap().value;
+        let price = position.current_price.as_ref().unwrap().value;
+        PositionValuation {
+            figi: position.figi.clone(),
+            ticker: String::new(),
+            instrument_type: position.instrument_type.clone(),
+            quantity,
+            currency: "RUB".to_string(),
+            price: Some(price),
+            price_source: Some(PriceSource::Portfolio),
+            price_time: None,
+            market_value: Some(price * quantity),
+            value_in_base: None,
+        }
+    }
+
+    #[test]
+    fn test_currency_positions_not_counted_twice() {
+        let snapshot = DbPortfolioSnapshot {
+            account_id: "2000000001".to_string(),
+            day: "2025-03-14".to_string(),
+            taken_at: "2025-03-14T10:00:00Z".to_string(),
+            currency: "RUB".to_string(),
+            totals: Default::default(),
+            expected_yield: None,
+            positions: vec![
+                snapshot_position("BBG004730N88", "share", 10, 300),
+                // Тот же рублёвый остаток, что и в money
+                snapshot_position("RUB000UTSTOM", "currency", 5000, 1),
+            ],
+            money: vec![TinkoffMoneyValueModel::new("rub", 5000, 0)],
+            blocked_money: Vec::new(),
+        };
+        let positions = snapshot.positions.iter().map(position_valuation).collect();
+        let converter = CurrencyConverter::new("RUB");
+        let valued_at = DateTime::parse_from_rfc3339(&snapshot.taken_at)
+            .unwrap()
+            .with_timezone(&Utc);
+
+        let valuation = build_valuation(&snapshot, positions, &converter, Vec::new(), "cbrf", valued_at);
+
+        assert_eq!(valuation.positions.len(), 1);
+        assert_eq!(valuation.positions_total, Decimal::from(3000));
+        assert_eq!(valuation.cash_total, Decimal::from(5000));
+        assert_eq!(valuation.total, Decimal::from(8000));
+        assert!(valuation.missing.is_empty());
+    }
+}
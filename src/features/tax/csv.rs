@@ -0,0 +1,60 @@
+// src/features/tax/csv.rs
+
+use super::ndfl::NdflReport;
+
+fn field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Отчёт НДФЛ в CSV: строки закрытых лотов, затем итоги
+pub fn ndfl_report_csv(report: &NdflReport) -> String {
+    let mut out = String::from(
+        "account_id,figi,ticker,quantity,short,opened,closed,held_days,cost_rub,proceeds_rub,gain_rub,long_term_exempt\n",
+    );
+    for trade in &report.trades {
+        let row = [
+            field(&trade.account_id),
+            field(&trade.figi),
+            field(&trade.ticker),
+            trade.quantity.to_string(),
+            trade.short.to_string(),
+            trade.opened.clone(),
+            trade.closed.clone(),
+            trade.held_days.to_string(),
+            trade.cost_rub.to_string(),
+            trade.proceeds_rub.to_string(),
+            trade.gain_rub.to_string(),
+            trade.long_term_exempt.to_string(),
+        ];
+        out.push_str(&row.join(","));
+        out.push('\n');
+    }
+
+    out.push('\n');
+    out.push_str("item,amount_rub\n");
+    let totals = [
+        ("year", report.year.to_string()),
+        ("trading_result", report.trading_result.to_string()),
+        ("long_term_exempt_result", report.long_term_exempt_result.to_string()),
+        ("coupon_income", report.coupon_income.to_string()),
+        ("dividend_income", report.dividend_income.to_string()),
+        ("tax_base", report.tax_base.to_string()),
+        ("tax_estimate", report.tax_estimate.to_string()),
+        ("withheld_trading", report.withheld.trading.to_string()),
+        ("withheld_coupons", report.withheld.coupons.to_string()),
+        ("withheld_dividends", report.withheld.dividends.to_string()),
+        ("withheld_other", report.withheld.other.to_string()),
+        ("tax_due", report.tax_due.to_string()),
+    ];
+    for (item, amount) in totals {
+        out.push_str(item);
+        out.push(',');
+        out.push_str(&amount);
+        out.push('\n');
+    }
+    out
+}
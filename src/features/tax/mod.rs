@@ -0,0 +1,5 @@
+pub mod csv;
+pub mod ndfl;
+pub mod service;
+
+pub use service::TaxService;
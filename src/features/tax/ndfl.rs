@@ -0,0 +1,503 @@
+// src/features/tax/ndfl.rs
+
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{DateTime, Datelike, Months, NaiveDate};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use tracing::warn;
+
+use crate::features::db::mongo_extensions::currency_rates::models::{
+    CurrencyRateSources, DbCurrencyRate,
+};
+use crate::features::db::mongo_extensions::operations::models::DbOperation;
+use crate::features::portfolio::pnl::events::{event_order, ledger_event};
+use crate::features::portfolio::pnl::fifo::{FifoBook, LedgerEvent};
+
+/// Льгота долгосрочного владения применяется к бумагам, купленным с этой даты
+const LONG_TERM_FROM: (i32, u32, u32) = (2014, 1, 1);
+/// Срок владения для льготы, месяцев
+const LONG_TERM_MONTHS: u32 = 36;
+
+/// Курсы ЦБ по валютам и датам
+#[derive(Debug, Default)]
+pub struct RateTable {
+    rates: HashMap<String, BTreeMap<NaiveDate, Decimal>>,
+}
+
+impl RateTable {
+    pub fn insert(&mut self, currency: &str, date: NaiveDate, rate: Decimal) {
+        self.rates
+            .entry(currency.to_lowercase())
+            .or_default()
+            .insert(date, rate);
+    }
+
+    /// Курсы ЦБ из архива курсов; записи других источников и с неверной датой пропускаются
+    pub fn insert_archived(&mut self, rates: &[DbCurrencyRate]) {
+        for rate in rates {
+            if rate.source != CurrencyRateSources::CBRF {
+                continue;
+            }
+            match NaiveDate::parse_from_str(&rate.date, "%Y-%m-%d") {
+                Ok(date) => self.insert(&rate.currency, date, rate.rate),
+                Err(_) => warn!("Skipping CBR rate with invalid date '{}'", rate.date),
+            }
+        }
+    }
+
+    /// Курс на дату: последний установленный не позже даты
+    pub fn rate(&self, currency: &str, date: NaiveDate) -> Option<Decimal> {
+        let currency = currency.to_lowercase();
+        if currency == "rub" {
+            return Some(Decimal::ONE);
+        }
+        self.rates
+            .get(&currency)?
+            .range(..=date)
+            .next_back()
+            .map(|(_, rate)| *rate)
+    }
+
+    /// Курс на дату операции; без курса оценка невозможна
+    fn require(&self, currency: &str, date: NaiveDate) -> Result<Decimal, String> {
+        self.rate(currency, date)
+            .ok_or_else(|| format!("Missing CBR rate for {} on {}", currency.to_uppercase(), date))
+    }
+}
+
+/// Закрытие лота в году отчёта, суммы в рублях
+#[derive(Debug, Clone, Serialize)]
+pub struct NdflTradeLine {
+    pub account_id: String,
+    pub figi: String,
+    pub ticker: String,
+    pub quantity: Decimal,
+    pub short: bool,
+    pub opened: String,
+    pub closed: String,
+    pub held_days: i64,
+    pub cost_rub: Decimal,
+    pub proceeds_rub: Decimal,
+    pub gain_rub: Decimal,
+    /// Льгота долгосрочного владения (3+ года)
+    pub long_term_exempt: bool,
+}
+
+/// Удержанный брокером налог за год по видам дохода
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct NdflWithheld {
+    pub trading: Decimal,
+    pub coupons: Decimal,
+    pub dividends: Decimal,
+    pub other: Decimal,
+}
+
+impl NdflWithheld {
+    pub fn total(&self) -> Decimal {
+        self.trading + self.coupons + self.dividends + self.other
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NdflReport {
+    pub year: i32,
+    pub accounts: Vec<String>,
+    /// Счета, не вошедшие в оценку (ИИС облагается при закрытии счёта)
+    pub excluded_accounts: Vec<String>,
+    pub trades: Vec<NdflTradeLine>,
+    /// Финансовый результат по сделкам без льготы
+    pub trading_result: Decimal,
+    /// Результат по сделкам, освобождённый льготой долгосрочного владения
+    pub long_term_exempt_result: Decimal,
+    pub coupon_income: Decimal,
+    pub dividend_income: Decimal,
+    pub tax_base: Decimal,
+    pub tax_estimate: Decimal,
+    pub withheld: NdflWithheld,
+    /// К доплате; отрицательное значение — переплата
+    pub tax_due: Decimal,
+}
+
+/// НДФЛ с налоговой базы по шкале года: 13%, с превышения порога — 15%
+pub fn tax_for_base(year: i32, base: Decimal) -> Decimal {
+    if base <= Decimal::ZERO {
+        return Decimal::ZERO;
+    }
+    let low = Decimal::new(13, 2);
+    let high = Decimal::new(15, 2);
+    let threshold = match year {
+        ..=2020 => return (base * low).round_dp(0),
+        2021..=2024 => Decimal::from(5_000_000),
+        _ => Decimal::from(2_400_000),
+    };
+
+    let tax = if base <= threshold {
+        base * low
+    } else {
+        threshold * low + (base - threshold) * high
+    };
+    tax.round_dp(0)
+}
+
+fn operation_date(operation: &DbOperation) -> NaiveDate {
+    DateTime::from_timestamp(operation.date_seconds, 0)
+        .map(|dt| dt.date_naive())
+        .unwrap_or_default()
+}
+
+fn seconds_date(seconds: i64) -> NaiveDate {
+    DateTime::from_timestamp(seconds, 0)
+        .map(|dt| dt.date_naive())
+        .unwrap_or_default()
+}
+
+fn is_long_term(opened: NaiveDate, closed: NaiveDate) -> bool {
+    let from = NaiveDate::from_ymd_opt(LONG_TERM_FROM.0, LONG_TERM_FROM.1, LONG_TERM_FROM.2)
+        .unwrap_or_default();
+    opened >= from
+        && opened
+            .checked_add_months(Months::new(LONG_TERM_MONTHS))
+            .is_some_and(|eligible| closed >= eligible)
+}
+
+/// Переводит суммы события в рубли по курсу на дату
+fn event_in_rub(event: LedgerEvent, rate: Decimal) -> LedgerEvent {
+    match event {
+        LedgerEvent::Buy {
+            quantity,
+            amount,
+            commission,
+        } => LedgerEvent::Buy {
+            quantity,
+            amount: amount * rate,
+            commission: commission * rate,
+        },
+        LedgerEvent::Sell {
+            quantity,
+            amount,
+            commission,
+        } => LedgerEvent::Sell {
+            quantity,
+            amount: amount * rate,
+            commission: commission * rate,
+        },
+        LedgerEvent::SecuritiesIn { quantity, amount } => LedgerEvent::SecuritiesIn {
+            quantity,
+            amount: amount * rate,
+        },
+        LedgerEvent::CapitalReturn { amount } => LedgerEvent::CapitalReturn {
+            amount: amount * rate,
+        },
+        LedgerEvent::Redemption { amount } => LedgerEvent::Redemption {
+            amount: amount * rate,
+        },
+        event @ LedgerEvent::SecuritiesOut { .. } => event,
+    }
+}
+
+/// Куда относится налоговая операция
+fn withheld_bucket<'a>(withheld: &'a mut NdflWithheld, operation_type: &str) -> Option<&'a mut Decimal> {
+    let bucket = match operation_type {
+        "OPERATION_TYPE_TAX"
+        | "OPERATION_TYPE_TAX_PROGRESSIVE"
+        | "OPERATION_TYPE_TAX_CORRECTION"
+        | "OPERATION_TYPE_TAX_CORRECTION_PROGRESSIVE" => &mut withheld.trading,
+        "OPERATION_TYPE_BOND_TAX"
+        | "OPERATION_TYPE_BOND_TAX_PROGRESSIVE"
+        | "OPERATION_TYPE_TAX_CORRECTION_COUPON" => &mut withheld.coupons,
+        "OPERATION_TYPE_DIVIDEND_TAX" | "OPERATION_TYPE_DIVIDEND_TAX_PROGRESSIVE" => {
+            &mut withheld.dividends
+        }
+        "OPERATION_TYPE_BENEFIT_TAX"
+        | "OPERATION_TYPE_BENEFIT_TAX_PROGRESSIVE"
+        | "OPERATION_TYPE_TAX_REPO"
+        | "OPERATION_TYPE_TAX_REPO_PROGRESSIVE"
+        | "OPERATION_TYPE_TAX_REPO_HOLD"
+        | "OPERATION_TYPE_TAX_REPO_HOLD_PROGRESSIVE"
+        | "OPERATION_TYPE_TAX_REPO_REFUND"
+        | "OPERATION_TYPE_TAX_REPO_REFUND_PROGRESSIVE" => &mut withheld.other,
+        _ => return None,
+    };
+    Some(bucket)
+}
+
+/// Оценка НДФЛ за год по журналам операций счетов.
+///
+/// Лоты сопоставляются FIFO по счёту и FIGI на всей истории, суммы сделок переводятся
+/// в рубли по курсу ЦБ на дату сделки. В отчёт попадают закрытия лотов в году `year`.
+/// Оценка упрощённая: предел льготы долгосрочного владения (3 млн ₽ за год владения)
+/// и перенос убытков прошлых лет не учитываются.
+///
+/// Если для сделки до конца года или дохода в году нет курса ЦБ на дату или раньше,
+/// возвращается ошибка: пропуск операции исказил бы FIFO и налоговую базу
+pub fn estimate(
+    year: i32,
+    accounts: &[(String, Vec<DbOperation>)],
+    rates: &RateTable,
+) -> Result<NdflReport, String> {
+    let mut trades = Vec::new();
+    let mut coupon_income = Decimal::ZERO;
+    let mut dividend_income = Decimal::ZERO;
+    let mut withheld = NdflWithheld::default();
+
+    for (account_id, operations) in accounts {
+        let mut events: Vec<(&DbOperation, LedgerEvent)> = Vec::new();
+
+        for operation in operations {
+            let date = operation_date(operation);
+            let Some(payment) = &operation.payment else {
+                continue;
+            };
+            // Операции после года отчёта на закрытия в нём не влияют
+            if date.year() > year {
+                continue;
+            }
+            let currency = payment.currency.as_str();
+
+            if let Some(event) = ledger_event(operation) {
+                if !operation.figi.is_empty() {
+                    let rate = rates.require(currency, date)?;
+                    events.push((operation, event_in_rub(event, rate)));
+                }
+                continue;
+            }
+            if date.year() != year {
+                continue;
+            }
+
+            match operation.operation_type.as_str() {
+                "OPERATION_TYPE_COUPON" => {
+                    coupon_income += payment.value * rates.require(currency, date)?
+                }
+                "OPERATION_TYPE_DIVIDEND" | "OPERATION_TYPE_DIV_EXT" => {
+                    dividend_income += payment.value * rates.require(currency, date)?
+                }
+                other => {
+                    // Налог списывается с минусом, возврат налога приходит с плюсом
+                    if let Some(bucket) = withheld_bucket(&mut withheld, other) {
+                        *bucket -= payment.value * rates.require(currency, date)?;
+                    }
+                }
+            }
+        }
+
+        events.sort_by_key(|(operation, event)| (operation.date_seconds, event_order(event)));
+        let mut books: BTreeMap<String, FifoBook> = BTreeMap::new();
+        for (operation, event) in &events {
+            books
+                .entry(operation.figi.clone())
+                .or_default()
+                .apply(event, operation.date_seconds, &operation.parent_operation_id);
+        }
+
+        for (figi, book) in books {
+            for closed in book.closed {
+                let opened = seconds_date(closed.opened_at);
+                let closed_date = seconds_date(closed.closed_at);
+                if closed_date.year() != year {
+                    continue;
+                }
+
+                let (cost, proceeds) = if closed.short {
+                    (closed.quantity * closed.unit_price, closed.quantity * closed.unit_cost)
+                } else {
+                    (closed.quantity * closed.unit_cost, closed.quantity * closed.unit_price)
+                };
+                trades.push(NdflTradeLine {
+                    account_id: account_id.clone(),
+                    figi: figi.clone(),
+                    ticker: String::new(),
+                    quantity: closed.quantity,
+                    short: closed.short,
+                    opened: opened.to_string(),
+                    closed: closed_date.to_string(),
+                    held_days: (closed_date - opened).num_days(),
+                    cost_rub: cost.round_dp(2),
+                    proceeds_rub: proceeds.round_dp(2),
+                    gain_rub: (proceeds - cost).round_dp(2),
+                    long_term_exempt: !closed.short && is_long_term(opened, closed_date),
+                });
+            }
+        }
+    }
+
+    trades.sort_by(|a, b| (&a.closed, &a.account_id, &a.figi).cmp(&(&b.closed, &b.account_id, &b.figi)));
+
+    let trading_result: Decimal = trades
+        .iter()
+        .filter(|trade| !trade.long_term_exempt)
+        .map(|trade| trade.gain_rub)
+        .sum();
+    let long_term_exempt_result: Decimal = trades
+        .iter()
+        .filter(|trade| trade.long_term_exempt)
+        .map(|trade| trade.gain_rub)
+        .sum();
+
+    let tax_base = (trading_result.max(Decimal::ZERO) + coupon_income + dividend_income).round_dp(2);
+    let tax_estimate = tax_for_base(year, tax_base);
+    let withheld = NdflWithheld {
+        trading: withheld.trading.round_dp(2),
+        coupons: withheld.coupons.round_dp(2),
+        dividends: withheld.dividends.round_dp(2),
+        other: withheld.other.round_dp(2),
+    };
+
+    Ok(NdflReport {
+        year,
+        accounts: accounts.iter().map(|(account_id, _)| account_id.clone()).collect(),
+        excluded_accounts: Vec::new(),
+        trades,
+        trading_result: trading_result.round_dp(2),
+        long_term_exempt_result: long_term_exempt_result.round_dp(2),
+        coupon_income: coupon_income.round_dp(2),
+        dividend_income: dividend_income.round_dp(2),
+        tax_base,
+        tax_estimate,
+        tax_due: tax_estimate - withheld.total(),
+        withheld,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::core::models::money_value::TinkoffMoneyValueModel;
+
+    fn day(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+    }
+
+    fn operation(operation_type: &str, date: &str, quantity: i64, payment: (i64, &str)) -> DbOperation {
+        let seconds = day(date).and_hms_opt(12, 0, 0).unwrap().and_utc().timestamp();
+        DbOperation {
+            account_id: "2000000001".to_string(),
+            operation_id: format!("{}-{}", operation_type, date),
+            parent_operation_id: String::new(),
+            operation_type: operation_type.to_string(),
+            state: "OPERATION_STATE_EXECUTED".to_string(),
+            name: String::new(),
+            description: String::new(),
+            date: date.to_string(),
+            date_seconds: seconds,
+            figi: "BBG000B9XRY4".to_string(),
+            instrument_uid: String::new(),
+            instrument_type: "share".to_string(),
+            position_uid: String::new(),
+            asset_uid: String::new(),
+            payment: Some(TinkoffMoneyValueModel::new(payment.1, payment.0, 0)),
+            price: None,
+            commission: None,
+            yield_amount: None,
+            yield_relative: None,
+            accrued_int: None,
+            quantity,
+            quantity_done: quantity,
+            quantity_rest: 0,
+            trades: Vec::new(),
+            imported_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_tax_for_base() {
+        assert_eq!(tax_for_base(2020, Decimal::from(10_000_000)), Decimal::from(1_300_000));
+        assert_eq!(tax_for_base(2024, Decimal::from(6_000_000)), Decimal::from(800_000));
+        assert_eq!(tax_for_base(2025, Decimal::from(2_400_000)), Decimal::from(312_000));
+        assert_eq!(tax_for_base(2025, Decimal::from(-5)), Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_estimate_converts_at_trade_date_rates() {
+        let mut rates = RateTable::default();
+        rates.insert("USD", day("2023-03-01"), Decimal::from(75));
+        rates.insert("USD", day("2024-06-01"), Decimal::from(90));
+
+        let operations = vec![
+            operation("OPERATION_TYPE_BUY", "2023-03-10", 10, (-1000, "usd")),
+            operation("OPERATION_TYPE_SELL", "2024-06-10", 10, (1000, "usd")),
+            operation("OPERATION_TYPE_DIVIDEND", "2024-07-01", 0, (10, "usd")),
+            operation("OPERATION_TYPE_DIVIDEND_TAX", "2024-07-01", 0, (-1, "usd")),
+        ];
+        let report = estimate(2024, &[("2000000001".to_string(), operations)], &rates).unwrap();
+
+        // Курсовая разница облагается: 1000 * (90 - 75)
+        assert_eq!(report.trading_result, Decimal::from(15_000));
+        assert_eq!(report.dividend_income, Decimal::from(900));
+        assert_eq!(report.withheld.dividends, Decimal::from(90));
+        assert_eq!(report.tax_base, Decimal::from(15_900));
+        assert_eq!(report.tax_estimate, Decimal::from(2067));
+        assert_eq!(report.tax_due, Decimal::from(1977));
+    }
+
+    #[test]
+    fn test_rate_table_from_archive() {
+        // Документы архива в том виде, в котором их читает TaxService: код валюты в верхнем
+        // регистре, курс — Decimal строкой
+        let archived = |currency: &str, source: &str, date: &str, rate: &str| {
+            bson::from_document::<DbCurrencyRate>(bson::doc! {
+                "currency": currency,
+                "source": source,
+                "date": date,
+                "rate": rate,
+                "updated_at": "2025-03-15T00:00:00+00:00",
+            })
+            .unwrap()
+        };
+        let mut rates = RateTable::default();
+        rates.insert_archived(&[
+            archived("CNY", "cbrf", "2024-03-01", "12.5"),
+            archived("CNY", "wap", "2024-06-01", "13.1"),
+            archived("CNY", "cbrf", "2024-06-01", "12.0"),
+            archived("CNY", "cbrf", "not-a-date", "1"),
+        ]);
+
+        assert_eq!(rates.rate("cny", day("2024-05-31")), Some(Decimal::new(125, 1)));
+        assert_eq!(rates.rate("cny", day("2024-06-10")), Some(Decimal::from(12)));
+
+        let operations = vec![
+            operation("OPERATION_TYPE_BUY", "2024-03-10", 10, (-1000, "cny")),
+            operation("OPERATION_TYPE_SELL", "2024-06-10", 10, (1100, "cny")),
+        ];
+        let report = estimate(2024, &[("2000000001".to_string(), operations)], &rates).unwrap();
+
+        // 1100 * 12 - 1000 * 12.5
+        assert_eq!(report.trading_result, Decimal::from(700));
+    }
+
+    #[test]
+    fn test_long_term_exemption() {
+        let rates = RateTable::default();
+        let operations = vec![
+            operation("OPERATION_TYPE_BUY", "2020-01-15", 10, (-1000, "rub")),
+            operation("OPERATION_TYPE_BUY", "2022-01-15", 10, (-1000, "rub")),
+            operation("OPERATION_TYPE_SELL", "2023-02-01", 20, (3000, "rub")),
+        ];
+        let report = estimate(2023, &[("2000000001".to_string(), operations)], &rates).unwrap();
+
+        assert_eq!(report.trades.len(), 2);
+        let lot = |opened: &str| report.trades.iter().find(|trade| trade.opened == opened).unwrap();
+        assert!(lot("2020-01-15").long_term_exempt);
+        assert!(!lot("2022-01-15").long_term_exempt);
+        assert_eq!(report.long_term_exempt_result, Decimal::from(500));
+        assert_eq!(report.trading_result, Decimal::from(500));
+    }
+
+    #[test]
+    fn test_estimate_fails_without_rate_on_or_before_date() {
+        let mut rates = RateTable::default();
+        // Курс появился только после покупки: брать будущий курс нельзя
+        rates.insert("USD", day("2024-03-01"), Decimal::from(90));
+
+        let operations = vec![
+            operation("OPERATION_TYPE_BUY", "2024-02-10", 10, (-1000, "usd")),
+            operation("OPERATION_TYPE_SELL", "2024-06-10", 10, (1000, "usd")),
+        ];
+        let error = estimate(2024, &[("2000000001".to_string(), operations)], &rates).unwrap_err();
+
+        assert_eq!(error, "Missing CBR rate for USD on 2024-02-10");
+        assert_eq!(rates.rate("usd", day("2024-02-10")), None);
+        assert_eq!(rates.rate("usd", day("2024-06-10")), Some(Decimal::from(90)));
+    }
+}
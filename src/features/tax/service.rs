@@ -0,0 +1,99 @@
+// src/features/tax/service.rs
+
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+
+use crate::features::db::{
+    mongo_extensions::currency_rates::models::CurrencyRateSources, MongoDb,
+};
+
+use super::ndfl::{estimate, NdflReport, RateTable};
+
+/// Счета ИИС облагаются при закрытии, в годовую оценку не входят
+const IIS_ACCOUNT_TYPE: &str = "ACCOUNT_TYPE_TINKOFF_IIS";
+
+/// Оценка НДФЛ по журналу операций и курсам ЦБ из MongoDB
+pub struct TaxService {
+    mongo_db: Arc<MongoDb>,
+}
+
+impl TaxService {
+    pub fn new(mongo_db: Arc<MongoDb>) -> Self {
+        Self { mongo_db }
+    }
+
+    /// Отчёт за год по счёту или по всем брокерским счетам
+    pub async fn ndfl_report(
+        &self,
+        year: i32,
+        account_id: Option<&str>,
+    ) -> Result<NdflReport, String> {
+        let accounts = self
+            .mongo_db
+            .get_accounts()
+            .await
+            .map_err(|e| format!("Failed to read accounts: {}", e))?;
+
+        let mut included = Vec::new();
+        let mut excluded = Vec::new();
+        for account in accounts {
+            if account_id.is_some_and(|id| id != account.account_id) {
+                continue;
+            }
+            if account.account_type == IIS_ACCOUNT_TYPE {
+                excluded.push(account.account_id);
+                continue;
+            }
+
+            let operations = self
+                .mongo_db
+                .find_account_operations(&account.account_id)
+                .await
+                .map_err(|e| format!("Failed to read operations: {}", e))?;
+            included.push((account.account_id, operations));
+        }
+
+        let currencies: BTreeSet<String> = included
+            .iter()
+            .flat_map(|(_, operations)| operations.iter())
+            .filter_map(|operation| operation.payment.as_ref())
+            .map(|payment| payment.currency.to_lowercase())
+            .filter(|currency| currency != "rub")
+            .collect();
+        let rates = self.rate_table(&currencies).await?;
+
+        let mut report = estimate(year, &included, &rates)?;
+        report.excluded_accounts = excluded;
+        self.fill_tickers(&mut report).await;
+        Ok(report)
+    }
+
+    async fn rate_table(&self, currencies: &BTreeSet<String>) -> Result<RateTable, String> {
+        let mut table = RateTable::default();
+        for currency in currencies {
+            let rates = self
+                .mongo_db
+                .get_currency_rate_history(currency, CurrencyRateSources::CBRF, None, None)
+                .await
+                .map_err(|e| format!("Failed to read CBR rates: {}", e))?;
+            table.insert_archived(&rates);
+        }
+        Ok(table)
+    }
+
+    async fn fill_tickers(&self, report: &mut NdflReport) {
+        let mut tickers: HashMap<String, String> = HashMap::new();
+        for trade in &mut report.trades {
+            if !tickers.contains_key(&trade.figi) {
+                let ticker = self
+                    .mongo_db
+                    .find_instrument_by_figi(&trade.figi)
+                    .await
+                    .map(|instrument| instrument.ticker().to_string())
+                    .unwrap_or_default();
+                tickers.insert(trade.figi.clone(), ticker);
+            }
+            trade.ticker = tickers[&trade.figi].clone();
+        }
+    }
+}
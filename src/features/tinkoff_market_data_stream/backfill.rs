@@ -0,0 +1,107 @@
+use chrono::{DateTime, Utc};
+use prost_types::Timestamp;
+use tonic::Status;
+use tracing::{error, info, warn};
+
+use crate::gen::tinkoff_public_invest_api_contract_v1::{
+    Candle, CandleInterval, GetCandlesRequest, SubscriptionInterval,
+};
+use crate::services::tinkoff::rate_limiter::TinkoffMethod;
+
+use super::MarketDataStreamer;
+
+/// Максимальное окно запроса минутных свечей в GetCandles
+const MAX_BACKFILL_SECONDS: i64 = 24 * 60 * 60;
+
+impl MarketDataStreamer {
+    /// Догружает минутные свечи, пропущенные за время разрыва стрима.
+    /// Для каждого FIGI запрашивает период с последней полученной свечи (или с момента разрыва)
+    /// до текущего момента, но не больше суток
+    pub(super) async fn backfill_missed_candles(&self, figis: &[String], disconnected_at: i64) {
+        let now = Utc::now().timestamp();
+
+        for figi in figis {
+            let last_seen = self.last_candle_seconds.lock().unwrap().get(figi).copied();
+            let mut from = last_seen.unwrap_or(disconnected_at);
+
+            if from < now - MAX_BACKFILL_SECONDS {
+                warn!(
+                    "Stream for {} was down longer than a day, backfilling only the last 24 hours",
+                    figi
+                );
+                from = now - MAX_BACKFILL_SECONDS;
+            }
+            if from >= now {
+                continue;
+            }
+
+            let request = GetCandlesRequest {
+                from: Some(Timestamp {
+                    seconds: from,
+                    nanos: 0,
+                }),
+                to: Some(Timestamp {
+                    seconds: now,
+                    nanos: 0,
+                }),
+                interval: CandleInterval::CandleInterval1Min as i32,
+                instrument_id: figi.clone(),
+                #[allow(deprecated)]
+                figi: figi.clone(),
+            };
+
+            let response = self
+                .client
+                .call_limited(TinkoffMethod::GetCandles, || {
+                    let mut market_data_client = self.client.market_data.clone();
+                    let request = self.client.create_request(request.clone());
+                    async move {
+                        let request = request.map_err(|e| Status::internal(e.to_string()))?;
+                        market_data_client.get_candles(request).await
+                    }
+                })
+                .await;
+
+            let candles = match response {
+                Ok(response) => response.candles,
+                Err(e) => {
+                    error!("Failed to backfill candles for {}: {}", figi, e);
+                    continue;
+                }
+            };
+
+            let count = candles.len();
+            for historic in candles {
+                let candle = Candle {
+                    figi: figi.clone(),
+                    interval: SubscriptionInterval::OneMinute as i32,
+                    open: historic.open,
+                    high: historic.high,
+                    low: historic.low,
+                    close: historic.close,
+                    volume: historic.volume,
+                    time: historic.time,
+                    last_trade_ts: None,
+                    instrument_uid: String::new(),
+                };
+
+                if let Some(time) = &candle.time {
+                    self.last_candle_seconds
+                        .lock()
+                        .unwrap()
+                        .insert(figi.clone(), time.seconds);
+                }
+                self.save_candle_to_mongodb(&candle).await;
+            }
+
+            info!(
+                "Backfilled {} candles for {} since {}",
+                count,
+                figi,
+                DateTime::from_timestamp(from, 0)
+                    .map(|dt| dt.to_rfc3339())
+                    .unwrap_or_default()
+            );
+        }
+    }
+}
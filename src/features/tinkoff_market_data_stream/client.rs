@@ -1,33 +1,51 @@
 
 use mongodb::bson::{doc, Document};
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::sync::Mutex;
+use std::time::Duration;
+
+use chrono::Utc;
 use tokio::sync::mpsc;
 use tokio_stream::wrappers::ReceiverStream;
 use tonic::{metadata::MetadataValue, Request};
 use tracing::{debug, error, info};
 
+use crate::features::core::models::candle_interval::MyCandleInterval;
 use crate::features::db::mongo_db::DbNames;
-use crate::features::db::mongo_extensions::watchlists::models::DbUserConfigWatchlist;
+use crate::features::db::mongo_extensions::candles::candles::candle_unique_index;
+use crate::features::db::mongo_extensions::candles::models::candle_price_bson;
+use crate::features::db::mongo_extensions::watchlists::models::{
+    DbUserConfigWatchlist, DbWatchlistSubscriptions,
+};
 use crate::features::db::MongoDb;
+use crate::features::market_candles::rollup::CandleRollupService;
+use crate::features::market_reference::trading_calendar::TradingCalendar;
 use crate::{
     env_config::models::app_setting::AppSettings,
 
     gen::tinkoff_public_invest_api_contract_v1::{
-        market_data_request, market_data_response, Candle, CandleInstrument, MarketDataRequest,
-        MarketDataResponse, SubscribeCandlesRequest,
+        market_data_response, Candle, MarketDataResponse, SubscriptionAction,
     },
     services::tinkoff::client_grpc::TinkoffClient,
 };
 
+use super::commands::{MarketDataStreamHandle, StreamCommand};
+use super::reconnect::SessionOutcome;
+use super::subscriptions::build_subscription_requests;
+
 pub struct MarketDataStreamer {
-    client: Arc<TinkoffClient>,
-    settings: Arc<AppSettings>,
-    mongo_db: Arc<MongoDb>,
-    figi_list: Vec<String>,
+    pub(super) client: Arc<TinkoffClient>,
+    pub(super) settings: Arc<AppSettings>,
+    pub(super) mongo_db: Arc<MongoDb>,
+    pub(super) subscriptions: Mutex<HashMap<String, DbWatchlistSubscriptions>>, // Виды данных по FIGI
+    command_sender: mpsc::Sender<StreamCommand>,
+    command_receiver: tokio::sync::Mutex<mpsc::Receiver<StreamCommand>>,
+    pub(super) last_candle_seconds: Mutex<HashMap<String, i64>>, // Время последней полученной свечи по FIGI
     indexed_collections: Mutex<HashSet<String>>, // Для отслеживания коллекций, где индекс уже создан
+    pub(super) calendar: TradingCalendar, // Расписание торгов: стрим открыт только на время сессии
+    rollup: Arc<CandleRollupService>, // Сборка старших интервалов из минут стрима
 }
 
 impl MarketDataStreamer {
@@ -37,22 +55,34 @@ impl MarketDataStreamer {
         mongo_db: Arc<MongoDb>,
         watchlists: Vec<DbUserConfigWatchlist>,
     ) -> Self {
-        // Extract FIGIs from watchlists
-        let figi_list = watchlists
+        // Extract FIGIs and their stream settings from watchlists
+        let subscriptions = watchlists
             .into_iter()
-            .filter(|watchlist| watchlist.enabled)
-            .map(|watchlist| watchlist.figi)
+            .filter(|watchlist| watchlist.enabled && !watchlist.subscriptions.is_empty())
+            .map(|watchlist| (watchlist.figi, watchlist.subscriptions))
             .collect();
 
+        let (command_sender, command_receiver) = mpsc::channel(32);
+
         Self {
+            calendar: TradingCalendar::new(client.clone(), mongo_db.clone(), settings.clone()),
+            rollup: Arc::new(CandleRollupService::new(mongo_db.clone(), settings.clone())),
             client,
             settings,
             mongo_db,
-            figi_list,
+            subscriptions: Mutex::new(subscriptions),
+            command_sender,
+            command_receiver: tokio::sync::Mutex::new(command_receiver),
+            last_candle_seconds: Mutex::new(HashMap::new()),
             indexed_collections: Mutex::new(HashSet::new()),
         }
     }
 
+    /// Хэндл для изменения подписок во время работы стрима
+    pub fn handle(&self) -> MarketDataStreamHandle {
+        MarketDataStreamHandle::new(self.command_sender.clone())
+    }
+
     pub async fn start_streaming(&self) {
         info!("Starting market data stream...");
         if !self.settings.app_config.tinkoff_market_data_stream.enabled {
@@ -64,27 +94,56 @@ impl MarketDataStreamer {
             self.settings.app_config.tinkoff_market_data_stream.timezone
         );
 
-        if self.figi_list.is_empty() {
-            info!("No active instruments found in watchlists");
-            return;
+        self.ensure_stream_indexes().await;
+        self.start_rollup_flush().await;
+
+        let mut commands = self.command_receiver.lock().await;
+
+        // Ждём, пока в watchlist появится хотя бы один активный инструмент
+        while self.subscriptions.lock().unwrap().is_empty() {
+            info!("No active instruments found in watchlists, waiting for watchlist changes");
+            match commands.recv().await {
+                Some(command) => {
+                    self.apply_command(command);
+                }
+                None => return,
+            }
         }
 
         info!(
             "Found {} active instruments to stream",
-            self.figi_list.len()
+            self.subscriptions.lock().unwrap().len()
         );
 
-        // Create subscription request for candles
-        let request = self.create_candles_subscription_request();
+        // Supervised loop: reconnects with backoff until retries are exhausted
+        self.run_with_reconnect(&mut commands).await;
+    }
+
+    /// Одна сессия стрима: подключение, подписка на весь текущий набор инструментов и чтение данных
+    /// до обрыва соединения или закрытия торгов (`session_end`, секунды UTC).
+    /// При переподключении догружает пропущенные минуты
+    pub(super) async fn run_stream_session(
+        &self,
+        commands: &mut mpsc::Receiver<StreamCommand>,
+        disconnected_at: Option<i64>,
+        session_end: Option<i64>,
+    ) -> SessionOutcome {
+        // Create subscription requests for every data kind in use
+        let requests = build_subscription_requests(
+            &self.subscribed_instruments(),
+            SubscriptionAction::Subscribe,
+        );
 
         // Create channel for streaming request
-        let (tx, rx) = mpsc::channel(1);
+        let (tx, rx) = mpsc::channel(16);
         let request_stream = ReceiverStream::new(rx);
 
-        // Send request to channel
-        if let Err(e) = tx.send(request).await {
-            error!("Failed to send request to stream: {}", e);
-            return;
+        // Send requests to channel
+        for request in requests {
+            if let Err(e) = tx.send(request).await {
+                error!("Failed to send request to stream: {}", e);
+                return SessionOutcome::Failed;
+            }
         }
 
         // Create stream
@@ -97,55 +156,90 @@ impl MarketDataStreamer {
             .metadata_mut()
             .insert("authorization", auth_header_value);
 
-        match client.market_data_stream(request).await {
+        let mut stream = match client.market_data_stream(request).await {
             Ok(streaming_response) => {
                 info!("Successfully connected to market data stream");
-                let mut stream = streaming_response.into_inner();
-
-                while let Ok(Some(response)) = stream.message().await {
-                    self.handle_market_data_response(response).await;
-                }
-
-                error!("Market data stream ended unexpectedly");
+                streaming_response.into_inner()
             }
             Err(e) => {
                 error!("Failed to create market data stream: {}", e);
+                return SessionOutcome::Failed;
             }
+        };
+
+        // Минуты, пропущенные за время разрыва, догружаем через GetCandles
+        if let Some(disconnected_at) = disconnected_at {
+            self.backfill_missed_candles(&self.candle_figis(), disconnected_at)
+                .await;
         }
-    }
 
-    fn create_candles_subscription_request(&self) -> MarketDataRequest {
-        // Use default 1-minute interval for all instruments
-        let default_interval = 1; // 1-minute interval
+        // Без расписания стрим не закрывается по времени
+        let until_close = session_end
+            .map(|end| Duration::from_secs((end - Utc::now().timestamp()).max(0) as u64))
+            .unwrap_or(Duration::MAX);
+        let session_close = tokio::time::sleep(until_close);
+        tokio::pin!(session_close);
 
-        // Create candle instruments for each FIGI
-        let candle_instruments: Vec<CandleInstrument> = self
-            .figi_list
-            .iter()
-            .map(|figi| CandleInstrument {
-                instrument_id: figi.clone(),
-                interval: default_interval,
-                #[allow(deprecated)]
-                figi: figi.clone(),
-            })
-            .collect();
+        let mut received_any = false;
+        loop {
+            tokio::select! {
+                _ = &mut session_close => {
+                    info!("Trading session is over, closing market data stream");
+                    return SessionOutcome::Closed;
+                }
+                message = stream.message() => match message {
+                    Ok(Some(response)) => {
+                        received_any = true;
+                        self.handle_market_data_response(response).await;
+                    }
+                    Ok(None) => break,
+                    Err(e) => {
+                        error!("Market data stream error: {}", e);
+                        break;
+                    }
+                },
+                Some(command) = commands.recv() => {
+                    // Изменение watchlist: досылаем подписку/отписку в открытый стрим
+                    for request in self.apply_command(command) {
+                        if let Err(e) = tx.send(request).await {
+                            error!("Failed to send subscription change to stream: {}", e);
+                        }
+                    }
+                }
+            }
+        }
 
-        info!(
-            "Created subscription for {} instruments",
-            candle_instruments.len()
-        );
+        error!("Market data stream ended unexpectedly");
 
-        MarketDataRequest {
-            payload: Some(market_data_request::Payload::SubscribeCandlesRequest(
-                SubscribeCandlesRequest {
-                    subscription_action: 1, // SUBSCRIPTION_ACTION_SUBSCRIBE
-                    instruments: candle_instruments,
-                    waiting_close: false,
-                },
-            )),
+        if received_any {
+            SessionOutcome::Established
+        } else {
+            SessionOutcome::Failed
         }
     }
 
+    /// Текущий набор подписок в стабильном порядке
+    pub(super) fn subscribed_instruments(&self) -> Vec<(String, DbWatchlistSubscriptions)> {
+        let mut instruments: Vec<(String, DbWatchlistSubscriptions)> = self
+            .subscriptions
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(figi, subscriptions)| (figi.clone(), *subscriptions))
+            .collect();
+        instruments.sort_by(|a, b| a.0.cmp(&b.0));
+        instruments
+    }
+
+    /// FIGI, по которым стрим получает минутные свечи
+    pub(super) fn candle_figis(&self) -> Vec<String> {
+        self.subscribed_instruments()
+            .into_iter()
+            .filter(|(_, subscriptions)| subscriptions.candles)
+            .map(|(figi, _)| figi)
+            .collect()
+    }
+
     async fn handle_market_data_response(&self, response: MarketDataResponse) {
         match response.payload {
             Some(payload) => match payload {
@@ -155,9 +249,51 @@ impl MarketDataStreamer {
                 market_data_response::Payload::Candle(candle) => {
                     debug!("Received candle update for FIGI {}", candle.figi);
 
+                    // Remember the latest minute to backfill from after a reconnect
+                    if let Some(time) = &candle.time {
+                        self.last_candle_seconds
+                            .lock()
+                            .unwrap()
+                            .insert(candle.figi.clone(), time.seconds);
+                    }
+
                     // Save candle data to MongoDB
                     self.save_candle_to_mongodb(&candle).await;
                 }
+                market_data_response::Payload::SubscribeOrderBookResponse(order_books) => {
+                    info!(
+                        "Received order book subscription response: {:?}",
+                        order_books
+                    );
+                }
+                market_data_response::Payload::SubscribeTradesResponse(trades) => {
+                    info!("Received trades subscription response: {:?}", trades);
+                }
+                market_data_response::Payload::SubscribeLastPriceResponse(last_prices) => {
+                    info!(
+                        "Received last price subscription response: {:?}",
+                        last_prices
+                    );
+                }
+                market_data_response::Payload::SubscribeInfoResponse(info) => {
+                    info!("Received info subscription response: {:?}", info);
+                }
+                market_data_response::Payload::Orderbook(order_book) => {
+                    debug!("Received order book for FIGI {}", order_book.figi);
+                    self.save_order_book(&order_book).await;
+                }
+                market_data_response::Payload::Trade(trade) => {
+                    debug!("Received trade for FIGI {}", trade.figi);
+                    self.save_trade(&trade).await;
+                }
+                market_data_response::Payload::LastPrice(last_price) => {
+                    debug!("Received last price for FIGI {}", last_price.figi);
+                    self.save_last_price(&last_price).await;
+                }
+                market_data_response::Payload::TradingStatus(status) => {
+                    debug!("Received trading status for FIGI {}", status.figi);
+                    self.save_trading_status(&status).await;
+                }
                 _ => {
                     debug!("Received other market data");
                 }
@@ -167,7 +303,7 @@ impl MarketDataStreamer {
             }
         }
     }
-    async fn save_candle_to_mongodb(&self, candle: &Candle) {
+    pub(super) async fn save_candle_to_mongodb(&self, candle: &Candle) {
         // Получаем FIGI для названия коллекции
         let figi = &candle.figi;
 
@@ -191,31 +327,18 @@ impl MarketDataStreamer {
         }
 
         // Получаем коллекцию для данного FIGI
-        let collection = self
-            .mongo_db
-            .client
-            .database(DbNames::MARKET_CANDLES)
-            .collection::<Document>(&collection_name);
+        let collection = self.mongo_db.stream_candles_collection(figi);
 
-        // Просто вставляем документ без проверки существования
+        // Внутри минуты стрим присылает свечу несколько раз: обновляем документ по ключу
+        // (figi, interval, time), а не вставляем новый
         let doc = doc! {
+            "figi": figi,
+            "interval": MyCandleInterval::OneMin.short_name(),
             "volume": candle.volume,
-            "open": {
-                "units": candle.open.as_ref().map_or(0, |q| q.units),
-                "nano": candle.open.as_ref().map_or(0, |q| q.nano)
-            },
-            "high": {
-                "units": candle.high.as_ref().map_or(0, |q| q.units),
-                "nano": candle.high.as_ref().map_or(0, |q| q.nano)
-            },
-            "low": {
-                "units": candle.low.as_ref().map_or(0, |q| q.units),
-                "nano": candle.low.as_ref().map_or(0, |q| q.nano)
-            },
-            "close": {
-                "units": candle.close.as_ref().map_or(0, |q| q.units),
-                "nano": candle.close.as_ref().map_or(0, |q| q.nano)
-            },
+            "open": candle_price_bson(candle.open.as_ref()),
+            "high": candle_price_bson(candle.high.as_ref()),
+            "low": candle_price_bson(candle.low.as_ref()),
+            "close": candle_price_bson(candle.close.as_ref()),
             "time": {
                 "seconds": candle.time.as_ref().map_or(0, |t| t.seconds),
                 "nanos": candle.time.as_ref().map_or(0, |t| t.nanos)
@@ -225,10 +348,39 @@ impl MarketDataStreamer {
                 "nanos": t.nanos
             }),
         };
-        if let Err(e) = collection.insert_one(doc).await {
-            error!("Failed to save candle for {}: {}", figi, e);
+        match self.mongo_db.upsert_candles(&collection, vec![doc]).await {
+            Ok(_) => {
+                if let Some(time) = &candle.time {
+                    self.rollup.mark_dirty(figi, time.seconds);
+                }
+            }
+            Err(e) => error!("Failed to save candle for {}: {}", figi, e),
         }
     }
+
+    /// Периодическая сборка старших интервалов из минут стрима
+    async fn start_rollup_flush(&self) {
+        if !self.rollup.is_enabled() {
+            return;
+        }
+        self.rollup.ensure_indexes().await;
+
+        let rollup = self.rollup.clone();
+        let period = Duration::from_secs(
+            self.settings
+                .app_config
+                .candle_rollup
+                .flush_interval_seconds
+                .max(1),
+        );
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            loop {
+                interval.tick().await;
+                rollup.flush_dirty().await;
+            }
+        });
+    }
     async fn ensure_time_index(&self, collection_name: &str) {
         let collection = self
             .mongo_db
@@ -246,5 +398,11 @@ impl MarketDataStreamer {
             Ok(_) => info!("Created time index for collection {}", collection_name),
             Err(e) => error!("Failed to create time index for {}: {}", collection_name, e),
         }
+        if let Err(e) = collection.create_index(candle_unique_index()).await {
+            error!(
+                "Failed to create unique candle index for {}: {}",
+                collection_name, e
+            );
+        }
     }
 }
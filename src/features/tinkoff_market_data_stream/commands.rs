@@ -0,0 +1,90 @@
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+use crate::features::db::mongo_extensions::watchlists::models::DbWatchlistSubscriptions;
+use crate::gen::tinkoff_public_invest_api_contract_v1::MarketDataRequest;
+
+use super::subscriptions::build_change_requests;
+use super::MarketDataStreamer;
+
+/// Команда управления подписками работающего стрима
+#[derive(Debug)]
+pub enum StreamCommand {
+    /// Подписаться на инструмент или изменить набор получаемых данных
+    Subscribe {
+        figi: String,
+        subscriptions: DbWatchlistSubscriptions,
+    },
+    Unsubscribe(String),
+}
+
+/// Хэндл для изменения подписок стрима без перезапуска процесса.
+/// Клонируется и передаётся в HTTP-обработчики
+#[derive(Clone)]
+pub struct MarketDataStreamHandle {
+    sender: mpsc::Sender<StreamCommand>,
+}
+
+impl MarketDataStreamHandle {
+    pub(super) fn new(sender: mpsc::Sender<StreamCommand>) -> Self {
+        Self { sender }
+    }
+
+    /// Подписаться на инструмент в работающем стриме.
+    /// Для уже подписанного инструмента досылаются только изменения набора данных
+    pub async fn subscribe(&self, figi: &str, subscriptions: DbWatchlistSubscriptions) {
+        self.send(StreamCommand::Subscribe {
+            figi: figi.to_string(),
+            subscriptions,
+        })
+        .await;
+    }
+
+    /// Отписаться от инструмента в работающем стриме
+    pub async fn unsubscribe(&self, figi: &str) {
+        self.send(StreamCommand::Unsubscribe(figi.to_string()))
+            .await;
+    }
+
+    async fn send(&self, command: StreamCommand) {
+        if let Err(e) = self.sender.send(command).await {
+            // Стрим выключен в конфигурации или завершился: изменение подхватится при следующем запуске
+            warn!(
+                "Market data stream is not running, command dropped: {:?}",
+                e.0
+            );
+        }
+    }
+}
+
+impl MarketDataStreamer {
+    /// Применяет команду к набору подписок и возвращает запросы,
+    /// которые нужно отправить в стрим (пусто, если подписка не изменилась)
+    pub(super) fn apply_command(&self, command: StreamCommand) -> Vec<MarketDataRequest> {
+        let mut instruments = self.subscriptions.lock().unwrap();
+
+        match command {
+            StreamCommand::Subscribe {
+                figi,
+                subscriptions,
+            } => {
+                let old = instruments.insert(figi.clone(), subscriptions);
+                if old == Some(subscriptions) {
+                    return Vec::new();
+                }
+                info!(
+                    "Updating market data subscriptions for {}: {:?}",
+                    figi, subscriptions
+                );
+                build_change_requests(&figi, old.as_ref(), Some(&subscriptions))
+            }
+            StreamCommand::Unsubscribe(figi) => {
+                let Some(old) = instruments.remove(&figi) else {
+                    return Vec::new();
+                };
+                info!("Unsubscribing from market data for {}", figi);
+                build_change_requests(&figi, Some(&old), None)
+            }
+        }
+    }
+}
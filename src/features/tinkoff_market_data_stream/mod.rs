@@ -1,4 +1,10 @@
+mod backfill;
 pub mod client;
+pub mod commands;
+mod persistence;
+mod reconnect;
+mod subscriptions;
 
 // Re-export the MarketDataStreamer struct for easier access
-pub use client::MarketDataStreamer;
\ No newline at end of file
+pub use client::MarketDataStreamer;
+pub use commands::MarketDataStreamHandle;
@@ -0,0 +1,182 @@
+use mongodb::bson::{doc, Bson, Document};
+use mongodb::{Collection, IndexModel};
+use tracing::{error, info};
+
+use crate::features::db::mongo_db::{Collections, DbNames};
+use crate::features::db::mongo_extensions::candles::models::candle_price_bson;
+use crate::gen::tinkoff_public_invest_api_contract_v1::{
+    LastPrice, Order, OrderBook, Quotation, SecurityTradingStatus, Trade, TradeDirection,
+    TradingStatus,
+};
+
+use super::MarketDataStreamer;
+
+/// Цена в том же виде, что и в документах свечей
+fn quotation_doc(quotation: Option<&Quotation>) -> Bson {
+    candle_price_bson(quotation)
+}
+
+fn timestamp_doc(timestamp: Option<&prost_types::Timestamp>) -> Document {
+    doc! {
+        "seconds": timestamp.map_or(0, |t| t.seconds),
+        "nanos": timestamp.map_or(0, |t| t.nanos)
+    }
+}
+
+fn orders_bson(orders: &[Order]) -> Vec<Bson> {
+    orders
+        .iter()
+        .map(|order| {
+            Bson::Document(doc! {
+                "price": quotation_doc(order.price.as_ref()),
+                "quantity": order.quantity
+            })
+        })
+        .collect()
+}
+
+impl MarketDataStreamer {
+    fn stream_collection(&self, name: &str) -> Collection<Document> {
+        self.mongo_db
+            .client
+            .database(DbNames::MARKET_STREAM)
+            .collection::<Document>(name)
+    }
+
+    /// Индексы коллекций стакана, сделок, последних цен и статусов.
+    /// Последняя цена и статус хранятся в одном документе на FIGI
+    pub(super) async fn ensure_stream_indexes(&self) {
+        let indexes = [
+            (
+                Collections::TINKOFF_ORDER_BOOKS,
+                doc! { "figi": 1, "time.seconds": -1 },
+                false,
+            ),
+            (
+                Collections::TINKOFF_TRADES,
+                doc! { "figi": 1, "time.seconds": -1 },
+                false,
+            ),
+            (Collections::TINKOFF_LAST_PRICES, doc! { "figi": 1 }, true),
+            (
+                Collections::TINKOFF_TRADING_STATUSES,
+                doc! { "figi": 1 },
+                true,
+            ),
+        ];
+
+        for (collection_name, keys, unique) in indexes {
+            let index = IndexModel::builder()
+                .keys(keys)
+                .options(
+                    mongodb::options::IndexOptions::builder()
+                        .unique(unique)
+                        .build(),
+                )
+                .build();
+
+            match self
+                .stream_collection(collection_name)
+                .create_index(index)
+                .await
+            {
+                Ok(_) => info!("Ensured index for collection {}", collection_name),
+                Err(e) => error!("Failed to create index for {}: {}", collection_name, e),
+            }
+        }
+    }
+
+    /// Снимок стакана сохраняется целиком, история не перезаписывается
+    pub(super) async fn save_order_book(&self, order_book: &OrderBook) {
+        let doc = doc! {
+            "figi": &order_book.figi,
+            "instrument_uid": &order_book.instrument_uid,
+            "depth": order_book.depth,
+            "is_consistent": order_book.is_consistent,
+            "bids": orders_bson(&order_book.bids),
+            "asks": orders_bson(&order_book.asks),
+            "limit_up": quotation_doc(order_book.limit_up.as_ref()),
+            "limit_down": quotation_doc(order_book.limit_down.as_ref()),
+            "time": timestamp_doc(order_book.time.as_ref()),
+        };
+
+        if let Err(e) = self
+            .stream_collection(Collections::TINKOFF_ORDER_BOOKS)
+            .insert_one(doc)
+            .await
+        {
+            error!("Failed to save order book for {}: {}", order_book.figi, e);
+        }
+    }
+
+    pub(super) async fn save_trade(&self, trade: &Trade) {
+        let direction = TradeDirection::try_from(trade.direction)
+            .map(|d| d.as_str_name())
+            .unwrap_or("TRADE_DIRECTION_UNSPECIFIED");
+
+        let doc = doc! {
+            "figi": &trade.figi,
+            "instrument_uid": &trade.instrument_uid,
+            "direction": direction,
+            "price": quotation_doc(trade.price.as_ref()),
+            "quantity": trade.quantity,
+            "time": timestamp_doc(trade.time.as_ref()),
+        };
+
+        if let Err(e) = self
+            .stream_collection(Collections::TINKOFF_TRADES)
+            .insert_one(doc)
+            .await
+        {
+            error!("Failed to save trade for {}: {}", trade.figi, e);
+        }
+    }
+
+    /// Хранится только последняя цена по каждому FIGI
+    pub(super) async fn save_last_price(&self, last_price: &LastPrice) {
+        let update = doc! {
+            "$set": {
+                "figi": &last_price.figi,
+                "instrument_uid": &last_price.instrument_uid,
+                "price": quotation_doc(last_price.price.as_ref()),
+                "time": timestamp_doc(last_price.time.as_ref()),
+            }
+        };
+
+        if let Err(e) = self
+            .stream_collection(Collections::TINKOFF_LAST_PRICES)
+            .update_one(doc! { "figi": &last_price.figi }, update)
+            .upsert(true)
+            .await
+        {
+            error!("Failed to save last price for {}: {}", last_price.figi, e);
+        }
+    }
+
+    /// Хранится только текущий торговый статус по каждому FIGI
+    pub(super) async fn save_trading_status(&self, status: &TradingStatus) {
+        let trading_status = SecurityTradingStatus::try_from(status.trading_status)
+            .map(|s| s.as_str_name())
+            .unwrap_or("SECURITY_TRADING_STATUS_UNSPECIFIED");
+
+        let update = doc! {
+            "$set": {
+                "figi": &status.figi,
+                "instrument_uid": &status.instrument_uid,
+                "trading_status": trading_status,
+                "limit_order_available_flag": status.limit_order_available_flag,
+                "market_order_available_flag": status.market_order_available_flag,
+                "time": timestamp_doc(status.time.as_ref()),
+            }
+        };
+
+        if let Err(e) = self
+            .stream_collection(Collections::TINKOFF_TRADING_STATUSES)
+            .update_one(doc! { "figi": &status.figi }, update)
+            .upsert(true)
+            .await
+        {
+            error!("Failed to save trading status for {}: {}", status.figi, e);
+        }
+    }
+}
@@ -0,0 +1,193 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use tokio::sync::mpsc;
+use tracing::{error, info, warn};
+
+use crate::features::market_reference::trading_calendar::SessionState;
+
+use super::{commands::StreamCommand, MarketDataStreamer};
+
+/// Верхняя граница задержки между переподключениями
+const MAX_BACKOFF_SECONDS: u64 = 300;
+
+/// Как часто перепроверять расписание, пока торгов нет и время открытия неизвестно
+const SCHEDULE_RECHECK_SECONDS: u64 = 600;
+
+/// Результат одной сессии стрима
+pub(super) enum SessionOutcome {
+    /// Соединение было установлено и данные приходили
+    Established,
+    /// Подключиться не удалось или стрим оборвался сразу
+    Failed,
+    /// Торговая сессия закончилась, стрим закрыт до следующей
+    Closed,
+}
+
+/// Экспоненциальная задержка: `base * 2^(attempt - 1)`, не больше [`MAX_BACKOFF_SECONDS`],
+/// умноженная на коэффициент джиттера (0.5..=1.0), чтобы реплики не переподключались синхронно
+pub(super) fn backoff_delay(base_seconds: u64, attempt: u32, jitter: f64) -> Duration {
+    let exponent = attempt.saturating_sub(1).min(16);
+    let delay_seconds = base_seconds
+        .max(1)
+        .saturating_mul(1u64 << exponent)
+        .min(MAX_BACKOFF_SECONDS);
+
+    Duration::from_secs_f64(delay_seconds as f64 * jitter.clamp(0.0, 1.0))
+}
+
+fn jitter_factor() -> f64 {
+    rand::thread_rng().gen_range(0.5..=1.0)
+}
+
+impl MarketDataStreamer {
+    /// Держит стрим открытым: после обрыва переподключается с экспоненциальной задержкой.
+    /// Счётчик попыток сбрасывается после каждой успешной сессии; после `max_retries`
+    /// неудачных попыток подряд стрим останавливается
+    pub(super) async fn run_with_reconnect(&self, commands: &mut mpsc::Receiver<StreamCommand>) {
+        let config = &self.settings.app_config.tinkoff_market_data_stream;
+        let mut failed_attempts: u32 = 0;
+        let mut disconnected_at: Option<i64> = None;
+
+        loop {
+            let session_end = self.wait_for_session(commands).await;
+
+            match self
+                .run_stream_session(commands, disconnected_at, session_end)
+                .await
+            {
+                SessionOutcome::Established => {
+                    failed_attempts = 0;
+                    disconnected_at = Some(Utc::now().timestamp());
+                }
+                SessionOutcome::Failed => {
+                    // Момент разрыва не сдвигаем: догрузка должна покрыть весь простой
+                }
+                SessionOutcome::Closed => {
+                    // Штатное закрытие: догружать нечего, ждём следующую сессию
+                    failed_attempts = 0;
+                    disconnected_at = None;
+                    continue;
+                }
+            }
+
+            failed_attempts += 1;
+            if failed_attempts > config.max_retries {
+                error!(
+                    "Market data stream reconnect failed {} times in a row, giving up",
+                    config.max_retries
+                );
+                return;
+            }
+
+            let delay = backoff_delay(config.retry_delay_seconds, failed_attempts, jitter_factor());
+            warn!(
+                "Reconnecting to market data stream in {:.1}s (attempt {}/{})",
+                delay.as_secs_f64(),
+                failed_attempts,
+                config.max_retries
+            );
+            self.wait_before_reconnect(delay, commands).await;
+        }
+    }
+
+    /// Ждёт начала торговой сессии по расписанию площадки по умолчанию и возвращает
+    /// время её окончания. Если расписание на сегодня неизвестно, стрим работает
+    /// в окне update_start_time/update_end_time без закрытия по времени
+    async fn wait_for_session(&self, commands: &mut mpsc::Receiver<StreamCommand>) -> Option<i64> {
+        let config = &self.settings.app_config.tinkoff_market_data_stream;
+        let days_ahead = self.settings.app_config.trading_calendar.days_ahead as i64;
+
+        loop {
+            let now = Utc::now();
+            let today = now.date_naive();
+            self.calendar
+                .ensure_range(today, today + chrono::Duration::days(days_ahead + 1))
+                .await;
+
+            let delay = match self.calendar.session_state(now, days_ahead) {
+                Some(SessionState::Open { until }) => return Some(until),
+                Some(SessionState::Closed {
+                    opens_at: Some(opens_at),
+                }) => {
+                    info!(
+                        "{} trading session is closed, market data stream opens at {}",
+                        self.calendar.default_exchange(),
+                        DateTime::from_timestamp(opens_at, 0)
+                            .map(|dt| dt.to_rfc3339())
+                            .unwrap_or_default()
+                    );
+                    // Расписание могло обновиться: перепроверяем не реже раза в сутки
+                    Duration::from_secs(((opens_at - now.timestamp()).max(1) as u64).min(86_400))
+                }
+                Some(SessionState::Closed { opens_at: None }) => {
+                    info!("No upcoming trading sessions in the schedule, rechecking later");
+                    Duration::from_secs(SCHEDULE_RECHECK_SECONDS)
+                }
+                None if config.is_update_time() => return None,
+                None => {
+                    info!(
+                        "No trading schedule for today and outside stream window ({}-{})",
+                        config.update_start_time, config.update_end_time
+                    );
+                    Duration::from_secs(SCHEDULE_RECHECK_SECONDS)
+                }
+            };
+
+            self.apply_commands_for(delay, commands).await;
+        }
+    }
+
+    /// Пауза перед переподключением. Команды watchlist продолжают применяться
+    /// к набору подписок и будут отправлены при следующем подключении
+    async fn wait_before_reconnect(
+        &self,
+        delay: Duration,
+        commands: &mut mpsc::Receiver<StreamCommand>,
+    ) {
+        self.apply_commands_for(delay, commands).await;
+        info!("Reconnecting to market data stream");
+    }
+
+    /// Применяет команды watchlist к набору подписок в течение `delay`
+    async fn apply_commands_for(
+        &self,
+        delay: Duration,
+        commands: &mut mpsc::Receiver<StreamCommand>,
+    ) {
+        let sleep = tokio::time::sleep(delay);
+        tokio::pin!(sleep);
+
+        loop {
+            tokio::select! {
+                _ = &mut sleep => break,
+                Some(command) = commands.recv() => {
+                    self.apply_command(command);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_grows_exponentially() {
+        assert_eq!(backoff_delay(5, 1, 1.0), Duration::from_secs(5));
+        assert_eq!(backoff_delay(5, 2, 1.0), Duration::from_secs(10));
+        assert_eq!(backoff_delay(5, 4, 1.0), Duration::from_secs(40));
+    }
+
+    #[test]
+    fn test_backoff_is_capped_and_jittered() {
+        assert_eq!(
+            backoff_delay(30, 10, 1.0),
+            Duration::from_secs(MAX_BACKOFF_SECONDS)
+        );
+        assert_eq!(backoff_delay(10, 1, 0.5), Duration::from_secs(5));
+        assert_eq!(backoff_delay(0, 1, 1.0), Duration::from_secs(1));
+    }
+}
@@ -0,0 +1,297 @@
+use tracing::info;
+
+use crate::features::db::mongo_extensions::watchlists::models::DbWatchlistSubscriptions;
+use crate::gen::tinkoff_public_invest_api_contract_v1::{
+    market_data_request, CandleInstrument, InfoInstrument, LastPriceInstrument, MarketDataRequest,
+    OrderBookInstrument, SubscribeCandlesRequest, SubscribeInfoRequest, SubscribeLastPriceRequest,
+    SubscribeOrderBookRequest, SubscribeTradesRequest, SubscriptionAction, TradeInstrument,
+};
+
+/// Строит запросы подписки/отписки для набора инструментов:
+/// по одному запросу на каждый вид данных, в который попал хотя бы один FIGI
+pub(super) fn build_subscription_requests(
+    instruments: &[(String, DbWatchlistSubscriptions)],
+    action: SubscriptionAction,
+) -> Vec<MarketDataRequest> {
+    let figis_with = |enabled: fn(&DbWatchlistSubscriptions) -> bool| -> Vec<String> {
+        instruments
+            .iter()
+            .filter(|(_, subscriptions)| enabled(subscriptions))
+            .map(|(figi, _)| figi.clone())
+            .collect()
+    };
+
+    let mut requests = Vec::new();
+
+    let candles = figis_with(|s| s.candles);
+    if !candles.is_empty() {
+        requests.push(create_candles_subscription_request(&candles, action));
+    }
+
+    let order_books: Vec<OrderBookInstrument> = instruments
+        .iter()
+        .filter(|(_, subscriptions)| subscriptions.order_book)
+        .map(|(figi, subscriptions)| OrderBookInstrument {
+            instrument_id: figi.clone(),
+            depth: subscriptions.order_book_depth,
+            #[allow(deprecated)]
+            figi: figi.clone(),
+        })
+        .collect();
+    if !order_books.is_empty() {
+        log_request("order book", action, order_books.len());
+        requests.push(MarketDataRequest {
+            payload: Some(market_data_request::Payload::SubscribeOrderBookRequest(
+                SubscribeOrderBookRequest {
+                    subscription_action: action as i32,
+                    instruments: order_books,
+                },
+            )),
+        });
+    }
+
+    let trades = figis_with(|s| s.trades);
+    if !trades.is_empty() {
+        log_request("trades", action, trades.len());
+        requests.push(MarketDataRequest {
+            payload: Some(market_data_request::Payload::SubscribeTradesRequest(
+                SubscribeTradesRequest {
+                    subscription_action: action as i32,
+                    instruments: trades
+                        .into_iter()
+                        .map(|figi| TradeInstrument {
+                            instrument_id: figi.clone(),
+                            #[allow(deprecated)]
+                            figi,
+                        })
+                        .collect(),
+                },
+            )),
+        });
+    }
+
+    let last_prices = figis_with(|s| s.last_price);
+    if !last_prices.is_empty() {
+        log_request("last price", action, last_prices.len());
+        requests.push(MarketDataRequest {
+            payload: Some(market_data_request::Payload::SubscribeLastPriceRequest(
+                SubscribeLastPriceRequest {
+                    subscription_action: action as i32,
+                    instruments: last_prices
+                        .into_iter()
+                        .map(|figi| LastPriceInstrument {
+                            instrument_id: figi.clone(),
+                            #[allow(deprecated)]
+                            figi,
+                        })
+                        .collect(),
+                },
+            )),
+        });
+    }
+
+    let info = figis_with(|s| s.info);
+    if !info.is_empty() {
+        log_request("info", action, info.len());
+        requests.push(MarketDataRequest {
+            payload: Some(market_data_request::Payload::SubscribeInfoRequest(
+                SubscribeInfoRequest {
+                    subscription_action: action as i32,
+                    instruments: info
+                        .into_iter()
+                        .map(|figi| InfoInstrument {
+                            instrument_id: figi.clone(),
+                            #[allow(deprecated)]
+                            figi,
+                        })
+                        .collect(),
+                },
+            )),
+        });
+    }
+
+    requests
+}
+
+/// Запросы, переводящие инструмент из старого набора подписок в новый.
+/// None означает «не подписан». Смена глубины стакана — это отписка
+/// от старой глубины и подписка на новую
+pub(super) fn build_change_requests(
+    figi: &str,
+    old: Option<&DbWatchlistSubscriptions>,
+    new: Option<&DbWatchlistSubscriptions>,
+) -> Vec<MarketDataRequest> {
+    let none = DbWatchlistSubscriptions {
+        candles: false,
+        order_book: false,
+        trades: false,
+        last_price: false,
+        info: false,
+        ..Default::default()
+    };
+    let old = old.copied().unwrap_or(none);
+    let new = new.copied().unwrap_or(none);
+
+    let depth_changed = old.order_book_depth != new.order_book_depth;
+    let removed = DbWatchlistSubscriptions {
+        candles: old.candles && !new.candles,
+        order_book: old.order_book && (!new.order_book || depth_changed),
+        order_book_depth: old.order_book_depth,
+        trades: old.trades && !new.trades,
+        last_price: old.last_price && !new.last_price,
+        info: old.info && !new.info,
+    };
+    let added = DbWatchlistSubscriptions {
+        candles: new.candles && !old.candles,
+        order_book: new.order_book && (!old.order_book || depth_changed),
+        order_book_depth: new.order_book_depth,
+        trades: new.trades && !old.trades,
+        last_price: new.last_price && !old.last_price,
+        info: new.info && !old.info,
+    };
+
+    let mut requests = build_subscription_requests(
+        &[(figi.to_string(), removed)],
+        SubscriptionAction::Unsubscribe,
+    );
+    requests.extend(build_subscription_requests(
+        &[(figi.to_string(), added)],
+        SubscriptionAction::Subscribe,
+    ));
+    requests
+}
+
+pub(super) fn create_candles_subscription_request(
+    figis: &[String],
+    action: SubscriptionAction,
+) -> MarketDataRequest {
+    // Use default 1-minute interval for all instruments
+    let default_interval = 1; // 1-minute interval
+
+    // Create candle instruments for each FIGI
+    let candle_instruments: Vec<CandleInstrument> = figis
+        .iter()
+        .map(|figi| CandleInstrument {
+            instrument_id: figi.clone(),
+            interval: default_interval,
+            #[allow(deprecated)]
+            figi: figi.clone(),
+        })
+        .collect();
+
+    log_request("candles", action, candle_instruments.len());
+
+    MarketDataRequest {
+        payload: Some(market_data_request::Payload::SubscribeCandlesRequest(
+            SubscribeCandlesRequest {
+                subscription_action: action as i32,
+                instruments: candle_instruments,
+                waiting_close: false,
+            },
+        )),
+    }
+}
+
+fn log_request(kind: &str, action: SubscriptionAction, count: usize) {
+    info!(
+        "Created {} {} request for {} instruments",
+        kind,
+        action.as_str_name(),
+        count
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(requests: &[MarketDataRequest]) -> Vec<(&'static str, i32)> {
+        requests
+            .iter()
+            .map(|request| match request.payload.as_ref().unwrap() {
+                market_data_request::Payload::SubscribeCandlesRequest(r) => {
+                    ("candles", r.subscription_action)
+                }
+                market_data_request::Payload::SubscribeOrderBookRequest(r) => {
+                    ("order_book", r.subscription_action)
+                }
+                market_data_request::Payload::SubscribeTradesRequest(r) => {
+                    ("trades", r.subscription_action)
+                }
+                market_data_request::Payload::SubscribeLastPriceRequest(r) => {
+                    ("last_price", r.subscription_action)
+                }
+                market_data_request::Payload::SubscribeInfoRequest(r) => {
+                    ("info", r.subscription_action)
+                }
+                _ => ("other", 0),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_subscription_requests_per_kind() {
+        let instruments = vec![
+            ("FIGI1".to_string(), DbWatchlistSubscriptions::default()),
+            (
+                "FIGI2".to_string(),
+                DbWatchlistSubscriptions {
+                    candles: false,
+                    order_book: true,
+                    order_book_depth: 20,
+                    last_price: true,
+                    ..Default::default()
+                },
+            ),
+        ];
+
+        let requests = build_subscription_requests(&instruments, SubscriptionAction::Subscribe);
+        let subscribe = SubscriptionAction::Subscribe as i32;
+        assert_eq!(
+            kinds(&requests),
+            vec![
+                ("candles", subscribe),
+                ("order_book", subscribe),
+                ("last_price", subscribe)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_change_requests_resubscribe_on_depth_change() {
+        let old = DbWatchlistSubscriptions {
+            order_book: true,
+            trades: true,
+            ..Default::default()
+        };
+        let new = DbWatchlistSubscriptions {
+            order_book: true,
+            order_book_depth: 50,
+            info: true,
+            ..Default::default()
+        };
+
+        let requests = build_change_requests("FIGI1", Some(&old), Some(&new));
+        let subscribe = SubscriptionAction::Subscribe as i32;
+        let unsubscribe = SubscriptionAction::Unsubscribe as i32;
+        assert_eq!(
+            kinds(&requests),
+            vec![
+                ("order_book", unsubscribe),
+                ("trades", unsubscribe),
+                ("order_book", subscribe),
+                ("info", subscribe)
+            ]
+        );
+
+        assert!(build_change_requests("FIGI1", Some(&new), Some(&new)).is_empty());
+        assert_eq!(
+            kinds(&build_change_requests("FIGI1", Some(&old), None)),
+            vec![
+                ("candles", unsubscribe),
+                ("order_book", unsubscribe),
+                ("trades", unsubscribe)
+            ]
+        );
+    }
+}
@@ -1,34 +1,52 @@
 // src/features/market_reference/currency_rates/updater.rs
 use crate::{
-    env_config::models::app_setting::AppSettings,
-    features::db::MongoDb,
-    features::moex_api::client::MoexApiClient,
+    env_config::models::{app_config::CurrencyRateConfig, app_setting::AppSettings},
+    features::db::{mongo_extensions::currency_rates::mappers::MoexRatesMapper, MongoDb},
+    features::cbr_api::client::CbrApiClient,
+    features::moex_api::{client::MoexApiClient, models::MoexRatesResponse},
 };
 
+use chrono::{NaiveDate, Utc};
 use std::sync::Arc;
-use tracing::{error, info};
+use tracing::{error, info, warn};
+
+/// За сколько последних дней перезапрашивать курсы ЦБ при ежедневной догрузке
+const CBR_REFRESH_DAYS: i64 = 7;
 
 pub struct CurrencyRatesUpdater {
     api_client: MoexApiClient,
+    cbr_client: CbrApiClient,
     mongo_db: Arc<MongoDb>,
     settings: Arc<AppSettings>,
+    /// Валюты из конфигурации; сверяются с каждым ответом MOEX, но не удаляются из списка
+    currencies: Vec<CurrencyRateConfig>,
+    /// Проблемы последней сверки, чтобы не повторять одно и то же в логе
+    currency_problems: Vec<String>,
+    /// День последней догрузки курсов ЦБ
+    cbr_loaded_on: Option<NaiveDate>,
 }
 
 impl CurrencyRatesUpdater {
     pub fn new(
         api_client: MoexApiClient, 
+        cbr_client: CbrApiClient,
         mongo_db: Arc<MongoDb>,
         settings: Arc<AppSettings>,
     ) -> Self {
+        let currencies = settings.app_config.currency_rates.currencies.clone();
         Self {
             api_client,
+            cbr_client,
             mongo_db,
             settings,
+            currencies,
+            currency_problems: Vec::new(),
+            cbr_loaded_on: None,
         }
     }
 
     /// Запуск цикла обновления курсов валют
-    pub async fn start_update_loop(self) {
+    pub async fn start_update_loop(mut self) {
         info!("Starting currency rates update scheduler");
 
         if !self.settings.app_config.currency_rates_updater.enabled {
@@ -42,6 +60,18 @@ impl CurrencyRatesUpdater {
             self.settings.app_config.currency_rates_updater.timezone
         );
 
+        // Сверка колонок валют с тем, что реально отдаёт MOEX; дальше она повторяется
+        // на каждом обновлении
+        match self.api_client.get_currency_rates().await {
+            Ok(moex_rates) => {
+                self.check_currencies(&moex_rates);
+            }
+            Err(e) => warn!("Could not validate currency config against MOEX: {}", e),
+        }
+
+        self.backfill_cbr_history().await;
+        self.cbr_loaded_on = Some(Utc::now().date_naive());
+
         let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(
             self.settings.app_config.currency_rates_updater.interval_seconds
         ));
@@ -60,18 +90,25 @@ impl CurrencyRatesUpdater {
 
             info!("Updating currency rates information");
             self.schedule_update().await;
+            self.refresh_cbr_rates().await;
         }
     }
 
     /// Запланировать одно обновление курсов валют
-    async fn schedule_update(&self) {
+    async fn schedule_update(&mut self) {
         info!("Scheduling currency rates update");
         
         // Получаем данные от API
         match self.api_client.get_currency_rates().await {
             Ok(moex_rates) => {
+                let currencies = self.check_currencies(&moex_rates);
+
                 // Use MongoDb implementation directly instead of repository
-                match self.mongo_db.save_currency_rates(&moex_rates).await {
+                match self
+                    .mongo_db
+                    .save_currency_rates(&moex_rates, &currencies)
+                    .await
+                {
                     Ok(currency_rates) => {
                         info!("Currency rates updated successfully. Date: {}", currency_rates.date);
                     },
@@ -85,4 +122,71 @@ impl CurrencyRatesUpdater {
             }
         }
     }
-}
\ No newline at end of file
+
+    /// Догружает в архив историю официальных курсов ЦБ с history_start: MOEX отдаёт
+    /// только текущий курс, а оценкам за прошлые годы нужны курсы на даты операций
+    async fn backfill_cbr_history(&self) {
+        let history_start = &self.settings.app_config.currency_rates.history_start;
+        match NaiveDate::parse_from_str(history_start, "%Y-%m-%d") {
+            Ok(from) => self.load_cbr_rates(from).await,
+            Err(e) => error!("Invalid currency_rates.history_start '{}': {}", history_start, e),
+        }
+    }
+
+    /// Раз в день догружает курсы ЦБ за последнюю неделю: у валют без колонок cbrf
+    /// в ответе MOEX (юань) другого источника официального курса нет
+    async fn refresh_cbr_rates(&mut self) {
+        let today = Utc::now().date_naive();
+        if self.cbr_loaded_on == Some(today) {
+            return;
+        }
+        self.load_cbr_rates(today - chrono::Duration::days(CBR_REFRESH_DAYS)).await;
+        self.cbr_loaded_on = Some(today);
+    }
+
+    /// Загружает курсы ЦБ с `from` по сегодня для валют с cbr_id
+    async fn load_cbr_rates(&self, from: NaiveDate) {
+        let today = Utc::now().date_naive();
+        for currency in &self.currencies {
+            let Some(cbr_id) = &currency.cbr_id else {
+                continue;
+            };
+
+            let rates = match self.cbr_client.get_rate_dynamics(cbr_id, from, today).await {
+                Ok(rates) => rates,
+                Err(e) => {
+                    warn!("Failed to load CBR rates for {}: {}", currency.code, e);
+                    continue;
+                }
+            };
+            match self.mongo_db.archive_cbr_rates(&currency.code, &rates).await {
+                Ok(inserted) => info!(
+                    "CBR rates for {} since {}: {} received, {} new",
+                    currency.code,
+                    from,
+                    rates.len(),
+                    inserted
+                ),
+                Err(e) => error!("Failed to archive CBR rates for {}: {}", currency.code, e),
+            }
+        }
+    }
+
+    /// Валюты, все колонки которых есть в этом ответе MOEX. Остальные пропускаются только
+    /// в этом обновлении и проверяются снова в следующем; проблемы пишутся в лог при изменении
+    fn check_currencies(&mut self, moex_rates: &MoexRatesResponse) -> Vec<CurrencyRateConfig> {
+        let (valid, problems) = MoexRatesMapper::validate_currencies(moex_rates, &self.currencies);
+        if problems != self.currency_problems {
+            for problem in &problems {
+                error!("Currency skipped in rates update, {}", problem);
+            }
+            info!(
+                "Currency rates config validated against MOEX: {} of {} currencies enabled",
+                valid.len(),
+                self.currencies.len()
+            );
+            self.currency_problems = problems;
+        }
+        valid
+    }
+}
@@ -2,7 +2,10 @@ use crate::{
     layers::{create_cors, create_trace},
     logger::init_logger,
 };
-use axum::{routing::get, Router};
+use axum::{
+    routing::{get, patch},
+    Router,
+};
 use dotenv::dotenv;
 use env_config::models::{
     app_config::AppConfig,
@@ -11,13 +14,18 @@ use env_config::models::{
 };
 use features::market_candles::tinkoff_shares_1m_historical::scheduler::initialize_historical_candle_services;
 use features::{
+    bond_analytics::{BondAnalyticsJob, BondAnalyticsService},
     db::{mongo_extensions::watchlists::models::DbUserConfigWatchlist, MongoDb},
     market_candles::tinkoff_shares_1m_historical::{
         scheduler::start_historical_candle_service, service::HistoricalCandleDataService,
     },
     market_data::TinkoffInstrumentsUpdater,
+    market_reference::{bond_coupons::BondCouponCollector, dividends::DividendCollector},
+    cbr_api::CbrApiClient,
     moex_api::MoexApiClient,
-    tinkoff_market_data_stream::MarketDataStreamer,
+    portfolio::{OperationsLedgerService, PnlService, PortfolioService, ValuationService},
+    tax::TaxService,
+    tinkoff_market_data_stream::{MarketDataStreamHandle, MarketDataStreamer},
     update::currency_rates::updater::CurrencyRatesUpdater,
 };
 
@@ -76,16 +84,118 @@ async fn setup_databases(settings: &AppSettings) -> MongoDb {
     // Connect to MongoDB
     let mongo_db = MongoDb::connect(settings).await;
 
+    // One-off data migrations, must finish before candle writers start
+    mongo_db.run_candles_dedup_migration().await;
+
+    // Field-level history of instrument catalogue changes
+    mongo_db.ensure_instrument_changelog_indexes().await;
+
+    // Cache of exchange trading schedules
+    mongo_db.ensure_trading_schedules_indexes().await;
+
+    // Dated currency rates archive for valuation and tax calculations
+    mongo_db.ensure_currency_rates_indexes().await;
+
+    // Accounts and portfolio snapshots
+    mongo_db.ensure_portfolio_indexes().await;
+
+    // Operations ledger and its sync cursors
+    mongo_db.ensure_operations_indexes().await;
+
+    // Dividend calendar and history
+    mongo_db.ensure_dividends_indexes().await;
+
+    // Bond coupon calendars and accrued interest
+    mongo_db.ensure_bond_coupons_indexes().await;
+
+    // Nightly bond yield, duration and convexity
+    mongo_db.ensure_bond_analytics_indexes().await;
+
     mongo_db
 }
 
 /// Create and configure the application router
-fn create_app(mongo_db: MongoDb) -> Router {
+fn create_app(
+    mongo_db: MongoDb,
+    stream_handle: MarketDataStreamHandle,
+    pnl_service: Arc<PnlService>,
+    tax_service: Arc<TaxService>,
+    valuation_service: Arc<ValuationService>,
+    bond_analytics_service: Arc<BondAnalyticsService>,
+) -> Router {
     Router::new()
         .layer(create_cors())
         .route("/api-health", get(api::health_api))
         .route("/db-health", get(api::health_db))
+        .route("/api/bonds/{figi}/coupons", get(api::get_bond_coupons))
+        .route(
+            "/api/bonds/{figi}/accrued-interest",
+            get(api::get_bond_accrued_interest),
+        )
+        .route("/api/bonds/{figi}/analytics", get(api::get_bond_analytics))
+        .route(
+            "/api/bonds/{figi}/analytics/history",
+            get(api::get_bond_analytics_history),
+        )
+        .route("/api/candles/{figi}", get(api::get_candles))
+        .route("/api/candles/{figi}/gaps", get(api::get_candle_gaps))
+        .route("/api/currency-rates/{currency}", get(api::get_currency_rate))
+        .route(
+            "/api/currency-rates/{currency}/history",
+            get(api::get_currency_rate_history),
+        )
+        .route("/api/dividends/upcoming", get(api::get_upcoming_dividends))
+        .route("/api/dividends/{figi}", get(api::get_dividend_history))
+        .route("/api/instruments", get(api::search_instruments))
+        .route("/api/instruments/{figi}", get(api::get_instrument))
+        .route(
+            "/api/instruments/{figi}/changes",
+            get(api::get_instrument_changes),
+        )
+        .route("/api/portfolio/accounts", get(api::list_accounts))
+        .route("/api/portfolio/pnl", get(api::get_aggregate_pnl))
+        .route("/api/portfolio/{account_id}", get(api::get_portfolio))
+        .route(
+            "/api/portfolio/{account_id}/snapshots",
+            get(api::get_portfolio_snapshots),
+        )
+        .route(
+            "/api/portfolio/{account_id}/snapshots/{day}",
+            get(api::get_portfolio_snapshot),
+        )
+        .route(
+            "/api/portfolio/{account_id}/operations",
+            get(api::get_operations),
+        )
+        .route("/api/portfolio/{account_id}/pnl", get(api::get_account_pnl))
+        .route(
+            "/api/portfolio/{account_id}/coupons",
+            get(api::get_coupon_projection),
+        )
+        .route(
+            "/api/portfolio/{account_id}/dividends",
+            get(api::get_dividend_projection),
+        )
+        .route(
+            "/api/portfolio/{account_id}/valuation",
+            get(api::get_portfolio_valuation),
+        )
+        .route("/api/tax/ndfl/{year}", get(api::get_ndfl_report))
+        .route("/api/tax/ndfl/{year}/csv", get(api::get_ndfl_report_csv))
+        .route(
+            "/api/watchlists",
+            get(api::list_watchlists).post(api::create_watchlist),
+        )
+        .route(
+            "/api/watchlists/{id}",
+            patch(api::update_watchlist).delete(api::delete_watchlist),
+        )
         .layer(axum::Extension(mongo_db.clone()))
+        .layer(axum::Extension(stream_handle))
+        .layer(axum::Extension(pnl_service))
+        .layer(axum::Extension(tax_service))
+        .layer(axum::Extension(valuation_service))
+        .layer(axum::Extension(bond_analytics_service))
         .layer(create_trace())
 }
 
@@ -136,9 +246,6 @@ async fn main() {
 
     let mongodb_arc = Arc::new(mongo_db.clone());
 
-    // Create application router
-    let app = create_app(mongo_db);
-
     // Initialize Tinkoff client
     let tinkoff_client = Arc::new(
         TinkoffClient::new(settings.clone())
@@ -158,7 +265,7 @@ async fn main() {
     let vec_watchlists = mongodb_arc.get_watchlists().await;
 
     // Start the market data stream with the watchlists
-    start_market_data_stream(
+    let stream_handle = start_market_data_stream(
         settings.clone(),
         tinkoff_client.clone(),
         mongodb_arc.clone(),
@@ -168,6 +275,20 @@ async fn main() {
 
     start_currency_rates_updater(mongodb_arc.clone(), settings.clone()).await;
 
+    start_portfolio_service(
+        mongodb_arc.clone(),
+        settings.clone(),
+        tinkoff_client.clone(),
+    )
+    .await;
+
+    start_income_collectors(
+        mongodb_arc.clone(),
+        settings.clone(),
+        tinkoff_client.clone(),
+    )
+    .await;
+
     // Initialize historical candle services (both one-time loader and periodic updater)
     initialize_historical_candle_services(
         tinkoff_client.clone(),
@@ -176,6 +297,31 @@ async fn main() {
     )
     .await;
 
+    let pnl_service = Arc::new(PnlService::new(
+        tinkoff_client.clone(),
+        mongodb_arc.clone(),
+    ));
+    let tax_service = Arc::new(TaxService::new(mongodb_arc.clone()));
+    let valuation_service = Arc::new(ValuationService::new(mongodb_arc.clone()));
+    let bond_analytics_service = Arc::new(BondAnalyticsService::new(mongodb_arc.clone()));
+
+    start_bond_analytics_job(
+        bond_analytics_service.clone(),
+        mongodb_arc.clone(),
+        settings.clone(),
+    )
+    .await;
+
+    // Create application router
+    let app = create_app(
+        mongo_db,
+        stream_handle,
+        pnl_service,
+        tax_service,
+        valuation_service,
+        bond_analytics_service,
+    );
+
     // Start HTTP server
     run_server(app, http_addr).await;
 }
@@ -185,7 +331,7 @@ async fn start_currency_rates_updater(mongo_db: Arc<MongoDb>, settings: Arc<AppS
     let api_client = MoexApiClient::new();
 
     // Создаем и запускаем планировщик обновлений с настройками
-    let updater = CurrencyRatesUpdater::new(api_client, mongo_db.clone(), settings);
+    let updater = CurrencyRatesUpdater::new(api_client, CbrApiClient::new(), mongo_db.clone(), settings);
 
     // Запускаем планировщик в отдельной задаче
     tokio::spawn(async move {
@@ -193,15 +339,61 @@ async fn start_currency_rates_updater(mongo_db: Arc<MongoDb>, settings: Arc<AppS
     });
 }
 
+/// Start the portfolio snapshot and operations ledger background services
+async fn start_portfolio_service(
+    mongo_db: Arc<MongoDb>,
+    settings: Arc<AppSettings>,
+    client: Arc<TinkoffClient>,
+) {
+    let service = PortfolioService::new(client.clone(), mongo_db.clone(), settings.clone());
+    tokio::spawn(async move {
+        service.start_update_loop().await;
+    });
+
+    let ledger = OperationsLedgerService::new(client, mongo_db, settings);
+    tokio::spawn(async move {
+        ledger.start_update_loop().await;
+    });
+}
+
+async fn start_income_collectors(
+    mongo_db: Arc<MongoDb>,
+    settings: Arc<AppSettings>,
+    client: Arc<TinkoffClient>,
+) {
+    let collector = DividendCollector::new(client.clone(), mongo_db.clone(), settings.clone());
+    tokio::spawn(async move {
+        collector.start_update_loop().await;
+    });
+
+    let coupons = BondCouponCollector::new(client, mongo_db, settings);
+    tokio::spawn(async move {
+        coupons.start_update_loop().await;
+    });
+}
+
+/// Start the nightly bond analytics job
+async fn start_bond_analytics_job(
+    service: Arc<BondAnalyticsService>,
+    mongo_db: Arc<MongoDb>,
+    settings: Arc<AppSettings>,
+) {
+    let job = BondAnalyticsJob::new(service, mongo_db, settings);
+    tokio::spawn(async move {
+        job.start_update_loop().await;
+    });
+}
+
 /// Start the market data stream service
 async fn start_market_data_stream(
     settings: Arc<AppSettings>,
     client: Arc<TinkoffClient>,
     mongo_db: Arc<MongoDb>,
     watchlists: Vec<DbUserConfigWatchlist>,
-) {
+) -> MarketDataStreamHandle {
     // Create a new MarketDataStreamer with watchlists data
     let streamer = MarketDataStreamer::new(settings, client, mongo_db, watchlists);
+    let handle = streamer.handle();
 
     // Start the streaming process in a separate task
     tokio::spawn(async move {
@@ -209,4 +401,6 @@ async fn start_market_data_stream(
     });
 
     info!("Market data stream service started");
+
+    handle
 }
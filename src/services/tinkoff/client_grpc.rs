@@ -8,13 +8,20 @@ use crate::gen::tinkoff_public_invest_api_contract_v1::{
 };
 use rustls::crypto::aws_lc_rs;
 
+use std::future::Future;
 use std::io::Result;
 use std::{sync::Arc, time::Duration};
 use tonic::{
     metadata::MetadataValue,
     transport::{Channel, ClientTlsConfig},
-    Request, 
+    Code, Request, Response, Status,
 };
+use tracing::warn;
+
+use super::rate_limiter::{reset_duration, RateLimiter, TinkoffMethod};
+
+/// Пауза перед повтором после RESOURCE_EXHAUSTED, если сервер не прислал x-ratelimit-reset
+const DEFAULT_RATE_LIMIT_RESET: Duration = Duration::from_secs(60);
 
 #[derive(Clone)]
 pub struct TinkoffClient {
@@ -24,6 +31,8 @@ pub struct TinkoffClient {
     pub operations: OperationsServiceClient<Channel>,
     pub users: UsersServiceClient<Channel>,
     pub token: String,
+    pub rate_limiter: Arc<RateLimiter>,
+    rate_limit_retries: u32,
 }
 
 impl TinkoffClient {
@@ -56,6 +65,10 @@ impl TinkoffClient {
             operations: OperationsServiceClient::new(channel.clone()),
             users: UsersServiceClient::new(channel.clone()),
             token: settings.app_env.tinkoff_token.clone(),
+            rate_limiter: Arc::new(RateLimiter::new(
+                settings.app_config.tinkoff_api.rate_limits.clone(),
+            )),
+            rate_limit_retries: settings.app_config.tinkoff_api.rate_limit_retries,
         })
     }
 
@@ -70,4 +83,46 @@ impl TinkoffClient {
         Ok(request)
     }
 
+    /// Вызов метода API через лимитер запросов.
+    ///
+    /// `call` создаёт и отправляет запрос; при RESOURCE_EXHAUSTED метод блокируется
+    /// до сброса лимита (x-ratelimit-reset) и вызов повторяется
+    pub async fn call_limited<T, F, Fut>(
+        &self,
+        method: TinkoffMethod,
+        mut call: F,
+    ) -> std::result::Result<T, Status>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = std::result::Result<Response<T>, Status>>,
+    {
+        let mut attempt = 0;
+        loop {
+            self.rate_limiter.acquire(method).await;
+
+            match call().await {
+                Ok(response) => {
+                    self.rate_limiter.observe(method, response.metadata());
+                    return Ok(response.into_inner());
+                }
+                Err(status)
+                    if status.code() == Code::ResourceExhausted
+                        && attempt < self.rate_limit_retries =>
+                {
+                    attempt += 1;
+                    let reset =
+                        reset_duration(status.metadata()).unwrap_or(DEFAULT_RATE_LIMIT_RESET);
+                    warn!(
+                        "{} hit the rate limit, retrying in {:?} (attempt {}/{})",
+                        method.as_str(),
+                        reset,
+                        attempt,
+                        self.rate_limit_retries
+                    );
+                    self.rate_limiter.block_for(method, reset);
+                }
+                Err(status) => return Err(status),
+            }
+        }
+    }
 }
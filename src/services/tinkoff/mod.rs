@@ -1 +1,2 @@
 pub mod client_grpc;
+pub mod rate_limiter;
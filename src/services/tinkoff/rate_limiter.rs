@@ -0,0 +1,223 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use tokio::time::Instant;
+use tonic::metadata::MetadataMap;
+use tracing::{debug, warn};
+
+/// Методы Tinkoff Invest API, вызовы которых проходят через лимитер.
+/// Лимиты по умолчанию — запросов в минуту на метод из документации API
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TinkoffMethod {
+    GetCandles,
+    TradingSchedules,
+    GetAccounts,
+    GetPortfolio,
+    GetPositions,
+    GetOperationsByCursor,
+    GetLastPrices,
+    GetDividends,
+    GetBondCoupons,
+    GetAccruedInterests,
+}
+
+impl TinkoffMethod {
+    /// Имя метода, как в контракте и в `[tinkoff_api.rate_limits]`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::GetCandles => "GetCandles",
+            Self::TradingSchedules => "TradingSchedules",
+            Self::GetAccounts => "GetAccounts",
+            Self::GetPortfolio => "GetPortfolio",
+            Self::GetPositions => "GetPositions",
+            Self::GetOperationsByCursor => "GetOperationsByCursor",
+            Self::GetLastPrices => "GetLastPrices",
+            Self::GetDividends => "GetDividends",
+            Self::GetBondCoupons => "GetBondCoupons",
+            Self::GetAccruedInterests => "GetAccruedInterests",
+        }
+    }
+
+    pub fn default_per_minute(&self) -> u32 {
+        match self {
+            Self::GetCandles | Self::GetLastPrices => 600,
+            Self::TradingSchedules
+            | Self::GetDividends
+            | Self::GetBondCoupons
+            | Self::GetAccruedInterests => 200,
+            Self::GetAccounts => 100,
+            Self::GetPortfolio | Self::GetPositions | Self::GetOperationsByCursor => 200,
+        }
+    }
+}
+
+/// Корзина токенов одного метода: ёмкость = лимит в минуту, пополнение равномерное
+struct TokenBucket {
+    capacity: f64,
+    tokens: f64,
+    refill_per_second: f64,
+    last_refill: Instant,
+    /// Сервер сообщил об исчерпании лимита: до этого момента запросы не отправляем
+    blocked_until: Option<Instant>,
+}
+
+impl TokenBucket {
+    fn new(per_minute: u32) -> Self {
+        let capacity = per_minute.max(1) as f64;
+        Self {
+            capacity,
+            tokens: capacity,
+            refill_per_second: capacity / 60.0,
+            last_refill: Instant::now(),
+            blocked_until: None,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_second).min(self.capacity);
+        self.last_refill = now;
+    }
+
+    /// Забирает токен или возвращает, сколько ждать до следующей попытки
+    fn try_acquire(&mut self, now: Instant) -> Result<(), Duration> {
+        if let Some(blocked_until) = self.blocked_until {
+            if now < blocked_until {
+                return Err(blocked_until - now);
+            }
+            self.blocked_until = None;
+        }
+
+        self.refill(now);
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64(
+                (1.0 - self.tokens) / self.refill_per_second,
+            ))
+        }
+    }
+
+    fn block_for(&mut self, now: Instant, duration: Duration) {
+        self.tokens = 0.0;
+        self.last_refill = now + duration;
+        self.blocked_until = Some(now + duration);
+    }
+}
+
+/// Лимитер запросов к Tinkoff API с отдельной корзиной на каждый метод
+pub struct RateLimiter {
+    limits: HashMap<String, u32>,
+    buckets: Mutex<HashMap<TinkoffMethod, TokenBucket>>,
+}
+
+impl RateLimiter {
+    /// `limits` переопределяют лимиты по умолчанию: имя метода -> запросов в минуту
+    pub fn new(limits: HashMap<String, u32>) -> Self {
+        Self {
+            limits,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Действующий лимит метода в минуту
+    pub fn per_minute(&self, method: TinkoffMethod) -> u32 {
+        self.limits
+            .get(method.as_str())
+            .copied()
+            .unwrap_or_else(|| method.default_per_minute())
+    }
+
+    /// Ждёт, пока для метода появится свободный токен
+    pub async fn acquire(&self, method: TinkoffMethod) {
+        loop {
+            let wait = {
+                let mut buckets = self.buckets.lock().unwrap();
+                let bucket = buckets
+                    .entry(method)
+                    .or_insert_with(|| TokenBucket::new(self.per_minute(method)));
+                match bucket.try_acquire(Instant::now()) {
+                    Ok(()) => return,
+                    Err(wait) => wait,
+                }
+            };
+
+            debug!("Rate limit for {}: waiting {:?}", method.as_str(), wait);
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Блокирует метод до сброса лимита на стороне сервера
+    pub fn block_for(&self, method: TinkoffMethod, duration: Duration) {
+        let mut buckets = self.buckets.lock().unwrap();
+        buckets
+            .entry(method)
+            .or_insert_with(|| TokenBucket::new(self.per_minute(method)))
+            .block_for(Instant::now(), duration);
+    }
+
+    /// Учитывает заголовки `x-ratelimit-remaining` / `x-ratelimit-reset` ответа:
+    /// если сервер сообщил, что лимит исчерпан, ждём до его сброса
+    pub fn observe(&self, method: TinkoffMethod, metadata: &MetadataMap) {
+        if ratelimit_header(metadata, "x-ratelimit-remaining") == Some(0) {
+            let reset = reset_duration(metadata).unwrap_or(Duration::from_secs(1));
+            warn!(
+                "Rate limit for {} is exhausted, pausing for {:?}",
+                method.as_str(),
+                reset
+            );
+            self.block_for(method, reset);
+        }
+    }
+}
+
+fn ratelimit_header(metadata: &MetadataMap, name: &str) -> Option<u64> {
+    metadata.get(name)?.to_str().ok()?.trim().parse().ok()
+}
+
+/// Время до сброса лимита из `x-ratelimit-reset` (секунды)
+pub fn reset_duration(metadata: &MetadataMap) -> Option<Duration> {
+    ratelimit_header(metadata, "x-ratelimit-reset").map(Duration::from_secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bucket_refills_at_per_minute_rate() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(60);
+        bucket.last_refill = start;
+
+        for _ in 0..60 {
+            assert!(bucket.try_acquire(start).is_ok());
+        }
+        let wait = bucket.try_acquire(start).unwrap_err();
+        assert_eq!(wait, Duration::from_secs(1));
+
+        assert!(bucket.try_acquire(start + Duration::from_secs(1)).is_ok());
+    }
+
+    #[test]
+    fn test_blocked_bucket_waits_for_reset() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(600);
+        bucket.block_for(start, Duration::from_secs(30));
+
+        let wait = bucket.try_acquire(start + Duration::from_secs(10)).unwrap_err();
+        assert_eq!(wait, Duration::from_secs(20));
+
+        let after_reset = start + Duration::from_secs(31);
+        assert!(bucket.try_acquire(after_reset).is_ok());
+    }
+
+    #[test]
+    fn test_reset_duration_from_metadata() {
+        let mut metadata = MetadataMap::new();
+        metadata.insert("x-ratelimit-reset", "12".parse().unwrap());
+        assert_eq!(reset_duration(&metadata), Some(Duration::from_secs(12)));
+    }
+}